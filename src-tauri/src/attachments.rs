@@ -0,0 +1,483 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::OnceLock;
+
+use base64::Engine;
+use regex::{Captures, Regex};
+use tauri::State;
+
+use crate::error::CommandError;
+use crate::sandbox::{checked_rel_path, resolve_in_root, VaultRegistry};
+use crate::{
+    is_importable_image_extension, is_markdown_file, move_entries, split_wikilink_inner,
+};
+
+/// Attachment types besides images.
+const DOCUMENT_EXTENSIONS: &[&str] = &[
+    "pdf", "mp3", "wav", "ogg", "m4a", "flac", "mp4", "webm", "mov", "mkv",
+];
+
+fn is_attachment_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase)
+        .is_some_and(|ext| {
+            is_importable_image_extension(&ext) || DOCUMENT_EXTENSIONS.contains(&ext.as_str())
+        })
+}
+
+fn collect_attachment_files(
+    root: &Path,
+    dir: &Path,
+    out: &mut Vec<String>,
+) -> Result<(), CommandError> {
+    let read_dir = fs::read_dir(dir).map_err(|e| CommandError::io(e, dir))?;
+    for entry in read_dir {
+        let entry = entry.map_err(|e| CommandError::io(e, dir))?;
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let path = entry.path();
+        if path.is_dir() {
+            collect_attachment_files(root, &path, out)?;
+        } else if is_attachment_path(&path) {
+            let rel = path
+                .strip_prefix(root)
+                .map_err(|_| CommandError::OutsideVault {
+                    path: path.to_string_lossy().into_owned(),
+                })?
+                .to_string_lossy()
+                .replace('\\', "/");
+            out.push(rel);
+        }
+    }
+    Ok(())
+}
+
+/// Images, PDFs and media in the vault, outside hidden folders such as the
+/// trash, sorted by path.
+pub(crate) fn list_attachment_paths(vault_root: &Path) -> Result<Vec<String>, CommandError> {
+    let mut out = Vec::new();
+    collect_attachment_files(vault_root, vault_root, &mut out)?;
+    out.sort();
+    Ok(out)
+}
+
+pub(crate) fn parent_dir(path: &str) -> &str {
+    path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("")
+}
+
+pub(crate) fn file_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+/// Resolves `.` and `..` segments; `None` when the path climbs out of the
+/// vault.
+pub(crate) fn collapse_segments(path: &str) -> Option<String> {
+    let mut parts: Vec<&str> = Vec::new();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            other => parts.push(other),
+        }
+    }
+    Some(parts.join("/"))
+}
+
+/// Whether a link target written in `note_path` points at `attachment`. Like
+/// the image preview, a target is tried against the note's folder and then
+/// the vault root; `by_name` also accepts a bare file name from anywhere, the
+/// way wiki embeds name attachments.
+fn link_points_at(note_path: &str, target: &str, attachment: &str, by_name: bool) -> bool {
+    let target = target.trim();
+    if target.is_empty() {
+        return false;
+    }
+    let same = |candidate: Option<String>| {
+        candidate.is_some_and(|candidate| candidate.eq_ignore_ascii_case(attachment))
+    };
+    if let Some(from_root) = target.strip_prefix('/') {
+        return same(collapse_segments(from_root));
+    }
+    same(collapse_segments(&format!(
+        "{}/{target}",
+        parent_dir(note_path)
+    ))) || same(collapse_segments(target))
+        || (by_name && !target.contains('/') && target.eq_ignore_ascii_case(file_name(attachment)))
+}
+
+/// `path` relative to the folder of `note_path`.
+pub(crate) fn relative_to_note(note_path: &str, path: &str) -> String {
+    let from: Vec<&str> = parent_dir(note_path)
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .collect();
+    let to: Vec<&str> = path.split('/').collect();
+    let shared = from
+        .iter()
+        .zip(&to[..to.len() - 1])
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut parts = vec![".."; from.len() - shared];
+    parts.extend(&to[shared..]);
+    parts.join("/")
+}
+
+/// The destination of a markdown link or image, `path#fragment "title"` or
+/// `<path with spaces#fragment> "title"`, split so the path can be replaced
+/// without disturbing the rest.
+pub(crate) struct MarkdownTarget<'a> {
+    pub(crate) path: &'a str,
+    fragment: &'a str,
+    rest: &'a str,
+    angled: bool,
+}
+
+impl<'a> MarkdownTarget<'a> {
+    pub(crate) fn parse(raw: &'a str) -> Self {
+        let (target, rest, angled) = match raw.strip_prefix('<').and_then(|r| r.split_once('>')) {
+            Some((target, rest)) => (target, rest, true),
+            None => {
+                let end = raw.find(char::is_whitespace).unwrap_or(raw.len());
+                (&raw[..end], &raw[end..], false)
+            }
+        };
+        let (path, fragment) = target.split_at(target.find(['#', '?']).unwrap_or(target.len()));
+        Self {
+            path,
+            fragment,
+            rest,
+            angled,
+        }
+    }
+
+    /// The path with `%20` read as a space.
+    pub(crate) fn decoded_path(&self) -> String {
+        self.path.replace("%20", " ")
+    }
+
+    /// The destination with `path` in place of the old one, keeping `%20`
+    /// encoding when the old path used it and angle brackets when they were
+    /// there or a space needs them.
+    pub(crate) fn with_path(&self, path: &str) -> String {
+        let path = if self.path.contains("%20") {
+            path.replace(' ', "%20")
+        } else {
+            path.to_string()
+        };
+        if self.angled || path.contains(' ') {
+            format!("<{path}{}>{}", self.fragment, self.rest)
+        } else {
+            format!("{path}{}{}", self.fragment, self.rest)
+        }
+    }
+}
+
+/// Points every wiki link or embed in `content` (the text of `note_path`)
+/// that refers to `old_path` at `new_path`: a bare name when the attachment
+/// sits next to the note and a vault path otherwise. Headings and aliases are
+/// kept.
+pub(crate) fn rewrite_attachment_wiki_links(
+    content: &str,
+    note_path: &str,
+    old_path: &str,
+    new_path: &str,
+    by_name: bool,
+) -> (String, bool) {
+    static WIKI: OnceLock<Regex> = OnceLock::new();
+    let wiki = WIKI.get_or_init(|| Regex::new(r"(!?)\[\[([^\]\n]+)\]\]").unwrap());
+
+    let mut changed = false;
+    let content = wiki.replace_all(content, |caps: &Captures| {
+        let (target, heading, alias) = split_wikilink_inner(&caps[2]);
+        if !link_points_at(note_path, &target, old_path, by_name) {
+            return caps[0].to_string();
+        }
+        changed = true;
+        let mut rebuilt = if parent_dir(new_path) == parent_dir(note_path) {
+            file_name(new_path).to_string()
+        } else {
+            new_path.to_string()
+        };
+        if let Some(heading) = heading.filter(|h| !h.is_empty()) {
+            rebuilt.push('#');
+            rebuilt.push_str(&heading);
+        }
+        if let Some(alias) = alias {
+            rebuilt.push('|');
+            rebuilt.push_str(&alias);
+        }
+        format!("{}[[{rebuilt}]]", &caps[1])
+    });
+    (content.into_owned(), changed)
+}
+
+/// Renames or moves an attachment and rewrites the links to it in every
+/// note, through `move_entries` like notes and folders. A new name without an
+/// extension keeps the old one.
+pub(crate) fn move_attachment(
+    vault_root: &Path,
+    old_path: &str,
+    new_path: &str,
+) -> Result<String, CommandError> {
+    let old_rel = checked_rel_path(old_path)?;
+    let mut new_rel = checked_rel_path(new_path)?;
+    if !is_attachment_path(Path::new(&old_rel)) {
+        return Err(CommandError::invalid_name(old_path, "Not an attachment"));
+    }
+    if Path::new(&new_rel).extension().is_none() {
+        if let Some(ext) = Path::new(&old_rel).extension().and_then(|ext| ext.to_str()) {
+            new_rel = format!("{new_rel}.{ext}");
+        }
+    }
+    if is_markdown_file(Path::new(&new_rel)) {
+        return Err(CommandError::invalid_name(
+            new_path,
+            "An attachment cannot become a note",
+        ));
+    }
+    if old_rel == new_rel {
+        return Ok(new_rel);
+    }
+    if !resolve_in_root(vault_root, &old_rel)?.is_file() {
+        return Err(CommandError::not_found(old_rel));
+    }
+
+    move_entries(vault_root, &[(old_rel, new_rel)]).map(|mut moved| moved.remove(0))
+}
+
+/// Keeps letters, digits, `-`, `_` and `.`; anything else, including spaces
+/// and the brackets that would break a link, becomes `-`.
+fn sanitize_file_name(name: &str) -> String {
+    let name = name.rsplit(['/', '\\']).next().unwrap_or(name).trim();
+    name.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// Writes `bytes` to `folder/file_name` inside the vault, adding `-1`, `-2`,
+/// ... before the extension while the name is taken. An empty folder is the
+/// vault root. Returns the vault-relative path that was written.
+pub(crate) fn write_attachment(
+    vault_root: &Path,
+    folder: &str,
+    file_name: &str,
+    bytes: &[u8],
+) -> Result<String, CommandError> {
+    let name = sanitize_file_name(file_name);
+    if name.trim_matches(['-', '.']).is_empty() {
+        return Err(CommandError::invalid_name(
+            file_name,
+            "Attachment needs a file name",
+        ));
+    }
+    let folder = folder.trim().trim_matches('/');
+    let folder = if folder.is_empty() {
+        String::new()
+    } else {
+        checked_rel_path(folder)?
+    };
+    let (stem, ext) = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem, format!(".{ext}")),
+        _ => (name.as_str(), String::new()),
+    };
+
+    for index in 0..10_000usize {
+        let candidate = if index == 0 {
+            name.clone()
+        } else {
+            format!("{stem}-{index}{ext}")
+        };
+        let rel = if folder.is_empty() {
+            candidate
+        } else {
+            format!("{folder}/{candidate}")
+        };
+        let abs = resolve_in_root(vault_root, &rel)?;
+        if let Some(parent) = abs.parent() {
+            fs::create_dir_all(parent).map_err(|e| CommandError::io(e, &folder))?;
+        }
+        // `create_new` keeps two pastes racing for one name from overwriting.
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&abs)
+        {
+            Ok(mut file) => {
+                file.write_all(bytes)
+                    .map_err(|e| CommandError::io(e, &rel))?;
+                return Ok(rel);
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(CommandError::io(e, &rel)),
+        }
+    }
+
+    Err(CommandError::Conflict {
+        message: format!("No free file name for attachment {name}"),
+    })
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn save_attachment(
+    vault_id: &str,
+    folder: &str,
+    file_name: &str,
+    data_base64: &str,
+    registry: State<VaultRegistry>,
+) -> Result<String, CommandError> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(data_base64.trim())
+        .map_err(|e| CommandError::Io {
+            message: format!("Attachment data is not valid base64: {e}"),
+        })?;
+    write_attachment(&registry.root(vault_id)?, folder, file_name, &bytes)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn list_attachments(
+    vault_id: &str,
+    registry: State<VaultRegistry>,
+) -> Result<Vec<String>, CommandError> {
+    list_attachment_paths(&registry.root(vault_id)?)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn rename_attachment(
+    vault_id: &str,
+    old_path: &str,
+    new_path: &str,
+    registry: State<VaultRegistry>,
+) -> Result<String, CommandError> {
+    move_attachment(&registry.root(vault_id)?, old_path, new_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::temp_vault;
+
+    #[test]
+    fn writes_attachments_under_collision_safe_names() {
+        let vault = temp_vault("attachments-names");
+
+        let first = write_attachment(&vault, "attachments", "Screen shot.png", b"one").unwrap();
+        let second = write_attachment(&vault, "attachments/", "Screen shot.png", b"two").unwrap();
+        let root = write_attachment(&vault, "", "diagram", b"three").unwrap();
+
+        assert_eq!(first, "attachments/Screen-shot.png");
+        assert_eq!(second, "attachments/Screen-shot-1.png");
+        assert_eq!(root, "diagram");
+        assert_eq!(fs::read(vault.join(&first)).unwrap(), b"one");
+        assert_eq!(fs::read(vault.join(&second)).unwrap(), b"two");
+
+        let _ = fs::remove_dir_all(vault);
+    }
+
+    #[test]
+    fn refuses_folders_outside_the_vault_and_empty_names() {
+        let vault = temp_vault("attachments-refuse");
+
+        assert!(matches!(
+            write_attachment(&vault, "../elsewhere", "a.png", b"x"),
+            Err(CommandError::OutsideVault { .. })
+        ));
+        assert!(matches!(
+            write_attachment(&vault, "", "  ", b"x"),
+            Err(CommandError::InvalidName { .. })
+        ));
+        // Path separators in the name never pick the folder.
+        assert_eq!(
+            write_attachment(&vault, "img", "../../x.png", b"x").unwrap(),
+            "img/x.png"
+        );
+
+        let _ = fs::remove_dir_all(vault);
+    }
+
+    #[test]
+    fn rewrites_wiki_and_markdown_references() {
+        let vault = temp_vault("attachments-links");
+        fs::create_dir_all(vault.join("notes")).unwrap();
+        fs::create_dir_all(vault.join("assets")).unwrap();
+        fs::write(vault.join("assets/shot.png"), b"s").unwrap();
+        fs::write(vault.join("assets/other.png"), b"o").unwrap();
+        fs::write(
+            vault.join("notes/Plan.md"),
+            "![[shot.png]] ![[assets/shot.png|300]] [[assets/shot.png#page=2]]\n\
+             ![a](../assets/shot.png \"Title\") ![b](</assets/shot.png>) [c](https://x.io/assets/shot.png)\n\
+             ![[other.png]] ![d](../assets/other.png)",
+        )
+        .unwrap();
+
+        move_attachment(&vault, "assets/shot.png", "media/q3 shot.png").unwrap();
+        assert_eq!(
+            fs::read_to_string(vault.join("notes/Plan.md")).unwrap(),
+            "![[media/q3 shot.png]] ![[media/q3 shot.png|300]] [[media/q3 shot.png#page=2]]\n\
+             ![a](<../media/q3 shot.png> \"Title\") ![b](</media/q3 shot.png>) [c](https://x.io/assets/shot.png)\n\
+             ![[other.png]] ![d](../assets/other.png)"
+        );
+
+        // Without a unique name, bare embeds may mean another file.
+        fs::create_dir_all(vault.join("notes/img")).unwrap();
+        fs::write(vault.join("notes/shot.png"), b"n").unwrap();
+        fs::write(vault.join("media/shot.png"), b"m").unwrap();
+        fs::write(vault.join("Shots.md"), "![[shot.png]] ![](notes/shot.png)").unwrap();
+        move_attachment(&vault, "notes/shot.png", "notes/img/s.png").unwrap();
+        assert_eq!(
+            fs::read_to_string(vault.join("Shots.md")).unwrap(),
+            "![[shot.png]] ![](notes/img/s.png)"
+        );
+
+        let _ = fs::remove_dir_all(vault);
+    }
+
+    #[test]
+    fn moves_attachments_and_updates_notes() {
+        let vault = temp_vault("attachments-move");
+        fs::create_dir_all(vault.join("img")).unwrap();
+        fs::create_dir_all(vault.join(".bedrock")).unwrap();
+        fs::write(vault.join("img/a.png"), b"a").unwrap();
+        fs::write(vault.join("img/b.pdf"), b"b").unwrap();
+        fs::write(vault.join(".bedrock/hidden.png"), b"h").unwrap();
+        fs::write(vault.join("Note.md"), "![[a.png]] and ![](img/a.png)").unwrap();
+
+        assert_eq!(
+            list_attachment_paths(&vault).unwrap(),
+            vec!["img/a.png", "img/b.pdf"]
+        );
+
+        let moved = move_attachment(&vault, "img/a.png", "archive/diagram").unwrap();
+        assert_eq!(moved, "archive/diagram.png");
+        assert!(vault.join("archive/diagram.png").is_file());
+        assert_eq!(
+            fs::read_to_string(vault.join("Note.md")).unwrap(),
+            "![[archive/diagram.png]] and ![](archive/diagram.png)"
+        );
+
+        assert!(matches!(
+            move_attachment(&vault, "img/b.pdf", "archive/diagram.png"),
+            Err(CommandError::AlreadyExists { .. })
+        ));
+        assert!(matches!(
+            move_attachment(&vault, "Note.md", "Other.md"),
+            Err(CommandError::InvalidName { .. })
+        ));
+        assert!(matches!(
+            move_attachment(&vault, "img/b.pdf", ".bedrock/b.pdf"),
+            Err(CommandError::PermissionDenied { .. })
+        ));
+
+        let _ = fs::remove_dir_all(vault);
+    }
+}
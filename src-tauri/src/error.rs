@@ -0,0 +1,140 @@
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use crate::sandbox::SandboxError;
+
+/// Error returned by every command. Serialized as `{"kind": "...", ...}` so
+/// the frontend can branch on the kind instead of parsing messages.
+#[derive(serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CommandError {
+    NotFound { path: String },
+    AlreadyExists { path: String },
+    PermissionDenied { path: String },
+    OutsideVault { path: String },
+    Conflict { message: String },
+    InvalidName { name: String, reason: String },
+    Io { message: String },
+}
+
+impl CommandError {
+    /// Classifies an I/O failure on `path` by its error kind.
+    pub(crate) fn io(err: io::Error, path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref().to_string_lossy().replace('\\', "/");
+        match err.kind() {
+            io::ErrorKind::NotFound => Self::NotFound { path },
+            io::ErrorKind::AlreadyExists => Self::AlreadyExists { path },
+            io::ErrorKind::PermissionDenied => Self::PermissionDenied { path },
+            _ => Self::Io {
+                message: format!("{path}: {err}"),
+            },
+        }
+    }
+
+    pub(crate) fn not_found(path: impl Into<String>) -> Self {
+        Self::NotFound { path: path.into() }
+    }
+
+    pub(crate) fn invalid_name(name: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self::InvalidName {
+            name: name.into(),
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound { path } => write!(f, "Not found: {path}"),
+            Self::AlreadyExists { path } => write!(f, "Already exists: {path}"),
+            Self::PermissionDenied { path } => write!(f, "Permission denied: {path}"),
+            Self::OutsideVault { path } => write!(f, "Outside the vault: {path}"),
+            Self::Conflict { message } => write!(f, "{message}"),
+            Self::InvalidName { name, reason } => write!(f, "Invalid name \"{name}\": {reason}"),
+            Self::Io { message } => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+impl From<SandboxError> for CommandError {
+    fn from(err: SandboxError) -> Self {
+        match err {
+            SandboxError::UnknownVault { vault_id } => Self::OutsideVault { path: vault_id },
+            SandboxError::AbsolutePath { path }
+            | SandboxError::Traversal { path }
+            | SandboxError::SymlinkEscape { path } => Self::OutsideVault { path },
+            SandboxError::EmptyPath => Self::invalid_name("", "Path cannot be empty"),
+            SandboxError::Io { message } => Self::Io { message },
+        }
+    }
+}
+
+impl From<serde_json::Error> for CommandError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Io {
+            message: err.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_io_errors_by_kind() {
+        let not_found = io::Error::new(io::ErrorKind::NotFound, "gone");
+        assert_eq!(
+            CommandError::io(not_found, "notes\\A.md"),
+            CommandError::NotFound {
+                path: "notes/A.md".to_string()
+            }
+        );
+
+        let denied = io::Error::new(io::ErrorKind::PermissionDenied, "no");
+        assert!(matches!(
+            CommandError::io(denied, "A.md"),
+            CommandError::PermissionDenied { .. }
+        ));
+
+        let other = io::Error::other("disk on fire");
+        assert_eq!(
+            CommandError::io(other, "A.md"),
+            CommandError::Io {
+                message: "A.md: disk on fire".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn sandbox_rejections_map_to_outside_vault() {
+        let err: CommandError = SandboxError::Traversal {
+            path: "../x.md".to_string(),
+        }
+        .into();
+        assert_eq!(
+            err,
+            CommandError::OutsideVault {
+                path: "../x.md".to_string()
+            }
+        );
+        let empty: CommandError = SandboxError::EmptyPath.into();
+        assert!(matches!(empty, CommandError::InvalidName { .. }));
+    }
+
+    #[test]
+    fn serializes_with_snake_case_kind_tag() {
+        let json = serde_json::to_value(CommandError::AlreadyExists {
+            path: "A.md".to_string(),
+        })
+        .unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "kind": "already_exists", "path": "A.md" })
+        );
+    }
+}
@@ -0,0 +1,508 @@
+use std::path::Path;
+use std::process::Command;
+
+use tauri::State;
+
+use crate::error::CommandError;
+use crate::sandbox::VaultRegistry;
+use crate::{current_time_millis, normalize_rel_path};
+
+/// Bedrock's own bookkeeping that should never be decorated or committed.
+const EXCLUDED_PATHSPECS: [&str; 2] = [":(exclude).bedrock/history", ":(exclude).bedrock/trash"];
+
+/// Separates fields in `git log` output; cannot appear in commit subjects.
+const LOG_FIELD_SEPARATOR: char = '\u{1f}';
+
+#[derive(serde::Serialize, Clone, Debug, PartialEq)]
+pub struct GitRepoInfo {
+    pub is_repo: bool,
+    pub branch: Option<String>,
+}
+
+#[derive(serde::Serialize, Clone, Debug, PartialEq)]
+pub struct GitFileStatus {
+    pub path: String,
+    pub status: String,
+}
+
+#[derive(serde::Serialize, Clone, Debug, PartialEq)]
+pub struct GitLogEntry {
+    pub hash: String,
+    pub short_hash: String,
+    pub author: String,
+    pub timestamp: i64,
+    pub subject: String,
+}
+
+fn git_command(repo_root: &Path) -> Command {
+    let mut command = Command::new("git");
+    command
+        .arg("-C")
+        .arg(repo_root)
+        .args(["-c", "core.quotepath=off"])
+        .env("GIT_TERMINAL_PROMPT", "0");
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    command
+}
+
+/// Runs git and returns stdout. Exit codes listed in `ok_codes` besides 0 are
+/// treated as success (e.g. `git diff --quiet` exits 1 when there are changes).
+fn run_git_with_codes(
+    repo_root: &Path,
+    args: &[&str],
+    ok_codes: &[i32],
+) -> Result<(i32, String), CommandError> {
+    let output = git_command(repo_root)
+        .args(args)
+        .output()
+        .map_err(|e| CommandError::Io {
+            message: format!("Failed to run git: {e}"),
+        })?;
+    let code = output.status.code().unwrap_or(-1);
+    if code != 0 && !ok_codes.contains(&code) {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        let message = if stderr.is_empty() {
+            format!(
+                "git {} failed with exit code {code}",
+                args.first().unwrap_or(&"")
+            )
+        } else {
+            stderr
+        };
+        return Err(CommandError::Io { message });
+    }
+    Ok((code, String::from_utf8_lossy(&output.stdout).into_owned()))
+}
+
+fn run_git(repo_root: &Path, args: &[&str]) -> Result<String, CommandError> {
+    run_git_with_codes(repo_root, args, &[]).map(|(_, stdout)| stdout)
+}
+
+fn not_a_repository() -> CommandError {
+    CommandError::Conflict {
+        message: "Vault is not a git repository".to_string(),
+    }
+}
+
+/// Only a repository rooted at the vault itself counts; a vault nested inside
+/// a larger checkout is left alone.
+pub(crate) fn is_repo_root(vault_root: &Path) -> bool {
+    vault_root.join(".git").exists()
+}
+
+fn has_head(repo_root: &Path) -> bool {
+    run_git(repo_root, &["rev-parse", "--verify", "-q", "HEAD"]).is_ok()
+}
+
+pub(crate) fn repo_info(vault_root: &Path) -> GitRepoInfo {
+    if !is_repo_root(vault_root) {
+        return GitRepoInfo {
+            is_repo: false,
+            branch: None,
+        };
+    }
+    let branch = run_git(vault_root, &["symbolic-ref", "--short", "-q", "HEAD"])
+        .ok()
+        .map(|out| out.trim().to_string())
+        .filter(|name| !name.is_empty());
+    GitRepoInfo {
+        is_repo: true,
+        branch,
+    }
+}
+
+fn status_label(code: &str) -> &'static str {
+    let mut chars = code.chars();
+    let index = chars.next().unwrap_or(' ');
+    let worktree = chars.next().unwrap_or(' ');
+    if code == "??" {
+        "untracked"
+    } else if index == 'U' || worktree == 'U' || code == "AA" || code == "DD" {
+        "conflicted"
+    } else if index == 'R' || worktree == 'R' || index == 'C' {
+        "renamed"
+    } else if index == 'A' {
+        "added"
+    } else if index == 'D' || worktree == 'D' {
+        "deleted"
+    } else {
+        "modified"
+    }
+}
+
+/// Parses `git status --porcelain=v1 -z` output.
+fn parse_porcelain_status(raw: &str) -> Vec<GitFileStatus> {
+    let mut entries = Vec::new();
+    let mut records = raw.split('\0');
+    while let Some(record) = records.next() {
+        if record.len() < 4 {
+            continue;
+        }
+        let code = &record[..2];
+        let path = &record[3..];
+        // Renames and copies are followed by the original path.
+        if code.starts_with('R') || code.starts_with('C') {
+            let _ = records.next();
+        }
+        entries.push(GitFileStatus {
+            path: path.to_string(),
+            status: status_label(code).to_string(),
+        });
+    }
+    entries
+}
+
+pub(crate) fn status_entries(vault_root: &Path) -> Result<Vec<GitFileStatus>, CommandError> {
+    if !is_repo_root(vault_root) {
+        return Ok(Vec::new());
+    }
+    let mut args = vec![
+        "status",
+        "--porcelain=v1",
+        "-z",
+        "--untracked-files=all",
+        "--",
+        ".",
+    ];
+    args.extend(EXCLUDED_PATHSPECS);
+    let raw = run_git(vault_root, &args)?;
+    Ok(parse_porcelain_status(&raw))
+}
+
+/// Unified diff of the working copy of `rel_path` against HEAD. Files that
+/// are not in HEAD yet are shown as entirely added.
+pub(crate) fn diff_against_head(vault_root: &Path, rel_path: &str) -> Result<String, CommandError> {
+    let rel = normalize_rel_path(rel_path);
+    if rel.is_empty() {
+        return Err(CommandError::invalid_name(rel_path, "Note path cannot be empty"));
+    }
+    if !is_repo_root(vault_root) {
+        return Err(not_a_repository());
+    }
+
+    let tracked = has_head(vault_root)
+        && run_git(vault_root, &["cat-file", "-e", &format!("HEAD:{rel}")]).is_ok();
+    if tracked {
+        return run_git(vault_root, &["diff", "--no-color", "HEAD", "--", &rel]);
+    }
+    if !vault_root.join(&rel).is_file() {
+        return Ok(String::new());
+    }
+    run_git_with_codes(
+        vault_root,
+        &["diff", "--no-color", "--no-index", "--", "/dev/null", &rel],
+        &[1],
+    )
+    .map(|(_, stdout)| stdout)
+}
+
+fn days_to_civil(days: i64) -> (i64, u32, u32) {
+    // Howard Hinnant's days-from-civil inverse.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+fn format_utc_minutes(millis: i64) -> String {
+    let secs = millis.div_euclid(1000);
+    let (year, month, day) = days_to_civil(secs.div_euclid(86_400));
+    let minute_of_day = secs.rem_euclid(86_400) / 60;
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02} UTC",
+        minute_of_day / 60,
+        minute_of_day % 60
+    )
+}
+
+/// Expands `{date}`, `{count}` and `{files}` in a commit message template.
+pub(crate) fn render_commit_message(template: &str, now: i64, changed: &[String]) -> String {
+    const LISTED_FILES: usize = 5;
+    let mut files = changed
+        .iter()
+        .take(LISTED_FILES)
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join(", ");
+    if changed.len() > LISTED_FILES {
+        files.push_str(&format!(" and {} more", changed.len() - LISTED_FILES));
+    }
+    let message = template
+        .replace("{date}", &format_utc_minutes(now))
+        .replace("{count}", &changed.len().to_string())
+        .replace("{files}", &files);
+    if message.trim().is_empty() {
+        format!("Vault backup: {}", format_utc_minutes(now))
+    } else {
+        message
+    }
+}
+
+/// Stages every change in the vault and commits it. Returns the new commit's
+/// short hash, or `None` when there was nothing to commit.
+pub(crate) fn commit_all(
+    vault_root: &Path,
+    template: &str,
+    now: i64,
+) -> Result<Option<String>, CommandError> {
+    if !is_repo_root(vault_root) {
+        return Err(not_a_repository());
+    }
+    let mut add_args = vec!["add", "-A", "--", "."];
+    add_args.extend(EXCLUDED_PATHSPECS);
+    run_git(vault_root, &add_args)?;
+
+    let (code, _) = run_git_with_codes(vault_root, &["diff", "--cached", "--quiet"], &[1])?;
+    if code == 0 {
+        return Ok(None);
+    }
+
+    let staged = run_git(vault_root, &["diff", "--cached", "--name-only", "-z"])?;
+    let changed: Vec<String> = staged
+        .split('\0')
+        .filter(|path| !path.is_empty())
+        .map(str::to_string)
+        .collect();
+    let message = render_commit_message(template, now, &changed);
+    run_git(vault_root, &["commit", "-q", "-m", &message])?;
+    let hash = run_git(vault_root, &["rev-parse", "--short", "HEAD"])?;
+    Ok(Some(hash.trim().to_string()))
+}
+
+pub(crate) fn file_log(
+    vault_root: &Path,
+    rel_path: &str,
+    limit: usize,
+) -> Result<Vec<GitLogEntry>, CommandError> {
+    let rel = normalize_rel_path(rel_path);
+    if !is_repo_root(vault_root) || !has_head(vault_root) || rel.is_empty() {
+        return Ok(Vec::new());
+    }
+    let limit_arg = format!("-n{}", limit.max(1));
+    let raw = run_git(
+        vault_root,
+        &[
+            "log",
+            "--follow",
+            &limit_arg,
+            "--format=%H%x1f%h%x1f%an%x1f%at%x1f%s",
+            "--",
+            &rel,
+        ],
+    )?;
+
+    Ok(raw
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(5, LOG_FIELD_SEPARATOR);
+            Some(GitLogEntry {
+                hash: fields.next()?.to_string(),
+                short_hash: fields.next()?.to_string(),
+                author: fields.next()?.to_string(),
+                timestamp: fields.next()?.parse().ok()?,
+                subject: fields.next().unwrap_or_default().to_string(),
+            })
+        })
+        .collect())
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn git_repo_info(vault_id: &str, registry: State<VaultRegistry>) -> GitRepoInfo {
+    match registry.root(vault_id) {
+        Ok(root) => repo_info(&root),
+        Err(_) => GitRepoInfo {
+            is_repo: false,
+            branch: None,
+        },
+    }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn git_status(
+    vault_id: &str,
+    registry: State<VaultRegistry>,
+) -> Result<Vec<GitFileStatus>, CommandError> {
+    status_entries(&registry.root(vault_id)?)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn git_diff_note(
+    vault_id: &str,
+    path: &str,
+    registry: State<VaultRegistry>,
+) -> Result<String, CommandError> {
+    // Resolving first keeps `--no-index` from reading files outside the vault.
+    registry.resolve(vault_id, path)?;
+    diff_against_head(&registry.root(vault_id)?, path)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn git_commit_all(
+    vault_id: &str,
+    template: &str,
+    registry: State<VaultRegistry>,
+) -> Result<Option<String>, CommandError> {
+    commit_all(&registry.root(vault_id)?, template, current_time_millis())
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn git_file_log(
+    vault_id: &str,
+    path: &str,
+    limit: Option<usize>,
+    registry: State<VaultRegistry>,
+) -> Result<Vec<GitLogEntry>, CommandError> {
+    registry.resolve(vault_id, path)?;
+    file_log(&registry.root(vault_id)?, path, limit.unwrap_or(50))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::unique_temp_dir;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn init_repo(prefix: &str) -> PathBuf {
+        let root = unique_temp_dir(prefix);
+        fs::create_dir_all(&root).unwrap();
+        run_git(&root, &["init", "-q"]).unwrap();
+        run_git(&root, &["config", "user.name", "Bedrock Test"]).unwrap();
+        run_git(&root, &["config", "user.email", "test@example.com"]).unwrap();
+        run_git(&root, &["config", "commit.gpgsign", "false"]).unwrap();
+        root
+    }
+
+    fn status_of<'a>(entries: &'a [GitFileStatus], path: &str) -> Option<&'a str> {
+        entries
+            .iter()
+            .find(|e| e.path == path)
+            .map(|e| e.status.as_str())
+    }
+
+    #[test]
+    fn detects_repository_only_at_vault_root() {
+        let repo = init_repo("git-detect");
+        assert!(repo_info(&repo).is_repo);
+
+        let nested = repo.join("sub");
+        fs::create_dir_all(&nested).unwrap();
+        assert!(!repo_info(&nested).is_repo);
+        assert!(matches!(
+            commit_all(&nested, "", 0),
+            Err(CommandError::Conflict { .. })
+        ));
+        assert!(matches!(
+            diff_against_head(&nested, "Note.md"),
+            Err(CommandError::Conflict { .. })
+        ));
+
+        let _ = fs::remove_dir_all(repo);
+    }
+
+    #[test]
+    fn reports_untracked_modified_and_deleted_files() {
+        let repo = init_repo("git-status");
+        fs::write(repo.join("a.md"), "a").unwrap();
+        fs::write(repo.join("b.md"), "b").unwrap();
+        commit_all(&repo, "init", 0).unwrap();
+
+        fs::write(repo.join("a.md"), "changed").unwrap();
+        fs::remove_file(repo.join("b.md")).unwrap();
+        fs::create_dir_all(repo.join("notes")).unwrap();
+        fs::write(repo.join("notes/new note.md"), "new").unwrap();
+
+        let entries = status_entries(&repo).unwrap();
+        assert_eq!(status_of(&entries, "a.md"), Some("modified"));
+        assert_eq!(status_of(&entries, "b.md"), Some("deleted"));
+        assert_eq!(status_of(&entries, "notes/new note.md"), Some("untracked"));
+
+        let _ = fs::remove_dir_all(repo);
+    }
+
+    #[test]
+    fn commit_all_commits_changes_and_skips_when_clean() {
+        let repo = init_repo("git-commit");
+        fs::write(repo.join("Note.md"), "hello").unwrap();
+        fs::create_dir_all(repo.join(".bedrock/history")).unwrap();
+        fs::write(repo.join(".bedrock/history/blob.md"), "snapshot").unwrap();
+
+        let hash = commit_all(&repo, "Backup {count}", 0).unwrap();
+        assert!(hash.is_some());
+        assert!(commit_all(&repo, "Backup {count}", 0).unwrap().is_none());
+
+        let subject = run_git(&repo, &["log", "-1", "--format=%s"]).unwrap();
+        assert_eq!(subject.trim(), "Backup 1");
+        let tracked = run_git(&repo, &["ls-files"]).unwrap();
+        assert!(!tracked.contains(".bedrock/history"));
+
+        let _ = fs::remove_dir_all(repo);
+    }
+
+    #[test]
+    fn diff_against_head_shows_working_copy_changes() {
+        let repo = init_repo("git-diff");
+        fs::write(repo.join("Note.md"), "one\ntwo\n").unwrap();
+        let untracked = diff_against_head(&repo, "Note.md").unwrap();
+        assert!(untracked.contains("+one"));
+
+        commit_all(&repo, "init", 0).unwrap();
+        assert!(diff_against_head(&repo, "Note.md").unwrap().is_empty());
+
+        fs::write(repo.join("Note.md"), "one\nthree\n").unwrap();
+        let diff = diff_against_head(&repo, "Note.md").unwrap();
+        assert!(diff.contains("-two"));
+        assert!(diff.contains("+three"));
+
+        let _ = fs::remove_dir_all(repo);
+    }
+
+    #[test]
+    fn file_log_lists_commits_touching_the_note() {
+        let repo = init_repo("git-log");
+        assert!(file_log(&repo, "Note.md", 10).unwrap().is_empty());
+
+        fs::write(repo.join("Note.md"), "v1").unwrap();
+        commit_all(&repo, "first", 0).unwrap();
+        fs::write(repo.join("Other.md"), "x").unwrap();
+        commit_all(&repo, "unrelated", 0).unwrap();
+        fs::write(repo.join("Note.md"), "v2").unwrap();
+        commit_all(&repo, "second", 0).unwrap();
+
+        let log = file_log(&repo, "Note.md", 10).unwrap();
+        let subjects: Vec<&str> = log.iter().map(|e| e.subject.as_str()).collect();
+        assert_eq!(subjects, vec!["second", "first"]);
+        assert_eq!(log[0].author, "Bedrock Test");
+
+        let _ = fs::remove_dir_all(repo);
+    }
+
+    #[test]
+    fn renders_commit_message_template() {
+        let changed: Vec<String> = (1..=7).map(|i| format!("n{i}.md")).collect();
+        let message = render_commit_message(
+            "{date}: {count} files ({files})",
+            86_400_000 + 90_000,
+            &changed,
+        );
+        assert_eq!(
+            message,
+            "1970-01-02 00:01 UTC: 7 files (n1.md, n2.md, n3.md, n4.md, n5.md and 2 more)"
+        );
+        assert_eq!(
+            render_commit_message("  ", 0, &[]),
+            "Vault backup: 1970-01-01 00:00 UTC"
+        );
+    }
+}
@@ -0,0 +1,427 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use tauri::State;
+
+use crate::error::CommandError;
+use crate::sandbox::{checked_rel_path, VaultRegistry};
+use crate::{current_time_millis, normalize_rel_path};
+
+const MILLIS_PER_DAY: i64 = 24 * 60 * 60 * 1000;
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub struct HistoryVersion {
+    pub id: String,
+    pub saved_at: i64,
+    pub hash: String,
+    pub size: usize,
+}
+
+/// Storage limits for note snapshots. Zero disables the corresponding limit.
+#[derive(serde::Deserialize, Clone, Copy, Debug)]
+pub struct HistoryPolicy {
+    #[serde(default = "default_min_interval_secs")]
+    pub min_interval_secs: u64,
+    #[serde(default = "default_max_versions")]
+    pub max_versions: usize,
+    #[serde(default = "default_max_age_days")]
+    pub max_age_days: u32,
+}
+
+fn default_min_interval_secs() -> u64 {
+    300
+}
+
+fn default_max_versions() -> usize {
+    50
+}
+
+fn default_max_age_days() -> u32 {
+    90
+}
+
+impl Default for HistoryPolicy {
+    fn default() -> Self {
+        Self {
+            min_interval_secs: default_min_interval_secs(),
+            max_versions: default_max_versions(),
+            max_age_days: default_max_age_days(),
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+struct HistoryIndex {
+    note_path: String,
+    #[serde(default)]
+    versions: Vec<HistoryVersion>,
+}
+
+/// 64-bit FNV-1a. Used for content addressing because it is stable across
+/// builds, unlike `std::hash::DefaultHasher`.
+fn fnv1a_hex(bytes: &[u8]) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in bytes {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    format!("{hash:016x}")
+}
+
+fn note_history_dir(vault_root: &Path, rel_path: &str) -> PathBuf {
+    let key = normalize_rel_path(rel_path);
+    vault_root
+        .join(".bedrock")
+        .join("history")
+        .join(fnv1a_hex(key.as_bytes()))
+}
+
+/// Loads a note's index; no index yet means no versions. An index that no
+/// longer parses is renamed to `index.corrupt-<millis>.json` and reported, so
+/// its blobs are kept rather than pruned as unreferenced.
+fn read_index(dir: &Path) -> Result<HistoryIndex, CommandError> {
+    let path = dir.join("index.json");
+    let raw = match fs::read_to_string(&path) {
+        Ok(raw) => raw,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(HistoryIndex::default()),
+        Err(e) => return Err(CommandError::io(e, &path)),
+    };
+    match serde_json::from_str::<HistoryIndex>(&raw) {
+        Ok(index) => Ok(index),
+        Err(err) => {
+            let aside = dir.join(format!("index.corrupt-{}.json", current_time_millis()));
+            fs::rename(&path, &aside).map_err(|e| CommandError::io(e, &aside))?;
+            Err(CommandError::Conflict {
+                message: format!(
+                    "Note history index was unreadable ({err}); kept it as {}",
+                    aside.display()
+                ),
+            })
+        }
+    }
+}
+
+fn write_index(dir: &Path, index: &HistoryIndex) -> Result<(), CommandError> {
+    fs::create_dir_all(dir).map_err(|e| CommandError::io(e, dir))?;
+    let json = serde_json::to_string_pretty(index)?;
+    let path = dir.join("index.json");
+    fs::write(&path, json).map_err(|e| CommandError::io(e, &path))
+}
+
+fn prune_versions(dir: &Path, index: &mut HistoryIndex, policy: &HistoryPolicy, now: i64) {
+    let mut dropped = Vec::new();
+    if policy.max_age_days > 0 {
+        let cutoff = now - i64::from(policy.max_age_days) * MILLIS_PER_DAY;
+        let (kept, expired) = std::mem::take(&mut index.versions)
+            .into_iter()
+            .partition(|v| v.saved_at >= cutoff);
+        index.versions = kept;
+        dropped = expired;
+    }
+    if policy.max_versions > 0 && index.versions.len() > policy.max_versions {
+        let excess = index.versions.len() - policy.max_versions;
+        dropped.extend(index.versions.drain(..excess));
+    }
+
+    // Snapshot bodies are shared between versions with identical content, so
+    // only delete blobs of dropped versions that no remaining version points
+    // at. Blobs the index does not know about are left alone.
+    let live: HashSet<&str> = index.versions.iter().map(|v| v.hash.as_str()).collect();
+    for version in &dropped {
+        if !live.contains(version.hash.as_str()) {
+            let _ = fs::remove_file(dir.join(format!("{}.md", version.hash)));
+        }
+    }
+}
+
+/// Records a snapshot of `content` unless it matches the newest version or the
+/// newest version is younger than the policy's minimum interval.
+pub(crate) fn record_snapshot(
+    vault_root: &Path,
+    rel_path: &str,
+    content: &str,
+    policy: &HistoryPolicy,
+    now: i64,
+) -> Result<Option<HistoryVersion>, CommandError> {
+    let rel = normalize_rel_path(rel_path);
+    if rel.is_empty() {
+        return Err(CommandError::invalid_name(rel_path, "Note path cannot be empty"));
+    }
+    let dir = note_history_dir(vault_root, &rel);
+    let mut index = read_index(&dir)?;
+    let hash = fnv1a_hex(content.as_bytes());
+
+    if let Some(latest) = index.versions.last() {
+        if latest.hash == hash {
+            return Ok(None);
+        }
+        let min_interval = i64::try_from(policy.min_interval_secs)
+            .unwrap_or(i64::MAX)
+            .saturating_mul(1000);
+        if now.saturating_sub(latest.saved_at) < min_interval {
+            return Ok(None);
+        }
+    }
+
+    fs::create_dir_all(&dir).map_err(|e| CommandError::io(e, &dir))?;
+    let blob = dir.join(format!("{hash}.md"));
+    if !blob.exists() {
+        fs::write(&blob, content).map_err(|e| CommandError::io(e, &blob))?;
+    }
+
+    let mut id = now.to_string();
+    let mut suffix = 1usize;
+    while index.versions.iter().any(|v| v.id == id) {
+        id = format!("{now}-{suffix}");
+        suffix += 1;
+    }
+    let version = HistoryVersion {
+        id,
+        saved_at: now,
+        hash,
+        size: content.len(),
+    };
+    index.note_path = rel;
+    index.versions.push(version.clone());
+    prune_versions(&dir, &mut index, policy, now);
+    write_index(&dir, &index)?;
+    Ok(Some(version))
+}
+
+pub(crate) fn list_versions(
+    vault_root: &Path,
+    rel_path: &str,
+) -> Result<Vec<HistoryVersion>, CommandError> {
+    let mut versions = read_index(&note_history_dir(vault_root, rel_path))?.versions;
+    versions.reverse();
+    Ok(versions)
+}
+
+pub(crate) fn read_version(
+    vault_root: &Path,
+    rel_path: &str,
+    version_id: &str,
+) -> Result<String, CommandError> {
+    let dir = note_history_dir(vault_root, rel_path);
+    let index = read_index(&dir)?;
+    let Some(version) = index.versions.iter().find(|v| v.id == version_id) else {
+        return Err(CommandError::not_found(format!("{rel_path}@{version_id}")));
+    };
+    let blob = dir.join(format!("{}.md", version.hash));
+    fs::read_to_string(&blob).map_err(|e| CommandError::io(e, &blob))
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn snapshot_note(
+    vault_id: &str,
+    path: &str,
+    content: &str,
+    policy: Option<HistoryPolicy>,
+    registry: State<VaultRegistry>,
+) -> Result<Option<HistoryVersion>, CommandError> {
+    let rel = checked_rel_path(path)?;
+    record_snapshot(
+        &registry.root(vault_id)?,
+        &rel,
+        content,
+        &policy.unwrap_or_default(),
+        current_time_millis(),
+    )
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn list_note_history(
+    vault_id: &str,
+    path: &str,
+    registry: State<VaultRegistry>,
+) -> Result<Vec<HistoryVersion>, CommandError> {
+    let rel = checked_rel_path(path)?;
+    list_versions(&registry.root(vault_id)?, &rel)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn read_note_version(
+    vault_id: &str,
+    path: &str,
+    version_id: &str,
+    registry: State<VaultRegistry>,
+) -> Result<String, CommandError> {
+    let rel = checked_rel_path(path)?;
+    read_version(&registry.root(vault_id)?, &rel, version_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::unique_temp_dir;
+
+    fn no_throttle() -> HistoryPolicy {
+        HistoryPolicy {
+            min_interval_secs: 0,
+            max_versions: 0,
+            max_age_days: 0,
+        }
+    }
+
+    #[test]
+    fn records_and_reads_back_versions() {
+        let vault = unique_temp_dir("history-roundtrip");
+        let policy = no_throttle();
+
+        let first = record_snapshot(&vault, "Note.md", "one", &policy, 1_000)
+            .unwrap()
+            .unwrap();
+        record_snapshot(&vault, "Note.md", "two", &policy, 2_000)
+            .unwrap()
+            .unwrap();
+
+        let versions = list_versions(&vault, "Note.md").unwrap();
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions[0].saved_at, 2_000);
+        assert_eq!(read_version(&vault, "Note.md", &first.id).unwrap(), "one");
+
+        let _ = fs::remove_dir_all(vault);
+    }
+
+    #[test]
+    fn skips_snapshot_when_content_hash_is_unchanged() {
+        let vault = unique_temp_dir("history-dedup");
+        let policy = no_throttle();
+
+        assert!(record_snapshot(&vault, "Note.md", "same", &policy, 1)
+            .unwrap()
+            .is_some());
+        assert!(record_snapshot(&vault, "Note.md", "same", &policy, 2)
+            .unwrap()
+            .is_none());
+        assert_eq!(list_versions(&vault, "Note.md").unwrap().len(), 1);
+
+        let _ = fs::remove_dir_all(vault);
+    }
+
+    #[test]
+    fn throttles_snapshots_within_min_interval() {
+        let vault = unique_temp_dir("history-throttle");
+        let policy = HistoryPolicy {
+            min_interval_secs: 60,
+            ..no_throttle()
+        };
+
+        assert!(record_snapshot(&vault, "Note.md", "a", &policy, 0)
+            .unwrap()
+            .is_some());
+        assert!(record_snapshot(&vault, "Note.md", "b", &policy, 59_000)
+            .unwrap()
+            .is_none());
+        assert!(record_snapshot(&vault, "Note.md", "c", &policy, 60_000)
+            .unwrap()
+            .is_some());
+
+        let _ = fs::remove_dir_all(vault);
+    }
+
+    #[test]
+    fn prunes_by_count_and_age_and_drops_unreferenced_blobs() {
+        let vault = unique_temp_dir("history-prune");
+        let policy = HistoryPolicy {
+            min_interval_secs: 0,
+            max_versions: 2,
+            max_age_days: 0,
+        };
+
+        let oldest = record_snapshot(&vault, "Note.md", "v1", &policy, 1)
+            .unwrap()
+            .unwrap();
+        record_snapshot(&vault, "Note.md", "v2", &policy, 2).unwrap();
+        record_snapshot(&vault, "Note.md", "v3", &policy, 3).unwrap();
+
+        let versions = list_versions(&vault, "Note.md").unwrap();
+        assert_eq!(versions.len(), 2);
+        assert!(read_version(&vault, "Note.md", &oldest.id).is_err());
+        let dir = note_history_dir(&vault, "Note.md");
+        assert!(!dir.join(format!("{}.md", oldest.hash)).exists());
+
+        let aged = HistoryPolicy {
+            max_age_days: 1,
+            ..policy
+        };
+        record_snapshot(&vault, "Note.md", "v4", &aged, 2 * MILLIS_PER_DAY).unwrap();
+        assert_eq!(list_versions(&vault, "Note.md").unwrap().len(), 1);
+
+        let _ = fs::remove_dir_all(vault);
+    }
+
+    #[test]
+    fn notes_with_same_name_in_different_folders_have_separate_history() {
+        let vault = unique_temp_dir("history-separate");
+        let policy = no_throttle();
+
+        record_snapshot(&vault, "a/Note.md", "a", &policy, 1).unwrap();
+        record_snapshot(&vault, "b/Note.md", "b", &policy, 1).unwrap();
+
+        assert_eq!(list_versions(&vault, "a/Note.md").unwrap().len(), 1);
+        assert_eq!(list_versions(&vault, "b/Note.md").unwrap().len(), 1);
+
+        let _ = fs::remove_dir_all(vault);
+    }
+
+    #[test]
+    fn notes_differing_only_in_case_have_separate_history() {
+        let vault = unique_temp_dir("history-case");
+        let policy = no_throttle();
+
+        record_snapshot(&vault, "Note.md", "upper", &policy, 1).unwrap();
+        record_snapshot(&vault, "note.md", "lower", &policy, 1).unwrap();
+
+        let upper = list_versions(&vault, "Note.md").unwrap();
+        let lower = list_versions(&vault, "note.md").unwrap();
+        assert_eq!(upper.len(), 1);
+        assert_eq!(lower.len(), 1);
+        assert_eq!(
+            read_version(&vault, "Note.md", &upper[0].id).unwrap(),
+            "upper"
+        );
+
+        let _ = fs::remove_dir_all(vault);
+    }
+
+    #[test]
+    fn corrupt_index_is_kept_aside_and_blobs_survive() {
+        let vault = unique_temp_dir("history-corrupt");
+        let policy = HistoryPolicy {
+            min_interval_secs: 0,
+            max_versions: 2,
+            max_age_days: 0,
+        };
+        let first = record_snapshot(&vault, "Note.md", "v1", &policy, 1)
+            .unwrap()
+            .unwrap();
+        let second = record_snapshot(&vault, "Note.md", "v2", &policy, 2)
+            .unwrap()
+            .unwrap();
+        let dir = note_history_dir(&vault, "Note.md");
+        fs::write(dir.join("index.json"), "{ not json").unwrap();
+
+        assert!(matches!(
+            record_snapshot(&vault, "Note.md", "v3", &policy, 3),
+            Err(CommandError::Conflict { .. })
+        ));
+        let kept = fs::read_dir(&dir)
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().starts_with("index.corrupt-"))
+            .count();
+        assert_eq!(kept, 1);
+
+        for (now, text) in [(4, "v4"), (5, "v5"), (6, "v6")] {
+            record_snapshot(&vault, "Note.md", text, &policy, now).unwrap();
+        }
+        assert_eq!(list_versions(&vault, "Note.md").unwrap().len(), 2);
+        for old in [&first, &second] {
+            assert!(dir.join(format!("{}.md", old.hash)).exists());
+        }
+
+        let _ = fs::remove_dir_all(vault);
+    }
+}
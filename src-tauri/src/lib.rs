@@ -1,25 +1,36 @@
 use base64::Engine;
 use regex::{Captures, Regex};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 use std::time::{SystemTime, UNIX_EPOCH};
 use tauri::{AppHandle, Emitter, Manager, RunEvent, State, WebviewUrl, WebviewWindowBuilder};
 
+mod attachments;
+mod error;
+mod git;
+mod history;
+mod sandbox;
 mod session;
+mod trash;
+#[cfg(test)]
+mod test_support;
+mod vault_load;
 
+pub use crate::error::CommandError;
+use crate::sandbox::VaultRegistry;
 use crate::session::{PendingClose, RecentNotesCache};
 
 pub use crate::session::{
     cache_recent_notes, close_window_now, load_vault_session, read_recent_notes,
     save_recent_notes, save_vault_session,
 };
-
-#[derive(serde::Serialize)]
-struct VaultNote {
-    path: String,
-    content: String,
-}
+pub use crate::attachments::{list_attachments, rename_attachment, save_attachment};
+pub use crate::git::{git_commit_all, git_diff_note, git_file_log, git_repo_info, git_status};
+pub use crate::history::{list_note_history, read_note_version, snapshot_note};
+pub use crate::trash::{empty_vault_trash, list_trash, restore_trash_item};
+pub use crate::vault_load::load_vault_notes;
 
 #[derive(serde::Serialize, Clone, Debug)]
 struct VaultImportReport {
@@ -97,10 +108,14 @@ fn strip_md(path: &str) -> String {
     }
 }
 
-fn collect_markdown_files(root: &Path, dir: &Path, out: &mut Vec<String>) -> Result<(), String> {
-    let read_dir = fs::read_dir(dir).map_err(|e| e.to_string())?;
+fn collect_markdown_files(
+    root: &Path,
+    dir: &Path,
+    out: &mut Vec<String>,
+) -> Result<(), CommandError> {
+    let read_dir = fs::read_dir(dir).map_err(|e| CommandError::io(e, dir))?;
     for entry in read_dir {
-        let entry = entry.map_err(|e| e.to_string())?;
+        let entry = entry.map_err(|e| CommandError::io(e, dir))?;
         let file_name = entry.file_name().to_string_lossy().into_owned();
         if file_name.starts_with('.') {
             continue;
@@ -118,7 +133,9 @@ fn collect_markdown_files(root: &Path, dir: &Path, out: &mut Vec<String>) -> Res
         }
         let rel = path
             .strip_prefix(root)
-            .map_err(|e| e.to_string())?
+            .map_err(|_| CommandError::OutsideVault {
+                path: path.to_string_lossy().into_owned(),
+            })?
             .to_string_lossy()
             .replace('\\', "/");
         out.push(rel);
@@ -126,7 +143,7 @@ fn collect_markdown_files(root: &Path, dir: &Path, out: &mut Vec<String>) -> Res
     Ok(())
 }
 
-fn collect_note_paths(vault_path: &str) -> Result<Vec<String>, String> {
+fn collect_note_paths(vault_path: &str) -> Result<Vec<String>, CommandError> {
     let root = Path::new(vault_path);
     if !root.exists() {
         return Ok(Vec::new());
@@ -137,15 +154,21 @@ fn collect_note_paths(vault_path: &str) -> Result<Vec<String>, String> {
     Ok(entries)
 }
 
-fn collect_relative_dirs(root: &Path, dir: &Path, out: &mut Vec<String>) -> Result<(), String> {
-    let read_dir = fs::read_dir(dir).map_err(|e| e.to_string())?;
+fn collect_relative_dirs(
+    root: &Path,
+    dir: &Path,
+    out: &mut Vec<String>,
+) -> Result<(), CommandError> {
+    let read_dir = fs::read_dir(dir).map_err(|e| CommandError::io(e, dir))?;
     for entry in read_dir {
-        let entry = entry.map_err(|e| e.to_string())?;
+        let entry = entry.map_err(|e| CommandError::io(e, dir))?;
         let path = entry.path();
         if path.is_dir() && !entry.file_name().to_string_lossy().starts_with('.') {
             let rel = path
                 .strip_prefix(root)
-                .map_err(|e| e.to_string())?
+                .map_err(|_| CommandError::OutsideVault {
+                    path: path.to_string_lossy().into_owned(),
+                })?
                 .to_string_lossy()
                 .replace('\\', "/");
             out.push(rel);
@@ -224,19 +247,6 @@ fn is_importable_image_extension(ext: &str) -> bool {
     )
 }
 
-fn find_vault_root_for_note(note_path: &Path) -> Option<PathBuf> {
-    let mut current = note_path.parent()?;
-    loop {
-        if current.join(".bedrock").is_dir() || current.join("settings.json").is_file() {
-            return Some(current.to_path_buf());
-        }
-        match current.parent() {
-            Some(parent) => current = parent,
-            None => return None,
-        }
-    }
-}
-
 fn is_importable_asset(path: &Path) -> bool {
     if is_markdown_file(path) {
         return true;
@@ -443,84 +453,196 @@ fn split_wikilink_inner(inner: &str) -> (String, Option<String>, Option<String>)
     (target, heading, alias)
 }
 
-fn rewrite_wiki_links(
-    content: &str,
-    old_path: &str,
-    new_path: &str,
-    include_stem_match: bool,
-) -> (String, bool) {
-    let wiki_re = Regex::new(r"\[\[([^\]]+)\]\]").expect("valid wiki link regex");
-    let old_no_ext = strip_md(old_path);
-    let new_no_ext = strip_md(new_path);
-    let old_stem = Path::new(&old_no_ext)
-        .file_name()
-        .and_then(|s| s.to_str())
-        .unwrap_or(&old_no_ext)
-        .to_string();
-    let new_stem = Path::new(&new_no_ext)
-        .file_name()
-        .and_then(|s| s.to_str())
-        .unwrap_or(&new_no_ext)
-        .to_string();
-
-    let mut old_refs = HashSet::new();
-    old_refs.insert(normalize_link_key(old_path));
-    old_refs.insert(normalize_link_key(&old_no_ext));
-    if include_stem_match {
-        old_refs.insert(normalize_link_key(&old_stem));
-    }
-
-    let mut changed = false;
-    let rewritten = wiki_re.replace_all(content, |caps: &Captures| {
-        let whole = caps.get(0).map(|m| m.as_str()).unwrap_or_default();
-        let inner = caps.get(1).map(|m| m.as_str()).unwrap_or_default();
-        let (target_raw, heading, alias) = split_wikilink_inner(inner);
-        let target_trimmed = target_raw.trim();
-        if target_trimmed.is_empty() {
-            return whole.to_string();
-        }
-        let target_key = normalize_link_key(target_trimmed);
-        if !old_refs.contains(&target_key) {
-            return whole.to_string();
-        }
-
-        changed = true;
-        let had_path = target_trimmed.contains('/');
-        let had_ext = target_trimmed.to_ascii_lowercase().ends_with(".md");
-        let replacement_base = if had_path {
-            new_no_ext.clone()
-        } else {
-            new_stem.clone()
-        };
-        let mut rebuilt = if had_ext {
-            format!("{replacement_base}.md")
-        } else {
-            replacement_base
-        };
-        if let Some(heading_part) = heading {
-            if !heading_part.is_empty() {
-                rebuilt.push('#');
-                rebuilt.push_str(&heading_part);
-            }
+/// Wiki-link rewrites for a set of note moves, collected up front so every
+/// note in the vault is scanned once however many notes moved.
+#[derive(Default)]
+struct WikiLinkRewriter {
+    /// Normalised old link key -> (new path without `.md`, new stem).
+    targets: HashMap<String, (String, String)>,
+}
+
+impl WikiLinkRewriter {
+    /// Records that `old_path` moved to `new_path`. With `include_stem_match`
+    /// bare `[[Stem]]` links are rewritten too, which is only safe while no
+    /// other note shares the stem.
+    fn add(&mut self, old_path: &str, new_path: &str, include_stem_match: bool) {
+        let old_no_ext = strip_md(old_path);
+        let new_no_ext = strip_md(new_path);
+        let new_stem = Path::new(&new_no_ext)
+            .file_name()
+            .and_then(|s| s.to_str())
+            .unwrap_or(&new_no_ext)
+            .to_string();
+        let target = (new_no_ext.clone(), new_stem);
+        if include_stem_match {
+            let old_stem = Path::new(&old_no_ext)
+                .file_name()
+                .and_then(|s| s.to_str())
+                .unwrap_or(&old_no_ext);
+            self.targets
+                .entry(normalize_link_key(old_stem))
+                .or_insert_with(|| target.clone());
         }
-        if let Some(alias_part) = alias {
-            rebuilt.push('|');
-            rebuilt.push_str(&alias_part);
+        self.targets.insert(normalize_link_key(old_path), target.clone());
+        self.targets.insert(normalize_link_key(&old_no_ext), target);
+    }
+
+    fn rewrite(&self, content: &str) -> (String, bool) {
+        if self.targets.is_empty() {
+            return (content.to_string(), false);
         }
-        format!("[[{rebuilt}]]")
-    });
-    (rewritten.into_owned(), changed)
+        let wiki_re = Regex::new(r"\[\[([^\]]+)\]\]").expect("valid wiki link regex");
+        let mut changed = false;
+        let rewritten = wiki_re.replace_all(content, |caps: &Captures| {
+            let whole = caps.get(0).map(|m| m.as_str()).unwrap_or_default();
+            let inner = caps.get(1).map(|m| m.as_str()).unwrap_or_default();
+            let (target_raw, heading, alias) = split_wikilink_inner(inner);
+            let target_trimmed = target_raw.trim();
+            if target_trimmed.is_empty() {
+                return whole.to_string();
+            }
+            let Some((new_no_ext, new_stem)) =
+                self.targets.get(&normalize_link_key(target_trimmed))
+            else {
+                return whole.to_string();
+            };
+
+            let had_path = target_trimmed.contains('/');
+            let had_ext = target_trimmed.to_ascii_lowercase().ends_with(".md");
+            let replacement_base = if had_path { new_no_ext } else { new_stem };
+            let mut rebuilt = if had_ext {
+                format!("{replacement_base}.md")
+            } else {
+                replacement_base.clone()
+            };
+            if let Some(heading_part) = heading {
+                if !heading_part.is_empty() {
+                    rebuilt.push('#');
+                    rebuilt.push_str(&heading_part);
+                }
+            }
+            if let Some(alias_part) = alias {
+                rebuilt.push('|');
+                rebuilt.push_str(&alias_part);
+            }
+            let rebuilt = format!("[[{rebuilt}]]");
+            changed |= rebuilt != whole;
+            rebuilt
+        });
+        (rewritten.into_owned(), changed)
+    }
 }
 
-#[tauri::command]
-fn read_dir(path: &str) -> Result<ReadDirResult, String> {
-    let root = Path::new(path);
-    if !root.exists() {
-        return Ok(ReadDirResult { notes: Vec::new(), empty_dirs: Vec::new() });
+/// Markdown-link rewrites, `[label](path)` and `![](path)`, for a set of note
+/// and attachment moves. Relative targets are resolved from where the linking
+/// note used to live, so both links to a moved file and relative links inside
+/// a moved note keep pointing at the same file.
+#[derive(Default)]
+struct MarkdownLinkRewriter {
+    /// Lowercased old path -> new path, for every file that moved.
+    moves: HashMap<String, String>,
+    /// New path -> old path, for every note that moved.
+    origins: HashMap<String, String>,
+    /// Lowercased paths of every note and attachment before the move.
+    known: HashSet<String>,
+}
+
+impl MarkdownLinkRewriter {
+    fn new(files: &[String]) -> Self {
+        Self {
+            known: files.iter().map(|path| path.to_lowercase()).collect(),
+            ..Self::default()
+        }
+    }
+
+    fn add(&mut self, old_path: &str, new_path: &str) {
+        self.moves.insert(old_path.to_lowercase(), new_path.to_string());
+        self.origins.insert(new_path.to_string(), old_path.to_string());
     }
-    let notes = collect_note_paths(path)?;
+
+    /// Where the file `old_target` lives now, if it is a vault file, and
+    /// whether it moved to get there. A target without `.md` that names a
+    /// note keeps leaving it off.
+    fn current_path(&self, old_target: &str) -> Option<(String, bool)> {
+        if old_target.is_empty() {
+            return None;
+        }
+        let lookup = |path: &str| {
+            let key = path.to_lowercase();
+            match self.moves.get(&key) {
+                Some(moved) => Some((moved.clone(), true)),
+                None => self.known.contains(&key).then(|| (path.to_string(), false)),
+            }
+        };
+        lookup(old_target).or_else(|| {
+            lookup(&format!("{old_target}.md")).map(|(note, moved)| (strip_md(&note), moved))
+        })
+    }
+
+    /// Rewrites the markdown links in `content`, the text of the note now at
+    /// `note_path`. Targets without `.md` that name a note keep leaving it off.
+    fn rewrite(&self, note_path: &str, content: &str) -> (String, bool) {
+        static MARKDOWN: OnceLock<Regex> = OnceLock::new();
+        let markdown = MARKDOWN.get_or_init(|| {
+            Regex::new(r"(!?\[[^\]\n]*\]\()([^)\n]+)\)").expect("valid markdown link regex")
+        });
+        let origin = self
+            .origins
+            .get(note_path)
+            .map(String::as_str)
+            .unwrap_or(note_path);
+        let note_moved = origin != note_path;
+
+        let mut changed = false;
+        let rewritten = markdown.replace_all(content, |caps: &Captures| {
+            let whole = caps[0].to_string();
+            let target = attachments::MarkdownTarget::parse(&caps[2]);
+            let path = target.decoded_path();
+            if path.is_empty() || path.contains(':') {
+                return whole;
+            }
+            // Like the image preview, try the note's folder first and then
+            // the vault root; a rooted target only means the latter.
+            let from_note = if path.starts_with('/') {
+                None
+            } else {
+                attachments::collapse_segments(&format!(
+                    "{}/{path}",
+                    attachments::parent_dir(origin)
+                ))
+                .and_then(|resolved| self.current_path(&resolved))
+            };
+            let next = match from_note {
+                Some((current, moved)) => {
+                    if !note_moved && !moved {
+                        return whole;
+                    }
+                    attachments::relative_to_note(note_path, &current)
+                }
+                None => match attachments::collapse_segments(&path)
+                    .and_then(|resolved| self.current_path(&resolved))
+                {
+                    Some((current, true)) if path.starts_with('/') => format!("/{current}"),
+                    Some((current, true)) => current,
+                    _ => return whole,
+                },
+            };
+            if next == path {
+                return whole;
+            }
+            changed = true;
+            format!("{}{})", &caps[1], target.with_path(&next))
+        });
+        (rewritten.into_owned(), changed)
+    }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn read_dir(vault_id: &str, registry: State<VaultRegistry>) -> Result<ReadDirResult, CommandError> {
+    let root = registry.root(vault_id)?;
+    let notes = collect_note_paths(&root.to_string_lossy())?;
     let mut all_dirs = Vec::new();
-    collect_relative_dirs(root, root, &mut all_dirs)?;
+    collect_relative_dirs(&root, &root, &mut all_dirs)?;
     let empty_dirs = all_dirs
         .into_iter()
         .filter(|d| {
@@ -530,45 +652,43 @@ fn read_dir(path: &str) -> Result<ReadDirResult, String> {
     Ok(ReadDirResult { notes, empty_dirs })
 }
 
-#[tauri::command]
-fn read_file(path: &str, cache: State<RecentNotesCache>) -> Result<String, String> {
-    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+#[tauri::command(rename_all = "snake_case")]
+fn read_file(
+    vault_id: &str,
+    path: &str,
+    registry: State<VaultRegistry>,
+    cache: State<RecentNotesCache>,
+) -> Result<String, CommandError> {
+    let note_path = registry.resolve(vault_id, path)?;
+    let content = fs::read_to_string(&note_path).map_err(|e| CommandError::io(e, path))?;
 
     // Backend-side recent-notes tracking: whenever a markdown note is read,
-    // infer its vault root from the filesystem and update the RecentNotesCache
-    // and `.bedrock/recent.json`. This provides a persistence fallback even if
-    // the frontend close flow or cache_recent_notes invocations fail or race.
-    let note_path = PathBuf::from(path);
+    // update the RecentNotesCache and `.bedrock/recent.json` for its vault.
+    // This provides a persistence fallback even if the frontend close flow or
+    // cache_recent_notes invocations fail or race.
     if is_markdown_file(&note_path) {
-        if let Some(vault_root) = find_vault_root_for_note(&note_path) {
-            let canon_root = session::canonicalize_vault_root(
-                &vault_root.to_string_lossy().to_string(),
-            );
-            let vault_key = canon_root.to_string_lossy().to_string();
-            if let Ok(rel) = note_path
-                .strip_prefix(&canon_root)
-                .or_else(|_| note_path.strip_prefix(&vault_root))
-            {
-                let rel_str = rel.to_string_lossy().replace('\\', "/");
-                let title = Path::new(&rel_str)
-                    .file_name()
-                    .and_then(|s| s.to_str())
-                    .unwrap_or(&rel_str)
-                    .to_string();
-                    let entry = session::RecentNoteEntry {
-                    path: rel_str.clone(),
-                    title,
-                    last_opened: current_time_millis(),
-                };
-                    if let Ok(mut guard) = cache.0.lock() {
-                    let list = guard.entry(vault_key.clone()).or_insert_with(Vec::new);
-                    list.retain(|e| e.path != entry.path);
-                    list.insert(0, entry);
-                    if list.len() > 50 {
+        let canon_root = registry.root(vault_id)?;
+        let vault_key = canon_root.to_string_lossy().to_string();
+        if let Ok(rel) = note_path.strip_prefix(&canon_root) {
+            let rel_str = rel.to_string_lossy().replace('\\', "/");
+            let title = Path::new(&rel_str)
+                .file_name()
+                .and_then(|s| s.to_str())
+                .unwrap_or(&rel_str)
+                .to_string();
+            let entry = session::RecentNoteEntry {
+                path: rel_str.clone(),
+                title,
+                last_opened: current_time_millis(),
+            };
+            if let Ok(mut guard) = cache.0.lock() {
+                let list = guard.entry(vault_key.clone()).or_insert_with(Vec::new);
+                list.retain(|e| e.path != entry.path);
+                list.insert(0, entry);
+                if list.len() > 50 {
                     list.truncate(50);
-                    }
-                    let _ = crate::session::write_recent_notes_to_disk(&vault_key, list);
                 }
+                let _ = crate::session::write_recent_notes_to_disk(&vault_key, list);
             }
         }
     }
@@ -576,126 +696,350 @@ fn read_file(path: &str, cache: State<RecentNotesCache>) -> Result<String, Strin
     Ok(content)
 }
 
-#[tauri::command]
-fn read_file_base64(path: &str) -> Result<String, String> {
-    let bytes = fs::read(path).map_err(|e| e.to_string())?;
+#[tauri::command(rename_all = "snake_case")]
+fn read_file_base64(
+    vault_id: &str,
+    path: &str,
+    registry: State<VaultRegistry>,
+) -> Result<String, CommandError> {
+    let bytes = fs::read(registry.resolve(vault_id, path)?).map_err(|e| CommandError::io(e, path))?;
     Ok(base64::engine::general_purpose::STANDARD.encode(bytes))
 }
 
-#[tauri::command]
-fn write_file(path: &str, content: &str) -> Result<(), String> {
-    fs::write(path, content).map_err(|e| e.to_string())
-}
-
-#[tauri::command]
-fn create_dir(path: &str) -> Result<(), String> {
-    fs::create_dir_all(path).map_err(|e| e.to_string())
+#[tauri::command(rename_all = "snake_case")]
+fn write_file(
+    vault_id: &str,
+    path: &str,
+    content: &str,
+    registry: State<VaultRegistry>,
+) -> Result<(), CommandError> {
+    fs::write(registry.resolve(vault_id, path)?, content).map_err(|e| CommandError::io(e, path))
 }
 
-#[tauri::command]
-fn delete_file(path: &str) -> Result<(), String> {
-    fs::remove_file(path).map_err(|e| e.to_string())
+#[tauri::command(rename_all = "snake_case")]
+fn create_dir(vault_id: &str, path: &str, registry: State<VaultRegistry>) -> Result<(), CommandError> {
+    fs::create_dir_all(registry.resolve(vault_id, path)?).map_err(|e| CommandError::io(e, path))
 }
 
-#[tauri::command]
-fn delete_dir(path: &str) -> Result<(), String> {
-    fs::remove_dir_all(path).map_err(|e| e.to_string())
+#[tauri::command(rename_all = "snake_case")]
+fn delete_file(
+    vault_id: &str,
+    path: &str,
+    registry: State<VaultRegistry>,
+) -> Result<trash::TrashEntry, CommandError> {
+    let abs = registry.resolve(vault_id, path)?;
+    if !abs.exists() {
+        return Err(CommandError::not_found(path));
+    }
+    if !abs.is_file() {
+        return Err(CommandError::Conflict {
+            message: format!("Not a file: {path}"),
+        });
+    }
+    trash::move_to_trash(&registry.root(vault_id)?, path, current_time_millis())
 }
 
-#[tauri::command]
-fn read_vault_notes(vault_path: &str) -> Result<Vec<VaultNote>, String> {
-    let root = PathBuf::from(vault_path);
-    let mut notes = Vec::new();
-    for rel_path in collect_note_paths(vault_path)? {
-        let abs = root.join(&rel_path);
-        let content = fs::read_to_string(abs).unwrap_or_default();
-        notes.push(VaultNote {
-            path: rel_path,
-            content,
+#[tauri::command(rename_all = "snake_case")]
+fn delete_dir(
+    vault_id: &str,
+    path: &str,
+    registry: State<VaultRegistry>,
+) -> Result<trash::TrashEntry, CommandError> {
+    let abs = registry.resolve(vault_id, path)?;
+    if !abs.exists() {
+        return Err(CommandError::not_found(path));
+    }
+    if !abs.is_dir() {
+        return Err(CommandError::Conflict {
+            message: format!("Not a folder: {path}"),
         });
     }
-    Ok(notes)
+    trash::move_to_trash(&registry.root(vault_id)?, path, current_time_millis())
 }
 
-#[tauri::command]
-fn rename_note(vault_path: &str, old_path: &str, new_path: &str) -> Result<String, String> {
-    let root = Path::new(vault_path);
+/// Renames or moves a note and rewrites the wiki and markdown links to it in
+/// every note, as well as the relative markdown links in the note itself.
+fn move_note(root: &Path, old_path: &str, new_path: &str) -> Result<String, CommandError> {
     let old_rel = ensure_markdown_extension(old_path);
     let new_rel = ensure_markdown_extension(new_path);
-    if old_rel.is_empty() || new_rel.is_empty() {
-        return Err("Note paths cannot be empty".to_string());
+    if new_rel.is_empty() {
+        return Err(CommandError::invalid_name(new_path, "Note name cannot be empty"));
+    }
+    if old_rel.is_empty() {
+        return Err(CommandError::not_found(old_path));
     }
     if old_rel == new_rel {
         return Ok(new_rel);
     }
 
-    let old_abs = root.join(&old_rel);
-    let new_abs = root.join(&new_rel);
-    if !old_abs.exists() {
-        return Err(format!("Note does not exist: {old_rel}"));
-    }
+    move_entries(root, &[(old_rel, new_rel)]).map(|mut moved| moved.remove(0))
+}
 
-    let old_stem = Path::new(&old_rel)
+#[tauri::command(rename_all = "snake_case")]
+fn rename_note(
+    vault_id: &str,
+    old_path: &str,
+    new_path: &str,
+    registry: State<VaultRegistry>,
+) -> Result<String, CommandError> {
+    move_note(&registry.root(vault_id)?, old_path, new_path)
+}
+
+fn note_stem_key(path: &str) -> String {
+    Path::new(path)
         .file_stem()
         .and_then(|s| s.to_str())
         .unwrap_or_default()
-        .to_ascii_lowercase();
-    let stem_occurrences = collect_note_paths(vault_path)?
-        .into_iter()
-        .filter(|path| {
-            Path::new(path)
-                .file_stem()
-                .and_then(|s| s.to_str())
-                .map(|stem| stem.eq_ignore_ascii_case(&old_stem))
-                .unwrap_or(false)
-        })
-        .count();
-    let include_stem_match = stem_occurrences <= 1;
+        .to_ascii_lowercase()
+}
 
-    if let Some(parent) = new_abs.parent() {
-        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+fn note_stem_counts(notes: &[String]) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for note in notes {
+        *counts.entry(note_stem_key(note)).or_insert(0) += 1;
     }
-    fs::rename(&old_abs, &new_abs).map_err(|e| e.to_string())?;
+    counts
+}
 
-    for rel in collect_note_paths(vault_path)? {
+/// An attachment move to apply to wiki embeds; `by_name` as in
+/// `attachments::rewrite_attachment_wiki_links`.
+struct AttachmentMove {
+    old_path: String,
+    new_path: String,
+    by_name: bool,
+}
+
+/// Applies every recorded move to each note in the vault, writing a note
+/// only when its text changed. Markdown links to attachments are left to
+/// `markdown`, which also knows where moved notes used to live.
+fn rewrite_vault_links(
+    root: &Path,
+    notes: &WikiLinkRewriter,
+    markdown: &MarkdownLinkRewriter,
+    attachments: &[AttachmentMove],
+) -> Result<(), CommandError> {
+    for rel in collect_note_paths(&root.to_string_lossy())? {
         let abs = root.join(&rel);
-        let content = fs::read_to_string(&abs).map_err(|e| e.to_string())?;
-        let (rewritten, changed) =
-            rewrite_wiki_links(&content, &old_rel, &new_rel, include_stem_match);
+        let content = fs::read_to_string(&abs).map_err(|e| CommandError::io(e, &rel))?;
+        let (rewritten, wiki_changed) = notes.rewrite(&content);
+        let (mut rewritten, markdown_changed) = markdown.rewrite(&rel, &rewritten);
+        let mut changed = wiki_changed || markdown_changed;
+        for moved in attachments {
+            let (next, next_changed) = attachments::rewrite_attachment_wiki_links(
+                &rewritten,
+                &rel,
+                &moved.old_path,
+                &moved.new_path,
+                moved.by_name,
+            );
+            rewritten = next;
+            changed |= next_changed;
+        }
         if changed {
-            fs::write(abs, rewritten).map_err(|e| e.to_string())?;
+            fs::write(abs, rewritten).map_err(|e| CommandError::io(e, &rel))?;
+        }
+    }
+    Ok(())
+}
+
+/// Moves the folder `old_path` to `new_path` with everything in it, then
+/// rewrites the links to every note and attachment that moved.
+fn move_folder(root: &Path, old_path: &str, new_path: &str) -> Result<String, CommandError> {
+    let old_rel = sandbox::checked_rel_path(old_path)?;
+    if !sandbox::resolve_in_root(root, &old_rel)?.is_dir() {
+        return Err(CommandError::not_found(old_rel));
+    }
+    move_entries(root, &[(old_rel, new_path.to_string())]).map(|mut moved| moved.remove(0))
+}
+
+/// Whether two existing paths name the same file, e.g. two spellings that
+/// differ only in case on a case-insensitive filesystem.
+fn is_same_file(a: &Path, b: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        match (fs::metadata(a), fs::metadata(b)) {
+            (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+            _ => false,
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        matches!((a.canonicalize(), b.canonicalize()), (Ok(a), Ok(b)) if a == b)
+    }
+}
+
+/// Moves notes, attachments and folders, as `(from, to)` pairs, then rewrites
+/// the links to everything that moved. Every move is checked and all the old
+/// to new mappings are worked out before anything is touched, so each note is
+/// rewritten once however much moved. Returns the new paths in order.
+fn move_entries(root: &Path, moves: &[(String, String)]) -> Result<Vec<String>, CommandError> {
+    let notes = collect_note_paths(&root.to_string_lossy())?;
+    let all_attachments = attachments::list_attachment_paths(root)?;
+    let mut files = notes.clone();
+    files.extend(all_attachments.iter().cloned());
+    let stems = note_stem_counts(&notes);
+
+    let mut wiki = WikiLinkRewriter::default();
+    let mut markdown = MarkdownLinkRewriter::new(&files);
+    let mut attachment_moves = Vec::new();
+    let mut planned = Vec::new();
+    let mut destinations = HashSet::new();
+    for (old_path, new_path) in moves {
+        let old_rel = sandbox::checked_rel_path(old_path)?;
+        let new_rel = sandbox::checked_rel_path(new_path)?;
+        for rel in [&old_rel, &new_rel] {
+            if rel == ".bedrock" || rel.starts_with(".bedrock/") {
+                return Err(CommandError::PermissionDenied { path: rel.clone() });
+            }
+        }
+        if old_rel == new_rel {
+            planned.push((old_rel, new_rel, None));
+            continue;
+        }
+        let old_prefix = format!("{old_rel}/");
+        if new_rel.starts_with(&old_prefix) {
+            return Err(CommandError::Conflict {
+                message: format!("Cannot move {old_rel} into itself"),
+            });
+        }
+        if planned.iter().any(|(other, _, _)| {
+            *other == old_rel
+                || old_rel.starts_with(&format!("{other}/"))
+                || other.starts_with(&old_prefix)
+        }) {
+            return Err(CommandError::Conflict {
+                message: format!("{old_rel} is moved twice"),
+            });
+        }
+
+        let old_abs = sandbox::resolve_in_root(root, &old_rel)?;
+        let new_abs = sandbox::resolve_in_root(root, &new_rel)?;
+        if !old_abs.exists() {
+            return Err(CommandError::not_found(old_rel));
+        }
+        // Only a case change on a case-insensitive filesystem may land on an
+        // existing path: that path is the item itself. Anything else would
+        // silently overwrite another file.
+        if (new_abs.exists() && !is_same_file(&old_abs, &new_abs))
+            || !destinations.insert(new_rel.to_lowercase())
+        {
+            return Err(CommandError::AlreadyExists { path: new_rel });
+        }
+
+        let moved_path = |path: &str| {
+            if path == old_rel {
+                Some(new_rel.clone())
+            } else {
+                path.strip_prefix(&old_prefix)
+                    .map(|rest| format!("{new_rel}/{rest}"))
+            }
+        };
+        for note in &notes {
+            if let Some(next) = moved_path(note) {
+                wiki.add(note, &next, stems[&note_stem_key(note)] <= 1);
+                markdown.add(note, &next);
+            }
+        }
+        for attachment in &all_attachments {
+            if let Some(next) = moved_path(attachment) {
+                // Bare-name embeds only identify the file while no other
+                // attachment shares its name; embeds match names in any case.
+                let name = attachments::file_name(attachment);
+                let by_name = all_attachments
+                    .iter()
+                    .filter(|other| attachments::file_name(other).eq_ignore_ascii_case(name))
+                    .count()
+                    <= 1;
+                markdown.add(attachment, &next);
+                attachment_moves.push(AttachmentMove {
+                    old_path: attachment.clone(),
+                    new_path: next,
+                    by_name,
+                });
+            }
+        }
+        planned.push((old_rel, new_rel, Some((old_abs, new_abs))));
+    }
+
+    // A failure part way puts back what already moved, so links are never
+    // left pointing at paths that only half the batch reached.
+    let mut done: Vec<(&PathBuf, &PathBuf)> = Vec::new();
+    for (old_rel, new_rel, paths) in &planned {
+        let Some((old_abs, new_abs)) = paths else {
+            continue;
+        };
+        let moved = match new_abs.parent() {
+            Some(parent) => fs::create_dir_all(parent).map_err(|e| CommandError::io(e, new_rel)),
+            None => Ok(()),
+        }
+        .and_then(|_| fs::rename(old_abs, new_abs).map_err(|e| CommandError::io(e, old_rel)));
+        if let Err(err) = moved {
+            for (old_abs, new_abs) in done.into_iter().rev() {
+                let _ = fs::rename(new_abs, old_abs);
+            }
+            return Err(err);
         }
+        done.push((old_abs, new_abs));
     }
-    Ok(new_rel)
+
+    rewrite_vault_links(root, &wiki, &markdown, &attachment_moves)?;
+    Ok(planned.into_iter().map(|(_, new_rel, _)| new_rel).collect())
+}
+
+/// One entry of a `move_items` batch.
+#[derive(serde::Deserialize)]
+struct ItemMove {
+    from: String,
+    to: String,
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn rename_folder(
+    vault_id: &str,
+    old_path: &str,
+    new_path: &str,
+    registry: State<VaultRegistry>,
+) -> Result<String, CommandError> {
+    move_folder(&registry.root(vault_id)?, old_path, new_path)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn move_items(
+    vault_id: &str,
+    moves: Vec<ItemMove>,
+    registry: State<VaultRegistry>,
+) -> Result<Vec<String>, CommandError> {
+    let moves: Vec<(String, String)> = moves.into_iter().map(|m| (m.from, m.to)).collect();
+    move_entries(&registry.root(vault_id)?, &moves)
 }
 
 #[tauri::command]
-fn init_vault(app_handle: tauri::AppHandle) -> Result<String, String> {
+fn init_vault(app_handle: tauri::AppHandle, registry: State<VaultRegistry>) -> Result<String, CommandError> {
     use tauri::Manager;
     let docs = app_handle
         .path()
         .document_dir()
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| CommandError::Io {
+            message: e.to_string(),
+        })?;
     let vault_path = docs.join("BedrockVault");
 
     let needs_welcome = !vault_path.exists();
-    ensure_bedrock_layout(&vault_path)?;
+    ensure_bedrock_layout(&vault_path).map_err(|message| CommandError::Io { message })?;
     if needs_welcome {
         // Create an initial welcome file
         let welcome_path = vault_path.join("Welcome.md");
-        fs::write(&welcome_path, "# Welcome to Bedrock\n\nBedrock is a fast, premium markdown note-taking tool.\n\n- Powered by **Rust** and **Tauri**\n- Extensible via CSS variables and plugins.\n").map_err(|e| e.to_string())?;
+        fs::write(&welcome_path, "# Welcome to Bedrock\n\nBedrock is a fast, premium markdown note-taking tool.\n\n- Powered by **Rust** and **Tauri**\n- Extensible via CSS variables and plugins.\n").map_err(|e| CommandError::io(e, &welcome_path))?;
     }
 
-    let canon = vault_path
-        .canonicalize()
-        .unwrap_or(vault_path);
-    Ok(canon.to_string_lossy().into_owned())
+    Ok(registry.register(&vault_path)?)
 }
 
-#[tauri::command]
-fn load_plugins_css(vault_path: &str) -> Result<String, String> {
+#[tauri::command(rename_all = "snake_case")]
+fn load_plugins_css(vault_id: &str, registry: State<VaultRegistry>) -> Result<String, CommandError> {
     let mut compiled_css = String::new();
-    let plugins_dir = format!("{}/.plugins", vault_path);
+    let plugins_dir = registry.root(vault_id)?.join(".plugins");
     if let Ok(entries) = fs::read_dir(plugins_dir) {
         for entry in entries.flatten() {
             let p = entry.path();
@@ -710,16 +1054,21 @@ fn load_plugins_css(vault_path: &str) -> Result<String, String> {
     Ok(compiled_css)
 }
 
-#[tauri::command]
-fn save_settings(app: AppHandle, vault_path: &str, settings: &str) -> Result<(), String> {
-    let settings_path = format!("{}/settings.json", vault_path);
-    fs::write(settings_path, settings).map_err(|e| e.to_string())?;
+#[tauri::command(rename_all = "snake_case")]
+fn save_settings(
+    app: AppHandle,
+    vault_id: &str,
+    settings: &str,
+    registry: State<VaultRegistry>,
+) -> Result<(), CommandError> {
+    let settings_path = registry.root(vault_id)?.join("settings.json");
+    fs::write(settings_path, settings).map_err(|e| CommandError::io(e, "settings.json"))?;
     let _ = app.emit("settings-updated", settings);
     Ok(())
 }
 
 #[tauri::command]
-fn open_settings_window(app: AppHandle) -> Result<(), String> {
+fn open_settings_window(app: AppHandle) -> Result<(), CommandError> {
     if let Some(window) = app.get_webview_window("settings") {
         window.set_focus().unwrap();
     } else {
@@ -731,19 +1080,28 @@ fn open_settings_window(app: AppHandle) -> Result<(), String> {
         .title("Theme Settings")
         .inner_size(800.0, 700.0)
         .build()
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| CommandError::Io {
+            message: e.to_string(),
+        })?;
     }
     Ok(())
 }
 
-#[tauri::command]
-fn load_settings(vault_path: &str) -> Result<String, String> {
-    let settings_path = format!("{}/settings.json", vault_path);
+#[tauri::command(rename_all = "snake_case")]
+fn load_settings(vault_id: &str, registry: State<VaultRegistry>) -> Result<String, CommandError> {
+    let settings_path = registry.root(vault_id)?.join("settings.json");
     fs::read_to_string(settings_path).or_else(|_| Ok("{}".to_string()))
 }
 
+/// User key bindings from `.bedrock/keymap.json`; `{}` (all defaults) when absent.
+#[tauri::command(rename_all = "snake_case")]
+fn load_keymap(vault_id: &str, registry: State<VaultRegistry>) -> Result<String, CommandError> {
+    let keymap_path = registry.root(vault_id)?.join(".bedrock").join("keymap.json");
+    fs::read_to_string(keymap_path).or_else(|_| Ok("{}".to_string()))
+}
+
 #[tauri::command]
-fn import_obsidian_vault_with_picker() -> VaultImportReport {
+fn import_obsidian_vault_with_picker(registry: State<VaultRegistry>) -> VaultImportReport {
     #[cfg(any(target_os = "android", target_os = "ios"))]
     {
         return VaultImportReport::failed(
@@ -774,7 +1132,10 @@ fn import_obsidian_vault_with_picker() -> VaultImportReport {
         }
 
         match import_obsidian_vault_notes(&source_vault, &destination_vault) {
-            Ok(report) => report,
+            Ok(report) => {
+                let _ = registry.register(&destination_vault);
+                report
+            }
             Err(err) => VaultImportReport::failed(
                 err,
                 Some(source_vault.to_string_lossy().to_string()),
@@ -785,10 +1146,13 @@ fn import_obsidian_vault_with_picker() -> VaultImportReport {
 }
 
 #[tauri::command]
-fn pick_bedrock_vault() -> Result<Option<String>, String> {
+fn pick_bedrock_vault(registry: State<VaultRegistry>) -> Result<Option<String>, CommandError> {
     #[cfg(any(target_os = "android", target_os = "ios"))]
     {
-        Err("Opening a vault with a native folder picker is desktop-only for now.".to_string())
+        Err(CommandError::Io {
+            message: "Opening a vault with a native folder picker is desktop-only for now."
+                .to_string(),
+        })
     }
 
     #[cfg(not(any(target_os = "android", target_os = "ios")))]
@@ -796,25 +1160,15 @@ fn pick_bedrock_vault() -> Result<Option<String>, String> {
         let Some(path) = pick_folder("Choose Bedrock vault") else {
             return Ok(None);
         };
-        ensure_bedrock_layout(&path)?;
-        let canon = path.canonicalize().map_err(|e| e.to_string())?;
-        Ok(Some(canon.to_string_lossy().to_string()))
+        ensure_bedrock_layout(&path).map_err(|message| CommandError::Io { message })?;
+        Ok(Some(registry.register(&path)?))
     }
 }
 
 #[cfg(test)]
 mod import_tests {
     use super::*;
-    use std::time::{SystemTime, UNIX_EPOCH};
-
-    fn unique_temp_dir(prefix: &str) -> PathBuf {
-        let nanos = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("clock should be valid")
-            .as_nanos();
-        let pid = std::process::id();
-        std::env::temp_dir().join(format!("bedrock-{prefix}-{pid}-{nanos}"))
-    }
+    use crate::test_support::unique_temp_dir;
 
     #[test]
     fn imports_markdown_files_without_mutating_source() {
@@ -891,27 +1245,295 @@ mod import_tests {
     }
 }
 
+#[cfg(test)]
+mod rename_tests {
+    use super::*;
+    use crate::test_support::unique_temp_dir;
+
+    #[test]
+    fn rewrites_links_for_every_moved_note_in_one_pass() {
+        let mut rewriter = WikiLinkRewriter::default();
+        rewriter.add("projects/Plan.md", "archive/projects/Plan.md", true);
+        rewriter.add("projects/Todo.md", "archive/projects/Todo.md", false);
+        let (text, changed) = rewriter.rewrite(
+            "[[projects/Plan#Goals|plan]] [[Plan]] [[projects/Todo.md]] [[Todo]] [[Other]]",
+        );
+        assert!(changed);
+        assert_eq!(
+            text,
+            "[[archive/projects/Plan#Goals|plan]] [[Plan]] [[archive/projects/Todo.md]] [[Todo]] [[Other]]"
+        );
+        assert!(!rewriter.rewrite("[[Plan]] only").1);
+    }
+
+    #[test]
+    fn rewrites_markdown_links_to_and_from_a_moved_note() {
+        let files: Vec<String> = ["Index.md", "projects/Plan.md", "projects/img/plan.png"]
+            .iter()
+            .map(|path| path.to_string())
+            .collect();
+        let mut rewriter = MarkdownLinkRewriter::new(&files);
+        rewriter.add("projects/Plan.md", "archive/2024/Plan.md");
+
+        let (text, changed) = rewriter.rewrite(
+            "Index.md",
+            "[a](projects/Plan.md#goals) [b](<projects/Plan.md> \"Plan\") [c](/projects/Plan.md) \
+             [d](projects/Plan) [e](https://x.io/projects/Plan.md) [f](#top) ![g](projects/img/plan.png)",
+        );
+        assert!(changed);
+        assert_eq!(
+            text,
+            "[a](archive/2024/Plan.md#goals) [b](<archive/2024/Plan.md> \"Plan\") [c](/archive/2024/Plan.md) \
+             [d](archive/2024/Plan) [e](https://x.io/projects/Plan.md) [f](#top) ![g](projects/img/plan.png)"
+        );
+
+        let (text, changed) = rewriter.rewrite(
+            "archive/2024/Plan.md",
+            "![](img/plan.png) [home](../Index.md#top) [root](/Index.md) [gone](missing.md)",
+        );
+        assert!(changed);
+        assert_eq!(
+            text,
+            "![](../../projects/img/plan.png) [home](../../Index.md#top) [root](/Index.md) [gone](missing.md)"
+        );
+    }
+
+    #[test]
+    fn keeps_encoded_and_bracketed_forms_when_moving_notes() {
+        let root = unique_temp_dir("note-rename");
+        fs::create_dir_all(root.join("my notes/img")).unwrap();
+        fs::write(
+            root.join("my notes/Some Note.md"),
+            "![](img/a%20b.png) [up](../Index.md)",
+        )
+        .unwrap();
+        fs::write(root.join("my notes/img/a b.png"), b"png").unwrap();
+        fs::write(
+            root.join("Index.md"),
+            "[x](my%20notes/Some%20Note.md#part) [y](<my notes/Some Note.md>) [[Some Note]]",
+        )
+        .unwrap();
+        let root = root.canonicalize().unwrap();
+
+        assert_eq!(
+            move_note(&root, "my notes/Some Note.md", "Moved Note").unwrap(),
+            "Moved Note.md"
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("Index.md")).unwrap(),
+            "[x](Moved%20Note.md#part) [y](<Moved Note.md>) [[Moved Note]]"
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("Moved Note.md")).unwrap(),
+            "![](my%20notes/img/a%20b.png) [up](Index.md)"
+        );
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn moves_a_batch_of_notes_and_folders_in_one_pass() {
+        let root = unique_temp_dir("batch-move");
+        fs::create_dir_all(root.join("inbox/img")).unwrap();
+        fs::create_dir_all(root.join("archive")).unwrap();
+        fs::write(root.join("Draft.md"), "[idea](inbox/Idea.md) ![](inbox/img/a.png)").unwrap();
+        fs::write(root.join("inbox/Idea.md"), "[[Draft]] [back](../Draft.md)").unwrap();
+        fs::write(root.join("inbox/img/a.png"), b"png").unwrap();
+        fs::write(root.join("archive/Draft.md"), "older draft").unwrap();
+        let root = root.canonicalize().unwrap();
+
+        let moves = vec![
+            ("Draft.md".to_string(), "archive/Draft.md".to_string()),
+            ("inbox".to_string(), "archive/inbox".to_string()),
+        ];
+        assert!(matches!(
+            move_entries(&root, &moves),
+            Err(CommandError::AlreadyExists { .. })
+        ));
+        assert!(root.join("inbox/Idea.md").exists(), "nothing moves when one move fails");
+
+        let moves = vec![
+            ("Draft.md".to_string(), "archive/Draft 2.md".to_string()),
+            ("inbox".to_string(), "archive/inbox".to_string()),
+        ];
+        assert_eq!(
+            move_entries(&root, &moves).unwrap(),
+            vec!["archive/Draft 2.md", "archive/inbox"]
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("archive/Draft 2.md")).unwrap(),
+            "[idea](inbox/Idea.md) ![](inbox/img/a.png)"
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("archive/inbox/Idea.md")).unwrap(),
+            "[[Draft 2]] [back](<../Draft 2.md>)"
+        );
+
+        let nested = vec![
+            ("archive".to_string(), "old".to_string()),
+            ("archive/inbox".to_string(), "inbox".to_string()),
+        ];
+        assert!(matches!(
+            move_entries(&root, &nested),
+            Err(CommandError::Conflict { .. })
+        ));
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn case_only_move_never_overwrites_a_different_file() {
+        let root = unique_temp_dir("case-move");
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("Note.md"), "upper").unwrap();
+        fs::write(root.join("note.md"), "lower").unwrap();
+        let root = root.canonicalize().unwrap();
+        // Only meaningful where both spellings can exist side by side.
+        if fs::read_dir(&root).unwrap().count() == 2 {
+            let moves = vec![("Note.md".to_string(), "note.md".to_string())];
+            assert!(matches!(
+                move_entries(&root, &moves),
+                Err(CommandError::AlreadyExists { .. })
+            ));
+            assert_eq!(fs::read_to_string(root.join("Note.md")).unwrap(), "upper");
+            assert_eq!(fs::read_to_string(root.join("note.md")).unwrap(), "lower");
+        }
+
+        fs::write(root.join("Plan.md"), "plan").unwrap();
+        let moves = vec![("Plan.md".to_string(), "PLAN.md".to_string())];
+        assert_eq!(move_entries(&root, &moves).unwrap(), vec!["PLAN.md"]);
+        assert_eq!(fs::read_to_string(root.join("PLAN.md")).unwrap(), "plan");
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn failed_batch_move_puts_earlier_moves_back() {
+        let root = unique_temp_dir("batch-rollback");
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("One.md"), "[two](Two.md)").unwrap();
+        fs::write(root.join("Two.md"), "[[One]]").unwrap();
+        fs::write(root.join("blocker.md"), "a file, not a folder").unwrap();
+        let root = root.canonicalize().unwrap();
+
+        let moves = vec![
+            ("One.md".to_string(), "moved/One.md".to_string()),
+            ("Two.md".to_string(), "blocker.md/Two.md".to_string()),
+        ];
+        assert!(move_entries(&root, &moves).is_err());
+        assert!(!root.join("moved/One.md").exists());
+        assert_eq!(fs::read_to_string(root.join("One.md")).unwrap(), "[two](Two.md)");
+        assert_eq!(fs::read_to_string(root.join("Two.md")).unwrap(), "[[One]]");
+
+        for moves in [
+            vec![("One.md".to_string(), ".bedrock/One.md".to_string())],
+            vec![(".bedrock".to_string(), "config".to_string())],
+        ] {
+            assert!(matches!(
+                move_entries(&root, &moves),
+                Err(CommandError::PermissionDenied { .. })
+            ));
+        }
+        assert!(root.join("One.md").exists());
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn moves_folders_and_rewrites_qualified_links() {
+        let root = unique_temp_dir("folder-rename");
+        fs::create_dir_all(root.join("projects/q3/img")).unwrap();
+        fs::write(
+            root.join("projects/Plan.md"),
+            "[[projects/q3/Notes]] ![](q3/img/chart.png)",
+        )
+        .unwrap();
+        fs::write(root.join("projects/q3/Notes.md"), "![[img/chart.png]] [[Plan]]").unwrap();
+        fs::write(root.join("projects/q3/img/chart.png"), b"png").unwrap();
+        fs::write(
+            root.join("Index.md"),
+            "[[projects/Plan|plan]] ![[projects/q3/img/chart.png]]",
+        )
+        .unwrap();
+        fs::write(root.join("Untouched.md"), "[[Plan]]").unwrap();
+        let root = root.canonicalize().unwrap();
+
+        assert_eq!(
+            move_folder(&root, "projects", "archive/2024").unwrap(),
+            "archive/2024"
+        );
+        assert!(!root.join("projects").exists());
+        assert!(root.join("archive/2024/q3/img/chart.png").exists());
+        assert_eq!(
+            fs::read_to_string(root.join("Index.md")).unwrap(),
+            "[[archive/2024/Plan|plan]] ![[archive/2024/q3/img/chart.png]]"
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("archive/2024/Plan.md")).unwrap(),
+            "[[archive/2024/q3/Notes]] ![](q3/img/chart.png)"
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("archive/2024/q3/Notes.md")).unwrap(),
+            "![[img/chart.png]] [[Plan]]"
+        );
+        assert_eq!(fs::read_to_string(root.join("Untouched.md")).unwrap(), "[[Plan]]");
+
+        fs::create_dir_all(root.join("other")).unwrap();
+        assert!(matches!(
+            move_folder(&root, "archive", "other"),
+            Err(CommandError::AlreadyExists { .. })
+        ));
+        assert!(matches!(
+            move_folder(&root, "archive", "archive/inner"),
+            Err(CommandError::Conflict { .. })
+        ));
+
+        let _ = fs::remove_dir_all(root);
+    }
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .manage(RecentNotesCache::default())
         .manage(PendingClose::default())
-        .setup(|_| Ok(()))
+        .manage(VaultRegistry::default())
+        .setup(|app| {
+            session::restore_session_vaults(app.handle());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             read_dir,
             read_file,
             read_file_base64,
             write_file,
+            save_attachment,
+            list_attachments,
+            rename_attachment,
             create_dir,
             delete_file,
             delete_dir,
-            read_vault_notes,
+            load_vault_notes,
             rename_note,
+            rename_folder,
+            move_items,
+            list_trash,
+            restore_trash_item,
+            empty_vault_trash,
+            snapshot_note,
+            list_note_history,
+            read_note_version,
+            git_repo_info,
+            git_status,
+            git_diff_note,
+            git_commit_all,
+            git_file_log,
             init_vault,
             load_plugins_css,
             save_settings,
             load_settings,
+            load_keymap,
             open_settings_window,
             import_obsidian_vault_with_picker,
             pick_bedrock_vault,
@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Why a `(vault_id, relative_path)` pair was refused.
+#[derive(serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SandboxError {
+    UnknownVault { vault_id: String },
+    EmptyPath,
+    AbsolutePath { path: String },
+    Traversal { path: String },
+    SymlinkEscape { path: String },
+    Io { message: String },
+}
+
+impl fmt::Display for SandboxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownVault { vault_id } => write!(f, "Vault is not open: {vault_id}"),
+            Self::EmptyPath => write!(f, "Path cannot be empty"),
+            Self::AbsolutePath { path } => {
+                write!(f, "Expected a path relative to the vault: {path}")
+            }
+            Self::Traversal { path } => write!(f, "Path leaves the vault: {path}"),
+            Self::SymlinkEscape { path } => {
+                write!(f, "Path resolves outside the vault through a link: {path}")
+            }
+            Self::Io { message } => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for SandboxError {}
+
+/// Vault roots the user has opened through a picker, the default vault, or a
+/// restored session. Ids are the canonical root paths so the frontend can keep
+/// using the strings it already has; roots stay registered until exit.
+#[derive(Default)]
+pub struct VaultRegistry(Mutex<HashMap<String, PathBuf>>);
+
+impl VaultRegistry {
+    pub(crate) fn register(&self, root: &Path) -> Result<String, SandboxError> {
+        let canonical = root.canonicalize().map_err(|e| SandboxError::Io {
+            message: format!("Unable to open vault {}: {e}", root.display()),
+        })?;
+        let id = canonical.to_string_lossy().into_owned();
+        if let Ok(mut guard) = self.0.lock() {
+            guard.insert(id.clone(), canonical);
+        }
+        Ok(id)
+    }
+
+    pub(crate) fn root(&self, vault_id: &str) -> Result<PathBuf, SandboxError> {
+        let unknown = || SandboxError::UnknownVault {
+            vault_id: vault_id.to_string(),
+        };
+        let guard = self.0.lock().map_err(|_| unknown())?;
+        if let Some(root) = guard.get(vault_id) {
+            return Ok(root.clone());
+        }
+        // Accept non-canonical spellings of a registered root, e.g. a trailing
+        // slash or a symlinked parent, but nothing that is not registered.
+        let canonical = Path::new(vault_id).canonicalize().map_err(|_| unknown())?;
+        guard
+            .values()
+            .find(|root| **root == canonical)
+            .cloned()
+            .ok_or_else(unknown)
+    }
+
+    pub(crate) fn resolve(&self, vault_id: &str, rel_path: &str) -> Result<PathBuf, SandboxError> {
+        let root = self.root(vault_id)?;
+        resolve_in_root(&root, rel_path)
+    }
+}
+
+/// Normalises a vault-relative path to forward slashes, refusing absolute
+/// paths and any `..` segment rather than trying to collapse them.
+pub(crate) fn checked_rel_path(rel_path: &str) -> Result<String, SandboxError> {
+    let raw = rel_path.trim().replace('\\', "/");
+    let bytes = raw.as_bytes();
+    let has_drive = bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':';
+    if raw.starts_with('/') || has_drive || Path::new(rel_path.trim()).is_absolute() {
+        return Err(SandboxError::AbsolutePath {
+            path: rel_path.to_string(),
+        });
+    }
+
+    let mut parts = Vec::new();
+    for segment in raw.split('/') {
+        match segment {
+            "" | "." => continue,
+            ".." => {
+                return Err(SandboxError::Traversal {
+                    path: rel_path.to_string(),
+                })
+            }
+            other => parts.push(other),
+        }
+    }
+    if parts.is_empty() {
+        return Err(SandboxError::EmptyPath);
+    }
+    Ok(parts.join("/"))
+}
+
+/// Joins `rel_path` onto an already canonical vault root and verifies that
+/// the deepest existing ancestor still resolves inside the root, so links
+/// inside the vault cannot point reads or writes elsewhere.
+pub(crate) fn resolve_in_root(root: &Path, rel_path: &str) -> Result<PathBuf, SandboxError> {
+    let rel = checked_rel_path(rel_path)?;
+    let candidate = root.join(&rel);
+
+    let mut existing = candidate.as_path();
+    while existing.symlink_metadata().is_err() {
+        match existing.parent() {
+            Some(parent) => existing = parent,
+            None => break,
+        }
+    }
+    let resolved = existing.canonicalize().map_err(|e| SandboxError::Io {
+        message: e.to_string(),
+    })?;
+    if !resolved.starts_with(root) {
+        return Err(SandboxError::SymlinkEscape {
+            path: rel_path.to_string(),
+        });
+    }
+    Ok(candidate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::unique_temp_dir;
+    use std::fs;
+
+    fn registered_vault(prefix: &str) -> (VaultRegistry, String, PathBuf) {
+        let root = unique_temp_dir(prefix);
+        fs::create_dir_all(&root).unwrap();
+        let registry = VaultRegistry::default();
+        let id = registry.register(&root).unwrap();
+        let canonical = root.canonicalize().unwrap();
+        (registry, id, canonical)
+    }
+
+    #[test]
+    fn resolves_relative_paths_including_missing_files() {
+        let (registry, id, root) = registered_vault("sandbox-resolve");
+
+        let resolved = registry.resolve(&id, "notes\\Daily/./Today.md").unwrap();
+        assert_eq!(resolved, root.join("notes/Daily/Today.md"));
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn rejects_unknown_vaults() {
+        let (registry, _id, root) = registered_vault("sandbox-unknown");
+        let other = unique_temp_dir("sandbox-unknown-other");
+        fs::create_dir_all(&other).unwrap();
+
+        let err = registry
+            .resolve(other.to_string_lossy().as_ref(), "Note.md")
+            .unwrap_err();
+        assert!(matches!(err, SandboxError::UnknownVault { .. }));
+
+        let _ = fs::remove_dir_all(root);
+        let _ = fs::remove_dir_all(other);
+    }
+
+    #[test]
+    fn rejects_traversal_absolute_and_empty_paths() {
+        let (registry, id, root) = registered_vault("sandbox-reject");
+
+        assert!(matches!(
+            registry.resolve(&id, "../outside.md"),
+            Err(SandboxError::Traversal { .. })
+        ));
+        assert!(matches!(
+            registry.resolve(&id, "notes/../../outside.md"),
+            Err(SandboxError::Traversal { .. })
+        ));
+        assert!(matches!(
+            registry.resolve(&id, "/etc/passwd"),
+            Err(SandboxError::AbsolutePath { .. })
+        ));
+        assert!(matches!(
+            registry.resolve(&id, "C:\\Windows\\win.ini"),
+            Err(SandboxError::AbsolutePath { .. })
+        ));
+        assert_eq!(registry.resolve(&id, " ./ "), Err(SandboxError::EmptyPath));
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn accepts_non_canonical_spelling_of_registered_root() {
+        let (registry, _id, root) = registered_vault("sandbox-spelling");
+        fs::create_dir_all(root.join("sub")).unwrap();
+        let alias = format!("{}/sub/..", root.display());
+
+        assert_eq!(registry.root(&alias).unwrap(), root);
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rejects_symlinks_that_escape_the_vault() {
+        let (registry, id, root) = registered_vault("sandbox-symlink");
+        let outside = unique_temp_dir("sandbox-symlink-outside");
+        fs::create_dir_all(&outside).unwrap();
+        fs::write(outside.join("secret.md"), "secret").unwrap();
+        std::os::unix::fs::symlink(&outside, root.join("link")).unwrap();
+        fs::create_dir_all(root.join("inner")).unwrap();
+        std::os::unix::fs::symlink(root.join("inner"), root.join("inner-link")).unwrap();
+
+        assert!(matches!(
+            registry.resolve(&id, "link/secret.md"),
+            Err(SandboxError::SymlinkEscape { .. })
+        ));
+        assert!(matches!(
+            registry.resolve(&id, "link/new.md"),
+            Err(SandboxError::SymlinkEscape { .. })
+        ));
+        assert!(registry.resolve(&id, "inner-link/ok.md").is_ok());
+
+        let _ = fs::remove_dir_all(root);
+        let _ = fs::remove_dir_all(outside);
+    }
+}
@@ -7,6 +7,9 @@ use std::sync::Mutex;
 
 use tauri::{AppHandle, Manager, State, WebviewWindow};
 
+use crate::error::CommandError;
+use crate::sandbox::VaultRegistry;
+
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
 pub struct RecentNoteEntry {
     pub path: String,
@@ -14,12 +17,28 @@ pub struct RecentNoteEntry {
     pub last_opened: i64,
 }
 
+/// One open editor tab, restored in order when its vault is reopened.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub struct SavedTab {
+    pub path: String,
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct SavedTabs {
+    pub tabs: Vec<SavedTab>,
+    pub active: Option<String>,
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
 pub struct VaultSessionState {
     pub open_vaults: Vec<String>,
     pub active_vault: Option<String>,
     #[serde(default)]
     pub recent_notes: HashMap<String, Vec<RecentNoteEntry>>,
+    #[serde(default)]
+    pub open_tabs: HashMap<String, SavedTabs>,
 }
 
 fn normalize_vault_session_state(state: VaultSessionState) -> VaultSessionState {
@@ -77,10 +96,20 @@ fn normalize_vault_session_state(state: VaultSessionState) -> VaultSessionState
         recent_notes.insert(normalized, entries);
     }
 
+    // Unlike recent notes, an empty tab list is meaningful: the user closed
+    // every tab in that vault.
+    let mut open_tabs = HashMap::<String, SavedTabs>::new();
+    for (raw_path, tabs) in state.open_tabs.into_iter() {
+        if let Some(normalized) = normalize_path(&raw_path) {
+            open_tabs.insert(normalized, tabs);
+        }
+    }
+
     VaultSessionState {
         open_vaults,
         active_vault,
         recent_notes,
+        open_tabs,
     }
 }
 
@@ -89,6 +118,7 @@ fn merge_vault_session_state(
     open_vaults: Vec<String>,
     active_vault: Option<String>,
     active_vault_recent_notes: Option<Vec<RecentNoteEntry>>,
+    active_vault_tabs: Option<SavedTabs>,
 ) -> VaultSessionState {
     let mut normalized = normalize_vault_session_state(VaultSessionState {
         open_vaults,
@@ -97,8 +127,17 @@ fn merge_vault_session_state(
             .as_ref()
             .map(|b| b.recent_notes.clone())
             .unwrap_or_default(),
+        open_tabs: base
+            .as_ref()
+            .map(|b| b.open_tabs.clone())
+            .unwrap_or_default(),
     });
 
+    if let (Some(active), Some(tabs)) = (&normalized.active_vault, active_vault_tabs) {
+        let key = canonicalize_vault_root(active).to_string_lossy().to_string();
+        normalized.open_tabs.insert(key, tabs);
+    }
+
     if let (Some(active), Some(entries)) = (&normalized.active_vault, &active_vault_recent_notes) {
         if !entries.is_empty() {
             let key = canonicalize_vault_root(active).to_string_lossy().to_string();
@@ -150,7 +189,10 @@ fn read_vault_session_from_path(path: &Path) -> Option<VaultSessionState> {
     serde_json::from_str::<VaultSessionState>(&raw).ok()
 }
 
-fn persist_vault_session_state(app: &AppHandle, state: &VaultSessionState) -> Result<(), String> {
+fn persist_vault_session_state(
+    app: &AppHandle,
+    state: &VaultSessionState,
+) -> Result<(), CommandError> {
     let mut wrote = false;
     let mut last_error = None::<String>;
 
@@ -171,7 +213,43 @@ fn persist_vault_session_state(app: &AppHandle, state: &VaultSessionState) -> Re
     if wrote {
         Ok(())
     } else {
-        Err(last_error.unwrap_or_else(|| "Unable to persist vault session state.".to_string()))
+        Err(CommandError::Io {
+            message: last_error
+                .unwrap_or_else(|| "Unable to persist vault session state.".to_string()),
+        })
+    }
+}
+
+fn read_stored_vault_session(app: &AppHandle) -> Option<VaultSessionState> {
+    let read = |path: Result<PathBuf, String>| {
+        path.ok()
+            .filter(|path| path.exists())
+            .and_then(|path| read_vault_session_from_path(&path))
+    };
+    read(vault_session_state_path(app)).or_else(|| read(vault_session_fallback_path(app)))
+}
+
+/// Drops vaults the user has not opened this run, so the webview cannot plant
+/// a path in the session file and have it registered on the next launch.
+fn registered_only(registry: &VaultRegistry, open_vaults: Vec<String>) -> Vec<String> {
+    open_vaults
+        .into_iter()
+        .filter(|vault| registry.root(vault).is_ok())
+        .collect()
+}
+
+fn register_vaults(registry: &VaultRegistry, state: &VaultSessionState) {
+    for vault in &state.open_vaults {
+        // Ones that no longer exist simply stay unregistered.
+        let _ = registry.register(Path::new(vault));
+    }
+}
+
+/// Registers the vaults from the last session at startup, before the webview
+/// can call any command.
+pub(crate) fn restore_session_vaults(app: &AppHandle) {
+    if let Some(state) = read_stored_vault_session(app) {
+        register_vaults(&app.state::<VaultRegistry>(), &state);
     }
 }
 
@@ -243,22 +321,22 @@ fn read_recent_notes_from_disk(vault_path: &str) -> Vec<RecentNoteEntry> {
 pub(crate) fn write_recent_notes_to_disk(
     vault_path: &str,
     entries: &[RecentNoteEntry],
-) -> Result<(), String> {
+) -> Result<(), CommandError> {
     let root = canonicalize_vault_root(vault_path);
     let dir = root.join(".bedrock");
-    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| CommandError::io(e, &dir))?;
     let path = dir.join("recent.json");
-    let json = serde_json::to_string(entries).map_err(|e| e.to_string())?;
-    let mut f = fs::File::create(&path).map_err(|e| e.to_string())?;
-    f.write_all(json.as_bytes()).map_err(|e| e.to_string())?;
-    f.sync_all().map_err(|e| e.to_string())
+    let json = serde_json::to_string(entries)?;
+    let mut f = fs::File::create(&path).map_err(|e| CommandError::io(e, &path))?;
+    f.write_all(json.as_bytes()).map_err(|e| CommandError::io(e, &path))?;
+    f.sync_all().map_err(|e| CommandError::io(e, &path))
 }
 
 fn cache_recent_notes_impl(
     vault_path: &str,
     entries: &[RecentNoteEntry],
     cache: &RecentNotesCache,
-) -> Result<(), String> {
+) -> Result<(), CommandError> {
     let canon = canonicalize_vault_root(vault_path);
     let key = canon.to_string_lossy().to_string();
 
@@ -288,15 +366,18 @@ fn cache_recent_notes_impl(
     write_recent_notes_to_disk(canon.to_string_lossy().as_ref(), entries)
 }
 
-#[tauri::command]
+#[tauri::command(rename_all = "snake_case")]
 pub fn read_recent_notes(
-    vault_path: String,
+    vault_id: String,
+    registry: State<VaultRegistry>,
     cache: State<RecentNotesCache>,
 ) -> Vec<RecentNoteEntry> {
-    let entries = read_recent_notes_from_disk(&vault_path);
+    let Ok(root) = registry.root(&vault_id) else {
+        return Vec::new();
+    };
+    let key = root.to_string_lossy().to_string();
+    let entries = read_recent_notes_from_disk(&key);
     if !entries.is_empty() {
-        let canon = canonicalize_vault_root(&vault_path);
-        let key = canon.to_string_lossy().to_string();
         if let Ok(mut c) = cache.0.lock() {
             c.insert(key, entries.clone());
         }
@@ -304,51 +385,42 @@ pub fn read_recent_notes(
     entries
 }
 
-#[tauri::command]
+#[tauri::command(rename_all = "snake_case")]
 pub fn save_recent_notes(
-    vault_path: String,
+    vault_id: String,
     entries: Vec<RecentNoteEntry>,
+    registry: State<VaultRegistry>,
     cache: State<RecentNotesCache>,
-) -> Result<(), String> {
-    cache_recent_notes_impl(&vault_path, &entries, &cache)
+) -> Result<(), CommandError> {
+    let root = registry.root(&vault_id)?;
+    cache_recent_notes_impl(root.to_string_lossy().as_ref(), &entries, &cache)
 }
 
-#[tauri::command]
+#[tauri::command(rename_all = "snake_case")]
 pub fn cache_recent_notes(
-    vault_path: String,
+    vault_id: String,
     entries: Vec<RecentNoteEntry>,
+    registry: State<VaultRegistry>,
     cache: State<RecentNotesCache>,
-) -> Result<(), String> {
-    cache_recent_notes_impl(&vault_path, &entries, &cache)
+) -> Result<(), CommandError> {
+    let root = registry.root(&vault_id)?;
+    cache_recent_notes_impl(root.to_string_lossy().as_ref(), &entries, &cache)
 }
 
 #[tauri::command]
 pub fn close_window_now(
     window: WebviewWindow,
     pending: State<PendingClose>,
-) -> Result<(), String> {
+) -> Result<(), CommandError> {
     pending.0.store(true, Ordering::SeqCst);
-    window.close().map_err(|e| e.to_string())
+    window.close().map_err(|e| CommandError::Io {
+        message: e.to_string(),
+    })
 }
 
 #[tauri::command]
-pub fn load_vault_session(app: AppHandle) -> Result<VaultSessionState, String> {
-    let mut parsed = None::<VaultSessionState>;
-
-    if let Ok(path) = vault_session_state_path(&app) {
-        if path.exists() {
-            parsed = read_vault_session_from_path(&path);
-        }
-    }
-    if parsed.is_none() {
-        if let Ok(path) = vault_session_fallback_path(&app) {
-            if path.exists() {
-                parsed = read_vault_session_from_path(&path);
-            }
-        }
-    }
-
-    let parsed = parsed.unwrap_or_default();
+pub fn load_vault_session(app: AppHandle) -> Result<VaultSessionState, CommandError> {
+    let parsed = read_stored_vault_session(&app).unwrap_or_default();
     let mut normalized = normalize_vault_session_state(parsed);
 
     // Hydrate recent-notes state for each open vault so the frontend can restore
@@ -379,31 +451,22 @@ pub fn load_vault_session(app: AppHandle) -> Result<VaultSessionState, String> {
 #[tauri::command]
 pub fn save_vault_session(
     app: AppHandle,
+    registry: State<VaultRegistry>,
     open_vaults: Vec<String>,
     active_vault: Option<String>,
     active_vault_recent_notes: Option<Vec<RecentNoteEntry>>,
-) -> Result<VaultSessionState, String> {
+    active_vault_tabs: Option<SavedTabs>,
+) -> Result<VaultSessionState, CommandError> {
     // Merge with existing session so we never overwrite recent_notes with empty.
     // Otherwise every frontend persist (vault switch, startup) would wipe recent_notes
     // from the session file and we'd rely only on load_vault_session's hydrate step.
-    let mut base = None::<VaultSessionState>;
-    if let Ok(path) = vault_session_state_path(&app) {
-        if path.exists() {
-            base = read_vault_session_from_path(&path);
-        }
-    }
-    if base.is_none() {
-        if let Ok(path) = vault_session_fallback_path(&app) {
-            if path.exists() {
-                base = read_vault_session_from_path(&path);
-            }
-        }
-    }
+    let base = read_stored_vault_session(&app);
     let normalized = merge_vault_session_state(
         base,
-        open_vaults,
-        active_vault,
+        registered_only(&registry, open_vaults),
+        active_vault.filter(|vault| registry.root(vault).is_ok()),
         active_vault_recent_notes,
+        active_vault_tabs,
     );
     persist_vault_session_state(&app, &normalized)?;
     Ok(normalized)
@@ -424,18 +487,8 @@ pub fn is_close_allowed(pending: &PendingClose) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::unique_temp_dir;
     use std::fs;
-    use std::path::{Path, PathBuf};
-    use std::time::{SystemTime, UNIX_EPOCH};
-
-    fn unique_temp_dir(prefix: &str) -> PathBuf {
-        let nanos = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("clock should be valid")
-            .as_nanos();
-        let pid = std::process::id();
-        std::env::temp_dir().join(format!("bedrock-{prefix}-{pid}-{nanos}"))
-    }
 
     #[test]
     fn write_and_read_recent_notes_roundtrip() {
@@ -505,6 +558,7 @@ mod tests {
             ],
             active_vault: Some(canonical_str.clone()),
             recent_notes: HashMap::new(),
+            open_tabs: HashMap::new(),
         };
 
         let normalized = normalize_vault_session_state(state);
@@ -537,6 +591,7 @@ mod tests {
             open_vaults: vec![canonical_str.clone()],
             active_vault: Some(canonical_str.clone()),
             recent_notes: recent_map,
+            open_tabs: HashMap::new(),
         });
 
         let merged = merge_vault_session_state(
@@ -544,6 +599,7 @@ mod tests {
             vec![canonical_str.clone()],
             Some(canonical_str.clone()),
             None,
+            None,
         );
 
         let list = merged
@@ -578,6 +634,7 @@ mod tests {
             open_vaults: vec![canonical_str.clone()],
             active_vault: Some(canonical_str.clone()),
             recent_notes: recent_map,
+            open_tabs: HashMap::new(),
         });
 
         let new_entry = RecentNoteEntry {
@@ -591,6 +648,7 @@ mod tests {
             vec![canonical_str.clone()],
             Some(canonical_str.clone()),
             Some(vec![new_entry.clone()]),
+            None,
         );
 
         let list = merged
@@ -627,6 +685,7 @@ mod tests {
             vec![canonical_str.clone()],
             Some(canonical_str.clone()),
             None,
+            None,
         );
 
         let list = merged
@@ -639,5 +698,86 @@ mod tests {
 
         let _ = fs::remove_dir_all(&root);
     }
-}
 
+    #[test]
+    fn merge_session_stores_active_tabs_and_keeps_other_vaults() {
+        let root = unique_temp_dir("vault-session-merge-tabs");
+        let first = root.join("first");
+        let second = root.join("second");
+        fs::create_dir_all(&first).unwrap();
+        fs::create_dir_all(&second).unwrap();
+        let first_str = first.canonicalize().unwrap().to_string_lossy().to_string();
+        let second_str = second.canonicalize().unwrap().to_string_lossy().to_string();
+
+        let second_tabs = SavedTabs {
+            tabs: vec![SavedTab {
+                path: "Other.md".to_string(),
+                pinned: true,
+            }],
+            active: Some("Other.md".to_string()),
+        };
+        let mut open_tabs = HashMap::new();
+        open_tabs.insert(second_str.clone(), second_tabs.clone());
+        let base = Some(VaultSessionState {
+            open_vaults: vec![first_str.clone(), second_str.clone()],
+            active_vault: Some(second_str.clone()),
+            recent_notes: HashMap::new(),
+            open_tabs,
+        });
+
+        let first_tabs = SavedTabs {
+            tabs: vec![SavedTab {
+                path: "Note.md".to_string(),
+                pinned: false,
+            }],
+            active: Some("Note.md".to_string()),
+        };
+        let merged = merge_vault_session_state(
+            base,
+            vec![first_str.clone(), second_str.clone()],
+            Some(first_str.clone()),
+            None,
+            Some(first_tabs.clone()),
+        );
+
+        assert_eq!(merged.open_tabs.get(&first_str), Some(&first_tabs));
+        assert_eq!(merged.open_tabs.get(&second_str), Some(&second_tabs));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn unregistered_open_vaults_are_dropped_and_never_registered() {
+        let root = unique_temp_dir("vault-session-registry");
+        let opened = root.join("opened");
+        let planted = root.join("planted");
+        fs::create_dir_all(&opened).unwrap();
+        fs::create_dir_all(&planted).unwrap();
+        let planted_str = planted.to_string_lossy().to_string();
+
+        let registry = VaultRegistry::default();
+        let opened_id = registry.register(&opened).unwrap();
+
+        let saved = merge_vault_session_state(
+            None,
+            registered_only(
+                &registry,
+                vec![opened_id.clone(), planted_str.clone()],
+            ),
+            Some(planted_str.clone()).filter(|vault| registry.root(vault).is_ok()),
+            None,
+            None,
+        );
+        assert_eq!(saved.open_vaults, vec![opened_id.clone()]);
+        assert_eq!(saved.active_vault, Some(opened_id.clone()));
+        assert!(registry.root(&planted_str).is_err());
+
+        // The next launch registers only what the session file kept.
+        let next_run = VaultRegistry::default();
+        register_vaults(&next_run, &saved);
+        assert!(next_run.root(&opened_id).is_ok());
+        assert!(next_run.root(&planted_str).is_err());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
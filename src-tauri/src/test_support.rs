@@ -0,0 +1,23 @@
+//! Helpers shared by the unit tests of every backend module.
+
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A fresh path under the system temp dir; nothing is created there.
+pub(crate) fn unique_temp_dir(prefix: &str) -> PathBuf {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("clock should be valid")
+        .as_nanos();
+    let pid = std::process::id();
+    std::env::temp_dir().join(format!("bedrock-{prefix}-{pid}-{nanos}"))
+}
+
+/// Like `unique_temp_dir`, but created and canonicalized, ready to serve as
+/// a vault root.
+pub(crate) fn temp_vault(prefix: &str) -> PathBuf {
+    let dir = unique_temp_dir(prefix);
+    fs::create_dir_all(&dir).expect("temp dir should be created");
+    dir.canonicalize().expect("temp dir should canonicalize")
+}
@@ -0,0 +1,432 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use tauri::State;
+
+use crate::error::CommandError;
+use crate::sandbox::VaultRegistry;
+use crate::{current_time_millis, normalize_rel_path};
+
+const MILLIS_PER_DAY: i64 = 24 * 60 * 60 * 1000;
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub struct TrashEntry {
+    pub id: String,
+    pub original_path: String,
+    pub deleted_at: i64,
+    pub is_dir: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+struct TrashManifest {
+    #[serde(default)]
+    entries: Vec<TrashEntry>,
+}
+
+fn trash_root(vault_root: &Path) -> PathBuf {
+    vault_root.join(".bedrock").join("trash")
+}
+
+fn trash_items_dir(vault_root: &Path) -> PathBuf {
+    trash_root(vault_root).join("items")
+}
+
+fn manifest_path(vault_root: &Path) -> PathBuf {
+    trash_root(vault_root).join("manifest.json")
+}
+
+/// Loads the manifest; a missing one is an empty trash. A manifest that no
+/// longer parses is renamed to `manifest.corrupt-<millis>.json` rather than
+/// treated as empty, so the next write cannot destroy its records.
+fn read_manifest(vault_root: &Path) -> Result<TrashManifest, CommandError> {
+    let path = manifest_path(vault_root);
+    let raw = match fs::read_to_string(&path) {
+        Ok(raw) => raw,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(TrashManifest::default()),
+        Err(e) => return Err(CommandError::io(e, &path)),
+    };
+    match serde_json::from_str::<TrashManifest>(&raw) {
+        Ok(manifest) => Ok(manifest),
+        Err(_) => {
+            let aside = trash_root(vault_root)
+                .join(format!("manifest.corrupt-{}.json", current_time_millis()));
+            fs::rename(&path, &aside).map_err(|e| CommandError::io(e, &aside))?;
+            Ok(TrashManifest::default())
+        }
+    }
+}
+
+fn write_manifest(vault_root: &Path, manifest: &TrashManifest) -> Result<(), CommandError> {
+    let dir = trash_root(vault_root);
+    fs::create_dir_all(&dir).map_err(|e| CommandError::io(e, &dir))?;
+    let json = serde_json::to_string_pretty(manifest)?;
+    let path = manifest_path(vault_root);
+    fs::write(&path, json).map_err(|e| CommandError::io(e, &path))
+}
+
+fn remove_trashed_item(vault_root: &Path, entry: &TrashEntry) -> Result<(), CommandError> {
+    let stored = trash_items_dir(vault_root).join(&entry.id);
+    if stored.is_dir() {
+        fs::remove_dir_all(&stored).map_err(|e| CommandError::io(e, &stored))
+    } else if stored.exists() {
+        fs::remove_file(&stored).map_err(|e| CommandError::io(e, &stored))
+    } else {
+        Ok(())
+    }
+}
+
+/// Picks a free restore location next to the original path. Restoring never
+/// overwrites a note or folder that was created after the item was deleted.
+fn unique_restore_target(vault_root: &Path, rel_path: &str) -> Result<PathBuf, CommandError> {
+    let direct = vault_root.join(rel_path);
+    if !direct.exists() {
+        return Ok(direct);
+    }
+
+    let rel = Path::new(rel_path);
+    let parent = rel.parent().unwrap_or_else(|| Path::new(""));
+    let stem = rel
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .filter(|s| !s.is_empty())
+        .unwrap_or("Restored");
+    let ext = rel.extension().and_then(|s| s.to_str());
+
+    for idx in 1..=10_000usize {
+        let filename = match ext {
+            Some(ext) => format!("{stem} (restored {idx}).{ext}"),
+            None => format!("{stem} (restored {idx})"),
+        };
+        let candidate = vault_root.join(parent).join(filename);
+        if !candidate.exists() {
+            return Ok(candidate);
+        }
+    }
+
+    Err(CommandError::Conflict {
+        message: format!("Unable to find a free restore location for {rel_path}"),
+    })
+}
+
+pub(crate) fn move_to_trash(
+    vault_root: &Path,
+    rel_path: &str,
+    now: i64,
+) -> Result<TrashEntry, CommandError> {
+    let rel = normalize_rel_path(rel_path);
+    if rel.is_empty() {
+        return Err(CommandError::invalid_name(
+            rel_path,
+            "Cannot move the vault root to the trash",
+        ));
+    }
+    if rel == ".bedrock" || rel.starts_with(".bedrock/") {
+        return Err(CommandError::PermissionDenied { path: rel });
+    }
+    let source = vault_root.join(&rel);
+    if !source.exists() {
+        return Err(CommandError::not_found(rel));
+    }
+
+    let items_dir = trash_items_dir(vault_root);
+    fs::create_dir_all(&items_dir).map_err(|e| CommandError::io(e, &items_dir))?;
+
+    let mut manifest = read_manifest(vault_root)?;
+    let mut id = now.to_string();
+    let mut suffix = 1usize;
+    while items_dir.join(&id).exists() || manifest.entries.iter().any(|e| e.id == id) {
+        id = format!("{now}-{suffix}");
+        suffix += 1;
+    }
+
+    let entry = TrashEntry {
+        id: id.clone(),
+        original_path: rel,
+        deleted_at: now,
+        is_dir: source.is_dir(),
+    };
+    fs::rename(&source, items_dir.join(&id)).map_err(|e| CommandError::io(e, &entry.original_path))?;
+    manifest.entries.push(entry.clone());
+    write_manifest(vault_root, &manifest)?;
+    Ok(entry)
+}
+
+pub(crate) fn restore_from_trash(vault_root: &Path, id: &str) -> Result<String, CommandError> {
+    let mut manifest = read_manifest(vault_root)?;
+    let Some(index) = manifest.entries.iter().position(|e| e.id == id) else {
+        return Err(CommandError::not_found(format!(".bedrock/trash/{id}")));
+    };
+    let entry = manifest.entries[index].clone();
+    let stored = trash_items_dir(vault_root).join(&entry.id);
+    if !stored.exists() {
+        manifest.entries.remove(index);
+        write_manifest(vault_root, &manifest)?;
+        return Err(CommandError::not_found(entry.original_path));
+    }
+
+    let target = unique_restore_target(vault_root, &entry.original_path)?;
+    // The original folder may have been deleted or renamed since; recreate it.
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).map_err(|e| CommandError::io(e, parent))?;
+    }
+    fs::rename(&stored, &target).map_err(|e| CommandError::io(e, &entry.original_path))?;
+
+    manifest.entries.remove(index);
+    write_manifest(vault_root, &manifest)?;
+
+    let restored = target
+        .strip_prefix(vault_root)
+        .map_err(|_| CommandError::OutsideVault {
+            path: entry.original_path.clone(),
+        })?
+        .to_string_lossy()
+        .replace('\\', "/");
+    Ok(restored)
+}
+
+pub(crate) fn empty_trash(vault_root: &Path) -> Result<usize, CommandError> {
+    let manifest = read_manifest(vault_root)?;
+    let removed = manifest.entries.len();
+    let items_dir = trash_items_dir(vault_root);
+    if items_dir.exists() {
+        fs::remove_dir_all(&items_dir).map_err(|e| CommandError::io(e, &items_dir))?;
+    }
+    write_manifest(vault_root, &TrashManifest::default())?;
+    Ok(removed)
+}
+
+/// Permanently removes items older than `retention_days`. A retention of zero
+/// keeps trashed items until the trash is emptied by hand.
+pub(crate) fn purge_expired_trash(
+    vault_root: &Path,
+    retention_days: u32,
+    now: i64,
+) -> Result<usize, CommandError> {
+    if retention_days == 0 {
+        return Ok(0);
+    }
+    let cutoff = now - i64::from(retention_days) * MILLIS_PER_DAY;
+    let mut manifest = read_manifest(vault_root)?;
+    let (expired, kept): (Vec<_>, Vec<_>) = manifest
+        .entries
+        .into_iter()
+        .partition(|e| e.deleted_at < cutoff);
+    for entry in &expired {
+        remove_trashed_item(vault_root, entry)?;
+    }
+    manifest.entries = kept;
+    if !expired.is_empty() {
+        write_manifest(vault_root, &manifest)?;
+    }
+    Ok(expired.len())
+}
+
+pub(crate) fn list_trash_entries(vault_root: &Path) -> Result<Vec<TrashEntry>, CommandError> {
+    let mut entries = read_manifest(vault_root)?.entries;
+    entries.sort_by_key(|e| std::cmp::Reverse(e.deleted_at));
+    Ok(entries)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn list_trash(
+    vault_id: &str,
+    retention_days: u32,
+    registry: State<VaultRegistry>,
+) -> Result<Vec<TrashEntry>, CommandError> {
+    let root = registry.root(vault_id)?;
+    purge_expired_trash(&root, retention_days, current_time_millis())?;
+    list_trash_entries(&root)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn restore_trash_item(
+    vault_id: &str,
+    id: &str,
+    registry: State<VaultRegistry>,
+) -> Result<String, CommandError> {
+    restore_from_trash(&registry.root(vault_id)?, id)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn empty_vault_trash(
+    vault_id: &str,
+    registry: State<VaultRegistry>,
+) -> Result<usize, CommandError> {
+    empty_trash(&registry.root(vault_id)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::unique_temp_dir;
+
+    #[test]
+    fn move_to_trash_records_manifest_entry() {
+        let vault = unique_temp_dir("trash-move");
+        fs::create_dir_all(vault.join("notes")).unwrap();
+        fs::write(vault.join("notes/A.md"), "# A\n").unwrap();
+
+        let entry = move_to_trash(&vault, "notes/A.md", 1_000).unwrap();
+        assert_eq!(entry.original_path, "notes/A.md");
+        assert_eq!(entry.deleted_at, 1_000);
+        assert!(!entry.is_dir);
+        assert!(!vault.join("notes/A.md").exists());
+
+        let listed = list_trash_entries(&vault).unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, entry.id);
+
+        let _ = fs::remove_dir_all(vault);
+    }
+
+    #[test]
+    fn corrupt_manifest_is_kept_aside_instead_of_overwritten() {
+        let vault = unique_temp_dir("trash-corrupt");
+        fs::create_dir_all(trash_root(&vault)).unwrap();
+        fs::write(manifest_path(&vault), "{ not json").unwrap();
+        fs::write(vault.join("A.md"), "a").unwrap();
+
+        move_to_trash(&vault, "A.md", 7).unwrap();
+        assert_eq!(list_trash_entries(&vault).unwrap().len(), 1);
+        let kept: Vec<String> = fs::read_dir(trash_root(&vault))
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .filter(|name| name.starts_with("manifest.corrupt-"))
+            .collect();
+        assert_eq!(kept.len(), 1);
+        assert_eq!(
+            fs::read_to_string(trash_root(&vault).join(&kept[0])).unwrap(),
+            "{ not json"
+        );
+
+        let _ = fs::remove_dir_all(vault);
+    }
+
+    #[test]
+    fn restore_recreates_missing_parent_folders() {
+        let vault = unique_temp_dir("trash-restore-parent");
+        fs::create_dir_all(vault.join("deep/nested")).unwrap();
+        fs::write(vault.join("deep/nested/Note.md"), "body").unwrap();
+
+        let entry = move_to_trash(&vault, "deep/nested/Note.md", 5).unwrap();
+        fs::remove_dir_all(vault.join("deep")).unwrap();
+
+        let restored = restore_from_trash(&vault, &entry.id).unwrap();
+        assert_eq!(restored, "deep/nested/Note.md");
+        assert_eq!(
+            fs::read_to_string(vault.join("deep/nested/Note.md")).unwrap(),
+            "body"
+        );
+        assert!(list_trash_entries(&vault).unwrap().is_empty());
+
+        let _ = fs::remove_dir_all(vault);
+    }
+
+    #[test]
+    fn restore_does_not_overwrite_existing_note() {
+        let vault = unique_temp_dir("trash-restore-conflict");
+        fs::create_dir_all(&vault).unwrap();
+        fs::write(vault.join("Note.md"), "old").unwrap();
+
+        let entry = move_to_trash(&vault, "Note.md", 5).unwrap();
+        fs::write(vault.join("Note.md"), "new").unwrap();
+
+        let restored = restore_from_trash(&vault, &entry.id).unwrap();
+        assert_eq!(restored, "Note (restored 1).md");
+        assert_eq!(fs::read_to_string(vault.join("Note.md")).unwrap(), "new");
+        assert_eq!(
+            fs::read_to_string(vault.join("Note (restored 1).md")).unwrap(),
+            "old"
+        );
+
+        let _ = fs::remove_dir_all(vault);
+    }
+
+    #[test]
+    fn folders_round_trip_through_trash() {
+        let vault = unique_temp_dir("trash-folder");
+        fs::create_dir_all(vault.join("Projects/Sub")).unwrap();
+        fs::write(vault.join("Projects/Sub/Plan.md"), "plan").unwrap();
+
+        let entry = move_to_trash(&vault, "Projects", 10).unwrap();
+        assert!(entry.is_dir);
+        assert!(!vault.join("Projects").exists());
+
+        restore_from_trash(&vault, &entry.id).unwrap();
+        assert!(vault.join("Projects/Sub/Plan.md").exists());
+
+        let _ = fs::remove_dir_all(vault);
+    }
+
+    #[test]
+    fn same_millisecond_deletions_get_distinct_ids() {
+        let vault = unique_temp_dir("trash-ids");
+        fs::create_dir_all(&vault).unwrap();
+        fs::write(vault.join("A.md"), "a").unwrap();
+        fs::write(vault.join("B.md"), "b").unwrap();
+
+        let a = move_to_trash(&vault, "A.md", 42).unwrap();
+        let b = move_to_trash(&vault, "B.md", 42).unwrap();
+        assert_ne!(a.id, b.id);
+        assert_eq!(list_trash_entries(&vault).unwrap().len(), 2);
+
+        let _ = fs::remove_dir_all(vault);
+    }
+
+    #[test]
+    fn purge_removes_only_expired_items() {
+        let vault = unique_temp_dir("trash-retention");
+        fs::create_dir_all(&vault).unwrap();
+        fs::write(vault.join("Old.md"), "old").unwrap();
+        fs::write(vault.join("Fresh.md"), "fresh").unwrap();
+
+        let now = 100 * MILLIS_PER_DAY;
+        let old = move_to_trash(&vault, "Old.md", now - 31 * MILLIS_PER_DAY).unwrap();
+        move_to_trash(&vault, "Fresh.md", now - MILLIS_PER_DAY).unwrap();
+
+        assert_eq!(purge_expired_trash(&vault, 0, now).unwrap(), 0);
+        assert_eq!(purge_expired_trash(&vault, 30, now).unwrap(), 1);
+
+        let remaining = list_trash_entries(&vault).unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].original_path, "Fresh.md");
+        assert!(!trash_items_dir(&vault).join(old.id).exists());
+
+        let _ = fs::remove_dir_all(vault);
+    }
+
+    #[test]
+    fn empty_trash_removes_everything() {
+        let vault = unique_temp_dir("trash-empty");
+        fs::create_dir_all(&vault).unwrap();
+        fs::write(vault.join("A.md"), "a").unwrap();
+        move_to_trash(&vault, "A.md", 1).unwrap();
+
+        assert_eq!(empty_trash(&vault).unwrap(), 1);
+        assert!(list_trash_entries(&vault).unwrap().is_empty());
+        assert!(!trash_items_dir(&vault).exists());
+
+        let _ = fs::remove_dir_all(vault);
+    }
+
+    #[test]
+    fn refuses_to_trash_vault_root_or_config() {
+        let vault = unique_temp_dir("trash-guard");
+        fs::create_dir_all(vault.join(".bedrock")).unwrap();
+
+        assert!(move_to_trash(&vault, "", 1).is_err());
+        assert_eq!(
+            move_to_trash(&vault, ".bedrock", 1),
+            Err(CommandError::PermissionDenied {
+                path: ".bedrock".to_string()
+            })
+        );
+        assert_eq!(
+            move_to_trash(&vault, "Missing.md", 1),
+            Err(CommandError::not_found("Missing.md"))
+        );
+
+        let _ = fs::remove_dir_all(vault);
+    }
+}
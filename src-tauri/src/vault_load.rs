@@ -0,0 +1,210 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use tauri::ipc::Channel;
+use tauri::State;
+
+use crate::collect_note_paths;
+use crate::error::CommandError;
+use crate::sandbox::VaultRegistry;
+
+/// Notes per `Batch` event; small enough that the first batch arrives quickly
+/// even when individual notes are large.
+const VAULT_LOAD_BATCH_SIZE: usize = 200;
+
+#[derive(serde::Serialize, Clone, Debug, PartialEq)]
+pub struct VaultNote {
+    pub path: String,
+    pub content: String,
+}
+
+/// Progress messages streamed while a vault's notes are read.
+#[derive(serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum VaultLoadEvent {
+    Started {
+        total: usize,
+    },
+    Batch {
+        notes: Vec<VaultNote>,
+        loaded: usize,
+        total: usize,
+    },
+    Unreadable {
+        path: String,
+        error: CommandError,
+    },
+    Finished {
+        loaded: usize,
+        unreadable: usize,
+    },
+}
+
+fn read_note(abs: &Path, rel_path: &str) -> Result<String, CommandError> {
+    let bytes = fs::read(abs).map_err(|e| CommandError::io(e, rel_path))?;
+    String::from_utf8(bytes).map_err(|_| CommandError::Io {
+        message: format!("{rel_path} is not valid UTF-8"),
+    })
+}
+
+/// Reads every note under `root`, handing them to `emit` in batches. Notes
+/// that cannot be read are reported individually and do not stop the load.
+pub(crate) fn stream_vault_notes(
+    root: &Path,
+    batch_size: usize,
+    mut emit: impl FnMut(VaultLoadEvent) -> Result<(), CommandError>,
+) -> Result<(), CommandError> {
+    let paths = collect_note_paths(&root.to_string_lossy())?;
+    let total = paths.len();
+    emit(VaultLoadEvent::Started { total })?;
+
+    let mut batch = Vec::with_capacity(batch_size.min(total));
+    let mut loaded = 0usize;
+    let mut unreadable = 0usize;
+    for rel_path in paths {
+        match read_note(&root.join(&rel_path), &rel_path) {
+            Ok(content) => batch.push(VaultNote {
+                path: rel_path,
+                content,
+            }),
+            Err(error) => {
+                unreadable += 1;
+                emit(VaultLoadEvent::Unreadable {
+                    path: rel_path,
+                    error,
+                })?;
+            }
+        }
+        if batch.len() >= batch_size.max(1) {
+            loaded += batch.len();
+            emit(VaultLoadEvent::Batch {
+                notes: std::mem::take(&mut batch),
+                loaded,
+                total,
+            })?;
+        }
+    }
+    if !batch.is_empty() {
+        loaded += batch.len();
+        emit(VaultLoadEvent::Batch {
+            notes: batch,
+            loaded,
+            total,
+        })?;
+    }
+    emit(VaultLoadEvent::Finished { loaded, unreadable })
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn load_vault_notes(
+    vault_id: String,
+    on_event: Channel<VaultLoadEvent>,
+    registry: State<'_, VaultRegistry>,
+) -> Result<(), CommandError> {
+    let root: PathBuf = registry.root(&vault_id)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        stream_vault_notes(&root, VAULT_LOAD_BATCH_SIZE, |event| {
+            on_event.send(event).map_err(|e| CommandError::Io {
+                message: e.to_string(),
+            })
+        })
+    })
+    .await
+    .map_err(|e| CommandError::Io {
+        message: e.to_string(),
+    })?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::unique_temp_dir;
+
+    fn collect_events(root: &Path, batch_size: usize) -> Vec<VaultLoadEvent> {
+        let mut events = Vec::new();
+        stream_vault_notes(root, batch_size, |event| {
+            events.push(event);
+            Ok(())
+        })
+        .unwrap();
+        events
+    }
+
+    #[test]
+    fn streams_notes_in_batches_with_progress() {
+        let vault = unique_temp_dir("vault-load-batches");
+        fs::create_dir_all(vault.join("sub")).unwrap();
+        fs::write(vault.join("A.md"), "a").unwrap();
+        fs::write(vault.join("B.md"), "b").unwrap();
+        fs::write(vault.join("sub/C.md"), "c").unwrap();
+
+        let events = collect_events(&vault, 2);
+        assert_eq!(events[0], VaultLoadEvent::Started { total: 3 });
+        let progress: Vec<(usize, usize)> = events
+            .iter()
+            .filter_map(|e| match e {
+                VaultLoadEvent::Batch {
+                    notes,
+                    loaded,
+                    total,
+                } => {
+                    assert_eq!(*total, 3);
+                    Some((notes.len(), *loaded))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(progress, vec![(2, 2), (1, 3)]);
+        assert_eq!(
+            events.last(),
+            Some(&VaultLoadEvent::Finished {
+                loaded: 3,
+                unreadable: 0
+            })
+        );
+
+        let _ = fs::remove_dir_all(vault);
+    }
+
+    #[test]
+    fn reports_invalid_utf8_instead_of_loading_empty_text() {
+        let vault = unique_temp_dir("vault-load-utf8");
+        fs::create_dir_all(&vault).unwrap();
+        fs::write(vault.join("Good.md"), "fine").unwrap();
+        fs::write(vault.join("Bad.md"), [0xff, 0xfe, 0x00, 0x80]).unwrap();
+
+        let events = collect_events(&vault, 10);
+        assert!(events.iter().any(|e| matches!(
+            e,
+            VaultLoadEvent::Unreadable { path, error: CommandError::Io { .. } } if path == "Bad.md"
+        )));
+        let loaded: Vec<&str> = events
+            .iter()
+            .filter_map(|e| match e {
+                VaultLoadEvent::Batch { notes, .. } => Some(notes),
+                _ => None,
+            })
+            .flatten()
+            .map(|n| n.path.as_str())
+            .collect();
+        assert_eq!(loaded, vec!["Good.md"]);
+        assert_eq!(
+            events.last(),
+            Some(&VaultLoadEvent::Finished {
+                loaded: 1,
+                unreadable: 1
+            })
+        );
+
+        let _ = fs::remove_dir_all(vault);
+    }
+
+    #[test]
+    fn serializes_events_with_tag_and_data() {
+        let json = serde_json::to_value(VaultLoadEvent::Started { total: 4 }).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "event": "started", "data": { "total": 4 } })
+        );
+    }
+}
@@ -1,15 +1,50 @@
-use crate::app_state::{AppSettings, RecentNoteEntry};
+use crate::app_state::{
+    AppSettings, AttachmentLinkFormat, AttachmentLocation, CommandError, GitLogEntry, GitRepoInfo,
+    HistoryVersion, RecentNoteEntry, SavedTabs, TrashEntry, VaultLoadEvent,
+};
+use crate::attachments::{
+    attachment_file_name, attachment_folder, attachment_link, encode_base64, orphaned_attachments,
+};
 use crate::editor_core::{
-    apply_markdown_command, ChangeOrigin, EditorSnapshot, MarkdownCommand, Selection, TextChange,
-    Transaction,
+    add_cursor_on_adjacent_line, build_markdown_transaction, column_selection, delete_transaction,
+    insert_text_transaction, link_selection_transaction, minimal_text_change, mirror_transaction,
+    replace_document_transaction, select_next_occurrence, ChangeOrigin, EditorSnapshot, History, MarkdownCommand, Selection,
+    SelectionSet, Transaction,
 };
-use crate::markdown_syntax::{FileCache, MetadataCacheState};
+use crate::git_panel::GitPanel;
+use crate::history_panel::HistoryPanel;
+use crate::html_to_markdown::html_to_markdown;
+use crate::markdown_syntax::{layer_find_matches, FileCache, MetadataCacheState};
 use crate::metadata_sidebar::MetadataSidebar;
-use crate::path_utils::{collapse_path, normalize_rel_path};
+use crate::path_utils::{collapse_path, moved_path, normalize_rel_path};
 use crate::recent_notes_pane::RecentNotesPane;
 use crate::sidebar_panel::SidebarPanel;
-use crate::sidebar_tree::{expand_parent_folders, SidebarContextMenu};
+use crate::sidebar_tree::{
+    drag_roots, expand_parent_folders, is_within, numbered_name, parent_folder, SidebarContextMenu,
+};
+use crate::split_pane::{SplitDirection, SplitPane};
+use crate::tab_bar::TabBar;
+use crate::command_palette::CommandPalette;
+use crate::quick_switcher::QuickSwitcher;
+use crate::link_completion::{accept_link_transaction, link_query_at, link_suggestions, LinkQuery};
+use crate::metadata_completion::{
+    accept_metadata_transaction, metadata_query_at, metadata_suggestions, MetadataQuery,
+};
+use crate::slash_menu::{
+    accept_slash_transaction, slash_query_at, slash_suggestions, template_paths, SlashContext,
+    SlashQuery, TEMPLATE_ID_PREFIX,
+};
+use crate::suggestion_popup::{Suggestion, SuggestionPopup};
+use crate::commands::{
+    find_command, remember_command, CommandAction, CommandContext, CommandScope, CommandSpec,
+    KeyResolution, KeyStroke, Keymap,
+};
 use crate::editor_pane::EditorPane;
+use crate::editor_tabs::{EditorTab, EditorTabs};
+use crate::find_bar::FindBar;
+use crate::find_replace::{
+    find_matches, replace_all_transaction, replace_match_transaction, step_match, FindOptions,
+};
 use crate::tauri_bridge;
 use crate::top_bar::TopBar;
 use js_sys::{Object, Reflect, Date};
@@ -24,6 +59,31 @@ use wasm_bindgen::JsCast;
 
 // File tree and sidebar helpers now live in `sidebar_tree.rs`.
 
+const SAVE_RETRY_DELAYS_MS: [i32; 3] = [500, 1500, 4000];
+
+fn report_command_error(action: &str, err: &CommandError) {
+    let _ = window().alert_with_message(&format!("{action} failed: {}", err.message()));
+}
+
+async fn sleep_ms(ms: i32) {
+    let promise = js_sys::Promise::new(&mut |resolve, _| {
+        if let Some(win) = leptos::web_sys::window() {
+            let _ = win.set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, ms);
+        }
+    });
+    let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+}
+
+/// Writes out saves that were still waiting on their debounce, so they land
+/// before a move instead of recreating the old paths after it.
+async fn write_pending_saves(vault_id: &str, pending: Vec<(String, String)>) {
+    for (path, text) in pending {
+        if let Err(err) = tauri_bridge::write_file(vault_id, &path, &text).await {
+            report_command_error("Saving note", &err);
+        }
+    }
+}
+
 fn normalize_pasted_text(text: &str) -> String {
     crate::markdown_syntax::normalize_pasted_text(text)
 }
@@ -301,6 +361,184 @@ fn set_selection_byte_offsets(root: &HtmlElement, selection: Selection) {
     let _ = dom_selection.add_range(&range);
 }
 
+/// Editor state as read back from the DOM. The DOM only carries the primary
+/// selection, so secondary ranges survive while it still matches.
+fn snapshot_with_dom_state(
+    mut snapshot: EditorSnapshot,
+    text: String,
+    selection: Selection,
+) -> EditorSnapshot {
+    let keep_ranges = snapshot.selections.is_multi()
+        && snapshot.text == text
+        && snapshot.selection() == selection;
+    if !keep_ranges {
+        snapshot.replace_from_input(text, selection);
+    }
+    snapshot
+}
+
+/// A secondary cursor or selection box, relative to the editor container.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct OverlayRect {
+    left: f64,
+    top: f64,
+    width: f64,
+    height: f64,
+    cursor: bool,
+}
+
+fn secondary_selection_rects(root: &HtmlElement, selections: &SelectionSet) -> Vec<OverlayRect> {
+    let mut rects = Vec::new();
+    if !selections.is_multi() {
+        return rects;
+    }
+    let Some(document) = leptos::web_sys::window().and_then(|win| win.document()) else {
+        return rects;
+    };
+    let Some(container) = root.parent_element() else {
+        return rects;
+    };
+    let origin = container.get_bounding_client_rect();
+    let root_node: Node = root.clone().unchecked_into();
+    let mut text_nodes = Vec::new();
+    collect_text_nodes(&root_node, &mut text_nodes);
+
+    for (index, range) in selections.ranges().iter().enumerate() {
+        if index == selections.primary_index() {
+            continue;
+        }
+        let (Some((start_node, start_offset)), Some((end_node, end_offset))) = (
+            find_text_position(&text_nodes, range.start),
+            find_text_position(&text_nodes, range.end),
+        ) else {
+            continue;
+        };
+        let Ok(dom_range) = document.create_range() else {
+            continue;
+        };
+        if dom_range.set_start(&start_node, start_offset).is_err()
+            || dom_range.set_end(&end_node, end_offset).is_err()
+        {
+            continue;
+        }
+        if range.is_cursor() {
+            let rect = dom_range.get_bounding_client_rect();
+            rects.push(OverlayRect {
+                left: rect.left() - origin.left(),
+                top: rect.top() - origin.top(),
+                width: 2.0,
+                height: rect.height(),
+                cursor: true,
+            });
+        } else if let Some(list) = dom_range.get_client_rects() {
+            for idx in 0..list.length() {
+                if let Some(rect) = list.get(idx) {
+                    rects.push(OverlayRect {
+                        left: rect.left() - origin.left(),
+                        top: rect.top() - origin.top(),
+                        width: rect.width(),
+                        height: rect.height(),
+                        cursor: false,
+                    });
+                }
+            }
+        }
+    }
+    rects
+}
+
+/// Inline completion open at the caret.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum ActiveCompletion {
+    Link(LinkQuery),
+    Slash(SlashQuery),
+    Metadata(MetadataQuery),
+}
+
+impl ActiveCompletion {
+    fn at(text: &str, caret: usize) -> Option<Self> {
+        link_query_at(text, caret)
+            .map(Self::Link)
+            .or_else(|| slash_query_at(text, caret).map(Self::Slash))
+            .or_else(|| metadata_query_at(text, caret).map(Self::Metadata))
+    }
+
+    fn start(&self) -> usize {
+        match self {
+            Self::Link(query) => query.start,
+            Self::Slash(query) => query.start,
+            Self::Metadata(query) => query.start,
+        }
+    }
+}
+
+/// Local date as `YYYY-MM-DD`.
+fn today_iso() -> String {
+    let now = Date::new_0();
+    format!(
+        "{:04}-{:02}-{:02}",
+        now.get_full_year(),
+        now.get_month() + 1,
+        now.get_date()
+    )
+}
+
+/// Local time as `YYYYMMDD-HHMMSS`, for naming pasted images.
+fn attachment_stamp() -> String {
+    let now = Date::new_0();
+    format!(
+        "{:04}{:02}{:02}-{:02}{:02}{:02}",
+        now.get_full_year(),
+        now.get_month() + 1,
+        now.get_date(),
+        now.get_hours(),
+        now.get_minutes(),
+        now.get_seconds()
+    )
+}
+
+/// Collapsed range at a viewport point. `caretRangeFromPoint` is not in
+/// the bindings, but both WebKit and WebView2 provide it.
+fn caret_range_at_point(x: f64, y: f64) -> Option<leptos::web_sys::Range> {
+    let doc = document();
+    let find = Reflect::get(&doc, &"caretRangeFromPoint".into())
+        .ok()?
+        .dyn_into::<js_sys::Function>()
+        .ok()?;
+    find.call2(&doc, &x.into(), &y.into())
+        .ok()?
+        .dyn_into::<leptos::web_sys::Range>()
+        .ok()
+}
+
+/// Image files carried by a paste or drop.
+fn image_files(data: &leptos::web_sys::DataTransfer) -> Vec<leptos::web_sys::File> {
+    let Some(files) = data.files() else {
+        return Vec::new();
+    };
+    (0..files.length())
+        .filter_map(|index| files.get(index))
+        .filter(|file| file.type_().starts_with("image/"))
+        .collect()
+}
+
+/// Bottom-left corner of the DOM caret in viewport coordinates.
+fn caret_client_position() -> Option<(f64, f64)> {
+    let selection = leptos::web_sys::window()?.get_selection().ok()??;
+    if selection.range_count() == 0 {
+        return None;
+    }
+    let range = selection.get_range_at(0).ok()?;
+    let rect = range
+        .get_client_rects()
+        .and_then(|list| list.get(0))
+        .unwrap_or_else(|| range.get_bounding_client_rect());
+    if rect.height() == 0.0 && rect.left() == 0.0 {
+        return None;
+    }
+    Some((rect.left(), rect.bottom() + 4.0))
+}
+
 fn line_index_at(text: &str, byte_offset: usize) -> usize {
     let end = byte_offset.min(text.len());
     text[..end].lines().count().saturating_sub(1).max(0)
@@ -421,7 +659,9 @@ pub fn App() -> impl IntoView {
     let (plugin_css, set_plugin_css) = signal(String::new());
     let (settings, set_settings) = signal(AppSettings::default());
 
-    let (save_timeout_id, set_save_timeout_id) = signal(Option::<i32>::None);
+    // Pending debounced saves, per note, so an edit in one pane does not
+    // cancel the save of a note open in the other.
+    let (save_timeout_ids, set_save_timeout_ids) = signal(HashMap::<String, i32>::new());
     let (recent_notes_persist_timeout_id, set_recent_notes_persist_timeout_id) =
         signal(Option::<i32>::None);
     let (save_status, set_save_status) = signal("Saved".to_string());
@@ -432,10 +672,54 @@ pub fn App() -> impl IntoView {
         signal(Option::<SidebarContextMenu>::None);
     let (sidebar_tab, set_sidebar_tab) = signal("search".to_string());
     let (recent_notes, set_recent_notes) = signal(Vec::<RecentNoteEntry>::new());
+    let (trash_entries, set_trash_entries) = signal(Vec::<TrashEntry>::new());
+    let (attachments, set_attachments) = signal(Vec::<String>::new());
+    // Which panel occupies the right-hand column: "metadata", "history" or "git".
+    let (right_panel, set_right_panel) = signal("metadata".to_string());
+    let (history_versions, set_history_versions) = signal(Vec::<HistoryVersion>::new());
+    let (selected_history_version, set_selected_history_version) = signal(None::<String>);
+    let (history_version_text, set_history_version_text) = signal(None::<String>);
+    let (git_repo, set_git_repo) = signal(GitRepoInfo::default());
+    let (git_statuses, set_git_statuses) = signal(HashMap::<String, String>::new());
+    let (git_note_diff, set_git_note_diff) = signal(String::new());
+    let (git_note_log, set_git_note_log) = signal(Vec::<GitLogEntry>::new());
+    // The running auto-commit interval and the callback it fires.
+    let git_auto_commit_timer = StoredValue::new_local(None::<(i32, Closure<dyn Fn()>)>);
     let (selection_restore_ticket, set_selection_restore_ticket) = signal(0u64);
     let (selection_sync_ticket, set_selection_sync_ticket) = signal(0u64);
-    let (undo_stack, set_undo_stack) = signal(Vec::<(String, Selection)>::new());
-    let (redo_stack, set_redo_stack) = signal(Vec::<(String, Selection)>::new());
+    // Set by an Alt+mousedown so the next selection sync adds a cursor
+    // (`Some(false)`) or a column selection (`Some(true)`, with Shift).
+    let (pending_alt_click, set_pending_alt_click) = signal(None::<bool>);
+    let (extra_selection_rects, set_extra_selection_rects) = signal(Vec::<OverlayRect>::new());
+    let (find_open, set_find_open) = signal(false);
+    let (find_query, set_find_query) = signal(String::new());
+    let (replace_text, set_replace_text) = signal(String::new());
+    let (find_options, set_find_options) = signal(FindOptions::default());
+    let (find_results, set_find_results) = signal(Vec::<Selection>::new());
+    let (find_error, set_find_error) = signal(None::<String>);
+    let (find_active, set_find_active) = signal(None::<usize>);
+    let find_input_ref = NodeRef::<html::Input>::new();
+    let (keymap, set_keymap) = signal(Keymap::defaults());
+    let (pending_chord, set_pending_chord) = signal(Vec::<KeyStroke>::new());
+    let (palette_open, set_palette_open) = signal(false);
+    let (switcher_open, set_switcher_open) = signal(false);
+    let (completion, set_completion) = signal(None::<ActiveCompletion>);
+    let (completion_items, set_completion_items) = signal(Vec::<Suggestion>::new());
+    let (completion_active, set_completion_active) = signal(0usize);
+    let (suggest_position, set_suggest_position) = signal(None::<(f64, f64)>);
+    // Start offset of a completion dismissed with Escape.
+    let (completion_dismissed, set_completion_dismissed) = signal(None::<usize>);
+    let (history, set_history) = signal(History::default());
+    let (editor_tabs, set_editor_tabs) = signal(EditorTabs::default());
+    // Tab layouts of the other open vaults, keyed by collapsed vault path.
+    let (saved_vault_tabs, set_saved_vault_tabs) = signal(HashMap::<String, SavedTabs>::new());
+    let (last_persisted_tabs, set_last_persisted_tabs) = signal(None::<SavedTabs>);
+    let (split_direction, set_split_direction) = signal(None::<SplitDirection>);
+    let (split_file, set_split_file) = signal(String::new());
+    let (split_snapshot, set_split_snapshot) = signal(EditorSnapshot::new(String::new()));
+    let (_split_history, set_split_history) = signal(History::default());
+    let (split_html, set_split_html) = signal(String::new());
+    let split_ref = NodeRef::<html::Div>::new();
     let (vault_loaded, set_vault_loaded) = signal(false);
     let (auto_recent_applied, set_auto_recent_applied) = signal(false);
     // Track the last note the user explicitly opened so the Recent pane can
@@ -621,13 +905,110 @@ pub fn App() -> impl IntoView {
         set_image_preview_failed.set(HashSet::new());
         set_expanded_folders.set(HashSet::new());
         set_recent_notes.set(Vec::new());
-        set_undo_stack.set(Vec::new());
-        set_redo_stack.set(Vec::new());
+        set_history.set(History::default());
+        set_editor_tabs.set(EditorTabs::default());
+        set_split_direction.set(None);
+        set_split_file.set(String::new());
+        set_split_history.set(History::default());
         set_plugin_css.set(String::new());
         set_vault_loaded.set(false);
         set_auto_recent_applied.set(false);
     };
 
+    // Saves the visible editor state into the active note's tab before another
+    // note replaces it.
+    let stash_active_tab = move || {
+        let file = current_file.get_untracked();
+        if file.is_empty() {
+            return;
+        }
+        let scroll_top = editor_ref
+            .get_untracked()
+            .map(|el| el.scroll_top())
+            .unwrap_or(0);
+        let snapshot = editor_snapshot.get_untracked();
+        let saved_history = history.get_untracked();
+        set_editor_tabs.update(|tabs| {
+            if let Some(tab) = tabs.get_mut(&file) {
+                tab.snapshot = Some(snapshot);
+                tab.history = saved_history;
+                tab.scroll_top = scroll_top;
+            }
+        });
+    };
+
+    // Loads `filename`'s tab state into the editor. History is dropped when the
+    // note changed on disk since the tab was last shown.
+    let restore_tab_state = move |filename: &str, text: &str| {
+        let tab = editor_tabs.get_untracked().get(filename).cloned();
+        let (snapshot, restored_history, scroll_top, restore_caret) = match tab {
+            Some(EditorTab {
+                snapshot: Some(saved),
+                history: saved_history,
+                scroll_top,
+                ..
+            }) => {
+                if saved.text == text {
+                    (saved, saved_history, scroll_top, true)
+                } else {
+                    let mut fresh = EditorSnapshot::new(text.to_string());
+                    fresh.set_selections(saved.selections);
+                    (fresh, History::default(), scroll_top, true)
+                }
+            }
+            _ => (
+                EditorSnapshot::new(text.to_string()),
+                History::default(),
+                0,
+                false,
+            ),
+        };
+        let selection = snapshot.selection();
+        set_history.set(restored_history);
+        set_editor_snapshot.set(snapshot);
+        // The editor surface is rebuilt for the new note; wait a frame before
+        // putting the caret and scroll position back.
+        if let Some(win) = leptos::web_sys::window() {
+            let cb = Closure::once(move || {
+                if let Some(el) = editor_ref.get_untracked() {
+                    if restore_caret {
+                        set_selection_byte_offsets(&el, selection);
+                    }
+                    el.set_scroll_top(scroll_top);
+                }
+            });
+            let _ = win.request_animation_frame(cb.as_ref().unchecked_ref());
+            cb.forget();
+        }
+    };
+
+    let render_split = move |caret: Option<usize>| {
+        set_split_html.set(highlight_markdown_for_editor(
+            &split_snapshot.get_untracked().text,
+            caret,
+            &vault_path.get_untracked(),
+            &split_file.get_untracked(),
+            &image_preview_cache.get_untracked(),
+        ));
+    };
+
+    // Brings the split pane up to date when `file` was edited elsewhere.
+    let mirror_into_split = move |file: &str, text: &str| {
+        if split_direction.get_untracked().is_none() || split_file.get_untracked() != file {
+            return;
+        }
+        let mut snapshot = split_snapshot.get_untracked();
+        let Some(transaction) = mirror_transaction(&snapshot, text) else {
+            return;
+        };
+        if snapshot.apply_transaction(transaction).is_ok() {
+            set_split_snapshot.set(snapshot);
+            // The pane's own history no longer matches its text.
+            set_split_history.set(History::default());
+            render_split(None);
+        }
+    };
+
     let push_to_recent_notes = move |path: String| {
         if path.is_empty() {
             return;
@@ -785,22 +1166,77 @@ pub fn App() -> impl IntoView {
                 // If normalized is empty we keep loaded_recent as-is; UI already has it from set_recent_notes.set(l) above.
             }
 
-            let notes_list = tauri_bridge::read_vault_notes(&path).await;
-
-            let mut note_map = HashMap::new();
-            let mut lower_map = HashMap::new();
-            for note in notes_list {
-                let path = note.path;
-                let content = note.content;
-                note_map.insert(path.clone(), content.clone());
-                lower_map.insert(path, content.to_lowercase());
+            // Notes arrive in batches from a backend worker; merge each one into
+            // the live maps so search and backlinks fill in while the rest loads.
+            let known_notes: HashSet<String> = dir_result.notes.iter().cloned().collect();
+            let load_path = path.clone();
+            let load_result = tauri_bridge::load_vault_notes(&path, move |event| {
+                if collapse_path(&load_path) != collapse_path(&vault_path.get_untracked()) {
+                    return;
+                }
+                match event {
+                    VaultLoadEvent::Started { total } => {
+                        set_note_texts.update(|map| map.retain(|p, _| known_notes.contains(p)));
+                        set_note_texts_lower.update(|map| map.retain(|p, _| known_notes.contains(p)));
+                        if total > 0 {
+                            set_save_status.set(format!("Loading notes 0/{total}"));
+                        }
+                    }
+                    VaultLoadEvent::Batch { notes, loaded, total } => {
+                        set_note_texts_lower.update(|map| {
+                            for note in &notes {
+                                map.insert(note.path.clone(), note.content.to_lowercase());
+                            }
+                        });
+                        set_note_texts.update(|map| {
+                            map.extend(notes.into_iter().map(|note| (note.path, note.content)));
+                        });
+                        set_save_status.set(format!("Loading notes {loaded}/{total}"));
+                    }
+                    VaultLoadEvent::Unreadable { path, error } => {
+                        leptos::logging::warn!("Could not read {path}: {}", error.message());
+                        set_note_texts.update(|map| {
+                            map.remove(&path);
+                        });
+                        set_note_texts_lower.update(|map| {
+                            map.remove(&path);
+                        });
+                    }
+                    VaultLoadEvent::Finished { loaded, unreadable } => {
+                        if unreadable > 0 {
+                            set_save_status.set(format!(
+                                "Loaded {loaded} notes; {unreadable} could not be read (see console)"
+                            ));
+                        } else if save_status.get_untracked().starts_with("Loading notes") {
+                            set_save_status.set("Saved".to_string());
+                        }
+                    }
+                }
+            })
+            .await;
+            if let Err(err) = load_result {
+                set_save_status.set(format!("Loading notes failed: {}", err.message()));
+            }
+            if collapse_path(&path) != collapse_path(&vault_path.get_untracked()) {
+                return;
             }
 
-            set_note_texts.set(note_map.clone());
-            set_note_texts_lower.set(lower_map);
+            let note_map = note_texts.get_untracked();
             let dir_list = &dir_result.notes;
             set_metadata_cache.set(build_metadata_cache(&note_map, dir_list));
 
+            stash_active_tab();
+            set_editor_tabs.update(|tabs| tabs.retain_existing(dir_list));
+            let split = split_file.get_untracked();
+            if !split.is_empty() {
+                match note_map.get(&split) {
+                    Some(text) => mirror_into_split(&split, text),
+                    None => {
+                        set_split_direction.set(None);
+                        set_split_file.set(String::new());
+                    }
+                }
+            }
             let next_file = preferred_file
                 .filter(|f| dir_list.contains(f))
                 .or_else(|| {
@@ -810,10 +1246,14 @@ pub fn App() -> impl IntoView {
                     } else {
                         None
                     }
-                });
+                })
+                .or_else(|| editor_tabs.get_untracked().active);
 
             if let Some(selected_file) = next_file {
                 let text = note_map.get(&selected_file).cloned().unwrap_or_default();
+                set_editor_tabs.update(|tabs| {
+                    tabs.open(&selected_file);
+                });
                 set_current_file.set(selected_file.clone());
                 set_recent_notes.update(|list| {
                     let now = Date::now() as i64;
@@ -853,8 +1293,6 @@ pub fn App() -> impl IntoView {
                 if loaded_recent.len() > 50 {
                     loaded_recent.truncate(50);
                 }
-                set_undo_stack.set(Vec::new());
-                set_redo_stack.set(Vec::new());
                 set_expanded_folders.update(|expanded| {
                     expand_parent_folders(expanded, &selected_file);
                 });
@@ -867,7 +1305,7 @@ pub fn App() -> impl IntoView {
                     &image_preview_cache.get_untracked(),
                 ));
                 set_caret_pos.set(None);
-                set_editor_snapshot.set(EditorSnapshot::new(text));
+                restore_tab_state(&selected_file, &text);
             } else {
                 // Only clear current_file if it's no longer in the vault (e.g. deleted).
                 // If it's still in dir_list, keep it so the Recent pane and fallbacks still show it
@@ -933,9 +1371,25 @@ pub fn App() -> impl IntoView {
     let load_vault_visual_state = move |path: String| {
         if path.is_empty() {
             set_plugin_css.set(String::new());
+            set_keymap.set(Keymap::defaults());
             return;
         }
         spawn_local(async move {
+            let keymap_json = tauri_bridge::load_keymap(&path)
+                .await
+                .unwrap_or_else(|| "{}".to_string());
+            let (user_keymap, issues) = Keymap::from_user_json(&keymap_json);
+            for issue in &issues {
+                leptos::logging::warn!("{}", issue.message());
+            }
+            if !issues.is_empty() {
+                set_save_status.set(format!(
+                    "keymap.json has {} problem(s) (see console)",
+                    issues.len()
+                ));
+            }
+            set_keymap.set(user_keymap);
+
             if let Some(css_str) = tauri_bridge::load_plugins_css(&path).await {
                 set_plugin_css.set(css_str);
             } else {
@@ -966,11 +1420,16 @@ pub fn App() -> impl IntoView {
                 None
             }
         });
+        let tabs_for_active = active.as_ref().and_then(|a| {
+            (collapse_path(a) == collapse_path(&vault_path.get_untracked()))
+                .then(|| editor_tabs.get_untracked().to_persisted())
+        });
         spawn_local(async move {
             let normalized = tauri_bridge::save_vault_session(
                 &open_list,
                 active.as_deref(),
                 recent_for_active.as_deref(),
+                tabs_for_active.as_ref(),
             )
             .await;
             set_open.set(normalized.open_vaults.clone());
@@ -990,6 +1449,27 @@ pub fn App() -> impl IntoView {
             open_now.push(path.clone());
         }
         set_open_vaults.set(open_now.clone());
+        let previous = vault_path.get_untracked();
+        let mut preferred_file = preferred_file;
+        if collapse_path(&previous) != normalized {
+            // Park the old vault's tabs and bring back the ones this vault had.
+            stash_active_tab();
+            let parked = editor_tabs.get_untracked().to_persisted();
+            set_saved_vault_tabs.update(|saved| {
+                if !previous.is_empty() {
+                    saved.insert(collapse_path(&previous), parked);
+                }
+            });
+            let restored = saved_vault_tabs
+                .get_untracked()
+                .get(&normalized)
+                .map(EditorTabs::from_persisted)
+                .unwrap_or_default();
+            preferred_file = preferred_file.or_else(|| restored.active.clone());
+            set_editor_tabs.set(restored);
+            set_history.set(History::default());
+            set_editor_snapshot.set(EditorSnapshot::new(String::new()));
+        }
         set_vault_path.set(path.clone());
         set_vault_loaded.set(false);
         persist_vault_session(open_now, Some(path.clone()));
@@ -1035,6 +1515,13 @@ pub fn App() -> impl IntoView {
             }
 
             set_open_vaults.set(session.open_vaults.clone());
+            set_saved_vault_tabs.set(
+                session
+                    .open_tabs
+                    .iter()
+                    .map(|(k, v)| (collapse_path(k), v.clone()))
+                    .collect(),
+            );
 
             if let Some(path) = active.clone() {
                 // If the session carried a recent-notes snapshot for this vault, restore
@@ -1180,6 +1667,22 @@ pub fn App() -> impl IntoView {
         persist_vault_session(open_list.clone(), Some(v.clone()));
     });
 
+    // Persist the open tab layout whenever it changes. Stashing editor state
+    // also touches `editor_tabs`, so compare the persisted form first.
+    Effect::new(move |_| {
+        let layout = editor_tabs.get().to_persisted();
+        let v = vault_path.get_untracked();
+        let open_list = open_vaults.get_untracked();
+        if v.is_empty() || open_list.is_empty() {
+            return;
+        }
+        if last_persisted_tabs.get_untracked().as_ref() == Some(&layout) {
+            return;
+        }
+        set_last_persisted_tabs.set(Some(layout));
+        persist_vault_session(open_list, Some(v));
+    });
+
     // When the user explicitly opens the Recent tab after a full restart,
     // make a last-chance disk read to hydrate the list if it is still empty.
     Effect::new(move |_| {
@@ -1308,6 +1811,7 @@ pub fn App() -> impl IntoView {
 
         pending_paths.sort();
         pending_paths.dedup();
+        let vault_prefix = format!("{}/", collapse_path(&v_path));
         if pending_paths.is_empty() {
             return;
         }
@@ -1322,8 +1826,20 @@ pub fn App() -> impl IntoView {
             let set_cache = set_image_preview_cache;
             let set_loading = set_image_preview_loading;
             let set_failed = set_image_preview_failed;
+            // Candidates are absolute cache keys; the backend only accepts
+            // paths relative to the vault.
+            let rel_path = image_path
+                .strip_prefix(&vault_prefix)
+                .unwrap_or_default()
+                .to_string();
+            let v_path = v_path.clone();
             spawn_local(async move {
-                if let Some(encoded) = tauri_bridge::read_file_base64(&image_path).await {
+                let encoded = if rel_path.is_empty() {
+                    None
+                } else {
+                    tauri_bridge::read_file_base64(&v_path, &rel_path).await
+                };
+                if let Some(encoded) = encoded {
                     let src = format!(
                         "data:{};base64,{}",
                         image_mime_for_path(&image_path),
@@ -1347,6 +1863,124 @@ pub fn App() -> impl IntoView {
         }
     });
 
+    let refresh_git_note = move || {
+        let v_path = vault_path.get_untracked();
+        let file = current_file.get_untracked();
+        if v_path.is_empty() || file.is_empty() || !git_repo.get_untracked().is_repo {
+            set_git_note_diff.set(String::new());
+            set_git_note_log.set(Vec::new());
+            return;
+        }
+        spawn_local(async move {
+            let diff = tauri_bridge::git_diff_note(&v_path, &file).await;
+            let log = tauri_bridge::git_file_log(&v_path, &file, None).await;
+            if current_file.get_untracked() == file {
+                set_git_note_diff.set(diff);
+                set_git_note_log.set(log);
+            }
+        });
+    };
+
+    let refresh_git = move || {
+        let v_path = vault_path.get_untracked();
+        if v_path.is_empty() {
+            set_git_repo.set(GitRepoInfo::default());
+            set_git_statuses.set(HashMap::new());
+            return;
+        }
+        spawn_local(async move {
+            let info = tauri_bridge::git_repo_info(&v_path).await;
+            let statuses = if info.is_repo {
+                tauri_bridge::git_status(&v_path)
+                    .await
+                    .into_iter()
+                    .map(|entry| (entry.path, entry.status))
+                    .collect()
+            } else {
+                HashMap::new()
+            };
+            if collapse_path(&v_path) != collapse_path(&vault_path.get_untracked()) {
+                return;
+            }
+            set_git_repo.set(info);
+            set_git_statuses.set(statuses);
+            if right_panel.get_untracked() == "git" {
+                refresh_git_note();
+            }
+        });
+    };
+
+    // File operations all end in a snapshot refresh, so re-reading git status
+    // whenever the file list changes keeps the sidebar decorations current.
+    Effect::new(move |_| {
+        let _ = vault_path.get();
+        let _ = files.get();
+        refresh_git();
+    });
+
+    Effect::new(move |_| {
+        let _ = current_file.get();
+        if right_panel.get() == "git" {
+            refresh_git_note();
+        }
+    });
+
+    let commit_vault = move |template: String| {
+        let v_path = vault_path.get_untracked();
+        if v_path.is_empty() {
+            return;
+        }
+        spawn_local(async move {
+            match tauri_bridge::git_commit_all(&v_path, &template).await {
+                Ok(Some(hash)) => set_save_status.set(format!("Committed {hash}")),
+                Ok(None) => set_save_status.set("Nothing to commit".to_string()),
+                Err(err) => set_save_status.set(format!("Commit failed: {}", err.message())),
+            }
+            refresh_git();
+        });
+    };
+
+    let clear_git_auto_commit_timer = move || {
+        if let Some((id, _cb)) = git_auto_commit_timer.try_update_value(Option::take).flatten() {
+            if let Some(win) = leptos::web_sys::window() {
+                win.clear_interval_with_handle(id);
+            }
+        }
+    };
+    let git_auto_commit_minutes = Memo::new(move |_| settings.get().git_auto_commit_minutes);
+    let git_is_repo = Memo::new(move |_| git_repo.get().is_repo);
+
+    // Restart the auto-commit timer whenever the interval or the vault changes.
+    Effect::new(move |_| {
+        let minutes = git_auto_commit_minutes.get();
+        let is_repo = git_is_repo.get();
+        clear_git_auto_commit_timer();
+        if minutes == 0 || !is_repo {
+            return;
+        }
+        let Some(win) = leptos::web_sys::window() else {
+            return;
+        };
+        let cb = Closure::<dyn Fn()>::new(move || {
+            let v_path = vault_path.get_untracked();
+            if v_path.is_empty() || git_statuses.get_untracked().is_empty() {
+                return;
+            }
+            let template = settings.get_untracked().git_commit_template;
+            spawn_local(async move {
+                let _ = tauri_bridge::git_commit_all(&v_path, &template).await;
+                refresh_git();
+            });
+        });
+        if let Ok(id) = win.set_interval_with_callback_and_timeout_and_arguments_0(
+            cb.as_ref().unchecked_ref(),
+            (minutes as i32).saturating_mul(60_000),
+        ) {
+            git_auto_commit_timer.set_value(Some((id, cb)));
+        }
+    });
+    on_cleanup(clear_git_auto_commit_timer);
+
     let schedule_disk_write = move |filename: String, new_text: String| {
         if filename.is_empty() {
             return;
@@ -1357,20 +1991,65 @@ pub fn App() -> impl IntoView {
         }
 
         if let Some(win) = leptos::web_sys::window() {
-            if let Some(timeout) = save_timeout_id.get_untracked() {
-                win.clear_timeout_with_handle(timeout);
+            if let Some(timeout) = save_timeout_ids.get_untracked().get(&filename) {
+                win.clear_timeout_with_handle(*timeout);
             }
 
             set_save_status.set("Saving...".to_string());
-            let file_path = format!("{}/{}", v_path, filename);
-            let set_timeout = set_save_timeout_id;
+            let set_timeouts = set_save_timeout_ids;
             let set_status = set_save_status;
+            let pending_file = filename.clone();
 
             let cb = Closure::once(move || {
-                set_timeout.set(None);
+                set_timeouts.update(|ids| {
+                    ids.remove(&filename);
+                });
                 spawn_local(async move {
-                    tauri_bridge::write_file(&file_path, &new_text).await;
+                    // Transient I/O errors (a sync client holding the file,
+                    // say) get a few retries before the failure is shown.
+                    let mut new_text = new_text;
+                    let mut result = tauri_bridge::write_file(&v_path, &filename, &new_text).await;
+                    for delay in SAVE_RETRY_DELAYS_MS {
+                        match &result {
+                            Err(err) if err.is_retryable() => {
+                                set_status.set("Retrying save...".to_string());
+                                sleep_ms(delay).await;
+                                // Edits made while waiting have their own save
+                                // queued; a moved or closed note is not rewritten.
+                                if save_timeout_ids.with_untracked(|ids| ids.contains_key(&filename)) {
+                                    return;
+                                }
+                                let Some(latest) =
+                                    note_texts.with_untracked(|notes| notes.get(&filename).cloned())
+                                else {
+                                    return;
+                                };
+                                new_text = latest;
+                                result = tauri_bridge::write_file(&v_path, &filename, &new_text).await;
+                            }
+                            _ => break,
+                        }
+                    }
+                    if let Err(err) = result {
+                        set_status.set(format!("Save failed: {}", err.message()));
+                        return;
+                    }
                     set_status.set("Saved".to_string());
+
+                    // The backend throttles and deduplicates, so every save
+                    // can offer a snapshot.
+                    let current = settings.get_untracked();
+                    let policy = tauri_bridge::HistoryPolicyArgs {
+                        min_interval_secs: current.history_min_interval_secs,
+                        max_versions: current.history_max_versions,
+                        max_age_days: current.history_max_age_days,
+                    };
+                    tauri_bridge::snapshot_note(&v_path, &filename, &new_text, policy).await;
+                    if right_panel.get_untracked() == "history" && current_file.get_untracked() == filename {
+                        let versions = tauri_bridge::list_note_history(&v_path, &filename).await;
+                        set_history_versions.set(versions);
+                    }
+                    refresh_git();
                 });
             });
 
@@ -1378,7 +2057,9 @@ pub fn App() -> impl IntoView {
                 cb.as_ref().unchecked_ref(),
                 220,
             ) {
-                set_save_timeout_id.set(Some(id));
+                set_save_timeout_ids.update(|ids| {
+                    ids.insert(pending_file, id);
+                });
                 cb.forget();
             } else {
                 set_save_status.set("Save Failed".to_string());
@@ -1386,6 +2067,37 @@ pub fn App() -> impl IntoView {
         }
     };
 
+    // Cancels the debounced saves of every note that `moves` relocates and
+    // hands back their latest text for `write_pending_saves`.
+    let take_pending_saves = move |moves: &[(String, String)]| -> Vec<(String, String)> {
+        let pending: Vec<(String, i32)> = save_timeout_ids.with_untracked(|ids| {
+            ids.iter()
+                .filter(|(path, _)| moved_path(path, moves).is_some())
+                .map(|(path, id)| (path.clone(), *id))
+                .collect()
+        });
+        if pending.is_empty() {
+            return Vec::new();
+        }
+        if let Some(win) = leptos::web_sys::window() {
+            for (_, id) in &pending {
+                win.clear_timeout_with_handle(*id);
+            }
+        }
+        set_save_timeout_ids.update(|ids| {
+            for (path, _) in &pending {
+                ids.remove(path);
+            }
+        });
+        pending
+            .into_iter()
+            .filter_map(|(path, _)| {
+                let text = note_texts.with_untracked(|notes| notes.get(&path).cloned())?;
+                Some((path, text))
+            })
+            .collect()
+    };
+
     let schedule_selection_restore = move |selection_to_restore: Selection| {
         let next_ticket = selection_restore_ticket.get_untracked().wrapping_add(1);
         set_selection_restore_ticket.set(next_ticket);
@@ -1399,6 +2111,15 @@ pub fn App() -> impl IntoView {
                 if selection_restore_ticket.get_untracked() != expected_ticket {
                     return;
                 }
+                // Placing the DOM selection would pull focus out of a text field
+                // such as the find bar; the snapshot already holds the selection.
+                let typing_elsewhere = leptos::web_sys::window()
+                    .and_then(|win| win.document())
+                    .and_then(|doc| doc.active_element())
+                    .is_some_and(|active| matches!(active.tag_name().as_str(), "INPUT" | "TEXTAREA"));
+                if typing_elsewhere {
+                    return;
+                }
                 if let Some(el) = eref.get() {
                     if let Ok(root) = el.dyn_into::<HtmlElement>() {
                         set_selection_byte_offsets(&root, selection_to_restore);
@@ -1429,7 +2150,7 @@ pub fn App() -> impl IntoView {
             return;
         }
 
-        let selection = editor_snapshot.get_untracked().selection.clamp(text.len());
+        let selection = editor_snapshot.get_untracked().selection().clamp(text.len());
         set_parsed_html.set(highlight_markdown_for_editor(
             &text,
             Some(selection.start),
@@ -1440,23 +2161,14 @@ pub fn App() -> impl IntoView {
         schedule_selection_restore(selection);
     });
 
-    let apply_editor_update = move |new_text: String, sel_start: usize, sel_end: usize, skip_undo: bool| {
-        let mut snapshot = editor_snapshot.get_untracked();
-        if !skip_undo && snapshot.text != new_text {
-            set_undo_stack.update(|u| {
-                u.push((snapshot.text.clone(), snapshot.selection));
-                if u.len() > 100 {
-                    u.remove(0);
-                }
-            });
-            set_redo_stack.set(Vec::new());
-        }
+    // Shows `snapshot` in the main editor and carries its text on to the split
+    // pane, the note caches and the disk. The undo history is already updated.
+    let publish_editor_snapshot = move |snapshot: EditorSnapshot| {
         set_composition_dirty.set(false);
-        let selection = Selection::new(sel_start, sel_end);
-        snapshot.replace_from_input(new_text, selection);
         let final_text = snapshot.text.clone();
-        let final_selection = snapshot.selection;
+        let final_selection = snapshot.selection();
         set_editor_snapshot.set(snapshot);
+        mirror_into_split(&current_file.get_untracked(), &final_text);
 
         set_content.set(final_text.clone());
         set_caret_pos.set(Some(final_selection.start));
@@ -1484,39 +2196,295 @@ pub fn App() -> impl IntoView {
         schedule_selection_restore(final_selection);
     };
 
-    let apply_composition_shadow_update =
-        move |new_text: String, sel_start: usize, sel_end: usize| {
-            let mut snapshot = editor_snapshot.get_untracked();
-            snapshot.replace_from_input(new_text.clone(), Selection::new(sel_start, sel_end));
-            let selection = snapshot.selection;
-            set_editor_snapshot.set(snapshot);
-            set_content.set(new_text);
-            set_caret_pos.set(Some(selection.start));
-            set_composition_dirty.set(true);
-        };
-
-    let schedule_focus_editor = move || {
-        let eref = editor_ref.clone();
-        if let Some(win) = leptos::web_sys::window() {
-            let cb = Closure::once(move || {
-                if let Some(el) = eref.get() {
-                    if let Ok(he) = el.dyn_into::<HtmlElement>() {
-                        let _ = he.focus();
-                    }
-                }
+    // Text typed straight into the surface. The DOM only reports the result,
+    // so the edit is recovered by diffing against the current snapshot.
+    let apply_editor_update = move |new_text: String, selection: SelectionSet| {
+        let mut snapshot = editor_snapshot.get_untracked();
+        let mut recorded = false;
+        if let Some(change) = minimal_text_change(&snapshot.text, &new_text) {
+            let transaction = Transaction {
+                changes: vec![change],
+                selection_after: Some(selection.clone()),
+                origin: ChangeOrigin::Input,
+                label: "edit",
+            };
+            set_history.update(|h| {
+                recorded = h.apply(&mut snapshot, transaction, Date::now()).is_ok();
             });
-            let _ = win.request_animation_frame(cb.as_ref().unchecked_ref());
-            cb.forget();
+        }
+        if !recorded {
+            snapshot.replace_from_input(new_text, selection);
+        }
+        publish_editor_snapshot(snapshot);
+    };
+
+    // Applies a transaction built against `snapshot`, files that same
+    // transaction in the undo history and shows the result. Returns whether
+    // the text or selection changed.
+    let apply_editor_transaction = move |mut snapshot: EditorSnapshot, transaction: Transaction| {
+        let mut changed = false;
+        set_history.update(|h| {
+            changed = h
+                .apply(&mut snapshot, transaction, Date::now())
+                .is_ok_and(|outcome| outcome.text_changed || outcome.selection_changed);
+        });
+        if changed {
+            publish_editor_snapshot(snapshot);
+        }
+        changed
+    };
+
+    // The split pane keeps its own undo history, except while both panes show
+    // the same note: that note then has one history, whichever pane edits it.
+    let split_history_writer = move || {
+        if split_file.get_untracked() == current_file.get_untracked() {
+            set_history
+        } else {
+            set_split_history
+        }
+    };
+
+    // Shows `snapshot` in the split pane and carries its text on to the main
+    // editor when it shows the same note, keeping that editor's caret, then to
+    // the note caches and the disk.
+    let publish_split_snapshot = move |snapshot: EditorSnapshot| {
+        let file = split_file.get_untracked();
+        let text_changed = split_snapshot.with_untracked(|old| old.text != snapshot.text);
+        let final_text = snapshot.text.clone();
+        let final_selection = snapshot.selection();
+        set_split_snapshot.set(snapshot);
+        render_split(Some(final_selection.start));
+        if let Some(win) = leptos::web_sys::window() {
+            let cb = Closure::once(move || {
+                if let Some(el) = split_ref.get_untracked() {
+                    set_selection_byte_offsets(&el, final_selection);
+                }
+            });
+            let _ = win.request_animation_frame(cb.as_ref().unchecked_ref());
+            cb.forget();
+        }
+        if !text_changed {
+            return;
+        }
+
+        if current_file.get_untracked() == file {
+            // The edit is already in the shared history; the split's own one
+            // no longer matches the text.
+            set_split_history.set(History::default());
+            let mut primary = editor_snapshot.get_untracked();
+            if let Some(transaction) = mirror_transaction(&primary, &final_text) {
+                if primary.apply_transaction(transaction).is_ok() {
+                    set_editor_snapshot.set(primary);
+                    set_content.set(final_text.clone());
+                    set_parsed_html.set(highlight_markdown_for_editor(
+                        &final_text,
+                        None,
+                        &vault_path.get_untracked(),
+                        &file,
+                        &image_preview_cache.get_untracked(),
+                    ));
+                }
+            }
+        }
+
+        let mut notes = note_texts.get_untracked();
+        notes.insert(file.clone(), final_text.clone());
+        let mut lower_notes = note_texts_lower.get_untracked();
+        lower_notes.insert(file.clone(), final_text.to_lowercase());
+        let cache = build_metadata_cache(&notes, &files.get_untracked());
+        set_note_texts.set(notes);
+        set_note_texts_lower.set(lower_notes);
+        set_metadata_cache.set(cache);
+        schedule_disk_write(file, final_text);
+    };
+
+    // Text typed into the split pane, recovered by diffing like
+    // `apply_editor_update`.
+    let apply_split_update = move |new_text: String, selection: Selection| {
+        if split_file.get_untracked().is_empty() {
+            return;
+        }
+        let mut snapshot = split_snapshot.get_untracked();
+        let mut recorded = false;
+        if let Some(change) = minimal_text_change(&snapshot.text, &new_text) {
+            let transaction =
+                Transaction::single(change, Some(selection), ChangeOrigin::Input, "edit");
+            split_history_writer().update(|h| {
+                recorded = h.apply(&mut snapshot, transaction, Date::now()).is_ok();
+            });
+        }
+        if !recorded {
+            snapshot.replace_from_input(new_text, selection);
+        }
+        publish_split_snapshot(snapshot);
+    };
+
+    // `apply_editor_transaction` for the split pane.
+    let apply_split_transaction = move |mut snapshot: EditorSnapshot, transaction: Transaction| {
+        if split_file.get_untracked().is_empty() {
+            return false;
+        }
+        let mut changed = false;
+        split_history_writer().update(|h| {
+            changed = h
+                .apply(&mut snapshot, transaction, Date::now())
+                .is_ok_and(|outcome| outcome.text_changed || outcome.selection_changed);
+        });
+        if changed {
+            publish_split_snapshot(snapshot);
+        }
+        changed
+    };
+
+    let handle_split_input = move || {
+        let Some(root) = split_ref.get_untracked() else {
+            return;
+        };
+        let text = root.text_content().unwrap_or_default();
+        let selection = get_selection_byte_offsets(&root)
+            .unwrap_or_else(|| Selection::cursor(text.len()))
+            .clamp(text.len());
+        apply_split_update(text, selection);
+    };
+
+    // Undo, redo and Enter follow the main editor's bindings; other commands
+    // stay with the main editor.
+    let handle_split_keydown = move |e: leptos::ev::KeyboardEvent| {
+        let key = e.key();
+        let command = match keymap
+            .get_untracked()
+            .resolve(&pending_chord.get_untracked(), &KeyStroke::from_event(&e))
+        {
+            KeyResolution::Run(spec) => Some(spec.action),
+            _ => None,
+        };
+        let continue_block =
+            command == Some(CommandAction::Markdown(MarkdownCommand::ContinueBlock));
+        let step = matches!(command, Some(CommandAction::Undo | CommandAction::Redo));
+        if !step && !continue_block && key != "Enter" {
+            return;
+        }
+        // The browser's own Enter and undo would edit the rendered markup.
+        e.prevent_default();
+        if step || continue_block {
+            // The document-level listener would see this key press again.
+            e.stop_propagation();
+            set_pending_chord.set(Vec::new());
+        }
+        let Some(root) = split_ref.get_untracked() else {
+            return;
+        };
+
+        if step {
+            let mut snapshot = split_snapshot.get_untracked();
+            let mut changed = false;
+            split_history_writer().update(|h| {
+                changed = if command == Some(CommandAction::Redo) {
+                    h.redo(&mut snapshot)
+                } else {
+                    h.undo(&mut snapshot)
+                };
+            });
+            if changed {
+                publish_split_snapshot(snapshot);
+            }
+            return;
+        }
+
+        let text = root.text_content().unwrap_or_default();
+        let selection = get_selection_byte_offsets(&root)
+            .unwrap_or_else(|| Selection::cursor(text.len()))
+            .clamp(text.len());
+        let snapshot = snapshot_with_dom_state(split_snapshot.get_untracked(), text, selection);
+        let continued = continue_block
+            && build_markdown_transaction(&snapshot, MarkdownCommand::ContinueBlock)
+                .is_some_and(|transaction| apply_split_transaction(snapshot.clone(), transaction));
+        if !continued {
+            // Outside lists and quotes Enter is a plain line break.
+            if let Some(newline) =
+                insert_text_transaction(&snapshot, "\n", ChangeOrigin::Command, "insert-newline")
+            {
+                apply_split_transaction(snapshot, newline);
+            }
+        }
+    };
+
+    let sync_split_selection = move || {
+        let cb = Closure::once(move || {
+            let Some(root) = split_ref.get_untracked() else {
+                return;
+            };
+            let text = root.text_content().unwrap_or_default();
+            let Some(selection) = get_selection_byte_offsets(&root).map(|s| s.clamp(text.len()))
+            else {
+                return;
+            };
+            let mut snapshot = split_snapshot.get_untracked();
+            if snapshot.selection() == selection {
+                return;
+            }
+            snapshot.set_selection(selection);
+            set_split_snapshot.set(snapshot);
+            render_split(Some(selection.start));
+            set_selection_byte_offsets(&root, selection);
+        });
+        if let Some(win) = leptos::web_sys::window() {
+            let _ = win.request_animation_frame(cb.as_ref().unchecked_ref());
+            cb.forget();
+        }
+    };
+
+    let load_split_file = move |path: String| {
+        let text = note_texts.get_untracked().get(&path).cloned().unwrap_or_default();
+        set_split_file.set(path);
+        set_split_snapshot.set(EditorSnapshot::new(text));
+        set_split_history.set(History::default());
+        render_split(None);
+    };
+
+    let open_split = move |direction: SplitDirection| {
+        set_split_direction.set(Some(direction));
+        let open = split_file.get_untracked();
+        if open.is_empty() || !files.get_untracked().contains(&open) {
+            load_split_file(current_file.get_untracked());
+        }
+    };
+
+    let apply_composition_shadow_update =
+        move |new_text: String, sel_start: usize, sel_end: usize| {
+            let mut snapshot = editor_snapshot.get_untracked();
+            snapshot.replace_from_input(new_text.clone(), Selection::new(sel_start, sel_end));
+            let selection = snapshot.selection();
+            set_editor_snapshot.set(snapshot);
+            set_content.set(new_text);
+            set_caret_pos.set(Some(selection.start));
+            set_composition_dirty.set(true);
+        };
+
+    let schedule_focus_editor = move || {
+        let eref = editor_ref.clone();
+        if let Some(win) = leptos::web_sys::window() {
+            let cb = Closure::once(move || {
+                if let Some(el) = eref.get() {
+                    if let Ok(he) = el.dyn_into::<HtmlElement>() {
+                        let _ = he.focus();
+                    }
+                }
+            });
+            let _ = win.request_animation_frame(cb.as_ref().unchecked_ref());
+            cb.forget();
         }
     };
 
     let select_file = move |filename: String| {
+        stash_active_tab();
+        set_editor_tabs.update(|tabs| {
+            tabs.open(&filename);
+        });
         if let Some(text) = note_texts.get_untracked().get(&filename).cloned() {
             set_current_file.set(filename.clone());
             set_last_opened_file.set(filename.clone());
             push_to_recent_notes(filename.clone());
-            set_undo_stack.set(Vec::new());
-            set_redo_stack.set(Vec::new());
             set_expanded_folders.update(|expanded| {
                 expand_parent_folders(expanded, &filename);
             });
@@ -1529,7 +2497,7 @@ pub fn App() -> impl IntoView {
                 &image_preview_cache.get_untracked(),
             ));
             set_caret_pos.set(None);
-            set_editor_snapshot.set(EditorSnapshot::new(text));
+            restore_tab_state(&filename, &text);
             schedule_focus_editor();
             return;
         }
@@ -1544,12 +2512,9 @@ pub fn App() -> impl IntoView {
         set_last_opened_file.set(filename.clone());
         push_to_recent_notes(filename.clone());
         spawn_local(async move {
-            let file_path = format!("{}/{}", v_path, filename);
-            if let Some(text) = tauri_bridge::read_file(&file_path).await {
+            if let Some(text) = tauri_bridge::read_file(&v_path, &filename).await {
                 set_current_file.set(filename.clone());
                 push_to_recent_notes(filename.clone());
-                set_undo_stack.set(Vec::new());
-                set_redo_stack.set(Vec::new());
                 set_expanded_folders.update(|expanded| {
                     expand_parent_folders(expanded, &filename);
                 });
@@ -1562,7 +2527,7 @@ pub fn App() -> impl IntoView {
                     &image_preview_cache.get_untracked(),
                 ));
                 set_caret_pos.set(None);
-                set_editor_snapshot.set(EditorSnapshot::new(text.clone()));
+                restore_tab_state(&filename, &text);
 
                 let mut notes = note_texts.get_untracked();
                 notes.insert(filename.clone(), text.clone());
@@ -1588,22 +2553,6 @@ pub fn App() -> impl IntoView {
         });
     };
 
-    Effect::new(move |_| {
-        let Some(win) = leptos::web_sys::window() else { return };
-        let Some(doc) = win.document() else { return };
-        let handler = Closure::wrap(Box::new(move |e: KeyboardEvent| {
-            if (e.meta_key() || e.ctrl_key()) && e.key() == "1" {
-                e.prevent_default();
-                let list = files.get_untracked();
-                if let Some(first) = list.first() {
-                    select_file(first.clone());
-                }
-            }
-        }) as Box<dyn FnMut(KeyboardEvent)>);
-        let _ = doc.add_event_listener_with_callback("keydown", handler.as_ref().unchecked_ref());
-        handler.forget();
-    });
-
     let schedule_selection_sync = move || {
         let next_ticket = selection_sync_ticket.get_untracked().wrapping_add(1);
         set_selection_sync_ticket.set(next_ticket);
@@ -1619,11 +2568,32 @@ pub fn App() -> impl IntoView {
                 return;
             };
             let text = root.text_content().unwrap_or_default();
+            let alt_click = pending_alt_click.get_untracked();
+            set_pending_alt_click.set(None);
             if let Some(selection) = get_selection_byte_offsets(&root).map(|s| s.clamp(text.len()))
             {
                 let mut snapshot = editor_snapshot.get_untracked();
-                if snapshot.selection != selection {
-                    snapshot.set_selection(selection);
+                let previous = snapshot.selections.clone();
+                match alt_click {
+                    Some(false) if snapshot.text == text => {
+                        let mut next = previous.clone();
+                        next.add(selection);
+                        snapshot.set_selections(next);
+                    }
+                    Some(true) if snapshot.text == text => {
+                        let anchor = previous.primary().start;
+                        let head = if selection.start == anchor {
+                            selection.end
+                        } else {
+                            selection.start
+                        };
+                        snapshot.set_selections(column_selection(&text, anchor, head));
+                    }
+                    _ if previous.is_multi() && previous.primary() == selection => {}
+                    _ => snapshot.set_selection(selection),
+                }
+                if snapshot.selections != previous {
+                    let selection = snapshot.selection();
                     set_editor_snapshot.set(snapshot);
                     set_caret_pos.set(Some(selection.start));
                     if !is_composing.get_untracked() {
@@ -1645,271 +2615,341 @@ pub fn App() -> impl IntoView {
         }
     };
 
-    let update_content = move |ev| {
-        let target = event_target::<leptos::web_sys::Element>(&ev);
-        let new_text = target
-            .dyn_into::<HtmlElement>()
-            .ok()
-            .and_then(|el| el.text_content())
-            .unwrap_or_default();
-        let selection = editor_ref
-            .get()
-            .and_then(|el| el.dyn_into::<HtmlElement>().ok())
-            .and_then(|root| get_selection_byte_offsets(&root))
-            .unwrap_or_else(|| Selection::cursor(new_text.len()))
-            .clamp(new_text.len());
+    let apply_selection_update = move |selections: SelectionSet| {
+        let mut snapshot = editor_snapshot.get_untracked();
+        snapshot.set_selections(selections);
+        let selection = snapshot.selection();
+        let text = snapshot.text.clone();
+        set_editor_snapshot.set(snapshot);
+        set_caret_pos.set(Some(selection.start));
+        set_parsed_html.set(highlight_markdown_for_editor(
+            &text,
+            Some(selection.start),
+            &vault_path.get_untracked(),
+            &current_file.get_untracked(),
+            &image_preview_cache.get_untracked(),
+        ));
+        schedule_selection_restore(selection);
+    };
 
-        if is_composing.get_untracked() {
-            apply_composition_shadow_update(new_text, selection.start, selection.end);
-        } else {
-            apply_editor_update(new_text, selection.start, selection.end, false);
+    let refresh_selection_overlay = move || {
+        let cb = Closure::once(move || {
+            let rects = editor_ref
+                .get()
+                .and_then(|el| el.dyn_into::<HtmlElement>().ok())
+                .map(|root| {
+                    secondary_selection_rects(&root, &editor_snapshot.get_untracked().selections)
+                })
+                .unwrap_or_default();
+            if extra_selection_rects.get_untracked() != rects {
+                set_extra_selection_rects.set(rects);
+            }
+        });
+        if let Some(win) = leptos::web_sys::window() {
+            let _ = win.request_animation_frame(cb.as_ref().unchecked_ref());
+            cb.forget();
         }
     };
 
-    let handle_editor_keydown = move |e: leptos::ev::KeyboardEvent| {
-        if is_composing.get_untracked() {
-            return;
+    Effect::new(move |_| {
+        editor_snapshot.track();
+        parsed_html.track();
+        refresh_selection_overlay();
+    });
+
+    let close_completion = move || {
+        if completion.get_untracked().is_some() {
+            set_completion.set(None);
+            set_completion_items.set(Vec::new());
+            set_suggest_position.set(None);
         }
-        let Some(el) = editor_ref.get() else {
-            return;
+    };
+
+    Effect::new(move |_| {
+        let snapshot = editor_snapshot.get();
+        let selection = snapshot.selection();
+        let active = if snapshot.selections.is_multi() || !selection.is_cursor() {
+            None
+        } else {
+            ActiveCompletion::at(&snapshot.text, selection.start)
         };
-        let Ok(root) = el.dyn_into::<HtmlElement>() else {
+        let Some(active) = active else {
+            if completion_dismissed.get_untracked().is_some() {
+                set_completion_dismissed.set(None);
+            }
+            close_completion();
             return;
         };
-
-        let text = root.text_content().unwrap_or_default();
-        let (selection, did_collapse) = get_selection_byte_offsets_with_collapsed(&root)
-            .map(|(s, c)| (s.clamp(text.len()), c))
-            .unwrap_or_else(|| (Selection::cursor(text.len()), false));
-
-        let mut snapshot = editor_snapshot.get_untracked();
-        snapshot.replace_from_input(text.clone(), selection);
-        set_editor_snapshot.set(snapshot.clone());
-
-        let key = e.key();
-        if did_collapse && (key == "ArrowUp" || key == "ArrowDown") {
+        if completion_dismissed.get_untracked() == Some(active.start()) {
+            close_completion();
             return;
         }
-
-        let ctrl_or_cmd = e.ctrl_key() || e.meta_key();
-
-        if ctrl_or_cmd && !e.alt_key() {
-            match key.as_str() {
-                "z" | "Z" => {
-                    if e.shift_key() {
-                        if let Some((text, sel)) = redo_stack.get_untracked().last().cloned() {
-                            set_redo_stack.update(|r| { r.pop(); });
-                            set_undo_stack.update(|u| {
-                                u.push((snapshot.text.clone(), snapshot.selection));
-                                if u.len() > 100 { u.remove(0); }
-                            });
-                            apply_editor_update(text, sel.start, sel.end, true);
-                            e.prevent_default();
-                            return;
-                        }
-                    } else {
-                        if let Some((text, sel)) = undo_stack.get_untracked().last().cloned() {
-                            set_undo_stack.update(|u| { u.pop(); });
-                            set_redo_stack.update(|r| {
-                                r.push((snapshot.text.clone(), snapshot.selection));
-                                if r.len() > 100 { r.remove(0); }
-                            });
-                            apply_editor_update(text, sel.start, sel.end, true);
-                            e.prevent_default();
-                            return;
-                        }
-                    }
-                }
-                "b" | "B" => {
-                    if apply_markdown_command(
-                        &mut snapshot,
-                        MarkdownCommand::Wrap {
-                            open: "**",
-                            close: "**",
-                            label: "bold",
-                        },
-                    )
-                    .unwrap_or(false)
-                    {
-                        e.prevent_default();
-                        apply_editor_update(
-                            snapshot.text.clone(),
-                            snapshot.selection.start,
-                            snapshot.selection.end,
-                            false,
-                        );
-                        return;
-                    }
-                }
-                "i" | "I" => {
-                    if apply_markdown_command(
-                        &mut snapshot,
-                        MarkdownCommand::Wrap {
-                            open: "*",
-                            close: "*",
-                            label: "italic",
-                        },
-                    )
-                    .unwrap_or(false)
-                    {
-                        e.prevent_default();
-                        apply_editor_update(
-                            snapshot.text.clone(),
-                            snapshot.selection.start,
-                            snapshot.selection.end,
-                            false,
-                        );
-                        return;
-                    }
-                }
-                "k" | "K" => {
-                    if apply_markdown_command(
-                        &mut snapshot,
-                        MarkdownCommand::Wrap {
-                            open: "[[",
-                            close: "]]",
-                            label: "wikilink",
-                        },
-                    )
-                    .unwrap_or(false)
-                    {
-                        e.prevent_default();
-                        apply_editor_update(
-                            snapshot.text.clone(),
-                            snapshot.selection.start,
-                            snapshot.selection.end,
-                            false,
-                        );
-                        return;
-                    }
-                }
-                _ => {}
+        if completion.get_untracked().as_ref() == Some(&active) {
+            return;
+        }
+        let items = metadata_cache.with_untracked(|cache| match &active {
+            ActiveCompletion::Link(query) => {
+                let source = current_file.get_untracked();
+                let source_cache = extract_file_cache(&snapshot.text);
+                files.with_untracked(|files| {
+                    link_suggestions(query, &source, &source_cache, files, &cache.file_cache)
+                })
             }
+            ActiveCompletion::Slash(query) => {
+                files.with_untracked(|files| slash_suggestions(query, &template_paths(files)))
+            }
+            ActiveCompletion::Metadata(query) => metadata_suggestions(query, cache),
+        });
+        set_completion.set(Some(active));
+        set_completion_items.set(items);
+        set_completion_active.set(0);
+        let cb = Closure::once(move || {
+            set_suggest_position.set(caret_client_position());
+        });
+        if let Some(win) = leptos::web_sys::window() {
+            let _ = win.request_animation_frame(cb.as_ref().unchecked_ref());
+            cb.forget();
         }
+    });
 
-        if key == "Tab" {
-            e.prevent_default();
-            let command = if e.shift_key() {
-                MarkdownCommand::Outdent
-            } else {
-                MarkdownCommand::Indent
-            };
-            if apply_markdown_command(&mut snapshot, command).unwrap_or(false) {
-                apply_editor_update(
-                    snapshot.text.clone(),
-                    snapshot.selection.start,
-                    snapshot.selection.end,
-                    false,
-                );
+    let accept_completion = move |index: usize| {
+        let snapshot = editor_snapshot.get_untracked();
+        let Some(suggestion) = completion_items.with_untracked(|items| items.get(index).cloned())
+        else {
+            return;
+        };
+        let transaction = match ActiveCompletion::at(&snapshot.text, snapshot.selection().start) {
+            Some(ActiveCompletion::Link(query)) => {
+                accept_link_transaction(&snapshot, &query, &suggestion)
+            }
+            Some(ActiveCompletion::Slash(query)) => {
+                let template = suggestion
+                    .insert
+                    .strip_prefix(TEMPLATE_ID_PREFIX)
+                    .and_then(|path| note_texts.with_untracked(|texts| texts.get(path).cloned()));
+                let file = current_file.get_untracked();
+                let title = Path::new(&file)
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .unwrap_or_default();
+                let today = today_iso();
+                let context = SlashContext {
+                    today: &today,
+                    title,
+                };
+                accept_slash_transaction(
+                    &snapshot,
+                    &query,
+                    &suggestion.insert,
+                    template.as_deref(),
+                    &context,
+                )
             }
+            Some(ActiveCompletion::Metadata(query)) => {
+                accept_metadata_transaction(&snapshot, &query, &suggestion)
+            }
+            None => None,
+        };
+        let Some(transaction) = transaction else {
             return;
+        };
+        if apply_editor_transaction(snapshot, transaction) {
+            close_completion();
         }
+    };
 
-        if key == "Enter" {
-            e.prevent_default();
-            if apply_markdown_command(&mut snapshot, MarkdownCommand::ContinueBlock)
-                .unwrap_or(false)
-            {
-                apply_editor_update(
-                    snapshot.text.clone(),
-                    snapshot.selection.start,
-                    snapshot.selection.end,
-                    false,
-                );
-                return;
-            }
-            let fallback_start = snapshot.selection.start;
-            let fallback = Transaction::single(
-                TextChange::new(snapshot.selection.start, snapshot.selection.end, "\n"),
-                Some(Selection::cursor(fallback_start + 1)),
-                ChangeOrigin::Command,
-                "insert-newline",
-            );
-            if snapshot.apply_transaction(fallback).is_ok() {
-                apply_editor_update(
-                    snapshot.text.clone(),
-                    snapshot.selection.start,
-                    snapshot.selection.end,
-                    false,
-                );
+    // Arrow keys, Enter/Tab and Escape drive the completion popup while it
+    // is open.
+    let handle_completion_key = move |e: &leptos::ev::KeyboardEvent| -> bool {
+        let count = completion_items.with_untracked(Vec::len);
+        if count == 0 || suggest_position.get_untracked().is_none() {
+            return false;
+        }
+        if e.ctrl_key() || e.meta_key() || e.alt_key() {
+            return false;
+        }
+        match e.key().as_str() {
+            "ArrowDown" => set_completion_active.update(|i| *i = (*i + 1) % count),
+            "ArrowUp" => set_completion_active.update(|i| *i = (*i + count - 1) % count),
+            "Enter" | "Tab" if !e.shift_key() => accept_completion(completion_active.get_untracked()),
+            "Escape" => {
+                set_completion_dismissed.set(completion.get_untracked().map(|c| c.start()));
+                close_completion();
             }
-            return;
+            _ => return false,
         }
+        e.prevent_default();
+        e.stop_propagation();
+        true
+    };
 
-        if !ctrl_or_cmd && !e.alt_key() {
-            let pair = match key.as_str() {
-                "(" => Some(("(", ")")),
-                "[" => Some(("[", "]")),
-                "{" => Some(("{", "}")),
-                "\"" => Some(("\"", "\"")),
-                "'" => Some(("'", "'")),
-                "`" => Some(("`", "`")),
-                _ => None,
+    let reveal_active_match = move || {
+        let cb = Closure::once(move || {
+            let Some(el) = editor_ref.get() else {
+                return;
             };
-
-            if let Some((open, close)) = pair {
-                if apply_markdown_command(&mut snapshot, MarkdownCommand::AutoPair { open, close })
-                    .unwrap_or(false)
-                {
-                    e.prevent_default();
-                    apply_editor_update(
-                        snapshot.text.clone(),
-                        snapshot.selection.start,
-                        snapshot.selection.end,
-                        false,
-                    );
-                }
+            if let Ok(Some(mark)) = el.query_selector(".hl-find-active") {
+                mark.scroll_into_view_with_bool(false);
             }
+        });
+        if let Some(win) = leptos::web_sys::window() {
+            let _ = win.request_animation_frame(cb.as_ref().unchecked_ref());
+            cb.forget();
         }
     };
 
-    let handle_editor_paste = move |e: leptos::ev::Event| {
-        let Some(raw) = e.dyn_ref::<leptos::web_sys::ClipboardEvent>() else {
-            return;
+    Effect::new(move |previous: Option<(String, FindOptions)>| {
+        let text = content.get();
+        let query = find_query.get();
+        let options = find_options.get();
+        let (matches, error) = if find_open.get() {
+            match find_matches(&text, &query, options) {
+                Ok(matches) => (matches, None),
+                Err(err) => (Vec::new(), Some(err)),
+            }
+        } else {
+            (Vec::new(), None)
         };
-        e.prevent_default();
+        let caret = editor_snapshot.get_untracked().selection();
+        let active = matches
+            .iter()
+            .position(|m| *m == caret)
+            .or_else(|| matches.iter().position(|m| m.start >= caret.start))
+            .or(if matches.is_empty() { None } else { Some(0) });
+        if find_error.get_untracked() != error {
+            set_find_error.set(error);
+        }
+        if find_results.get_untracked() != matches {
+            set_find_results.set(matches);
+        }
+        if find_active.get_untracked() != active {
+            set_find_active.set(active);
+        }
+        let current = (query, options);
+        if previous.as_ref() != Some(&current) {
+            reveal_active_match();
+        }
+        current
+    });
 
-        let pasted = raw
-            .clipboard_data()
-            .and_then(|dt| dt.get_data("text/plain").ok())
-            .unwrap_or_default();
-        if pasted.is_empty() {
-            return;
+    let open_find = move || {
+        let snapshot = editor_snapshot.get_untracked();
+        let selection = snapshot.selection();
+        if !selection.is_cursor() {
+            let selected = &snapshot.text[selection.start..selection.end];
+            if !selected.contains('\n') {
+                set_find_query.set(selected.to_string());
+            }
+        }
+        set_find_open.set(true);
+        let cb = Closure::once(move || {
+            if let Some(input) = find_input_ref.get() {
+                let _ = input.focus();
+                input.select();
+            }
+        });
+        if let Some(win) = leptos::web_sys::window() {
+            let _ = win.request_animation_frame(cb.as_ref().unchecked_ref());
+            cb.forget();
         }
-        let pasted = normalize_pasted_text(&pasted);
+    };
 
-        let Some(el) = editor_ref.get() else {
+    let close_find = move || {
+        set_find_open.set(false);
+        if let Some(el) = editor_ref.get() {
+            if let Ok(root) = el.dyn_into::<HtmlElement>() {
+                let _ = root.focus();
+            }
+        }
+        apply_selection_update(editor_snapshot.get_untracked().selections);
+    };
+
+    let step_find = move |forward: bool| {
+        let matches = find_results.get_untracked();
+        let from = find_active
+            .get_untracked()
+            .and_then(|index| matches.get(index).copied())
+            .unwrap_or_else(|| editor_snapshot.get_untracked().selection());
+        let Some(index) = step_match(&matches, from, forward) else {
             return;
         };
-        let Ok(root) = el.dyn_into::<HtmlElement>() else {
+        let mut snapshot = editor_snapshot.get_untracked();
+        snapshot.set_selection(matches[index]);
+        set_editor_snapshot.set(snapshot);
+        set_find_active.set(Some(index));
+        reveal_active_match();
+    };
+
+    let replace_current_match = move || {
+        let matches = find_results.get_untracked();
+        let Some(range) = find_active
+            .get_untracked()
+            .and_then(|index| matches.get(index).copied())
+        else {
+            step_find(true);
             return;
         };
+        let snapshot = editor_snapshot.get_untracked();
+        match replace_match_transaction(
+            &snapshot,
+            range,
+            &find_query.get_untracked(),
+            &replace_text.get_untracked(),
+            find_options.get_untracked(),
+        ) {
+            Ok(transaction) => {
+                if apply_editor_transaction(snapshot, transaction) {
+                    reveal_active_match();
+                }
+            }
+            Err(err) => set_find_error.set(Some(err)),
+        }
+    };
 
-        let text = root.text_content().unwrap_or_default();
-        let selection = get_selection_byte_offsets(&root)
-            .unwrap_or_else(|| Selection::cursor(text.len()))
-            .clamp(text.len());
+    let replace_all_matches = move || {
+        let snapshot = editor_snapshot.get_untracked();
+        match replace_all_transaction(
+            &snapshot,
+            &find_query.get_untracked(),
+            &replace_text.get_untracked(),
+            find_options.get_untracked(),
+        ) {
+            Ok(Some(transaction)) => {
+                apply_editor_transaction(snapshot, transaction);
+            }
+            Ok(None) => {}
+            Err(err) => set_find_error.set(Some(err)),
+        }
+    };
 
-        let mut snapshot = editor_snapshot.get_untracked();
-        snapshot.replace_from_input(text, selection);
-        let transaction = Transaction::single(
-            TextChange::new(
-                snapshot.selection.start,
-                snapshot.selection.end,
-                pasted.clone(),
-            ),
-            Some(Selection::cursor(snapshot.selection.start + pasted.len())),
-            ChangeOrigin::Command,
-            "paste-plain-text",
-        );
+    let editor_html = move || {
+        let html = parsed_html.get();
+        let matches = find_results.get();
+        if matches.is_empty() {
+            return html;
+        }
+        let ranges: Vec<(usize, usize)> = matches.iter().map(|m| (m.start, m.end)).collect();
+        layer_find_matches(&html, &ranges, find_active.get())
+    };
 
-        if snapshot.apply_transaction(transaction).is_ok() {
-            apply_editor_update(
-                snapshot.text.clone(),
-                snapshot.selection.start,
-                snapshot.selection.end,
-                false,
-            );
+    let update_content = move |ev| {
+        let target = event_target::<leptos::web_sys::Element>(&ev);
+        let new_text = target
+            .dyn_into::<HtmlElement>()
+            .ok()
+            .and_then(|el| el.text_content())
+            .unwrap_or_default();
+        let selection = editor_ref
+            .get()
+            .and_then(|el| el.dyn_into::<HtmlElement>().ok())
+            .and_then(|root| get_selection_byte_offsets(&root))
+            .unwrap_or_else(|| Selection::cursor(new_text.len()))
+            .clamp(new_text.len());
+
+        if is_composing.get_untracked() {
+            apply_composition_shadow_update(new_text, selection.start, selection.end);
+        } else {
+            apply_editor_update(new_text, selection.into());
         }
     };
 
@@ -1934,65 +2974,7 @@ pub fn App() -> impl IntoView {
             .unwrap_or_else(|| Selection::cursor(text.len()))
             .clamp(text.len());
         set_composition_dirty.set(false);
-        apply_editor_update(text, selection.start, selection.end, false);
-    };
-
-    let run_editor_action = move |action: &'static str| {
-        let Some(el) = editor_ref.get() else {
-            return;
-        };
-        let Ok(root) = el.dyn_into::<HtmlElement>() else {
-            return;
-        };
-
-        let text = root.text_content().unwrap_or_default();
-        let selection = get_selection_byte_offsets(&root)
-            .unwrap_or_else(|| Selection::cursor(text.len()))
-            .clamp(text.len());
-
-        let mut snapshot = editor_snapshot.get_untracked();
-        snapshot.replace_from_input(text, selection);
-
-        let command = match action {
-            "bold" => MarkdownCommand::Wrap {
-                open: "**",
-                close: "**",
-                label: "bold",
-            },
-            "italic" => MarkdownCommand::Wrap {
-                open: "*",
-                close: "*",
-                label: "italic",
-            },
-            "code" => MarkdownCommand::Wrap {
-                open: "`",
-                close: "`",
-                label: "code",
-            },
-            "link" => MarkdownCommand::Wrap {
-                open: "[[",
-                close: "]]",
-                label: "wikilink",
-            },
-            "quote" => MarkdownCommand::PrefixLine {
-                prefix: "> ",
-                label: "quote",
-            },
-            "task" => MarkdownCommand::PrefixLine {
-                prefix: "- [ ] ",
-                label: "task",
-            },
-            _ => return,
-        };
-
-        if apply_markdown_command(&mut snapshot, command).unwrap_or(false) {
-            apply_editor_update(
-                snapshot.text.clone(),
-                snapshot.selection.start,
-                snapshot.selection.end,
-                false,
-            );
-        }
+        apply_editor_update(text, selection.into());
     };
 
     let save_settings_to_disk = move |s: AppSettings| {
@@ -2001,35 +2983,45 @@ pub fn App() -> impl IntoView {
             return;
         }
         spawn_local(async move {
-            tauri_bridge::save_settings(&v_path, &s).await;
+            if let Err(err) = tauri_bridge::save_settings(&v_path, &s).await {
+                report_command_error("Saving settings", &err);
+            }
         });
     };
 
-    let create_new_note = move || {
+    let create_named_note = move |raw: String| {
         let v_path = vault_path.get_untracked();
         if v_path.is_empty() {
             return;
         }
+        let name = normalize_rel_path(raw.trim());
+        if name.is_empty() {
+            return;
+        }
+        let filename = if name.to_ascii_lowercase().ends_with(".md") {
+            name
+        } else {
+            format!("{name}.md")
+        };
+        let initial = "# New Note\n\n".to_string();
+        let path_for_refresh = v_path.clone();
+        let file_for_refresh = filename.clone();
 
-        if let Ok(Some(raw)) = window().prompt_with_message("New note path") {
-            let name = normalize_rel_path(raw.trim());
-            if name.is_empty() {
+        spawn_local(async move {
+            if let Err(err) = tauri_bridge::write_file(&v_path, &filename, &initial).await {
+                report_command_error("Creating note", &err);
                 return;
             }
-            let filename = if name.to_ascii_lowercase().ends_with(".md") {
-                name
-            } else {
-                format!("{name}.md")
-            };
-            let file_path = format!("{}/{}", v_path, filename);
-            let initial = "# New Note\n\n".to_string();
-            let path_for_refresh = v_path.clone();
-            let file_for_refresh = filename.clone();
+            refresh_vault_snapshot(path_for_refresh, Some(file_for_refresh));
+        });
+    };
 
-            spawn_local(async move {
-                tauri_bridge::write_file(&file_path, &initial).await;
-                refresh_vault_snapshot(path_for_refresh, Some(file_for_refresh));
-            });
+    let create_new_note = move || {
+        if vault_path.get_untracked().is_empty() {
+            return;
+        }
+        if let Ok(Some(raw)) = window().prompt_with_message("New note path") {
+            create_named_note(raw);
         }
     };
 
@@ -2046,7 +3038,6 @@ pub fn App() -> impl IntoView {
             }
             let base = if name.to_ascii_lowercase().ends_with(".md") { name } else { format!("{name}.md") };
             let filename = if folder_path.is_empty() { base } else { format!("{}/{}", folder_path, base) };
-            let file_path = format!("{}/{}", v_path, filename);
             let initial = "# New Note\n\n".to_string();
             let path_for_refresh = v_path.clone();
             let file_for_refresh = filename.clone();
@@ -2054,7 +3045,10 @@ pub fn App() -> impl IntoView {
                 expand_parent_folders(expanded, &filename);
             });
             spawn_local(async move {
-                tauri_bridge::write_file(&file_path, &initial).await;
+                if let Err(err) = tauri_bridge::write_file(&v_path, &filename, &initial).await {
+                    report_command_error("Creating note", &err);
+                    return;
+                }
                 refresh_vault_snapshot(path_for_refresh, Some(file_for_refresh));
             });
         }
@@ -2072,163 +3066,999 @@ pub fn App() -> impl IntoView {
             if name.is_empty() || name.contains('/') {
                 return;
             }
-            let full_path = if folder_path.is_empty() {
-                format!("{}/{}", v_path, name)
+            let rel_path = if folder_path.is_empty() {
+                name
             } else {
-                format!("{}/{}/{}", v_path, folder_path, name)
+                format!("{}/{}", folder_path, name)
             };
             let path_for_refresh = v_path.clone();
             spawn_local(async move {
-                tauri_bridge::create_dir(&full_path).await;
+                if let Err(err) = tauri_bridge::create_dir(&v_path, &rel_path).await {
+                    report_command_error("Creating folder", &err);
+                }
                 refresh_vault_snapshot(path_for_refresh, None);
             });
         }
     };
 
+    // Points open tabs, the split pane and expanded folders at paths that just
+    // moved, and returns where the current note went.
+    let follow_moves = move |moves: &[(String, String)]| {
+        stash_active_tab();
+        set_editor_tabs.update(|tabs| {
+            for file in files.get_untracked() {
+                if let Some(next) = moved_path(&file, moves) {
+                    tabs.rename(&file, &next);
+                }
+            }
+        });
+        if let Some(next) = moved_path(&split_file.get_untracked(), moves) {
+            set_split_file.set(next);
+        }
+        set_expanded_folders.update(|expanded| {
+            let moved: Vec<String> = expanded.iter().filter_map(|dir| moved_path(dir, moves)).collect();
+            expanded.retain(|dir| moved_path(dir, moves).is_none());
+            expanded.extend(moved);
+            for (_, to) in moves {
+                expand_parent_folders(expanded, to);
+            }
+        });
+        let current = current_file.get_untracked();
+        moved_path(&current, moves).unwrap_or(current)
+    };
+
+    let rename_folder = move |old_folder: String| {
+        set_sidebar_context_menu.set(None);
+        let v_path = vault_path.get_untracked();
+        if v_path.is_empty() {
+            return;
+        }
+        let Ok(Some(raw)) = window().prompt_with_message_and_default("Rename or move folder", &old_folder) else {
+            return;
+        };
+        let next_folder = normalize_rel_path(raw.trim());
+        if next_folder.is_empty() || next_folder == old_folder {
+            return;
+        }
+        let pending = take_pending_saves(&[(old_folder.clone(), next_folder.clone())]);
+        spawn_local(async move {
+            write_pending_saves(&v_path, pending).await;
+            let preferred = match tauri_bridge::rename_folder(&v_path, &old_folder, &next_folder).await {
+                Ok(moved_to) => follow_moves(&[(old_folder, moved_to)]),
+                Err(err) => {
+                    report_command_error("Renaming folder", &err);
+                    current_file.get_untracked()
+                }
+            };
+            let preferred = if preferred.is_empty() { None } else { Some(preferred) };
+            refresh_vault_snapshot(v_path, preferred);
+        });
+    };
+
+    // Moves dragged notes and folders into `folder` ("" for the vault root).
+    // A name that is already taken there prompts for another; cancelling the
+    // prompt leaves that item where it is.
+    let move_items = move |paths: Vec<String>, folder: String| {
+        let v_path = vault_path.get_untracked();
+        if v_path.is_empty() {
+            return;
+        }
+        let mut taken = HashSet::new();
+        for path in files.get_untracked().iter().chain(empty_dirs.get_untracked().iter()) {
+            let mut path = path.to_lowercase();
+            loop {
+                let parent = parent_folder(&path).to_string();
+                taken.insert(path);
+                if parent.is_empty() {
+                    break;
+                }
+                path = parent;
+            }
+        }
+        let join = |name: &str| {
+            if folder.is_empty() {
+                name.to_string()
+            } else {
+                format!("{folder}/{name}")
+            }
+        };
+        let place = if folder.is_empty() {
+            "the vault root".to_string()
+        } else {
+            format!("\"{folder}\"")
+        };
+
+        let mut moves = Vec::new();
+        for path in drag_roots(&paths) {
+            if parent_folder(&path) == folder || is_within(&folder, &path) {
+                continue;
+            }
+            let mut name = path.rsplit('/').next().unwrap_or(&path).to_string();
+            let mut target = join(&name);
+            while taken.contains(&target.to_lowercase()) {
+                let suggestion = (2..)
+                    .map(|number| numbered_name(&name, number))
+                    .find(|candidate| !taken.contains(&join(candidate).to_lowercase()))
+                    .unwrap_or_default();
+                let message = format!("\"{name}\" already exists in {place}. Move it as:");
+                let Ok(Some(raw)) = window().prompt_with_message_and_default(&message, &suggestion) else {
+                    target.clear();
+                    break;
+                };
+                name = normalize_rel_path(raw.trim()).rsplit('/').next().unwrap_or_default().to_string();
+                if name.is_empty() {
+                    target.clear();
+                    break;
+                }
+                if path.ends_with(".md") && !name.to_ascii_lowercase().ends_with(".md") {
+                    name.push_str(".md");
+                }
+                target = join(&name);
+            }
+            if target.is_empty() {
+                continue;
+            }
+            taken.insert(target.to_lowercase());
+            moves.push((path, target));
+        }
+        if moves.is_empty() {
+            return;
+        }
+
+        let pending = take_pending_saves(&moves);
+        spawn_local(async move {
+            write_pending_saves(&v_path, pending).await;
+            let preferred = match tauri_bridge::move_items(&v_path, &moves).await {
+                Ok(_) => follow_moves(&moves),
+                Err(err) => {
+                    report_command_error("Moving items", &err);
+                    current_file.get_untracked()
+                }
+            };
+            let preferred = if preferred.is_empty() { None } else { Some(preferred) };
+            refresh_vault_snapshot(v_path, preferred);
+        });
+    };
+
+    let refresh_trash = move || {
+        let v_path = vault_path.get_untracked();
+        if v_path.is_empty() {
+            set_trash_entries.set(Vec::new());
+            return;
+        }
+        let retention_days = settings.get_untracked().trash_retention_days;
+        spawn_local(async move {
+            let entries = tauri_bridge::list_trash(&v_path, retention_days).await;
+            if collapse_path(&v_path) == collapse_path(&vault_path.get_untracked()) {
+                set_trash_entries.set(entries);
+            }
+        });
+    };
+
+    // Reload the trash listing whenever the Trash tab is shown or the vault
+    // changes; listing also applies the retention policy on the backend.
+    Effect::new(move |_| {
+        let _ = vault_path.get();
+        if sidebar_tab.get() == "trash" {
+            refresh_trash();
+        }
+    });
+
     let delete_note = move |file_path: String| {
         set_sidebar_context_menu.set(None);
         let v_path = vault_path.get_untracked();
-        if v_path.is_empty() || !window().confirm_with_message(&format!("Delete note \"{}\"?", file_path)).unwrap_or(false) {
+        if v_path.is_empty() || !window().confirm_with_message(&format!("Move note \"{}\" to the trash?", file_path)).unwrap_or(false) {
             return;
         }
-        let full_path = format!("{}/{}", v_path, file_path);
         let path_for_refresh = v_path.clone();
         let next_file = if current_file.get_untracked() == file_path { None } else { Some(current_file.get_untracked().clone()) };
         spawn_local(async move {
-            tauri_bridge::delete_file(&full_path).await;
+            if let Err(err) = tauri_bridge::delete_file(&v_path, &file_path).await {
+                report_command_error("Deleting note", &err);
+            }
             refresh_vault_snapshot(path_for_refresh, next_file);
+            refresh_trash();
         });
     };
 
     let delete_folder = move |folder_path: String| {
         set_sidebar_context_menu.set(None);
         let v_path = vault_path.get_untracked();
-        if v_path.is_empty() || !window().confirm_with_message(&format!("Delete folder \"{}\" and its contents?", folder_path)).unwrap_or(false) {
+        if v_path.is_empty() || !window().confirm_with_message(&format!("Move folder \"{}\" and its contents to the trash?", folder_path)).unwrap_or(false) {
+            return;
+        }
+        let path_for_refresh = v_path.clone();
+        let current = current_file.get_untracked();
+        let next_file = if !current.is_empty() && current != folder_path && !current.starts_with(&format!("{}/", folder_path)) {
+            Some(current.clone())
+        } else {
+            None
+        };
+        spawn_local(async move {
+            if let Err(err) = tauri_bridge::delete_dir(&v_path, &folder_path).await {
+                report_command_error("Deleting folder", &err);
+            }
+            refresh_vault_snapshot(path_for_refresh, next_file);
+            refresh_trash();
+        });
+    };
+
+    let restore_trash_item = move |id: String| {
+        let v_path = vault_path.get_untracked();
+        if v_path.is_empty() {
+            return;
+        }
+        spawn_local(async move {
+            match tauri_bridge::restore_trash_item(&v_path, &id).await {
+                Ok(restored_path) => set_expanded_folders.update(|expanded| {
+                    expand_parent_folders(expanded, &restored_path);
+                }),
+                Err(err) => report_command_error("Restoring from trash", &err),
+            }
+            let current = current_file.get_untracked();
+            let preferred = if current.is_empty() { None } else { Some(current) };
+            refresh_vault_snapshot(v_path, preferred);
+            refresh_trash();
+        });
+    };
+
+    let empty_trash = move || {
+        let v_path = vault_path.get_untracked();
+        if v_path.is_empty() || !window().confirm_with_message("Permanently delete everything in the trash?").unwrap_or(false) {
+            return;
+        }
+        spawn_local(async move {
+            tauri_bridge::empty_vault_trash(&v_path).await;
+            refresh_trash();
+        });
+    };
+
+    let refresh_attachments = move || {
+        let v_path = vault_path.get_untracked();
+        if v_path.is_empty() {
+            set_attachments.set(Vec::new());
+            return;
+        }
+        spawn_local(async move {
+            let paths = tauri_bridge::list_attachments(&v_path).await;
+            if collapse_path(&v_path) == collapse_path(&vault_path.get_untracked()) {
+                set_attachments.set(paths);
+            }
+        });
+    };
+
+    Effect::new(move |_| {
+        let _ = vault_path.get();
+        if sidebar_tab.get() == "attachments" {
+            refresh_attachments();
+        }
+    });
+
+    let orphans = Memo::new(move |_| {
+        note_texts.with(|notes| orphaned_attachments(&attachments.get(), notes))
+    });
+
+    let rename_attachment_item = move |old_path: String| {
+        let v_path = vault_path.get_untracked();
+        if v_path.is_empty() {
+            return;
+        }
+        let Ok(Some(raw)) = window().prompt_with_message_and_default("Rename attachment", &old_path) else {
+            return;
+        };
+        let next_path = normalize_rel_path(raw.trim());
+        if next_path.is_empty() || next_path == old_path {
+            return;
+        }
+        spawn_local(async move {
+            if let Err(err) = tauri_bridge::rename_attachment(&v_path, &old_path, &next_path).await {
+                report_command_error("Renaming attachment", &err);
+            }
+            let current = current_file.get_untracked();
+            let preferred = if current.is_empty() { None } else { Some(current) };
+            refresh_vault_snapshot(v_path, preferred);
+            refresh_attachments();
+        });
+    };
+
+    let trash_attachments = move |paths: Vec<String>| {
+        let v_path = vault_path.get_untracked();
+        let message = match paths.as_slice() {
+            [] => return,
+            [path] => format!("Move attachment \"{path}\" to the trash?"),
+            _ => format!("Move {} unreferenced attachments to the trash?", paths.len()),
+        };
+        if v_path.is_empty() || !window().confirm_with_message(&message).unwrap_or(false) {
+            return;
+        }
+        spawn_local(async move {
+            for path in &paths {
+                if let Err(err) = tauri_bridge::delete_file(&v_path, path).await {
+                    report_command_error("Deleting attachment", &err);
+                    break;
+                }
+            }
+            refresh_attachments();
+            refresh_trash();
+        });
+    };
+
+    let refresh_history = move || {
+        let v_path = vault_path.get_untracked();
+        let file = current_file.get_untracked();
+        set_selected_history_version.set(None);
+        set_history_version_text.set(None);
+        if v_path.is_empty() || file.is_empty() {
+            set_history_versions.set(Vec::new());
+            return;
+        }
+        spawn_local(async move {
+            let versions = tauri_bridge::list_note_history(&v_path, &file).await;
+            if current_file.get_untracked() == file {
+                set_history_versions.set(versions);
+            }
+        });
+    };
+
+    Effect::new(move |_| {
+        let _ = current_file.get();
+        let _ = vault_path.get();
+        if right_panel.get() == "history" {
+            refresh_history();
+        }
+    });
+
+    let select_history_version = move |version_id: String| {
+        let v_path = vault_path.get_untracked();
+        let file = current_file.get_untracked();
+        if v_path.is_empty() || file.is_empty() {
+            return;
+        }
+        set_selected_history_version.set(Some(version_id.clone()));
+        set_history_version_text.set(None);
+        spawn_local(async move {
+            let text = tauri_bridge::read_note_version(&v_path, &file, &version_id).await;
+            if selected_history_version.get_untracked().as_deref() == Some(version_id.as_str()) {
+                set_history_version_text.set(text);
+            }
+        });
+    };
+
+    // Restoring goes through a transaction and `apply_editor_update`, so the
+    // previous buffer stays on the undo stack.
+    let restore_history_version = move || {
+        let Some(version_text) = history_version_text.get_untracked() else {
+            return;
+        };
+        let snapshot = editor_snapshot.get_untracked();
+        let Some(tx) = replace_document_transaction(&snapshot, &version_text, "restore-version")
+        else {
+            return;
+        };
+        apply_editor_transaction(snapshot, tx);
+    };
+
+    let toggle_right_panel = move |panel: &'static str| {
+        set_right_panel.update(|current| {
+            *current = if current == panel { "metadata" } else { panel }.to_string();
+        });
+    };
+
+    let close_tab = move |path: String| {
+        let was_active = current_file.get_untracked() == path;
+        let mut next = None;
+        set_editor_tabs.update(|tabs| next = tabs.close(&path));
+        if !was_active {
+            return;
+        }
+        match next {
+            Some(next) => select_file(next),
+            None => {
+                set_current_file.set(String::new());
+                set_content.set(String::new());
+                set_parsed_html.set(String::new());
+                set_caret_pos.set(None);
+                set_editor_snapshot.set(EditorSnapshot::new(String::new()));
+                set_history.set(History::default());
+            }
+        }
+    };
+
+    let close_other_tabs = move |keep: String| {
+        set_editor_tabs.update(|tabs| tabs.close_others(&keep));
+        if current_file.get_untracked() != keep {
+            select_file(keep);
+        }
+    };
+
+    let rename_current_note = move || {
+        let v_path = vault_path.get_untracked();
+        let old_name = current_file.get_untracked();
+        if v_path.is_empty() || old_name.is_empty() {
+            return;
+        }
+
+        if let Ok(Some(raw)) = window().prompt_with_message_and_default("Rename note", &old_name) {
+            let next_name = normalize_rel_path(raw.trim());
+            if next_name.is_empty() {
+                return;
+            }
+            let final_name = if next_name.to_ascii_lowercase().ends_with(".md") {
+                next_name
+            } else {
+                format!("{next_name}.md")
+            };
+            if final_name == old_name {
+                return;
+            }
+
+            let path_for_refresh = v_path.clone();
+            let old_for_api = old_name.clone();
+            let next_for_api = final_name.clone();
+            spawn_local(async move {
+                let selected =
+                    match tauri_bridge::rename_note(&v_path, &old_for_api, &next_for_api).await {
+                        Ok(selected) => {
+                            if split_file.get_untracked() == old_for_api {
+                                set_split_file.set(selected.clone());
+                            }
+                            stash_active_tab();
+                            set_editor_tabs.update(|tabs| tabs.rename(&old_for_api, &selected));
+                            selected
+                        }
+                        Err(err) => {
+                            report_command_error("Renaming note", &err);
+                            old_for_api
+                        }
+                    };
+                refresh_vault_snapshot(path_for_refresh, Some(selected));
+            });
+        }
+    };
+
+    let import_from_obsidian_vault = move || {
+        spawn_local(async move {
+            let Some(report) = tauri_bridge::import_obsidian_vault_with_picker().await else {
+                let _ = window()
+                    .alert_with_message("Import failed: backend returned an invalid response.");
+                return;
+            };
+
+            let mut summary = report.message.clone();
+            if report.success {
+                if let Some(source) = report.source_vault.as_ref() {
+                    summary.push_str(&format!("\nSource: {source}"));
+                }
+                if let Some(destination) = report.destination_vault.as_ref() {
+                    summary.push_str(&format!("\nDestination: {destination}"));
+                }
+                summary.push_str(&format!(
+                    "\nNotes: scanned {} imported {} | Images: scanned {} imported {} | Renamed: {}",
+                    report.scanned_notes,
+                    report.imported_notes,
+                    report.scanned_images,
+                    report.imported_images,
+                    report.renamed_notes
+                ));
+            } else if report.cancelled {
+                summary = "Import cancelled by user.".to_string();
+            }
+            let _ = window().alert_with_message(&summary);
+
+            if report.success {
+                if let Some(destination) = report.destination_vault {
+                    activate_vault(destination, None);
+                }
+            }
+        });
+    };
+
+    let open_bedrock_vault = move || {
+        spawn_local(async move {
+            let Some(path) = tauri_bridge::pick_bedrock_vault().await else {
+                return;
+            };
+            activate_vault(path, None);
+        });
+    };
+
+    let switch_to_vault = move |path: String| {
+        if path.is_empty() {
+            return;
+        }
+        let current = collapse_path(&vault_path.get_untracked());
+        let next = collapse_path(&path);
+        if current == next {
+            return;
+        }
+        activate_vault(path, None);
+    };
+
+    let close_current_vault = move || {
+        let current = vault_path.get_untracked();
+        if current.is_empty() {
+            return;
+        }
+
+        let current_norm = collapse_path(&current);
+        let mut next_vault = None::<String>;
+        set_open_vaults.update(|vaults| {
+            vaults.retain(|p| collapse_path(p) != current_norm);
+            next_vault = vaults.last().cloned();
+        });
+
+        if let Some(path) = next_vault {
+            activate_vault(path, None);
+        } else {
+            set_vault_path.set(String::new());
+            clear_active_vault_state();
+            persist_vault_session(Vec::new(), None);
+        }
+    };
+
+    // The editor's state read back from the DOM, for commands that edit it.
+    let editor_dom_snapshot = move || -> Option<EditorSnapshot> {
+        let root = editor_ref.get()?.dyn_into::<HtmlElement>().ok()?;
+        let text = root.text_content().unwrap_or_default();
+        let selection = get_selection_byte_offsets(&root)
+            .unwrap_or_else(|| Selection::cursor(text.len()))
+            .clamp(text.len());
+        Some(snapshot_with_dom_state(
+            editor_snapshot.get_untracked(),
+            text,
+            selection,
+        ))
+    };
+
+    // Copies the selected markdown source only, so a copy inside the editor
+    // never comes back through the HTML conversion on paste.
+    let handle_editor_copy = move |e: leptos::ev::Event, cut: bool| {
+        let Some(raw) = e.dyn_ref::<leptos::web_sys::ClipboardEvent>() else {
+            return;
+        };
+        let Some(snapshot) = editor_dom_snapshot() else {
+            return;
+        };
+        let copied: Vec<&str> = snapshot
+            .selections
+            .ranges()
+            .iter()
+            .filter(|range| !range.is_cursor())
+            .map(|range| &snapshot.text[range.start..range.end])
+            .collect();
+        let Some(data) = raw.clipboard_data() else {
+            return;
+        };
+        if copied.is_empty() || data.set_data("text/plain", &copied.join("\n")).is_err() {
+            return;
+        }
+        e.prevent_default();
+        if cut {
+            if let Some(transaction) = delete_transaction(&snapshot, false) {
+                apply_editor_transaction(snapshot, transaction);
+            }
+        }
+    };
+
+    // Saves pasted or dropped images as attachments of the current note and
+    // embeds them at the caret once they are written.
+    let insert_image_files = move |images: Vec<leptos::web_sys::File>| {
+        let v_path = vault_path.get_untracked();
+        let file = current_file.get_untracked();
+        if v_path.is_empty() || file.is_empty() || images.is_empty() {
+            return;
+        }
+        let current = settings.get_untracked();
+        let folder = attachment_folder(
+            current.attachment_location,
+            &current.attachment_folder,
+            &file,
+        );
+        let format = current.attachment_link_format;
+        spawn_local(async move {
+            let stamp = attachment_stamp();
+            let mut links = Vec::new();
+            for image in images {
+                let Ok(buffer) = wasm_bindgen_futures::JsFuture::from(image.array_buffer()).await
+                else {
+                    continue;
+                };
+                let bytes = js_sys::Uint8Array::new(&buffer).to_vec();
+                let name = attachment_file_name(&image.name(), &image.type_(), &stamp);
+                match tauri_bridge::save_attachment(&v_path, &folder, &name, &encode_base64(&bytes))
+                    .await
+                {
+                    Ok(saved) => links.push(attachment_link(format, &file, &saved)),
+                    Err(err) => report_command_error("Saving attachment", &err),
+                }
+            }
+            if links.is_empty() || current_file.get_untracked() != file {
+                return;
+            }
+            let Some(snapshot) = editor_dom_snapshot() else {
+                return;
+            };
+            if let Some(transaction) = insert_text_transaction(
+                &snapshot,
+                &links.join("\n"),
+                ChangeOrigin::Command,
+                "insert-attachment",
+            ) {
+                apply_editor_transaction(snapshot, transaction);
+            }
+        });
+    };
+
+    let handle_editor_paste = move |e: leptos::ev::Event| {
+        let Some(raw) = e.dyn_ref::<leptos::web_sys::ClipboardEvent>() else {
+            return;
+        };
+        e.prevent_default();
+
+        let data = raw.clipboard_data();
+        let read = |format: &str| {
+            data.as_ref()
+                .and_then(|dt| dt.get_data(format).ok())
+                .unwrap_or_default()
+        };
+        let plain = read("text/plain");
+        let html = read("text/html");
+        // Screenshots and copied images come without plain text; office apps
+        // add a picture of the selection next to its text, which we skip.
+        if plain.is_empty() {
+            let images = data.as_ref().map(image_files).unwrap_or_default();
+            if !images.is_empty() {
+                insert_image_files(images);
+                return;
+            }
+        }
+        if plain.is_empty() && html.is_empty() {
+            return;
+        }
+        let plain = normalize_pasted_text(&plain);
+
+        let Some(snapshot) = editor_dom_snapshot() else {
+            return;
+        };
+        // A URL over a selection links it; rich text arrives as markdown.
+        let markdown = normalize_pasted_text(&html_to_markdown(&html));
+        let transaction = link_selection_transaction(&snapshot, &plain)
+            .or_else(|| {
+                if markdown.trim().is_empty() {
+                    return None;
+                }
+                insert_text_transaction(&snapshot, &markdown, ChangeOrigin::Command, "paste-markdown")
+            })
+            .or_else(|| {
+                insert_text_transaction(&snapshot, &plain, ChangeOrigin::Command, "paste-plain-text")
+            });
+        let Some(transaction) = transaction else {
+            return;
+        };
+
+        apply_editor_transaction(snapshot, transaction);
+    };
+
+    let handle_editor_drop = move |e: leptos::ev::DragEvent| {
+        let images = e.data_transfer().map(|data| image_files(&data)).unwrap_or_default();
+        if images.is_empty() {
+            return;
+        }
+        e.prevent_default();
+        // Move the caret to the drop point so the embeds land there.
+        if let (Some(range), Ok(Some(selection))) = (
+            caret_range_at_point(e.client_x() as f64, e.client_y() as f64),
+            window().get_selection(),
+        ) {
+            let _ = selection.remove_all_ranges();
+            let _ = selection.add_range(&range);
+        }
+        insert_image_files(images);
+    };
+
+    let run_command = move |spec: &'static CommandSpec| match spec.action {
+        CommandAction::NewNote => create_new_note(),
+        CommandAction::RenameNote => rename_current_note(),
+        CommandAction::OpenFirstNote => {
+            if let Some(first) = files.get_untracked().first() {
+                select_file(first.clone());
+            }
+        }
+        CommandAction::CloseTab => {
+            if let Some(active) = editor_tabs.get_untracked().active {
+                close_tab(active);
+            }
+        }
+        CommandAction::SplitRight => open_split(SplitDirection::Vertical),
+        CommandAction::SplitDown => open_split(SplitDirection::Horizontal),
+        CommandAction::ToggleMarkdownSyntax => set_show_markdown_syntax.update(|v| *v = !*v),
+        CommandAction::Find => open_find(),
+        CommandAction::QuickSwitcher => set_switcher_open.set(true),
+        CommandAction::CommandPalette => set_palette_open.set(true),
+        CommandAction::PastePlainText => spawn_local(async move {
+            let Some(pasted) = tauri_bridge::read_clipboard_text().await else {
+                return;
+            };
+            let Some(snapshot) = editor_dom_snapshot() else {
+                return;
+            };
+            let pasted = normalize_pasted_text(&pasted);
+            if let Some(transaction) =
+                insert_text_transaction(&snapshot, &pasted, ChangeOrigin::Command, "paste-plain-text")
+            {
+                apply_editor_transaction(snapshot, transaction);
+            }
+        }),
+        editor_action => {
+            let Some(mut snapshot) = editor_dom_snapshot() else {
+                return;
+            };
+            match editor_action {
+                CommandAction::Markdown(command) => {
+                    let changed = build_markdown_transaction(&snapshot, command)
+                        .is_some_and(|transaction| {
+                            apply_editor_transaction(snapshot.clone(), transaction)
+                        });
+                    if !changed && command == MarkdownCommand::ContinueBlock {
+                        // Outside lists and quotes Enter is a plain line break.
+                        if let Some(newline) = insert_text_transaction(
+                            &snapshot,
+                            "\n",
+                            ChangeOrigin::Command,
+                            "insert-newline",
+                        ) {
+                            apply_editor_transaction(snapshot, newline);
+                        }
+                    }
+                }
+                CommandAction::Undo | CommandAction::Redo => {
+                    let mut changed = false;
+                    set_history.update(|h| {
+                        changed = if editor_action == CommandAction::Redo {
+                            h.redo(&mut snapshot)
+                        } else {
+                            h.undo(&mut snapshot)
+                        };
+                    });
+                    if changed {
+                        publish_editor_snapshot(snapshot);
+                    }
+                }
+                CommandAction::InsertDate => {
+                    if let Some(transaction) = insert_text_transaction(
+                        &snapshot,
+                        &today_iso(),
+                        ChangeOrigin::Command,
+                        "insert-date",
+                    ) {
+                        apply_editor_transaction(snapshot, transaction);
+                    }
+                }
+                CommandAction::SelectNextOccurrence if select_next_occurrence(&mut snapshot) => {
+                    apply_selection_update(snapshot.selections);
+                }
+                CommandAction::AddCursorAbove | CommandAction::AddCursorBelow => {
+                    let down = editor_action == CommandAction::AddCursorBelow;
+                    if add_cursor_on_adjacent_line(&mut snapshot, down) {
+                        apply_selection_update(snapshot.selections);
+                    }
+                }
+                _ => {}
+            }
+        }
+    };
+
+    let run_named_command = move |id: &str| {
+        if let Some(spec) = find_command(id) {
+            run_command(spec);
+        }
+    };
+
+    let command_context = Signal::derive(move || CommandContext {
+        has_vault: !vault_path.get().is_empty(),
+        has_note: !current_file.get().is_empty(),
+    });
+
+    let run_palette_command = move |id: &'static str| {
+        set_palette_open.set(false);
+        let Some(spec) = find_command(id) else {
+            return;
+        };
+        let mut next = settings.get_untracked();
+        remember_command(&mut next.recent_commands, id);
+        set_settings.set(next.clone());
+        save_settings_to_disk(next);
+
+        if spec.scope == CommandScope::Editor {
+            // The palette input took focus; put the caret back before the
+            // command reads it from the editor.
+            if let Some(root) = editor_ref.get().and_then(|el| el.dyn_into::<HtmlElement>().ok()) {
+                let _ = root.focus();
+                set_selection_byte_offsets(&root, editor_snapshot.get_untracked().selection());
+            }
+        }
+        run_command(spec);
+    };
+
+    let switcher_aliases = Signal::derive(move || {
+        metadata_cache.with(|cache| {
+            cache
+                .file_cache
+                .iter()
+                .filter(|(_, fc)| !fc.aliases.is_empty())
+                .map(|(path, fc)| (path.clone(), fc.aliases.clone()))
+                .collect::<HashMap<_, _>>()
+        })
+    });
+
+    let switcher_recent = Signal::derive(move || {
+        recent_notes.with(|list| list.iter().map(|entry| entry.path.clone()).collect::<Vec<_>>())
+    });
+
+    let open_from_switcher = move |path: String| {
+        set_switcher_open.set(false);
+        select_file(path);
+    };
+
+    let open_split_from_switcher = move |path: String| {
+        set_switcher_open.set(false);
+        if split_direction.get_untracked().is_none() {
+            set_split_direction.set(Some(SplitDirection::Vertical));
+        }
+        load_split_file(path);
+    };
+
+    let create_from_switcher = move |name: String| {
+        set_switcher_open.set(false);
+        create_named_note(name);
+    };
+
+    // Feeds one key press into the keymap. Returns true when it was part of a
+    // binding, including the first strokes of a chord.
+    let dispatch_key = move |e: &leptos::ev::KeyboardEvent, in_editor: bool| -> bool {
+        let stroke = KeyStroke::from_event(e);
+        let mut pending = pending_chord.get_untracked();
+        match keymap.get_untracked().resolve(&pending, &stroke) {
+            KeyResolution::Pending => {
+                e.prevent_default();
+                if !stroke.is_modifier_only() {
+                    pending.push(stroke);
+                    set_pending_chord.set(pending);
+                }
+                true
+            }
+            KeyResolution::Run(spec) => {
+                set_pending_chord.set(Vec::new());
+                if spec.scope == CommandScope::Editor && !in_editor {
+                    return false;
+                }
+                e.prevent_default();
+                run_command(spec);
+                true
+            }
+            KeyResolution::NoMatch => {
+                // A key that breaks off a chord is swallowed with it.
+                let was_pending = !pending.is_empty();
+                if was_pending {
+                    e.prevent_default();
+                    set_pending_chord.set(Vec::new());
+                }
+                was_pending
+            }
+        }
+    };
+
+    Effect::new(move |_| {
+        let Some(win) = leptos::web_sys::window() else { return };
+        let Some(doc) = win.document() else { return };
+        let handler = Closure::wrap(Box::new(move |e: KeyboardEvent| {
+            dispatch_key(&e, false);
+        }) as Box<dyn FnMut(KeyboardEvent)>);
+        let _ = doc.add_event_listener_with_callback("keydown", handler.as_ref().unchecked_ref());
+        handler.forget();
+    });
+
+    let handle_editor_keydown = move |e: leptos::ev::KeyboardEvent| {
+        if is_composing.get_untracked() {
+            return;
+        }
+        let Some(el) = editor_ref.get() else {
+            return;
+        };
+        let Ok(root) = el.dyn_into::<HtmlElement>() else {
+            return;
+        };
+
+        let text = root.text_content().unwrap_or_default();
+        let (selection, did_collapse) = get_selection_byte_offsets_with_collapsed(&root)
+            .map(|(s, c)| (s.clamp(text.len()), c))
+            .unwrap_or_else(|| (Selection::cursor(text.len()), false));
+
+        let snapshot =
+            snapshot_with_dom_state(editor_snapshot.get_untracked(), text.clone(), selection);
+        set_editor_snapshot.set(snapshot.clone());
+
+        if handle_completion_key(&e) {
+            return;
+        }
+
+        let key = e.key();
+        if did_collapse && (key == "ArrowUp" || key == "ArrowDown") && !e.alt_key() {
+            return;
+        }
+
+        if key == "Escape" && snapshot.selections.is_multi() && pending_chord.get_untracked().is_empty() {
+            e.prevent_default();
+            apply_selection_update(snapshot.selection().into());
             return;
         }
-        let full_path = format!("{}/{}", v_path, folder_path);
-        let path_for_refresh = v_path.clone();
-        let current = current_file.get_untracked();
-        let next_file = if !current.is_empty() && current != folder_path && !current.starts_with(&format!("{}/", folder_path)) {
-            Some(current.clone())
-        } else {
-            None
-        };
-        spawn_local(async move {
-            tauri_bridge::delete_dir(&full_path).await;
-            refresh_vault_snapshot(path_for_refresh, next_file);
-        });
-    };
 
-    let rename_current_note = move || {
-        let v_path = vault_path.get_untracked();
-        let old_name = current_file.get_untracked();
-        if v_path.is_empty() || old_name.is_empty() {
+        if dispatch_key(&e, true) {
+            // The document-level listener would see this key press again.
+            e.stop_propagation();
             return;
         }
 
-        if let Ok(Some(raw)) = window().prompt_with_message_and_default("Rename note", &old_name) {
-            let next_name = normalize_rel_path(raw.trim());
-            if next_name.is_empty() {
-                return;
-            }
-            let final_name = if next_name.to_ascii_lowercase().ends_with(".md") {
-                next_name
-            } else {
-                format!("{next_name}.md")
-            };
-            if final_name == old_name {
-                return;
-            }
+        let ctrl_or_cmd = e.ctrl_key() || e.meta_key();
 
-            let path_for_refresh = v_path.clone();
-            let old_for_api = old_name.clone();
-            let next_for_api = final_name.clone();
-            spawn_local(async move {
-                let selected =
-                    tauri_bridge::rename_note(&v_path, &old_for_api, &next_for_api).await;
-                refresh_vault_snapshot(path_for_refresh, Some(selected));
-            });
+        if key == "Enter" {
+            // The browser's own Enter inserts block elements into the surface;
+            // keep plain newlines even when Enter is unbound.
+            e.prevent_default();
+            if let Some(newline) =
+                insert_text_transaction(&snapshot, "\n", ChangeOrigin::Command, "insert-newline")
+            {
+                apply_editor_transaction(snapshot, newline);
+            }
+            return;
         }
-    };
 
-    let import_from_obsidian_vault = move || {
-        spawn_local(async move {
-            let Some(report) = tauri_bridge::import_obsidian_vault_with_picker().await else {
-                let _ = window()
-                    .alert_with_message("Import failed: backend returned an invalid response.");
-                return;
+        if !ctrl_or_cmd && !e.alt_key() {
+            let pair = match key.as_str() {
+                "(" => Some(("(", ")")),
+                "[" => Some(("[", "]")),
+                "{" => Some(("{", "}")),
+                "\"" => Some(("\"", "\"")),
+                "'" => Some(("'", "'")),
+                "`" => Some(("`", "`")),
+                _ => None,
             };
 
-            let mut summary = report.message.clone();
-            if report.success {
-                if let Some(source) = report.source_vault.as_ref() {
-                    summary.push_str(&format!("\nSource: {source}"));
-                }
-                if let Some(destination) = report.destination_vault.as_ref() {
-                    summary.push_str(&format!("\nDestination: {destination}"));
+            if let Some((open, close)) = pair {
+                let auto_pair = MarkdownCommand::AutoPair { open, close };
+                if let Some(transaction) = build_markdown_transaction(&snapshot, auto_pair) {
+                    if apply_editor_transaction(snapshot.clone(), transaction) {
+                        e.prevent_default();
+                        return;
+                    }
                 }
-                summary.push_str(&format!(
-                    "\nNotes: scanned {} imported {} | Images: scanned {} imported {} | Renamed: {}",
-                    report.scanned_notes,
-                    report.imported_notes,
-                    report.scanned_images,
-                    report.imported_images,
-                    report.renamed_notes
-                ));
-            } else if report.cancelled {
-                summary = "Import cancelled by user.".to_string();
             }
-            let _ = window().alert_with_message(&summary);
+        }
 
-            if report.success {
-                if let Some(destination) = report.destination_vault {
-                    activate_vault(destination, None);
+        // The browser only edits the primary range, so typing and deleting
+        // with several cursors goes through editor_core instead.
+        if snapshot.selections.is_multi() && !ctrl_or_cmd {
+            let transaction = match key.as_str() {
+                "Backspace" => delete_transaction(&snapshot, false),
+                "Delete" => delete_transaction(&snapshot, true),
+                _ if key.chars().count() == 1 => {
+                    insert_text_transaction(&snapshot, &key, ChangeOrigin::Input, "type")
                 }
-            }
-        });
-    };
-
-    let open_bedrock_vault = move || {
-        spawn_local(async move {
-            let Some(path) = tauri_bridge::pick_bedrock_vault().await else {
-                return;
+                _ => None,
             };
-            activate_vault(path, None);
-        });
-    };
-
-    let switch_to_vault = move |path: String| {
-        if path.is_empty() {
-            return;
-        }
-        let current = collapse_path(&vault_path.get_untracked());
-        let next = collapse_path(&path);
-        if current == next {
-            return;
+            if let Some(transaction) = transaction {
+                e.prevent_default();
+                apply_editor_transaction(snapshot, transaction);
+            }
         }
-        activate_vault(path, None);
     };
 
-    let close_current_vault = move || {
-        let current = vault_path.get_untracked();
-        if current.is_empty() {
-            return;
-        }
-
-        let current_norm = collapse_path(&current);
-        let mut next_vault = None::<String>;
-        set_open_vaults.update(|vaults| {
-            vaults.retain(|p| collapse_path(p) != current_norm);
-            next_vault = vaults.last().cloned();
-        });
 
-        if let Some(path) = next_vault {
-            activate_vault(path, None);
-        } else {
-            set_vault_path.set(String::new());
-            clear_active_vault_state();
-            persist_vault_session(Vec::new(), None);
+    let command_tooltip = move |id: &str| {
+        let title = find_command(id).map(|spec| spec.title).unwrap_or(id);
+        match keymap.get().shortcut_label(id) {
+            Some(keys) => format!("{title} ({keys})"),
+            None => title.to_string(),
         }
     };
 
@@ -2312,6 +4142,161 @@ pub fn App() -> impl IntoView {
                         </div>
                     </div>
 
+                    <h3 style="margin-top: 2.5rem; border-bottom: 1px solid var(--border-color); padding-bottom: 0.5rem;">"Vault"</h3>
+                    <div style="display: grid; grid-template-columns: repeat(auto-fill, minmax(200px, 1fr)); gap: 1.5rem; margin-top: 1.5rem;">
+                        <div style="display: flex; flex-direction: column; gap: 0.5rem;">
+                            <label style="font-weight: 600; font-size: 0.9em;">"Trash Retention (days, 0 = forever)"</label>
+                            <input
+                                style="padding: 0.5rem; border-radius: 4px; border: 1px solid var(--border-color); background: var(--bg-secondary); color: var(--text-primary); width: 100%; box-sizing: border-box;"
+                                type="number"
+                                min="0"
+                                prop:value=move || settings.get().trash_retention_days.to_string()
+                                on:input=move |e| {
+                                    let mut s = settings.get_untracked();
+                                    s.trash_retention_days = event_target_value(&e).parse().unwrap_or(30);
+                                    set_settings.set(s.clone());
+                                    save_settings_to_disk(s);
+                                }
+                            />
+                        </div>
+                        <div style="display: flex; flex-direction: column; gap: 0.5rem;">
+                            <label style="font-weight: 600; font-size: 0.9em;">"History Snapshot Interval (seconds)"</label>
+                            <input
+                                style="padding: 0.5rem; border-radius: 4px; border: 1px solid var(--border-color); background: var(--bg-secondary); color: var(--text-primary); width: 100%; box-sizing: border-box;"
+                                type="number"
+                                min="0"
+                                prop:value=move || settings.get().history_min_interval_secs.to_string()
+                                on:input=move |e| {
+                                    let mut s = settings.get_untracked();
+                                    s.history_min_interval_secs = event_target_value(&e).parse().unwrap_or(300);
+                                    set_settings.set(s.clone());
+                                    save_settings_to_disk(s);
+                                }
+                            />
+                        </div>
+                        <div style="display: flex; flex-direction: column; gap: 0.5rem;">
+                            <label style="font-weight: 600; font-size: 0.9em;">"Max Versions per Note (0 = unlimited)"</label>
+                            <input
+                                style="padding: 0.5rem; border-radius: 4px; border: 1px solid var(--border-color); background: var(--bg-secondary); color: var(--text-primary); width: 100%; box-sizing: border-box;"
+                                type="number"
+                                min="0"
+                                prop:value=move || settings.get().history_max_versions.to_string()
+                                on:input=move |e| {
+                                    let mut s = settings.get_untracked();
+                                    s.history_max_versions = event_target_value(&e).parse().unwrap_or(50);
+                                    set_settings.set(s.clone());
+                                    save_settings_to_disk(s);
+                                }
+                            />
+                        </div>
+                        <div style="display: flex; flex-direction: column; gap: 0.5rem;">
+                            <label style="font-weight: 600; font-size: 0.9em;">"History Retention (days, 0 = forever)"</label>
+                            <input
+                                style="padding: 0.5rem; border-radius: 4px; border: 1px solid var(--border-color); background: var(--bg-secondary); color: var(--text-primary); width: 100%; box-sizing: border-box;"
+                                type="number"
+                                min="0"
+                                prop:value=move || settings.get().history_max_age_days.to_string()
+                                on:input=move |e| {
+                                    let mut s = settings.get_untracked();
+                                    s.history_max_age_days = event_target_value(&e).parse().unwrap_or(90);
+                                    set_settings.set(s.clone());
+                                    save_settings_to_disk(s);
+                                }
+                            />
+                        </div>
+                        <div style="display: flex; flex-direction: column; gap: 0.5rem;">
+                            <label style="font-weight: 600; font-size: 0.9em;">"Git Commit Message ({date}, {count}, {files})"</label>
+                            <input
+                                style="padding: 0.5rem; border-radius: 4px; border: 1px solid var(--border-color); background: var(--bg-secondary); color: var(--text-primary); width: 100%; box-sizing: border-box;"
+                                type="text"
+                                prop:value=move || settings.get().git_commit_template.clone()
+                                on:input=move |e| {
+                                    let mut s = settings.get_untracked();
+                                    s.git_commit_template = event_target_value(&e);
+                                    set_settings.set(s.clone());
+                                    save_settings_to_disk(s);
+                                }
+                            />
+                        </div>
+                        <div style="display: flex; flex-direction: column; gap: 0.5rem;">
+                            <label style="font-weight: 600; font-size: 0.9em;">"Git Auto-commit (minutes, 0 = off)"</label>
+                            <input
+                                style="padding: 0.5rem; border-radius: 4px; border: 1px solid var(--border-color); background: var(--bg-secondary); color: var(--text-primary); width: 100%; box-sizing: border-box;"
+                                type="number"
+                                min="0"
+                                prop:value=move || settings.get().git_auto_commit_minutes.to_string()
+                                on:input=move |e| {
+                                    let mut s = settings.get_untracked();
+                                    s.git_auto_commit_minutes = event_target_value(&e).parse().unwrap_or(0);
+                                    set_settings.set(s.clone());
+                                    save_settings_to_disk(s);
+                                }
+                            />
+                        </div>
+                        <div style="display: flex; flex-direction: column; gap: 0.5rem;">
+                            <label style="font-weight: 600; font-size: 0.9em;">"Save Pasted Images In"</label>
+                            <select
+                                style="padding: 0.5rem; border-radius: 4px; border: 1px solid var(--border-color); background: var(--bg-secondary); color: var(--text-primary); width: 100%; box-sizing: border-box;"
+                                prop:value=move || match settings.get().attachment_location {
+                                    AttachmentLocation::VaultFolder => "vault_folder",
+                                    AttachmentLocation::NoteSubfolder => "note_subfolder",
+                                    AttachmentLocation::NextToNote => "next_to_note",
+                                }
+                                on:change=move |e| {
+                                    let mut s = settings.get_untracked();
+                                    s.attachment_location = match event_target_value(&e).as_str() {
+                                        "note_subfolder" => AttachmentLocation::NoteSubfolder,
+                                        "next_to_note" => AttachmentLocation::NextToNote,
+                                        _ => AttachmentLocation::VaultFolder,
+                                    };
+                                    set_settings.set(s.clone());
+                                    save_settings_to_disk(s);
+                                }
+                            >
+                                <option value="vault_folder">"Attachment folder in the vault"</option>
+                                <option value="note_subfolder">"Attachment folder next to the note"</option>
+                                <option value="next_to_note">"Same folder as the note"</option>
+                            </select>
+                        </div>
+                        <div style="display: flex; flex-direction: column; gap: 0.5rem;">
+                            <label style="font-weight: 600; font-size: 0.9em;">"Attachment Folder Name"</label>
+                            <input
+                                style="padding: 0.5rem; border-radius: 4px; border: 1px solid var(--border-color); background: var(--bg-secondary); color: var(--text-primary); width: 100%; box-sizing: border-box;"
+                                type="text"
+                                prop:value=move || settings.get().attachment_folder.clone()
+                                on:input=move |e| {
+                                    let mut s = settings.get_untracked();
+                                    s.attachment_folder = event_target_value(&e);
+                                    set_settings.set(s.clone());
+                                    save_settings_to_disk(s);
+                                }
+                            />
+                        </div>
+                        <div style="display: flex; flex-direction: column; gap: 0.5rem;">
+                            <label style="font-weight: 600; font-size: 0.9em;">"Image Link Format"</label>
+                            <select
+                                style="padding: 0.5rem; border-radius: 4px; border: 1px solid var(--border-color); background: var(--bg-secondary); color: var(--text-primary); width: 100%; box-sizing: border-box;"
+                                prop:value=move || match settings.get().attachment_link_format {
+                                    AttachmentLinkFormat::Wiki => "wiki",
+                                    AttachmentLinkFormat::Markdown => "markdown",
+                                }
+                                on:change=move |e| {
+                                    let mut s = settings.get_untracked();
+                                    s.attachment_link_format = if event_target_value(&e) == "markdown" {
+                                        AttachmentLinkFormat::Markdown
+                                    } else {
+                                        AttachmentLinkFormat::Wiki
+                                    };
+                                    set_settings.set(s.clone());
+                                    save_settings_to_disk(s);
+                                }
+                            >
+                                <option value="wiki">"![[name.png]]"</option>
+                                <option value="markdown">"![](path/name.png)"</option>
+                            </select>
+                        </div>
+                    </div>
+
                     <h3 style="margin-top: 2.5rem; border-bottom: 1px solid var(--border-color); padding-bottom: 0.5rem;">"Markdown Colors"</h3>
                     <div style="display: grid; grid-template-columns: repeat(auto-fill, minmax(200px, 1fr)); gap: 1.5rem; margin-top: 1.5rem;">
                         <div style="display: flex; flex-direction: column; gap: 0.5rem;"><label style="font-weight: 600; font-size: 0.9em;">"H1 Color"</label><input style="padding: 0; border: none; border-radius: 4px; height: 35px; width: 100%; cursor: pointer;" type="color" prop:value=move || settings.get().md_h1_color.clone() on:input=move |e| { let mut s = settings.get_untracked(); s.md_h1_color = event_target_value(&e); set_settings.set(s.clone()); save_settings_to_disk(s); } /></div>
@@ -2347,6 +4332,11 @@ pub fn App() -> impl IntoView {
                     note_texts_lower=note_texts_lower
                     search_query=search_query
                     set_search_query=set_search_query
+                    trash_entries=trash_entries
+                    trash_retention_days=Signal::derive(move || settings.get().trash_retention_days)
+                    attachments=attachments
+                    orphaned_attachments=orphans
+                    git_statuses=git_statuses
                     on_open_vault=move || open_bedrock_vault()
                     on_close_vault=move || close_current_vault()
                     on_new_note=move || create_new_note()
@@ -2360,10 +4350,28 @@ pub fn App() -> impl IntoView {
                     on_select_file=move |filename| select_file(filename)
                     create_note_in_folder=move |path| create_note_in_folder(path)
                     create_folder_in_folder=move |path| create_folder_in_folder(path)
+                    rename_folder=move |path| rename_folder(path)
                     delete_folder=move |path| delete_folder(path)
                     delete_note=move |path| delete_note(path)
+                    on_restore_trash=move |id| restore_trash_item(id)
+                    on_empty_trash=move || empty_trash()
+                    on_rename_attachment=move |path| rename_attachment_item(path)
+                    on_trash_attachments=move |paths| trash_attachments(paths)
+                    on_move_items=move |paths, folder| move_items(paths, folder)
                 />
                 <EditorPane>
+                    <TabBar
+                        tabs=editor_tabs
+                        on_select=move |path| select_file(path)
+                        on_close=move |path| close_tab(path)
+                        on_close_others=move |path| close_other_tabs(path)
+                        on_toggle_pin=move |path: String| {
+                            set_editor_tabs.update(|tabs| tabs.toggle_pin(&path))
+                        }
+                        on_move=move |path: String, index| {
+                            set_editor_tabs.update(|tabs| tabs.move_tab(&path, index))
+                        }
+                    />
                     {move || if current_file.get().is_empty() {
                         view! {
                             <RecentNotesPane
@@ -2383,14 +4391,19 @@ pub fn App() -> impl IntoView {
                                 on_open_vault=move || open_bedrock_vault()
                                 on_import_obsidian=move || import_from_obsidian_vault()
                                 on_rename=move || rename_current_note()
+                                show_git=Signal::derive(move || git_repo.get().is_repo)
+                                on_history=move || toggle_right_panel("history")
+                                on_git=move || toggle_right_panel("git")
                             />
                             <div class="editor-toolbar" style="display: flex; align-items: center; gap: 0.5rem; padding: 0.5rem 1.25rem; border-bottom: 1px solid var(--border-color); background: var(--bg-secondary);">
-                                <button style="padding: 0.2rem 0.5rem; font-size: 0.75rem;" on:click=move |_| run_editor_action("bold")>"Bold"</button>
-                                <button style="padding: 0.2rem 0.5rem; font-size: 0.75rem;" on:click=move |_| run_editor_action("italic")>"Italic"</button>
-                                <button style="padding: 0.2rem 0.5rem; font-size: 0.75rem;" on:click=move |_| run_editor_action("code")>"Code"</button>
-                                <button style="padding: 0.2rem 0.5rem; font-size: 0.75rem;" on:click=move |_| run_editor_action("link")>"WikiLink"</button>
-                                <button style="padding: 0.2rem 0.5rem; font-size: 0.75rem;" on:click=move |_| run_editor_action("quote")>"Quote"</button>
-                                <button style="padding: 0.2rem 0.5rem; font-size: 0.75rem;" on:click=move |_| run_editor_action("task")>"Task"</button>
+                                <button style="padding: 0.2rem 0.5rem; font-size: 0.75rem;" title=move || command_tooltip("editor:toggle-bold") on:click=move |_| run_named_command("editor:toggle-bold")>"Bold"</button>
+                                <button style="padding: 0.2rem 0.5rem; font-size: 0.75rem;" title=move || command_tooltip("editor:toggle-italic") on:click=move |_| run_named_command("editor:toggle-italic")>"Italic"</button>
+                                <button style="padding: 0.2rem 0.5rem; font-size: 0.75rem;" title=move || command_tooltip("editor:toggle-code") on:click=move |_| run_named_command("editor:toggle-code")>"Code"</button>
+                                <button style="padding: 0.2rem 0.5rem; font-size: 0.75rem;" title=move || command_tooltip("editor:insert-wikilink") on:click=move |_| run_named_command("editor:insert-wikilink")>"WikiLink"</button>
+                                <button style="padding: 0.2rem 0.5rem; font-size: 0.75rem;" title=move || command_tooltip("editor:blockquote") on:click=move |_| run_named_command("editor:blockquote")>"Quote"</button>
+                                <button style="padding: 0.2rem 0.5rem; font-size: 0.75rem;" title=move || command_tooltip("editor:task") on:click=move |_| run_named_command("editor:task")>"Task"</button>
+                                <button style="padding: 0.2rem 0.5rem; font-size: 0.75rem;" title=move || command_tooltip("editor:insert-table") on:click=move |_| run_named_command("editor:insert-table")>"Table"</button>
+                                <button style="padding: 0.2rem 0.5rem; font-size: 0.75rem;" title=move || command_tooltip("editor:insert-code-block") on:click=move |_| run_named_command("editor:insert-code-block")>"Code block"</button>
                                 <div style="margin-left: auto; display: flex; align-items: center; gap: 0.6rem;">
                                     <button
                                         style="padding: 0.2rem 0.5rem; font-size: 0.75rem;"
@@ -2400,10 +4413,48 @@ pub fn App() -> impl IntoView {
                                     >
                                         {move || if show_markdown_syntax.get() { "Hide Markdown" } else { "Show Markdown" }}
                                     </button>
+                                    <button
+                                        style="padding: 0.2rem 0.5rem; font-size: 0.75rem;"
+                                        title="Split right"
+                                        on:click=move |_| open_split(SplitDirection::Vertical)
+                                    >
+                                        "Split ⇥"
+                                    </button>
+                                    <button
+                                        style="padding: 0.2rem 0.5rem; font-size: 0.75rem;"
+                                        title="Split down"
+                                        on:click=move |_| open_split(SplitDirection::Horizontal)
+                                    >
+                                        "Split ⤓"
+                                    </button>
                                     <span style="font-size: 0.75rem; color: var(--text-muted);">"Cmd/Ctrl+B, I, K • Tab/Shift+Tab • Enter continues lists"</span>
                                 </div>
                             </div>
-                            <div class="editor-container" style="flex: 1; position: relative; overflow: hidden; background: var(--bg-primary);">
+                            <FindBar
+                                open=find_open
+                                query=find_query
+                                set_query=set_find_query
+                                replacement=replace_text
+                                set_replacement=set_replace_text
+                                options=find_options
+                                set_options=set_find_options
+                                match_count=Signal::derive(move || find_results.get().len())
+                                active_match=find_active
+                                error=find_error
+                                input_ref=find_input_ref
+                                on_step=step_find
+                                on_replace=replace_current_match
+                                on_replace_all=replace_all_matches
+                                on_close=close_find
+                            />
+                            <div
+                                class="editor-split"
+                                style=move || format!(
+                                    "flex: 1; display: flex; flex-direction: {}; min-height: 0;",
+                                    if split_direction.get() == Some(SplitDirection::Horizontal) { "column" } else { "row" }
+                                )
+                            >
+                            <div class="editor-container" style="flex: 1; position: relative; overflow: hidden; background: var(--bg-primary); min-width: 0; min-height: 0;">
                                 <div
                                     node_ref=editor_ref
                                     class="editor-surface"
@@ -2411,10 +4462,18 @@ pub fn App() -> impl IntoView {
                                     style="width: 100%; height: 100%; padding: 2rem 3rem; font-family: var(--font-editor); font-size: var(--editor-font-size); line-height: 1.6; color: var(--text-primary); white-space: pre-wrap; word-wrap: break-word; box-sizing: border-box; overflow-x: auto; overflow-y: auto; outline: none; caret-color: var(--text-primary);"
                                     contenteditable="true"
                                     spellcheck="false"
-                                    inner_html=move || parsed_html.get()
+                                    inner_html=editor_html
                                     on:keydown=handle_editor_keydown
                                     on:input=update_content
                                     on:paste=handle_editor_paste
+                                    on:dragover=move |e: leptos::ev::DragEvent| {
+                                        if e.data_transfer().is_some_and(|data| data.types().includes(&"Files".into(), 0)) {
+                                            e.prevent_default();
+                                        }
+                                    }
+                                    on:drop=handle_editor_drop
+                                    on:copy=move |e| handle_editor_copy(e, false)
+                                    on:cut=move |e| handle_editor_copy(e, true)
                                     on:compositionstart=handle_composition_start
                                     on:compositionend=handle_composition_end
                                     on:click=move |_| schedule_selection_sync()
@@ -2426,17 +4485,90 @@ pub fn App() -> impl IntoView {
                                     }
                                     on:mouseup=move |_| schedule_selection_sync()
                                     on:focus=move |_| schedule_selection_sync()
+                                    on:blur=move |_| close_completion()
+                                    on:mousedown=move |e: leptos::ev::MouseEvent| {
+                                        set_pending_alt_click.set(e.alt_key().then(|| e.shift_key()));
+                                    }
+                                    on:scroll=move |_| refresh_selection_overlay()
                                 ></div>
+                                {move || {
+                                    extra_selection_rects
+                                        .get()
+                                        .into_iter()
+                                        .map(|rect| {
+                                            let fill = if rect.cursor {
+                                                "background: var(--text-primary);"
+                                            } else {
+                                                "background: var(--accent-color); opacity: 0.25;"
+                                            };
+                                            view! {
+                                                <div style=format!(
+                                                    "position: absolute; left: {}px; top: {}px; width: {}px; height: {}px; pointer-events: none; {fill}",
+                                                    rect.left, rect.top, rect.width, rect.height
+                                                )></div>
+                                            }
+                                        })
+                                        .collect::<Vec<_>>()
+                                }}
+                            </div>
+                            <SplitPane
+                                direction=split_direction
+                                file=split_file
+                                files=files
+                                html=split_html
+                                show_syntax=show_markdown_syntax
+                                editor_ref=split_ref
+                                on_input=handle_split_input
+                                on_keydown=handle_split_keydown
+                                on_selection=sync_split_selection
+                                on_pick=load_split_file
+                                on_close=move || set_split_direction.set(None)
+                            />
                             </div>
                         }
                         .into_any()
                     }}
                 </EditorPane>
-                <MetadataSidebar
-                    files=files
-                    current_file=current_file
-                    metadata_cache=metadata_cache
-                />
+                {move || {
+                    let panel = right_panel.get();
+                    if panel == "git" {
+                        view! {
+                            <GitPanel
+                                repo_info=git_repo
+                                git_statuses=git_statuses
+                                note_diff=git_note_diff
+                                note_log=git_note_log
+                                commit_template=Signal::derive(move || settings.get().git_commit_template)
+                                on_commit=commit_vault
+                                on_refresh=move || refresh_git()
+                                on_close=move || set_right_panel.set("metadata".to_string())
+                            />
+                        }
+                        .into_any()
+                    } else if panel == "history" {
+                        view! {
+                            <HistoryPanel
+                                history_versions=history_versions
+                                selected_version=selected_history_version
+                                version_text=history_version_text
+                                current_text=Signal::derive(move || content.get())
+                                on_select=select_history_version
+                                on_restore=restore_history_version
+                                on_close=move || set_right_panel.set("metadata".to_string())
+                            />
+                        }
+                        .into_any()
+                    } else {
+                        view! {
+                            <MetadataSidebar
+                                files=files
+                                current_file=current_file
+                                metadata_cache=metadata_cache
+                            />
+                        }
+                        .into_any()
+                    }
+                }}
             }
             .into_any()
         }
@@ -2446,6 +4578,30 @@ pub fn App() -> impl IntoView {
         <style>{move || plugin_css.get()}</style>
         <main class="app-layout" style=move || format!("display: flex; height: 100vh; width: 100vw; background: var(--bg-primary); color: var(--text-primary); {}", dynamic_style())>
             {app_view}
+            <CommandPalette
+                open=palette_open
+                keymap=keymap
+                recent=Signal::derive(move || settings.get().recent_commands)
+                context=command_context
+                on_run=run_palette_command
+                on_close=move || set_palette_open.set(false)
+            />
+            <QuickSwitcher
+                open=switcher_open
+                files=files
+                aliases=switcher_aliases
+                recent=switcher_recent
+                on_open=open_from_switcher
+                on_open_split=open_split_from_switcher
+                on_create=create_from_switcher
+                on_close=move || set_switcher_open.set(false)
+            />
+            <SuggestionPopup
+                items=completion_items
+                active=completion_active
+                position=suggest_position
+                on_pick=accept_completion
+            />
         </main>
     }
 }
@@ -14,6 +14,16 @@ pub struct VaultNote {
     pub content: String,
 }
 
+/// Progress streamed by `load_vault_notes`.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum VaultLoadEvent {
+    Started { total: usize },
+    Batch { notes: Vec<VaultNote>, loaded: usize, total: usize },
+    Unreadable { path: String, error: CommandError },
+    Finished { loaded: usize, unreadable: usize },
+}
+
 #[derive(Deserialize, Clone, Debug)]
 pub struct VaultImportReport {
     pub success: bool,
@@ -28,12 +38,99 @@ pub struct VaultImportReport {
     pub renamed_notes: usize,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SavedTab {
+    pub path: String,
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct SavedTabs {
+    pub tabs: Vec<SavedTab>,
+    pub active: Option<String>,
+}
+
 #[derive(Deserialize, Clone, Debug, Default)]
 pub struct VaultSessionState {
     pub open_vaults: Vec<String>,
     pub active_vault: Option<String>,
     #[serde(default)]
     pub recent_notes: HashMap<String, Vec<RecentNoteEntry>>,
+    #[serde(default)]
+    pub open_tabs: HashMap<String, SavedTabs>,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct TrashEntry {
+    pub id: String,
+    pub original_path: String,
+    pub deleted_at: i64,
+    pub is_dir: bool,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct HistoryVersion {
+    pub id: String,
+    pub saved_at: i64,
+    pub hash: String,
+    pub size: usize,
+}
+
+#[derive(Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct GitRepoInfo {
+    pub is_repo: bool,
+    pub branch: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct GitFileStatus {
+    pub path: String,
+    pub status: String,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct GitLogEntry {
+    pub hash: String,
+    pub short_hash: String,
+    pub author: String,
+    pub timestamp: i64,
+    pub subject: String,
+}
+
+/// Mirror of the backend `CommandError`; rejected commands arrive as
+/// `{"kind": "...", ...}`.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CommandError {
+    NotFound { path: String },
+    AlreadyExists { path: String },
+    PermissionDenied { path: String },
+    OutsideVault { path: String },
+    Conflict { message: String },
+    InvalidName { name: String, reason: String },
+    Io { message: String },
+}
+
+impl CommandError {
+    pub fn message(&self) -> String {
+        match self {
+            Self::NotFound { path } => format!("\"{path}\" no longer exists."),
+            Self::AlreadyExists { path } => format!("\"{path}\" already exists. Choose another name."),
+            Self::PermissionDenied { path } => format!("Permission denied for \"{path}\"."),
+            Self::OutsideVault { path } => format!("\"{path}\" is outside the open vault."),
+            Self::Conflict { message } => message.clone(),
+            Self::InvalidName { name, reason } if name.is_empty() => reason.clone(),
+            Self::InvalidName { name, reason } => format!("\"{name}\": {reason}"),
+            Self::Io { message } => message.clone(),
+        }
+    }
+
+    /// Generic I/O failures (locked files, full disks, sync clients) may clear
+    /// up on their own; the other kinds need the user to act first.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::Io { .. })
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -52,6 +149,74 @@ pub struct AppSettings {
     pub md_code_bg: String,
     pub md_code_text: String,
     pub md_quote_color: String,
+    #[serde(default = "default_trash_retention_days")]
+    pub trash_retention_days: u32,
+    #[serde(default = "default_history_min_interval_secs")]
+    pub history_min_interval_secs: u64,
+    #[serde(default = "default_history_max_versions")]
+    pub history_max_versions: u32,
+    #[serde(default = "default_history_max_age_days")]
+    pub history_max_age_days: u32,
+    #[serde(default = "default_git_commit_template")]
+    pub git_commit_template: String,
+    #[serde(default)]
+    pub git_auto_commit_minutes: u32,
+    /// Command ids last run from the command palette, most recent first.
+    #[serde(default)]
+    pub recent_commands: Vec<String>,
+    #[serde(default)]
+    pub attachment_location: AttachmentLocation,
+    #[serde(default = "default_attachment_folder")]
+    pub attachment_folder: String,
+    #[serde(default)]
+    pub attachment_link_format: AttachmentLinkFormat,
+}
+
+/// Where pasted and dropped images are saved.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AttachmentLocation {
+    /// `attachment_folder`, relative to the vault root.
+    #[default]
+    VaultFolder,
+    /// `attachment_folder` inside the note's folder.
+    NoteSubfolder,
+    /// The note's own folder.
+    NextToNote,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AttachmentLinkFormat {
+    /// `![[name.png]]`
+    #[default]
+    Wiki,
+    /// `![](path/to/name.png)`
+    Markdown,
+}
+
+fn default_trash_retention_days() -> u32 {
+    30
+}
+
+fn default_history_min_interval_secs() -> u64 {
+    300
+}
+
+fn default_history_max_versions() -> u32 {
+    50
+}
+
+fn default_history_max_age_days() -> u32 {
+    90
+}
+
+fn default_git_commit_template() -> String {
+    "Vault backup: {date}".to_string()
+}
+
+fn default_attachment_folder() -> String {
+    "attachments".to_string()
 }
 
 impl Default for AppSettings {
@@ -71,6 +236,16 @@ impl Default for AppSettings {
             md_code_bg: "#e9ecef".to_string(),
             md_code_text: "#1a1a1a".to_string(),
             md_quote_color: "#9ca3af".to_string(),
+            trash_retention_days: default_trash_retention_days(),
+            history_min_interval_secs: default_history_min_interval_secs(),
+            history_max_versions: default_history_max_versions(),
+            history_max_age_days: default_history_max_age_days(),
+            git_commit_template: default_git_commit_template(),
+            git_auto_commit_minutes: 0,
+            recent_commands: Vec::new(),
+            attachment_location: AttachmentLocation::default(),
+            attachment_folder: default_attachment_folder(),
+            attachment_link_format: AttachmentLinkFormat::default(),
         }
     }
 }
@@ -0,0 +1,273 @@
+use crate::app_state::{AttachmentLinkFormat, AttachmentLocation};
+use crate::markdown_syntax::{
+    collect_image_targets_for_note, collect_link_targets_for_note, looks_like_external_url,
+};
+use crate::path_utils::collapse_path;
+use std::collections::{HashMap, HashSet};
+
+fn parent_dir(path: &str) -> &str {
+    path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("")
+}
+
+fn join_dirs(dir: &str, child: &str) -> String {
+    match (dir.is_empty(), child.is_empty()) {
+        (true, _) => child.to_string(),
+        (_, true) => dir.to_string(),
+        _ => format!("{dir}/{child}"),
+    }
+}
+
+/// Vault-relative folder that attachments pasted into `note_path` are saved
+/// in. Empty means the vault root.
+pub fn attachment_folder(location: AttachmentLocation, folder: &str, note_path: &str) -> String {
+    let folder = folder.trim().trim_matches('/');
+    let note_dir = parent_dir(note_path);
+    match location {
+        AttachmentLocation::VaultFolder => folder.to_string(),
+        AttachmentLocation::NoteSubfolder => join_dirs(note_dir, folder),
+        AttachmentLocation::NextToNote => note_dir.to_string(),
+    }
+}
+
+fn extension_for_mime(mime: &str) -> &'static str {
+    match mime.trim().to_ascii_lowercase().as_str() {
+        "image/jpeg" | "image/jpg" => "jpg",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        "image/svg+xml" => "svg",
+        "image/bmp" => "bmp",
+        "image/avif" => "avif",
+        "image/tiff" => "tiff",
+        _ => "png",
+    }
+}
+
+/// Name to save an image under. Dropped files keep their own name;
+/// clipboard images, which browsers call `image.png`, get
+/// `pasted-image-<stamp>` with an extension from their MIME type.
+pub fn attachment_file_name(original: &str, mime: &str, stamp: &str) -> String {
+    let original = original.trim();
+    let generic = original
+        .rsplit_once('.')
+        .map_or(original, |(stem, _)| stem)
+        .eq_ignore_ascii_case("image");
+    if original.is_empty() || generic {
+        format!("pasted-image-{stamp}.{}", extension_for_mime(mime))
+    } else {
+        original.to_string()
+    }
+}
+
+/// `saved_path` relative to the folder of `note_path`, with `..` segments
+/// where it lies outside that folder.
+fn relative_to_note(note_path: &str, saved_path: &str) -> String {
+    let from: Vec<&str> = parent_dir(note_path)
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .collect();
+    let to: Vec<&str> = saved_path.split('/').collect();
+    let shared = from
+        .iter()
+        .zip(&to[..to.len() - 1])
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut parts = vec![".."; from.len() - shared];
+    parts.extend(&to[shared..]);
+    parts.join("/")
+}
+
+/// Embed for an attachment saved at `saved_path`. Wiki embeds use the bare
+/// name next to the note and the vault path elsewhere, since both resolve;
+/// markdown embeds use a path relative to the note.
+pub fn attachment_link(format: AttachmentLinkFormat, note_path: &str, saved_path: &str) -> String {
+    match format {
+        AttachmentLinkFormat::Wiki => {
+            let target = if parent_dir(saved_path) == parent_dir(note_path) {
+                saved_path.rsplit('/').next().unwrap_or(saved_path)
+            } else {
+                saved_path
+            };
+            format!("![[{target}]]")
+        }
+        AttachmentLinkFormat::Markdown => {
+            let target = relative_to_note(note_path, saved_path);
+            if target.contains(' ') {
+                format!("![](<{target}>)")
+            } else {
+                format!("![]({target})")
+            }
+        }
+    }
+}
+
+/// Attachments that no note embeds or links to. A target counts for a file
+/// when it resolves from the note's folder or the vault root, or, for a bare
+/// name, when the file has that name anywhere in the vault. Paths compare
+/// case-insensitively, so a file is only reported when nothing could mean it.
+pub fn orphaned_attachments(
+    attachments: &[String],
+    notes: &HashMap<String, String>,
+) -> Vec<String> {
+    let mut paths = HashSet::new();
+    let mut names = HashSet::new();
+    for (note, text) in notes {
+        let note_dir = parent_dir(note).to_lowercase();
+        let targets = collect_image_targets_for_note(text)
+            .into_iter()
+            .chain(collect_link_targets_for_note(text));
+        for (target, _) in targets {
+            let target = target.replace("%20", " ").to_lowercase();
+            if target.is_empty() || looks_like_external_url(&target) {
+                continue;
+            }
+            if let Some(from_root) = target.strip_prefix('/') {
+                paths.insert(collapse_path(from_root));
+                continue;
+            }
+            paths.insert(collapse_path(&join_dirs(&note_dir, &target)));
+            paths.insert(collapse_path(&target));
+            if !target.contains('/') {
+                names.insert(target);
+            }
+        }
+    }
+    attachments
+        .iter()
+        .filter(|path| {
+            let key = path.to_lowercase();
+            let name = key.rsplit('/').next().unwrap_or(&key);
+            !paths.contains(&key) && !names.contains(name)
+        })
+        .cloned()
+        .collect()
+}
+
+/// Standard base64 with padding, for sending file bytes to the backend.
+pub fn encode_base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for (i, shift) in [18, 12, 6, 0].into_iter().enumerate() {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> shift) as usize & 63] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folders_follow_the_location_setting() {
+        let note = "projects/q3/Plan.md";
+        assert_eq!(
+            attachment_folder(AttachmentLocation::VaultFolder, "/attachments/", note),
+            "attachments"
+        );
+        assert_eq!(
+            attachment_folder(AttachmentLocation::NoteSubfolder, "assets", note),
+            "projects/q3/assets"
+        );
+        assert_eq!(
+            attachment_folder(AttachmentLocation::NextToNote, "assets", note),
+            "projects/q3"
+        );
+        assert_eq!(
+            attachment_folder(AttachmentLocation::NoteSubfolder, "assets", "Top.md"),
+            "assets"
+        );
+        assert_eq!(
+            attachment_folder(AttachmentLocation::NextToNote, "", "Top.md"),
+            ""
+        );
+    }
+
+    #[test]
+    fn names_and_links_for_saved_images() {
+        assert_eq!(
+            attachment_file_name("image.png", "image/png", "20241018-091500"),
+            "pasted-image-20241018-091500.png"
+        );
+        assert_eq!(
+            attachment_file_name("", "image/jpeg", "1"),
+            "pasted-image-1.jpg"
+        );
+        assert_eq!(
+            attachment_file_name("chart.webp", "image/webp", "1"),
+            "chart.webp"
+        );
+
+        let note = "projects/Plan.md";
+        let wiki = AttachmentLinkFormat::Wiki;
+        let markdown = AttachmentLinkFormat::Markdown;
+        assert_eq!(attachment_link(wiki, note, "projects/a.png"), "![[a.png]]");
+        assert_eq!(
+            attachment_link(wiki, note, "attachments/a.png"),
+            "![[attachments/a.png]]"
+        );
+        assert_eq!(
+            attachment_link(markdown, note, "projects/img/a.png"),
+            "![](img/a.png)"
+        );
+        assert_eq!(
+            attachment_link(markdown, note, "attachments/a.png"),
+            "![](../attachments/a.png)"
+        );
+        assert_eq!(
+            attachment_link(markdown, "Top.md", "my files/a.png"),
+            "![](<my files/a.png>)"
+        );
+    }
+
+    #[test]
+    fn encodes_base64_with_padding() {
+        assert_eq!(encode_base64(b""), "");
+        assert_eq!(encode_base64(b"f"), "Zg==");
+        assert_eq!(encode_base64(b"fo"), "Zm8=");
+        assert_eq!(encode_base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(encode_base64(&[0xff, 0xfe, 0x00]), "//4A");
+    }
+
+    #[test]
+    fn orphans_are_attachments_nothing_points_at() {
+        let attachments: Vec<String> = [
+            "attachments/used.png",
+            "attachments/Named.PNG",
+            "notes/img/rel.jpg",
+            "docs/spec.pdf",
+            "docs/space name.pdf",
+            "attachments/unused.png",
+            "notes/unused.gif",
+        ]
+        .iter()
+        .map(|path| path.to_string())
+        .collect();
+        let notes = HashMap::from([
+            (
+                "notes/A.md".to_string(),
+                "![[attachments/used.png|200]] ![[named.png]] ![](img/rel.jpg)".to_string(),
+            ),
+            (
+                "B.md".to_string(),
+                "See [[docs/spec.pdf#page=3]] and [x](docs/space%20name.pdf), \
+                 not ![](https://example.com/notes/unused.gif)"
+                    .to_string(),
+            ),
+        ]);
+        assert_eq!(
+            orphaned_attachments(&attachments, &notes),
+            vec!["attachments/unused.png", "notes/unused.gif"]
+        );
+    }
+}
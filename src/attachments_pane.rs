@@ -0,0 +1,109 @@
+use leptos::prelude::*;
+
+#[component]
+pub fn AttachmentsPane<FRename, FTrash>(
+    attachments: ReadSignal<Vec<String>>,
+    orphans: Memo<Vec<String>>,
+    on_rename: FRename,
+    on_trash_orphans: FTrash,
+) -> impl IntoView
+where
+    FRename: Fn(String) + 'static + Clone + Send,
+    FTrash: Fn(Vec<String>) + 'static + Clone + Send,
+{
+    let (orphans_only, set_orphans_only) = signal(false);
+    let trash_orphans = on_trash_orphans.clone();
+
+    view! {
+        <div style="display: flex; flex-direction: column; gap: 0.5rem;">
+            <div style="display: flex; align-items: center; gap: 0.5rem; padding: 0 0.1rem;">
+                <label style="display: flex; align-items: center; gap: 0.3rem; font-size: 0.75rem; color: var(--text-muted);">
+                    <input
+                        type="checkbox"
+                        prop:checked=move || orphans_only.get()
+                        on:change=move |ev| set_orphans_only.set(event_target_checked(&ev))
+                    />
+                    {move || format!("Orphans only ({})", orphans.get().len())}
+                </label>
+                <button
+                    style="margin-left: auto; padding: 0.2rem 0.5rem; font-size: 0.75rem;"
+                    disabled=move || orphans.get().is_empty()
+                    on:click=move |_| trash_orphans(orphans.get_untracked())
+                >
+                    "Trash orphans"
+                </button>
+            </div>
+            {move || {
+                let orphan_paths = orphans.get();
+                let shown: Vec<String> = if orphans_only.get() {
+                    orphan_paths.clone()
+                } else {
+                    attachments.get()
+                };
+                if shown.is_empty() {
+                    let message = if orphans_only.get() {
+                        "Every attachment is referenced by a note."
+                    } else {
+                        "No attachments in this vault."
+                    };
+                    return view! {
+                        <div style="padding: 0.25rem 0.1rem; font-size: 0.8rem; color: var(--text-muted);">
+                            {message}
+                        </div>
+                    }
+                    .into_any();
+                }
+
+                view! {
+                    <>
+                        {shown
+                            .into_iter()
+                            .map(|path| {
+                                let name = path.rsplit('/').next().unwrap_or(&path).to_string();
+                                let is_orphan = orphan_paths.contains(&path);
+                                let rename = on_rename.clone();
+                                let trash = on_trash_orphans.clone();
+                                let rename_path = path.clone();
+                                let trash_path = path.clone();
+                                view! {
+                                    <div
+                                        style="padding: 0.3rem 0.6rem 0.3rem 0.8rem; border-radius: var(--radius-md); margin-bottom: 2px; font-size: 0.82rem; color: var(--text-secondary); display: flex; align-items: center; gap: 0.4rem;"
+                                        title=path.clone()
+                                    >
+                                        <div style="min-width: 0; flex: 1;">
+                                            <div style="overflow: hidden; text-overflow: ellipsis; white-space: nowrap;">
+                                                {name}
+                                            </div>
+                                            <div style="font-size: 0.74rem; color: var(--text-muted); overflow: hidden; text-overflow: ellipsis; white-space: nowrap;">
+                                                {if is_orphan {
+                                                    format!("{path} • Not referenced")
+                                                } else {
+                                                    path.clone()
+                                                }}
+                                            </div>
+                                        </div>
+                                        <button
+                                            style="padding: 0.15rem 0.45rem; font-size: 0.72rem;"
+                                            on:click=move |_| rename(rename_path.clone())
+                                        >
+                                            "Rename"
+                                        </button>
+                                        {is_orphan.then(|| view! {
+                                            <button
+                                                style="padding: 0.15rem 0.45rem; font-size: 0.72rem;"
+                                                on:click=move |_| trash(vec![trash_path.clone()])
+                                            >
+                                                "Trash"
+                                            </button>
+                                        })}
+                                    </div>
+                                }
+                            })
+                            .collect::<Vec<_>>()}
+                    </>
+                }
+                .into_any()
+            }}
+        </div>
+    }
+}
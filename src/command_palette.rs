@@ -0,0 +1,150 @@
+use crate::commands::{rank_commands, CommandContext, Keymap};
+use leptos::html;
+use leptos::prelude::*;
+
+/// Ctrl+Shift+P overlay listing every command with its binding. Running a
+/// command is left to `App`, which knows the editor and vault state.
+#[component]
+pub fn CommandPalette<FRun, FClose>(
+    open: ReadSignal<bool>,
+    keymap: ReadSignal<Keymap>,
+    recent: Signal<Vec<String>>,
+    context: Signal<CommandContext>,
+    on_run: FRun,
+    on_close: FClose,
+) -> impl IntoView
+where
+    FRun: Fn(&'static str) + 'static + Clone + Send,
+    FClose: Fn() + 'static + Clone + Send,
+{
+    let (query, set_query) = signal(String::new());
+    let (selected, set_selected) = signal(0usize);
+    let input_ref = NodeRef::<html::Input>::new();
+
+    let entries = move || rank_commands(&query.get(), &recent.get());
+
+    Effect::new(move |_| {
+        if open.get() {
+            set_query.set(String::new());
+            set_selected.set(0);
+            request_animation_frame(move || {
+                if let Some(input) = input_ref.get() {
+                    let _ = input.focus();
+                }
+            });
+        }
+    });
+
+    let run_key = on_run.clone();
+    let close_key = on_close.clone();
+    let close_backdrop = on_close;
+
+    let on_keydown = move |ev: leptos::ev::KeyboardEvent| {
+        let count = entries().len();
+        match ev.key().as_str() {
+            "ArrowDown" if count > 0 => {
+                ev.prevent_default();
+                set_selected.update(|i| *i = (*i + 1) % count);
+            }
+            "ArrowUp" if count > 0 => {
+                ev.prevent_default();
+                set_selected.update(|i| *i = (*i + count - 1) % count);
+            }
+            "Enter" => {
+                ev.prevent_default();
+                let ctx = context.get_untracked();
+                if let Some(spec) = entries().get(selected.get_untracked()) {
+                    if spec.is_available(ctx) {
+                        run_key(spec.id);
+                    }
+                }
+            }
+            "Escape" => {
+                ev.prevent_default();
+                close_key();
+            }
+            _ => {}
+        }
+    };
+
+    view! {
+        <div
+            class="command-palette-backdrop"
+            style=move || format!(
+                "display: {}; position: fixed; inset: 0; z-index: 1100; background: rgba(0,0,0,0.2); justify-content: center; align-items: flex-start; padding-top: 12vh;",
+                if open.get() { "flex" } else { "none" }
+            )
+            on:click=move |_| close_backdrop()
+        >
+            <div
+                style="width: min(36rem, 90vw); max-height: 60vh; display: flex; flex-direction: column; background: var(--bg-secondary); border: 1px solid var(--border-color); border-radius: var(--radius-lg); box-shadow: 0 12px 32px rgba(0,0,0,0.25); overflow: hidden;"
+                on:click=|ev| ev.stop_propagation()
+            >
+                <input
+                    node_ref=input_ref
+                    type="text"
+                    placeholder="Type a command"
+                    style="padding: 0.7rem 0.9rem; font-size: 0.95rem; border: none; border-bottom: 1px solid var(--border-color); background: var(--bg-primary); color: var(--text-primary); outline: none;"
+                    prop:value=move || query.get()
+                    on:input=move |ev| {
+                        set_query.set(event_target_value(&ev));
+                        set_selected.set(0);
+                    }
+                    on:keydown=on_keydown
+                />
+                <div style="overflow-y: auto; padding: 0.25rem 0;">
+                    {move || {
+                        let ctx = context.get();
+                        let keys = keymap.get();
+                        let recent_ids = recent.get();
+                        let current = selected.get();
+                        let list = entries();
+                        if list.is_empty() {
+                            return view! {
+                                <div style="padding: 0.6rem 0.9rem; font-size: 0.85rem; color: var(--text-muted);">
+                                    "No matching commands"
+                                </div>
+                            }
+                            .into_any();
+                        }
+                        list.into_iter()
+                            .enumerate()
+                            .map(|(index, spec)| {
+                                let available = spec.is_available(ctx);
+                                let shortcut = keys.shortcut_label(spec.id).unwrap_or_default();
+                                let is_recent = query.get_untracked().trim().is_empty()
+                                    && recent_ids.iter().any(|id| id == spec.id);
+                                let run = on_run.clone();
+                                let style = format!(
+                                    "display: flex; align-items: center; gap: 0.75rem; padding: 0.45rem 0.9rem; font-size: 0.85rem; cursor: {}; {} {}",
+                                    if available { "pointer" } else { "default" },
+                                    if index == current { "background: color-mix(in srgb, var(--accent-color) 15%, transparent);" } else { "" },
+                                    if available { "color: var(--text-primary);" } else { "color: var(--text-muted); opacity: 0.6;" }
+                                );
+                                view! {
+                                    <div
+                                        style=style
+                                        title=spec.id
+                                        on:mouseenter=move |_| set_selected.set(index)
+                                        on:click=move |_| {
+                                            if available {
+                                                run(spec.id);
+                                            }
+                                        }
+                                    >
+                                        <span style="flex: 1; overflow: hidden; text-overflow: ellipsis; white-space: nowrap;">{spec.title}</span>
+                                        {is_recent.then(|| view! {
+                                            <span style="font-size: 0.7rem; color: var(--text-muted);">"recent"</span>
+                                        })}
+                                        <span style="font-size: 0.75rem; font-family: var(--font-mono); color: var(--text-muted);">{shortcut}</span>
+                                    </div>
+                                }
+                            })
+                            .collect::<Vec<_>>()
+                            .into_any()
+                    }}
+                </div>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,832 @@
+use crate::editor_core::{BlockKind, MarkdownCommand};
+use crate::table_editor::TableCommand;
+use crate::fuzzy::fuzzy_score;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// What a named command does when it runs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommandAction {
+    Markdown(MarkdownCommand),
+    Undo,
+    Redo,
+    Find,
+    SelectNextOccurrence,
+    AddCursorAbove,
+    AddCursorBelow,
+    InsertDate,
+    PastePlainText,
+    NewNote,
+    RenameNote,
+    OpenFirstNote,
+    QuickSwitcher,
+    CloseTab,
+    SplitRight,
+    SplitDown,
+    ToggleMarkdownSyntax,
+    CommandPalette,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommandScope {
+    /// Only runs while the editor has focus.
+    Editor,
+    /// Runs from anywhere in the window.
+    Global,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CommandSpec {
+    pub id: &'static str,
+    pub title: &'static str,
+    pub action: CommandAction,
+    pub scope: CommandScope,
+    pub default_keys: &'static [&'static str],
+}
+
+const fn editor(
+    id: &'static str,
+    title: &'static str,
+    action: CommandAction,
+    default_keys: &'static [&'static str],
+) -> CommandSpec {
+    CommandSpec {
+        id,
+        title,
+        action,
+        scope: CommandScope::Editor,
+        default_keys,
+    }
+}
+
+const fn global(
+    id: &'static str,
+    title: &'static str,
+    action: CommandAction,
+    default_keys: &'static [&'static str],
+) -> CommandSpec {
+    CommandSpec {
+        id,
+        title,
+        action,
+        scope: CommandScope::Global,
+        default_keys,
+    }
+}
+
+/// Every command that can be bound to keys or triggered from the toolbar.
+pub const COMMANDS: &[CommandSpec] = &[
+    editor(
+        "editor:toggle-bold",
+        "Bold",
+        CommandAction::Markdown(MarkdownCommand::Wrap {
+            open: "**",
+            close: "**",
+            label: "bold",
+        }),
+        &["Mod+B"],
+    ),
+    editor(
+        "editor:toggle-italic",
+        "Italic",
+        CommandAction::Markdown(MarkdownCommand::Wrap {
+            open: "*",
+            close: "*",
+            label: "italic",
+        }),
+        &["Mod+I"],
+    ),
+    editor(
+        "editor:toggle-code",
+        "Inline code",
+        CommandAction::Markdown(MarkdownCommand::Wrap {
+            open: "`",
+            close: "`",
+            label: "code",
+        }),
+        &[],
+    ),
+    editor(
+        "editor:insert-wikilink",
+        "WikiLink",
+        CommandAction::Markdown(MarkdownCommand::Wrap {
+            open: "[[",
+            close: "]]",
+            label: "wikilink",
+        }),
+        &["Mod+K"],
+    ),
+    editor(
+        "editor:blockquote",
+        "Quote",
+        CommandAction::Markdown(MarkdownCommand::PrefixLine {
+            prefix: "> ",
+            label: "quote",
+        }),
+        &[],
+    ),
+    editor(
+        "editor:task",
+        "Task",
+        CommandAction::Markdown(MarkdownCommand::PrefixLine {
+            prefix: "- [ ] ",
+            label: "task",
+        }),
+        &[],
+    ),
+    editor(
+        "editor:indent",
+        "Indent",
+        CommandAction::Markdown(MarkdownCommand::Indent),
+        &["Tab"],
+    ),
+    editor(
+        "editor:outdent",
+        "Outdent",
+        CommandAction::Markdown(MarkdownCommand::Outdent),
+        &["Shift+Tab"],
+    ),
+    editor(
+        "editor:newline",
+        "New line (continue list)",
+        CommandAction::Markdown(MarkdownCommand::ContinueBlock),
+        &["Enter"],
+    ),
+    editor(
+        "editor:move-line-up",
+        "Move line up",
+        CommandAction::Markdown(MarkdownCommand::MoveLines { down: false }),
+        &["Alt+ArrowUp"],
+    ),
+    editor(
+        "editor:move-line-down",
+        "Move line down",
+        CommandAction::Markdown(MarkdownCommand::MoveLines { down: true }),
+        &["Alt+ArrowDown"],
+    ),
+    editor(
+        "editor:duplicate-line",
+        "Duplicate line",
+        CommandAction::Markdown(MarkdownCommand::DuplicateLines),
+        &["Mod+Shift+D"],
+    ),
+    editor(
+        "editor:delete-line",
+        "Delete line",
+        CommandAction::Markdown(MarkdownCommand::DeleteLines),
+        &["Mod+Shift+K"],
+    ),
+    editor(
+        "editor:toggle-task",
+        "Toggle task checkbox",
+        CommandAction::Markdown(MarkdownCommand::ToggleTask),
+        &["Mod+Enter"],
+    ),
+    editor(
+        "editor:cycle-list-marker",
+        "Cycle list marker",
+        CommandAction::Markdown(MarkdownCommand::CycleListMarker),
+        &[],
+    ),
+    editor("editor:undo", "Undo", CommandAction::Undo, &["Mod+Z"]),
+    editor("editor:redo", "Redo", CommandAction::Redo, &["Mod+Shift+Z", "Mod+Y"]),
+    editor("editor:find", "Find and replace", CommandAction::Find, &["Mod+F"]),
+    editor(
+        "editor:select-next-occurrence",
+        "Select next occurrence",
+        CommandAction::SelectNextOccurrence,
+        &["Mod+D"],
+    ),
+    editor(
+        "editor:add-cursor-above",
+        "Add cursor above",
+        CommandAction::AddCursorAbove,
+        &["Alt+Shift+ArrowUp"],
+    ),
+    editor(
+        "editor:add-cursor-below",
+        "Add cursor below",
+        CommandAction::AddCursorBelow,
+        &["Alt+Shift+ArrowDown"],
+    ),
+    editor(
+        "editor:insert-heading-1",
+        "Insert heading 1",
+        CommandAction::Markdown(MarkdownCommand::InsertBlock(BlockKind::Heading(1))),
+        &[],
+    ),
+    editor(
+        "editor:insert-heading-2",
+        "Insert heading 2",
+        CommandAction::Markdown(MarkdownCommand::InsertBlock(BlockKind::Heading(2))),
+        &[],
+    ),
+    editor(
+        "editor:insert-heading-3",
+        "Insert heading 3",
+        CommandAction::Markdown(MarkdownCommand::InsertBlock(BlockKind::Heading(3))),
+        &[],
+    ),
+    editor(
+        "editor:insert-callout",
+        "Insert callout",
+        CommandAction::Markdown(MarkdownCommand::InsertBlock(BlockKind::Callout("note"))),
+        &[],
+    ),
+    editor(
+        "editor:insert-table",
+        "Insert table",
+        CommandAction::Markdown(MarkdownCommand::InsertBlock(BlockKind::Table)),
+        &[],
+    ),
+    editor(
+        "editor:insert-code-block",
+        "Insert code block",
+        CommandAction::Markdown(MarkdownCommand::InsertBlock(BlockKind::CodeFence(""))),
+        &[],
+    ),
+    editor(
+        "editor:insert-math-block",
+        "Insert math block",
+        CommandAction::Markdown(MarkdownCommand::InsertBlock(BlockKind::MathBlock)),
+        &[],
+    ),
+    editor(
+        "editor:insert-horizontal-rule",
+        "Insert horizontal rule",
+        CommandAction::Markdown(MarkdownCommand::InsertBlock(BlockKind::HorizontalRule)),
+        &[],
+    ),
+    editor(
+        "editor:table-format",
+        "Table: format",
+        CommandAction::Markdown(MarkdownCommand::Table(TableCommand::Format)),
+        &[],
+    ),
+    editor(
+        "editor:table-insert-row-above",
+        "Table: insert row above",
+        CommandAction::Markdown(MarkdownCommand::Table(TableCommand::InsertRowAbove)),
+        &[],
+    ),
+    editor(
+        "editor:table-insert-row-below",
+        "Table: insert row below",
+        CommandAction::Markdown(MarkdownCommand::Table(TableCommand::InsertRowBelow)),
+        &[],
+    ),
+    editor(
+        "editor:table-delete-row",
+        "Table: delete row",
+        CommandAction::Markdown(MarkdownCommand::Table(TableCommand::DeleteRow)),
+        &[],
+    ),
+    editor(
+        "editor:table-move-row-up",
+        "Table: move row up",
+        CommandAction::Markdown(MarkdownCommand::Table(TableCommand::MoveRowUp)),
+        &[],
+    ),
+    editor(
+        "editor:table-move-row-down",
+        "Table: move row down",
+        CommandAction::Markdown(MarkdownCommand::Table(TableCommand::MoveRowDown)),
+        &[],
+    ),
+    editor(
+        "editor:table-insert-column-left",
+        "Table: insert column left",
+        CommandAction::Markdown(MarkdownCommand::Table(TableCommand::InsertColumnLeft)),
+        &[],
+    ),
+    editor(
+        "editor:table-insert-column-right",
+        "Table: insert column right",
+        CommandAction::Markdown(MarkdownCommand::Table(TableCommand::InsertColumnRight)),
+        &[],
+    ),
+    editor(
+        "editor:table-delete-column",
+        "Table: delete column",
+        CommandAction::Markdown(MarkdownCommand::Table(TableCommand::DeleteColumn)),
+        &[],
+    ),
+    editor(
+        "editor:table-move-column-left",
+        "Table: move column left",
+        CommandAction::Markdown(MarkdownCommand::Table(TableCommand::MoveColumnLeft)),
+        &[],
+    ),
+    editor(
+        "editor:table-move-column-right",
+        "Table: move column right",
+        CommandAction::Markdown(MarkdownCommand::Table(TableCommand::MoveColumnRight)),
+        &[],
+    ),
+    editor(
+        "editor:table-sort-ascending",
+        "Table: sort by column (A to Z)",
+        CommandAction::Markdown(MarkdownCommand::Table(TableCommand::SortAscending)),
+        &[],
+    ),
+    editor(
+        "editor:table-sort-descending",
+        "Table: sort by column (Z to A)",
+        CommandAction::Markdown(MarkdownCommand::Table(TableCommand::SortDescending)),
+        &[],
+    ),
+    editor(
+        "editor:table-cycle-alignment",
+        "Table: cycle column alignment",
+        CommandAction::Markdown(MarkdownCommand::Table(TableCommand::CycleAlignment)),
+        &[],
+    ),
+    editor(
+        "editor:insert-date",
+        "Insert today's date",
+        CommandAction::InsertDate,
+        &[],
+    ),
+    editor(
+        "editor:paste-plain-text",
+        "Paste as plain text",
+        CommandAction::PastePlainText,
+        &["Mod+Shift+V"],
+    ),
+    global("vault:new-note", "New note", CommandAction::NewNote, &[]),
+    global("vault:rename-note", "Rename note", CommandAction::RenameNote, &[]),
+    global(
+        "vault:open-first-note",
+        "Open first note",
+        CommandAction::OpenFirstNote,
+        &["Mod+1"],
+    ),
+    global(
+        "vault:quick-switcher",
+        "Quick switcher: open note",
+        CommandAction::QuickSwitcher,
+        &["Mod+O"],
+    ),
+    global("workspace:close-tab", "Close tab", CommandAction::CloseTab, &[]),
+    global("workspace:split-right", "Split right", CommandAction::SplitRight, &[]),
+    global("workspace:split-down", "Split down", CommandAction::SplitDown, &[]),
+    global(
+        "workspace:toggle-markdown-syntax",
+        "Show/hide Markdown syntax",
+        CommandAction::ToggleMarkdownSyntax,
+        &[],
+    ),
+    global(
+        "workspace:command-palette",
+        "Command palette",
+        CommandAction::CommandPalette,
+        &["Mod+Shift+P"],
+    ),
+];
+
+/// How many commands the palette remembers as recently used.
+pub const RECENT_COMMANDS_LIMIT: usize = 8;
+
+pub fn find_command(id: &str) -> Option<&'static CommandSpec> {
+    COMMANDS.iter().find(|spec| spec.id == id)
+}
+
+/// What is open right now, to tell which commands can run.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CommandContext {
+    pub has_vault: bool,
+    pub has_note: bool,
+}
+
+impl CommandSpec {
+    pub fn is_available(&self, context: CommandContext) -> bool {
+        match self.action {
+            CommandAction::ToggleMarkdownSyntax | CommandAction::CommandPalette => true,
+            CommandAction::NewNote | CommandAction::OpenFirstNote | CommandAction::QuickSwitcher => {
+                context.has_vault
+            }
+            _ => context.has_note,
+        }
+    }
+}
+
+/// Commands for the palette, best first. An empty query lists recently used
+/// commands (most recent first) and then the rest in registry order; a query
+/// keeps fuzzy matches on the title or id, with recent use breaking ties.
+pub fn rank_commands(query: &str, recent: &[String]) -> Vec<&'static CommandSpec> {
+    let recency = |spec: &CommandSpec| {
+        recent
+            .iter()
+            .position(|id| id == spec.id)
+            .unwrap_or(usize::MAX)
+    };
+    let mut scored: Vec<(i32, usize, usize, &'static CommandSpec)> = COMMANDS
+        .iter()
+        .enumerate()
+        .filter_map(|(order, spec)| {
+            let score = if query.trim().is_empty() {
+                0
+            } else {
+                let title = fuzzy_score(spec.title, query);
+                let id = fuzzy_score(spec.id, query).map(|score| score / 2);
+                title.max(id)?
+            };
+            Some((score, recency(spec), order, spec))
+        })
+        .collect();
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)).then(a.2.cmp(&b.2)));
+    scored.into_iter().map(|(.., spec)| spec).collect()
+}
+
+/// Moves `id` to the front of the recent list, keeping it short.
+pub fn remember_command(recent: &mut Vec<String>, id: &str) {
+    recent.retain(|existing| existing != id);
+    recent.insert(0, id.to_string());
+    recent.truncate(RECENT_COMMANDS_LIMIT);
+}
+
+/// One key press with its modifiers. `Mod` is Ctrl or Cmd, so bindings work
+/// the same on every platform.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct KeyStroke {
+    pub modifier: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub key: String,
+}
+
+impl KeyStroke {
+    pub fn from_event(ev: &leptos::ev::KeyboardEvent) -> Self {
+        Self::new(
+            ev.ctrl_key() || ev.meta_key(),
+            ev.alt_key(),
+            ev.shift_key(),
+            &ev.key(),
+        )
+    }
+
+    fn new(modifier: bool, alt: bool, shift: bool, key: &str) -> Self {
+        let key = match key {
+            " " => "space".to_string(),
+            "Esc" => "escape".to_string(),
+            other => other.to_lowercase(),
+        };
+        // Shift is already part of symbols like `(` or `?`; only letters and
+        // named keys keep it as a separate modifier.
+        let single_symbol = key.chars().count() == 1 && !key.chars().all(char::is_alphabetic);
+        Self {
+            modifier,
+            alt,
+            shift: shift && !single_symbol,
+            key,
+        }
+    }
+
+    /// Parses `Mod+Shift+K` style text. Modifier names are case-insensitive.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let (mods, key) = match text.strip_suffix("++") {
+            Some(mods) => (mods, "+"),
+            None if text == "+" => ("", "+"),
+            None => match text.rsplit_once('+') {
+                Some((mods, key)) => (mods, key),
+                None => ("", text),
+            },
+        };
+        if key.is_empty() {
+            return None;
+        }
+        let (mut modifier, mut alt, mut shift) = (false, false, false);
+        for part in mods.split('+').filter(|part| !part.is_empty()) {
+            match part.trim().to_ascii_lowercase().as_str() {
+                "mod" | "ctrl" | "control" | "cmd" | "meta" => modifier = true,
+                "alt" | "option" => alt = true,
+                "shift" => shift = true,
+                _ => return None,
+            }
+        }
+        Some(Self::new(modifier, alt, shift, key))
+    }
+
+    pub fn is_modifier_only(&self) -> bool {
+        matches!(self.key.as_str(), "control" | "meta" | "alt" | "shift")
+    }
+
+    pub fn label(&self) -> String {
+        let mut out = String::new();
+        if self.modifier {
+            out.push_str("Ctrl+");
+        }
+        if self.alt {
+            out.push_str("Alt+");
+        }
+        if self.shift {
+            out.push_str("Shift+");
+        }
+        match self.key.as_str() {
+            "arrowup" => out.push_str("Up"),
+            "arrowdown" => out.push_str("Down"),
+            "arrowleft" => out.push_str("Left"),
+            "arrowright" => out.push_str("Right"),
+            "escape" => out.push_str("Esc"),
+            key => {
+                let mut chars = key.chars();
+                if let Some(first) = chars.next() {
+                    out.extend(first.to_uppercase());
+                    out.push_str(chars.as_str());
+                }
+            }
+        }
+        out
+    }
+}
+
+/// A sequence of key strokes, e.g. `Mod+K Mod+B`.
+pub type Chord = Vec<KeyStroke>;
+
+pub fn parse_chord(text: &str) -> Option<Chord> {
+    let chord: Option<Chord> = text.split_whitespace().map(KeyStroke::parse).collect();
+    chord.filter(|strokes| !strokes.is_empty())
+}
+
+pub fn chord_label(chord: &[KeyStroke]) -> String {
+    chord
+        .iter()
+        .map(KeyStroke::label)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Problems found while reading `keymap.json`. Bindings that caused them are
+/// left out; everything else still applies.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum KeymapIssue {
+    InvalidJson(String),
+    UnknownCommand(String),
+    InvalidKeys { command: String, keys: String },
+    /// The same chord, or one that starts another, is bound to several commands.
+    Conflict { keys: String, commands: Vec<&'static str> },
+}
+
+impl KeymapIssue {
+    pub fn message(&self) -> String {
+        match self {
+            Self::InvalidJson(err) => format!("keymap.json is not valid JSON: {err}"),
+            Self::UnknownCommand(id) => format!("keymap.json: unknown command `{id}`"),
+            Self::InvalidKeys { command, keys } => {
+                format!("keymap.json: cannot read keys `{keys}` for `{command}`")
+            }
+            Self::Conflict { keys, commands } => {
+                format!("keymap.json: `{keys}` is bound to {}", commands.join(", "))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum KeyResolution {
+    Run(&'static CommandSpec),
+    /// The strokes so far start a longer chord; wait for the next one.
+    Pending,
+    NoMatch,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Keymap {
+    bindings: Vec<(Chord, &'static str)>,
+}
+
+impl Keymap {
+    pub fn defaults() -> Self {
+        Self::from_user_json("{}").0
+    }
+
+    /// Built-in bindings with the user's `keymap.json` on top. The file maps
+    /// command ids to a key string or a list of them; `[]` or `null` unbinds.
+    pub fn from_user_json(json: &str) -> (Self, Vec<KeymapIssue>) {
+        let mut issues = Vec::new();
+        let overrides = match serde_json::from_str::<HashMap<String, Value>>(json) {
+            Ok(map) => map,
+            Err(err) => {
+                issues.push(KeymapIssue::InvalidJson(err.to_string()));
+                HashMap::new()
+            }
+        };
+        let mut ids: Vec<&String> = overrides.keys().collect();
+        ids.sort();
+        for id in ids {
+            if find_command(id).is_none() {
+                issues.push(KeymapIssue::UnknownCommand(id.clone()));
+            }
+        }
+
+        let mut bindings = Vec::new();
+        for spec in COMMANDS {
+            let keys: Vec<String> = match overrides.get(spec.id) {
+                None => spec.default_keys.iter().map(|k| k.to_string()).collect(),
+                Some(Value::Null) => Vec::new(),
+                Some(Value::String(keys)) => vec![keys.clone()],
+                Some(Value::Array(items)) => items
+                    .iter()
+                    .map(|item| item.as_str().map(str::to_string).unwrap_or_default())
+                    .collect(),
+                Some(other) => vec![other.to_string()],
+            };
+            for keys in keys {
+                match parse_chord(&keys) {
+                    Some(chord) => bindings.push((chord, spec.id)),
+                    None => issues.push(KeymapIssue::InvalidKeys {
+                        command: spec.id.to_string(),
+                        keys,
+                    }),
+                }
+            }
+        }
+
+        let keymap = Self { bindings };
+        issues.extend(keymap.conflicts());
+        (keymap, issues)
+    }
+
+    fn conflicts(&self) -> Vec<KeymapIssue> {
+        let mut issues: Vec<KeymapIssue> = Vec::new();
+        for (index, (chord, id)) in self.bindings.iter().enumerate() {
+            let mut commands = vec![*id];
+            for (other, other_id) in &self.bindings[index + 1..] {
+                let shorter = chord.len().min(other.len());
+                if chord[..shorter] == other[..shorter] && !commands.contains(other_id) {
+                    commands.push(other_id);
+                }
+            }
+            let keys = chord_label(chord);
+            let already = issues
+                .iter()
+                .any(|issue| matches!(issue, KeymapIssue::Conflict { keys: k, .. } if *k == keys));
+            if commands.len() > 1 && !already {
+                issues.push(KeymapIssue::Conflict { keys, commands });
+            }
+        }
+        issues
+    }
+
+    /// Looks up `pending` strokes followed by `stroke`. Exact matches win;
+    /// conflicting bindings resolve to the first command in `COMMANDS`.
+    pub fn resolve(&self, pending: &[KeyStroke], stroke: &KeyStroke) -> KeyResolution {
+        if stroke.is_modifier_only() {
+            return if pending.is_empty() {
+                KeyResolution::NoMatch
+            } else {
+                KeyResolution::Pending
+            };
+        }
+        let mut typed = pending.to_vec();
+        typed.push(stroke.clone());
+        let mut prefix = false;
+        for (chord, id) in &self.bindings {
+            if *chord == typed {
+                if let Some(spec) = find_command(id) {
+                    return KeyResolution::Run(spec);
+                }
+            } else if chord.len() > typed.len() && chord[..typed.len()] == typed[..] {
+                prefix = true;
+            }
+        }
+        if prefix {
+            KeyResolution::Pending
+        } else {
+            KeyResolution::NoMatch
+        }
+    }
+
+    /// Display text for the first binding of `id`, for tooltips.
+    pub fn shortcut_label(&self, id: &str) -> Option<String> {
+        self.bindings
+            .iter()
+            .find(|(_, bound)| *bound == id)
+            .map(|(chord, _)| chord_label(chord))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stroke(text: &str) -> KeyStroke {
+        KeyStroke::parse(text).unwrap()
+    }
+
+    #[test]
+    fn parses_modifiers_named_keys_and_symbols() {
+        assert_eq!(stroke("Ctrl+Shift+z"), stroke("mod+shift+Z"));
+        assert_eq!(stroke("Cmd+B"), KeyStroke::new(true, false, false, "b"));
+        assert_eq!(stroke("Shift+("), KeyStroke::new(false, false, false, "("));
+        assert_eq!(stroke("Mod++").key, "+");
+        assert_eq!(stroke("Alt+Shift+ArrowUp").label(), "Alt+Shift+Up");
+        assert!(KeyStroke::parse("Hyper+K").is_none());
+        assert_eq!(parse_chord("Mod+K  Mod+B").map(|c| c.len()), Some(2));
+        assert!(parse_chord("  ").is_none());
+    }
+
+    #[test]
+    fn defaults_resolve_and_have_no_conflicts() {
+        let (keymap, issues) = Keymap::from_user_json("{}");
+        assert!(issues.is_empty(), "{issues:?}");
+        match keymap.resolve(&[], &stroke("Mod+B")) {
+            KeyResolution::Run(spec) => assert_eq!(spec.id, "editor:toggle-bold"),
+            other => panic!("unexpected {other:?}"),
+        }
+        assert_eq!(keymap.resolve(&[], &stroke("Mod+Q")), KeyResolution::NoMatch);
+        assert_eq!(keymap.shortcut_label("editor:redo").as_deref(), Some("Ctrl+Shift+Z"));
+    }
+
+    #[test]
+    fn user_bindings_override_defaults_and_support_chords() {
+        let (keymap, issues) = Keymap::from_user_json(
+            r#"{
+                "editor:toggle-bold": ["Mod+K Mod+B"],
+                "editor:insert-wikilink": null,
+                "vault:new-note": "Mod+N"
+            }"#,
+        );
+        assert!(issues.is_empty(), "{issues:?}");
+        assert_eq!(keymap.resolve(&[], &stroke("Mod+B")), KeyResolution::NoMatch);
+        assert_eq!(keymap.resolve(&[], &stroke("Mod+K")), KeyResolution::Pending);
+        assert_eq!(
+            keymap.resolve(&[stroke("Mod+K")], &stroke("Control")),
+            KeyResolution::Pending
+        );
+        match keymap.resolve(&[stroke("Mod+K")], &stroke("Mod+B")) {
+            KeyResolution::Run(spec) => assert_eq!(spec.id, "editor:toggle-bold"),
+            other => panic!("unexpected {other:?}"),
+        }
+        match keymap.resolve(&[], &stroke("Mod+N")) {
+            KeyResolution::Run(spec) => assert_eq!(spec.action, CommandAction::NewNote),
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+
+    #[test]
+    fn palette_ranks_recent_commands_and_fuzzy_matches() {
+        let recent = vec!["vault:new-note".to_string(), "editor:find".to_string()];
+        let ids: Vec<&str> = rank_commands("", &recent).iter().map(|s| s.id).collect();
+        assert_eq!(&ids[..2], &["vault:new-note", "editor:find"]);
+        assert_eq!(ids.len(), COMMANDS.len());
+
+        let ids: Vec<&str> = rank_commands("splt rig", &[]).iter().map(|s| s.id).collect();
+        assert_eq!(ids.first(), Some(&"workspace:split-right"));
+        assert!(rank_commands("zzzz", &[]).is_empty());
+
+        let mut recent = recent;
+        remember_command(&mut recent, "editor:find");
+        assert_eq!(recent, vec!["editor:find", "vault:new-note"]);
+    }
+
+    #[test]
+    fn availability_follows_open_vault_and_note() {
+        let bold = find_command("editor:toggle-bold").unwrap();
+        let new_note = find_command("vault:new-note").unwrap();
+        let palette = find_command("workspace:command-palette").unwrap();
+        let nothing = CommandContext::default();
+        let vault_only = CommandContext {
+            has_vault: true,
+            has_note: false,
+        };
+        assert!(!bold.is_available(vault_only));
+        assert!(new_note.is_available(vault_only));
+        assert!(!new_note.is_available(nothing));
+        assert!(palette.is_available(nothing));
+    }
+
+    #[test]
+    fn reports_unknown_commands_bad_keys_and_conflicts() {
+        let (keymap, issues) = Keymap::from_user_json(
+            r#"{
+                "editor:bogus": "Mod+J",
+                "editor:toggle-code": "Hyper+C",
+                "editor:task": "Mod+B",
+                "vault:new-note": "Mod+I Mod+N"
+            }"#,
+        );
+        assert!(issues.contains(&KeymapIssue::UnknownCommand("editor:bogus".to_string())));
+        assert!(issues.contains(&KeymapIssue::InvalidKeys {
+            command: "editor:toggle-code".to_string(),
+            keys: "Hyper+C".to_string(),
+        }));
+        assert!(issues.contains(&KeymapIssue::Conflict {
+            keys: "Ctrl+B".to_string(),
+            commands: vec!["editor:toggle-bold", "editor:task"],
+        }));
+        assert!(issues.contains(&KeymapIssue::Conflict {
+            keys: "Ctrl+I".to_string(),
+            commands: vec!["editor:toggle-italic", "vault:new-note"],
+        }));
+        // The earlier command keeps a conflicting chord.
+        match keymap.resolve(&[], &stroke("Mod+B")) {
+            KeyResolution::Run(spec) => assert_eq!(spec.id, "editor:toggle-bold"),
+            other => panic!("unexpected {other:?}"),
+        }
+
+        let (_, issues) = Keymap::from_user_json("not json");
+        assert!(matches!(issues.as_slice(), [KeymapIssue::InvalidJson(_)]));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::table_editor::{table_transaction, TableCommand};
 use regex::Regex;
 use std::sync::OnceLock;
 
@@ -35,6 +36,104 @@ impl Selection {
     }
 }
 
+/// One or more selections, kept sorted and non-overlapping. The primary
+/// range is the one the DOM caret follows; the others are drawn on top.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SelectionSet {
+    ranges: Vec<Selection>,
+    primary: usize,
+}
+
+impl SelectionSet {
+    pub fn single(selection: Selection) -> Self {
+        Self {
+            ranges: vec![selection],
+            primary: 0,
+        }
+    }
+
+    /// Sorts `ranges` and merges overlapping ones. `primary` indexes into
+    /// `ranges` as given.
+    pub fn new(ranges: Vec<Selection>, primary: usize) -> Self {
+        if ranges.is_empty() {
+            return Self::single(Selection::default());
+        }
+        let primary_range = ranges[primary.min(ranges.len() - 1)];
+        let mut sorted = ranges;
+        sorted.sort_by_key(|range| (range.start, range.end));
+
+        let mut merged: Vec<Selection> = Vec::with_capacity(sorted.len());
+        let mut primary = 0;
+        for range in sorted {
+            match merged.last_mut() {
+                Some(last) if range.start < last.end || range.start == last.start => {
+                    *last = Selection::new(last.start, last.end.max(range.end));
+                }
+                _ => merged.push(range),
+            }
+            if range == primary_range {
+                primary = merged.len() - 1;
+            }
+        }
+        Self {
+            ranges: merged,
+            primary,
+        }
+    }
+
+    pub fn primary(&self) -> Selection {
+        self.ranges[self.primary]
+    }
+
+    pub fn primary_index(&self) -> usize {
+        self.primary
+    }
+
+    pub fn ranges(&self) -> &[Selection] {
+        &self.ranges
+    }
+
+    pub fn is_multi(&self) -> bool {
+        self.ranges.len() > 1
+    }
+
+    /// Adds `selection` and makes it the primary range.
+    pub fn add(&mut self, selection: Selection) {
+        let mut ranges = self.ranges.clone();
+        ranges.push(selection);
+        let primary = ranges.len() - 1;
+        *self = Self::new(ranges, primary);
+    }
+
+    fn clamp(&self, len: usize) -> Self {
+        Self::new(
+            self.ranges.iter().map(|range| range.clamp(len)).collect(),
+            self.primary,
+        )
+    }
+
+    fn map_through(&self, changes: &[TextChange]) -> Self {
+        Self::new(
+            self.ranges
+                .iter()
+                .map(|range| {
+                    Selection::new(
+                        map_position_through_changes(range.start, changes),
+                        map_position_through_changes(range.end, changes),
+                    )
+                })
+                .collect(),
+            self.primary,
+        )
+    }
+}
+
+impl From<Selection> for SelectionSet {
+    fn from(selection: Selection) -> Self {
+        Self::single(selection)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct TextChange {
     pub start: usize,
@@ -65,7 +164,7 @@ pub enum ChangeOrigin {
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Transaction {
     pub changes: Vec<TextChange>,
-    pub selection_after: Option<Selection>,
+    pub selection_after: Option<SelectionSet>,
     pub origin: ChangeOrigin,
     pub label: &'static str,
 }
@@ -79,7 +178,7 @@ impl Transaction {
     ) -> Self {
         Self {
             changes: vec![change],
-            selection_after,
+            selection_after: selection_after.map(SelectionSet::single),
             origin,
             label,
         }
@@ -111,7 +210,7 @@ pub enum CoreError {
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct EditorSnapshot {
     pub text: String,
-    pub selection: Selection,
+    pub selections: SelectionSet,
     pub revision: u64,
 }
 
@@ -120,22 +219,35 @@ impl EditorSnapshot {
         let len = text.len();
         Self {
             text,
-            selection: Selection::cursor(len),
+            selections: SelectionSet::single(Selection::cursor(len)),
             revision: 0,
         }
     }
 
+    /// The primary selection.
+    pub fn selection(&self) -> Selection {
+        self.selections.primary()
+    }
+
     pub fn set_selection(&mut self, selection: Selection) {
-        self.selection = selection.clamp(self.text.len());
+        self.selections = SelectionSet::single(selection.clamp(self.text.len()));
+    }
+
+    pub fn set_selections(&mut self, selections: SelectionSet) {
+        self.selections = selections.clamp(self.text.len());
     }
 
-    pub fn replace_from_input(&mut self, new_text: String, selection: Selection) -> ApplyOutcome {
-        let next_selection = selection.clamp(new_text.len());
+    pub fn replace_from_input(
+        &mut self,
+        new_text: String,
+        selection: impl Into<SelectionSet>,
+    ) -> ApplyOutcome {
+        let next_selection = selection.into().clamp(new_text.len());
         let text_changed = self.text != new_text;
-        let selection_changed = self.selection != next_selection;
+        let selection_changed = self.selections != next_selection;
 
         self.text = new_text;
-        self.selection = next_selection;
+        self.selections = next_selection;
         if text_changed {
             self.revision += 1;
         }
@@ -160,20 +272,14 @@ impl EditorSnapshot {
 
         let next_selection = transaction
             .selection_after
-            .map(|selection| selection.clamp(next_text.len()))
-            .unwrap_or_else(|| {
-                Selection::new(
-                    map_position_through_changes(self.selection.start, &normalized),
-                    map_position_through_changes(self.selection.end, &normalized),
-                )
-                .clamp(next_text.len())
-            });
+            .unwrap_or_else(|| self.selections.map_through(&normalized))
+            .clamp(next_text.len());
 
         let text_changed = self.text != next_text;
-        let selection_changed = self.selection != next_selection;
+        let selection_changed = self.selections != next_selection;
 
         self.text = next_text;
-        self.selection = next_selection;
+        self.selections = next_selection;
         if text_changed {
             self.revision += 1;
         }
@@ -247,6 +353,308 @@ fn map_position_through_changes(mut pos: usize, changes: &[TextChange]) -> usize
     pos
 }
 
+/// Consecutive input transactions closer together than this undo as one step.
+pub const HISTORY_GROUP_INTERVAL_MS: f64 = 800.0;
+const HISTORY_MAX_ENTRIES: usize = 500;
+const HISTORY_MAX_BYTES: usize = 4 * 1024 * 1024;
+
+/// One applied transaction: its changes and the changes that revert them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct HistoryStep {
+    forward: Vec<TextChange>,
+    inverse: Vec<TextChange>,
+}
+
+impl HistoryStep {
+    fn bytes(&self) -> usize {
+        self.forward
+            .iter()
+            .chain(&self.inverse)
+            .map(|change| change.insert.len())
+            .sum()
+    }
+}
+
+/// A unit of undo: one transaction, or a run of grouped typing.
+#[derive(Clone, Debug, PartialEq)]
+struct HistoryEntry {
+    steps: Vec<HistoryStep>,
+    selection_before: SelectionSet,
+    selection_after: SelectionSet,
+    origin: ChangeOrigin,
+    last_edit_ms: f64,
+    bytes: usize,
+}
+
+/// Undo/redo history stored as inverted change sets rather than document
+/// copies, so its size tracks the edits made instead of the note length.
+#[derive(Clone, Debug, PartialEq)]
+pub struct History {
+    undo: Vec<HistoryEntry>,
+    redo: Vec<HistoryEntry>,
+    /// Approximate memory held by the recorded changes, in bytes of text.
+    bytes: usize,
+    max_entries: usize,
+    max_bytes: usize,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::with_limits(HISTORY_MAX_ENTRIES, HISTORY_MAX_BYTES)
+    }
+}
+
+impl History {
+    pub fn with_limits(max_entries: usize, max_bytes: usize) -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            bytes: 0,
+            max_entries: max_entries.max(1),
+            max_bytes,
+        }
+    }
+
+    /// Applies `transaction` to `snapshot` and records it for undo.
+    pub fn apply(
+        &mut self,
+        snapshot: &mut EditorSnapshot,
+        transaction: Transaction,
+        now_ms: f64,
+    ) -> Result<ApplyOutcome, CoreError> {
+        let forward = normalize_changes(&transaction.changes, snapshot.text.len())?;
+        let inverse = invert_changes(&snapshot.text, &forward);
+        let selection_before = snapshot.selections.clone();
+        let origin = transaction.origin;
+        let outcome = snapshot.apply_transaction(transaction)?;
+        if outcome.text_changed {
+            self.record(
+                HistoryStep { forward, inverse },
+                selection_before,
+                snapshot.selections.clone(),
+                origin,
+                now_ms,
+            );
+        }
+        Ok(outcome)
+    }
+
+    fn record(
+        &mut self,
+        step: HistoryStep,
+        selection_before: SelectionSet,
+        selection_after: SelectionSet,
+        origin: ChangeOrigin,
+        now_ms: f64,
+    ) {
+        let redo_bytes: usize = self.redo.drain(..).map(|entry| entry.bytes).sum();
+        self.bytes -= redo_bytes;
+
+        let step_bytes = step.bytes();
+        self.bytes += step_bytes;
+        // Typing continues the previous entry when it edits at the caret that
+        // entry left behind, soon enough after it.
+        if let Some(last) = self.undo.last_mut() {
+            let caret = last.selection_after.primary().start;
+            let at_caret = step.forward.len() == 1
+                && step.forward[0].start <= caret
+                && caret <= step.forward[0].end;
+            if origin == ChangeOrigin::Input
+                && last.origin == ChangeOrigin::Input
+                && at_caret
+                && now_ms - last.last_edit_ms <= HISTORY_GROUP_INTERVAL_MS
+            {
+                last.steps.push(step);
+                last.selection_after = selection_after;
+                last.last_edit_ms = now_ms;
+                last.bytes += step_bytes;
+                self.trim();
+                return;
+            }
+        }
+        self.undo.push(HistoryEntry {
+            steps: vec![step],
+            selection_before,
+            selection_after,
+            origin,
+            last_edit_ms: now_ms,
+            bytes: step_bytes,
+        });
+        self.trim();
+    }
+
+    /// Drops the oldest entries until the history fits its limits. The newest
+    /// entry is always kept so the last edit can be undone.
+    fn trim(&mut self) {
+        while self.undo.len() > 1
+            && (self.undo.len() > self.max_entries || self.bytes > self.max_bytes)
+        {
+            let dropped = self.undo.remove(0);
+            self.bytes -= dropped.bytes;
+        }
+    }
+
+    /// Reverts the latest entry. Returns `false` when there is nothing to
+    /// undo or the snapshot no longer matches the recorded changes; the
+    /// snapshot is only touched when every step applies.
+    pub fn undo(&mut self, snapshot: &mut EditorSnapshot) -> bool {
+        let Some(entry) = self.undo.pop() else {
+            return false;
+        };
+        let mut next = snapshot.clone();
+        for step in entry.steps.iter().rev() {
+            if !replay(&mut next, &step.inverse, "undo") {
+                self.forget();
+                return false;
+            }
+        }
+        next.set_selections(entry.selection_before.clone());
+        *snapshot = next;
+        self.redo.push(entry);
+        true
+    }
+
+    /// Re-applies the latest undone entry, all or nothing like `undo`.
+    pub fn redo(&mut self, snapshot: &mut EditorSnapshot) -> bool {
+        let Some(entry) = self.redo.pop() else {
+            return false;
+        };
+        let mut next = snapshot.clone();
+        for step in &entry.steps {
+            if !replay(&mut next, &step.forward, "redo") {
+                self.forget();
+                return false;
+            }
+        }
+        next.set_selections(entry.selection_after.clone());
+        *snapshot = next;
+        self.undo.push(entry);
+        true
+    }
+
+    /// The document changed underneath the history; it cannot be trusted.
+    fn forget(&mut self) {
+        *self = Self::with_limits(self.max_entries, self.max_bytes);
+    }
+}
+
+fn replay(snapshot: &mut EditorSnapshot, changes: &[TextChange], label: &'static str) -> bool {
+    let transaction = Transaction {
+        changes: changes.to_vec(),
+        selection_after: None,
+        origin: ChangeOrigin::System,
+        label,
+    };
+    snapshot.apply_transaction(transaction).is_ok()
+}
+
+/// Changes that turn the result of applying `changes` (sorted and
+/// non-overlapping) to `text` back into `text`.
+fn invert_changes(text: &str, changes: &[TextChange]) -> Vec<TextChange> {
+    let mut shift = 0isize;
+    changes
+        .iter()
+        .map(|change| {
+            let start = (change.start as isize + shift) as usize;
+            shift += change.insert.len() as isize - (change.end - change.start) as isize;
+            TextChange::new(
+                start,
+                start + change.insert.len(),
+                &text[change.start..change.end],
+            )
+        })
+        .collect()
+}
+
+/// Smallest single change turning `old` into `new`, found by trimming the
+/// common prefix and suffix. Returns `None` when the texts are identical.
+pub fn minimal_text_change(old: &str, new: &str) -> Option<TextChange> {
+    if old == new {
+        return None;
+    }
+
+    let mut prefix = old
+        .bytes()
+        .zip(new.bytes())
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !old.is_char_boundary(prefix) || !new.is_char_boundary(prefix) {
+        prefix -= 1;
+    }
+
+    let max_suffix = old.len().min(new.len()) - prefix;
+    let mut suffix = old
+        .bytes()
+        .rev()
+        .zip(new.bytes().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !old.is_char_boundary(old.len() - suffix) || !new.is_char_boundary(new.len() - suffix) {
+        suffix -= 1;
+    }
+
+    Some(TextChange::new(
+        prefix,
+        old.len() - suffix,
+        &new[prefix..new.len() - suffix],
+    ))
+}
+
+/// Replaces the whole document with `next_text` as a single minimal change so
+/// the caret is mapped through the edit instead of jumping to the end.
+pub fn replace_document_transaction(
+    snapshot: &EditorSnapshot,
+    next_text: &str,
+    label: &'static str,
+) -> Option<Transaction> {
+    let change = minimal_text_change(&snapshot.text, next_text)?;
+    Some(Transaction::single(change, None, ChangeOrigin::Command, label))
+}
+
+/// Catches a second view of the same note up with an edit made in another
+/// pane. The view keeps its own selection, mapped through the change.
+pub fn mirror_transaction(snapshot: &EditorSnapshot, next_text: &str) -> Option<Transaction> {
+    let change = minimal_text_change(&snapshot.text, next_text)?;
+    Some(Transaction::single(change, None, ChangeOrigin::System, "mirror"))
+}
+
+/// A multi-line markdown block that can be inserted at the caret.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlockKind {
+    Heading(u8),
+    /// Callout of the given type, e.g. `note` or `warning`.
+    Callout(&'static str),
+    Table,
+    /// Fenced code block; an empty language leaves the fence bare.
+    CodeFence(&'static str),
+    MathBlock,
+    TaskList,
+    HorizontalRule,
+}
+
+impl BlockKind {
+    /// The block as text before the caret, text selected after insertion,
+    /// and text after it.
+    fn snippet(self) -> (String, &'static str, &'static str) {
+        match self {
+            BlockKind::Heading(level) => {
+                (format!("{} ", "#".repeat(level.clamp(1, 6) as usize)), "", "")
+            }
+            BlockKind::Callout(kind) => (format!("> [!{kind}]\n> "), "", ""),
+            BlockKind::Table => (
+                "| ".to_string(),
+                "Column 1",
+                " | Column 2 |\n| --- | --- |\n|  |  |",
+            ),
+            BlockKind::CodeFence(lang) => (format!("```{lang}\n"), "", "\n```"),
+            BlockKind::MathBlock => ("$$\n".to_string(), "", "\n$$"),
+            BlockKind::TaskList => ("- [ ] ".to_string(), "", ""),
+            BlockKind::HorizontalRule => ("---\n".to_string(), "", ""),
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum MarkdownCommand {
     Wrap {
@@ -265,6 +673,19 @@ pub enum MarkdownCommand {
         open: &'static str,
         close: &'static str,
     },
+    InsertBlock(BlockKind),
+    /// Edits the pipe table around the caret; a no-op elsewhere.
+    Table(TableCommand),
+    /// Swaps the selected lines with the line above or below.
+    MoveLines {
+        down: bool,
+    },
+    DuplicateLines,
+    DeleteLines,
+    /// Checks or unchecks a task, turning other lines into open tasks.
+    ToggleTask,
+    /// Steps list markers through `-`, `*`, `+` and `1.`.
+    CycleListMarker,
 }
 
 pub fn apply_markdown_command(
@@ -278,36 +699,378 @@ pub fn apply_markdown_command(
     Ok(outcome.text_changed || outcome.selection_changed)
 }
 
-fn build_markdown_transaction(
+/// What a command does to one selection range. `selection_after` is in the
+/// coordinates of the text with only this edit applied.
+struct RangeEdit {
+    change: TextChange,
+    selection_after: Selection,
+    label: &'static str,
+}
+
+pub fn build_markdown_transaction(
     snapshot: &EditorSnapshot,
     command: MarkdownCommand,
 ) -> Option<Transaction> {
-    match command {
-        MarkdownCommand::Wrap { open, close, label } => {
-            Some(wrap_transaction(snapshot, open, close, label))
-        }
-        MarkdownCommand::PrefixLine { prefix, label } => {
-            Some(prefix_line_transaction(snapshot, prefix, label))
+    // Inside a table, Tab, Shift+Tab and Enter move between cells instead.
+    let table_command = match command {
+        MarkdownCommand::Indent => Some(TableCommand::NextCell),
+        MarkdownCommand::Outdent => Some(TableCommand::PreviousCell),
+        MarkdownCommand::ContinueBlock => Some(TableCommand::NewRow),
+        MarkdownCommand::Table(table_command) => return table_transaction(snapshot, table_command),
+        _ => None,
+    };
+    if let Some(transaction) = table_command.and_then(|c| table_transaction(snapshot, c)) {
+        return Some(transaction);
+    }
+    let multi = snapshot.selections.is_multi();
+    let transaction = combine_range_edits(snapshot, ChangeOrigin::Command, |text, selection| {
+        match command {
+            MarkdownCommand::Wrap { open, close, label } => {
+                Some(wrap_edit(text, selection, open, close, label))
+            }
+            MarkdownCommand::PrefixLine { prefix, label } => {
+                Some(prefix_line_edit(text, selection, prefix, label))
+            }
+            MarkdownCommand::Indent => indent_or_outdent_edit(text, selection, false),
+            MarkdownCommand::Outdent => indent_or_outdent_edit(text, selection, true),
+            // With several cursors a plain line break keeps them in step;
+            // a single cursor leaves that fallback to the caller.
+            MarkdownCommand::ContinueBlock => continue_markdown_block_edit(text, selection)
+                .or_else(|| multi.then(|| insert_edit(selection, "\n", "insert-newline"))),
+            MarkdownCommand::AutoPair { open, close } => {
+                Some(wrap_edit(text, selection, open, close, "autopair"))
+            }
+            MarkdownCommand::InsertBlock(kind) => {
+                let (before, selected, after) = kind.snippet();
+                Some(block_edit(text, selection, &before, selected, after, "insert-block"))
+            }
+            MarkdownCommand::Table(_) => None,
+            MarkdownCommand::MoveLines { down } => move_lines_edit(text, selection, down),
+            MarkdownCommand::DuplicateLines => Some(duplicate_lines_edit(text, selection)),
+            MarkdownCommand::DeleteLines => delete_lines_edit(text, selection),
+            MarkdownCommand::ToggleTask => {
+                edit_each_line(text, selection, "toggle-task", toggle_task)
+            }
+            MarkdownCommand::CycleListMarker => {
+                edit_each_line(text, selection, "cycle-list-marker", cycle_list_marker)
+            }
         }
-        MarkdownCommand::Indent => indent_or_outdent_transaction(snapshot, false),
-        MarkdownCommand::Outdent => indent_or_outdent_transaction(snapshot, true),
-        MarkdownCommand::ContinueBlock => continue_markdown_block_transaction(snapshot),
-        MarkdownCommand::AutoPair { open, close } => {
-            Some(wrap_transaction(snapshot, open, close, "autopair"))
+    })?;
+    match command {
+        MarkdownCommand::Indent
+        | MarkdownCommand::Outdent
+        | MarkdownCommand::ContinueBlock
+        | MarkdownCommand::MoveLines { .. }
+        | MarkdownCommand::DuplicateLines
+        | MarkdownCommand::DeleteLines
+        | MarkdownCommand::CycleListMarker => with_renumbered_lists(snapshot, transaction),
+        _ => Some(transaction),
+    }
+}
+
+/// Runs `edit_for` on every selection range and merges the edits into one
+/// transaction. Earlier edits shift the selections of later ranges; an edit
+/// that overlaps or repeats the previous one is dropped, and its range is
+/// mapped through the edits kept so far.
+fn combine_range_edits(
+    snapshot: &EditorSnapshot,
+    origin: ChangeOrigin,
+    mut edit_for: impl FnMut(&str, Selection) -> Option<RangeEdit>,
+) -> Option<Transaction> {
+    let text = &snapshot.text;
+    let mut changes: Vec<TextChange> = Vec::new();
+    let mut selections = Vec::new();
+    let mut shift = 0isize;
+    let mut label = None;
+
+    for range in snapshot.selections.ranges() {
+        let range = range.clamp(text.len());
+        let edit = edit_for(text, range).filter(|edit| {
+            changes
+                .last()
+                .is_none_or(|prev| edit.change.start >= prev.end && edit.change != *prev)
+        });
+        match edit {
+            Some(edit) => {
+                let after = edit.selection_after;
+                selections.push(Selection::new(
+                    shift_position(after.start, shift),
+                    shift_position(after.end, shift),
+                ));
+                shift += edit.change.insert.len() as isize
+                    - (edit.change.end - edit.change.start) as isize;
+                label.get_or_insert(edit.label);
+                changes.push(edit.change);
+            }
+            None => selections.push(Selection::new(
+                map_position_through_changes(range.start, &changes),
+                map_position_through_changes(range.end, &changes),
+            )),
         }
     }
+
+    let label = label?;
+    Some(Transaction {
+        changes,
+        selection_after: Some(SelectionSet::new(
+            selections,
+            snapshot.selections.primary_index(),
+        )),
+        origin,
+        label,
+    })
+}
+
+fn shift_position(pos: usize, shift: isize) -> usize {
+    (pos as isize + shift).max(0) as usize
+}
+
+fn insert_edit(selection: Selection, insert: &str, label: &'static str) -> RangeEdit {
+    RangeEdit {
+        change: TextChange::new(selection.start, selection.end, insert),
+        selection_after: Selection::cursor(selection.start + insert.len()),
+        label,
+    }
+}
+
+/// Puts a block on its own line: it replaces the caret's line when that line
+/// is blank, otherwise it goes on a new line after the selection.
+fn block_edit(
+    text: &str,
+    selection: Selection,
+    before: &str,
+    selected: &str,
+    after: &str,
+    label: &'static str,
+) -> RangeEdit {
+    let ls = line_start(text, selection.start);
+    let le = line_end(text, selection.start);
+    let (start, end, lead) = if selection.is_cursor() && text[ls..le].trim().is_empty() {
+        (ls, le, "")
+    } else {
+        let le = line_end(text, selection.end);
+        (le, le, "\n")
+    };
+    let insert = format!("{lead}{before}{selected}{after}");
+    let anchor = start + lead.len() + before.len();
+    RangeEdit {
+        change: TextChange::new(start, end, insert),
+        selection_after: Selection::new(anchor, anchor + selected.len()),
+        label,
+    }
 }
 
-fn wrap_transaction(
+/// Inserts `block` (e.g. a template body) as its own block at each range,
+/// leaving the caret at its end.
+pub fn insert_block_transaction(
     snapshot: &EditorSnapshot,
+    block: &str,
+    label: &'static str,
+) -> Option<Transaction> {
+    combine_range_edits(snapshot, ChangeOrigin::Command, |text, selection| {
+        Some(block_edit(text, selection, block, "", "", label))
+    })
+}
+
+/// Replaces every selection range with `insert`, leaving a cursor after each.
+pub fn insert_text_transaction(
+    snapshot: &EditorSnapshot,
+    insert: &str,
+    origin: ChangeOrigin,
+    label: &'static str,
+) -> Option<Transaction> {
+    combine_range_edits(snapshot, origin, |_, selection| {
+        Some(insert_edit(selection, insert, label))
+    })
+}
+
+/// Pasting a lone web URL over selected text links the text instead of
+/// replacing it. `None` when `pasted` is not a URL or any range is a cursor.
+pub fn link_selection_transaction(snapshot: &EditorSnapshot, pasted: &str) -> Option<Transaction> {
+    let url = pasted.trim();
+    let scheme = url.split_once("://").map(|(scheme, _)| scheme)?;
+    if scheme.is_empty()
+        || !scheme.chars().all(|c| c.is_ascii_alphabetic())
+        || url.contains(char::is_whitespace)
+        || snapshot.selections.ranges().iter().any(|range| range.is_cursor())
+    {
+        return None;
+    }
+    combine_range_edits(snapshot, ChangeOrigin::Input, |text, selection| {
+        let link = format!("[{}]({url})", &text[selection.start..selection.end]);
+        Some(insert_edit(selection, &link, "paste-link"))
+    })
+}
+
+/// Deletes each selected range, or the character before (or after, with
+/// `forward`) each cursor.
+pub fn delete_transaction(snapshot: &EditorSnapshot, forward: bool) -> Option<Transaction> {
+    combine_range_edits(snapshot, ChangeOrigin::Input, |text, selection| {
+        let (start, end) = if !selection.is_cursor() {
+            (selection.start, selection.end)
+        } else if forward {
+            let next = text[selection.end..].chars().next()?;
+            (selection.start, selection.end + next.len_utf8())
+        } else {
+            let prev = text[..selection.start].chars().next_back()?;
+            (selection.start - prev.len_utf8(), selection.end)
+        };
+        Some(RangeEdit {
+            change: TextChange::new(start, end, ""),
+            selection_after: Selection::cursor(start),
+            label: "delete",
+        })
+    })
+}
+
+fn word_range_at(text: &str, pos: usize) -> Option<Selection> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let start = text[..pos]
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| is_word(*c))
+        .last()
+        .map(|(i, _)| i)
+        .unwrap_or(pos);
+    let end = text[pos..]
+        .char_indices()
+        .find(|(_, c)| !is_word(*c))
+        .map(|(i, _)| pos + i)
+        .unwrap_or(text.len());
+    (start < end).then(|| Selection::new(start, end))
+}
+
+/// Ctrl+D: a cursor first grows to the word around it; a selection adds the
+/// next occurrence of its text after the last range, wrapping around the
+/// note. Returns `false` when nothing new can be selected.
+pub fn select_next_occurrence(snapshot: &mut EditorSnapshot) -> bool {
+    let primary = snapshot.selection();
+    if primary.is_cursor() {
+        let Some(word) = word_range_at(&snapshot.text, primary.start) else {
+            return false;
+        };
+        let mut selections = snapshot.selections.clone();
+        let ranges: Vec<Selection> = selections
+            .ranges()
+            .iter()
+            .map(|range| if *range == primary { word } else { *range })
+            .collect();
+        selections = SelectionSet::new(ranges, selections.primary_index());
+        snapshot.selections = selections;
+        return true;
+    }
+
+    let needle = &snapshot.text[primary.start..primary.end];
+    let after = snapshot
+        .selections
+        .ranges()
+        .last()
+        .map(|range| range.end)
+        .unwrap_or(primary.end);
+    let taken = |start: usize| {
+        snapshot
+            .selections
+            .ranges()
+            .iter()
+            .any(|range| range.start == start)
+    };
+    let found = snapshot.text[after..]
+        .match_indices(needle)
+        .map(|(i, _)| after + i)
+        .chain(snapshot.text[..after].match_indices(needle).map(|(i, _)| i))
+        .find(|start| !taken(*start));
+    let Some(start) = found else {
+        return false;
+    };
+    snapshot
+        .selections
+        .add(Selection::new(start, start + needle.len()));
+    true
+}
+
+fn line_column(text: &str, pos: usize) -> (usize, usize) {
+    let ls = line_start(text, pos);
+    let line = text[..ls].matches('\n').count();
+    (line, text[ls..pos].chars().count())
+}
+
+/// Byte offset of `column` (in characters) on the line starting at `ls`,
+/// clamped to the end of that line.
+fn offset_at_column(text: &str, ls: usize, column: usize) -> usize {
+    let le = line_end(text, ls);
+    text[ls..le]
+        .char_indices()
+        .nth(column)
+        .map(|(i, _)| ls + i)
+        .unwrap_or(le)
+}
+
+fn line_starts(text: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+        .collect()
+}
+
+/// Column (box) selection between two positions: one range per line,
+/// spanning the same character columns on each.
+pub fn column_selection(text: &str, anchor: usize, head: usize) -> SelectionSet {
+    let (anchor_line, anchor_col) = line_column(text, anchor.min(text.len()));
+    let (head_line, head_col) = line_column(text, head.min(text.len()));
+    let starts = line_starts(text);
+    let (first, last) = (anchor_line.min(head_line), anchor_line.max(head_line));
+    let (left, right) = (anchor_col.min(head_col), anchor_col.max(head_col));
+    let ranges: Vec<Selection> = (first..=last)
+        .map(|line| {
+            let ls = starts[line];
+            Selection::new(
+                offset_at_column(text, ls, left),
+                offset_at_column(text, ls, right),
+            )
+        })
+        .collect();
+    let primary = head_line - first;
+    SelectionSet::new(ranges, primary)
+}
+
+/// Alt+Shift+Arrow: adds a cursor on the line above or below the primary,
+/// which becomes the new primary. The column is taken from the cursor the
+/// stack started at, so short lines in between do not pull it left.
+pub fn add_cursor_on_adjacent_line(snapshot: &mut EditorSnapshot, down: bool) -> bool {
+    let text = &snapshot.text;
+    let (line, _) = line_column(text, snapshot.selection().end);
+    let origin = if down {
+        snapshot.selections.ranges().first()
+    } else {
+        snapshot.selections.ranges().last()
+    };
+    let (_, column) = line_column(text, origin.map_or(0, |range| range.end));
+    let starts = line_starts(text);
+    let target = if down {
+        line + 1
+    } else {
+        match line.checked_sub(1) {
+            Some(line) => line,
+            None => return false,
+        }
+    };
+    let Some(&ls) = starts.get(target) else {
+        return false;
+    };
+    let pos = offset_at_column(text, ls, column);
+    snapshot.selections.add(Selection::cursor(pos));
+    true
+}
+
+fn wrap_edit(
+    text: &str,
+    selection: Selection,
     open: &str,
     close: &str,
     label: &'static str,
-) -> Transaction {
-    let selection = snapshot.selection.clamp(snapshot.text.len());
+) -> RangeEdit {
     let mut insert = String::new();
     insert.push_str(open);
-    insert.push_str(&snapshot.text[selection.start..selection.end]);
+    insert.push_str(&text[selection.start..selection.end]);
     insert.push_str(close);
     let selection_after = if selection.is_cursor() {
         Selection::cursor(selection.start + open.len())
@@ -316,43 +1079,33 @@ fn wrap_transaction(
         // This avoids keeping an invisible selection range in the transparent textarea layer.
         Selection::cursor(selection.end + open.len() + close.len())
     };
-    Transaction::single(
-        TextChange::new(selection.start, selection.end, insert),
-        Some(selection_after),
-        ChangeOrigin::Command,
+    RangeEdit {
+        change: TextChange::new(selection.start, selection.end, insert),
+        selection_after,
         label,
-    )
+    }
 }
 
-fn prefix_line_transaction(
-    snapshot: &EditorSnapshot,
+fn prefix_line_edit(
+    text: &str,
+    selection: Selection,
     prefix: &str,
     label: &'static str,
-) -> Transaction {
-    let selection = snapshot.selection.clamp(snapshot.text.len());
-    let start = line_start(&snapshot.text, selection.start);
+) -> RangeEdit {
+    let start = line_start(text, selection.start);
     let selection_after =
         Selection::new(selection.start + prefix.len(), selection.end + prefix.len());
-    Transaction::single(
-        TextChange::new(start, start, prefix),
-        Some(selection_after),
-        ChangeOrigin::Command,
+    RangeEdit {
+        change: TextChange::new(start, start, prefix),
+        selection_after,
         label,
-    )
+    }
 }
 
-fn indent_or_outdent_transaction(snapshot: &EditorSnapshot, outdent: bool) -> Option<Transaction> {
-    let text = &snapshot.text;
-    let selection = snapshot.selection.clamp(text.len());
-
+fn indent_or_outdent_edit(text: &str, selection: Selection, outdent: bool) -> Option<RangeEdit> {
     if selection.is_cursor() {
         if !outdent {
-            return Some(Transaction::single(
-                TextChange::new(selection.start, selection.end, "    "),
-                Some(Selection::cursor(selection.start + 4)),
-                ChangeOrigin::Command,
-                "indent",
-            ));
+            return Some(insert_edit(selection, "    ", "indent"));
         }
 
         let ls = line_start(text, selection.start);
@@ -376,12 +1129,11 @@ fn indent_or_outdent_transaction(snapshot: &EditorSnapshot, outdent: bool) -> Op
             ls
         };
 
-        return Some(Transaction::single(
-            TextChange::new(ls, le, replaced),
-            Some(Selection::cursor(new_cursor)),
-            ChangeOrigin::Command,
-            "outdent",
-        ));
+        return Some(RangeEdit {
+            change: TextChange::new(ls, le, replaced),
+            selection_after: Selection::cursor(new_cursor),
+            label: "outdent",
+        });
     }
 
     let block_start = line_start(text, selection.start);
@@ -394,8 +1146,8 @@ fn indent_or_outdent_transaction(snapshot: &EditorSnapshot, outdent: bool) -> Op
             transformed.push('\n');
         }
         if outdent {
-            if line.starts_with('\t') {
-                transformed.push_str(&line[1..]);
+            if let Some(rest) = line.strip_prefix('\t') {
+                transformed.push_str(rest);
             } else {
                 let remove = line.chars().take_while(|c| *c == ' ').take(4).count();
                 transformed.push_str(&line[remove..]);
@@ -406,21 +1158,18 @@ fn indent_or_outdent_transaction(snapshot: &EditorSnapshot, outdent: bool) -> Op
         }
     }
 
-    Some(Transaction::single(
-        TextChange::new(block_start, block_end, transformed.clone()),
-        Some(Selection::new(block_start, block_start + transformed.len())),
-        ChangeOrigin::Command,
-        if outdent {
+    Some(RangeEdit {
+        selection_after: Selection::new(block_start, block_start + transformed.len()),
+        change: TextChange::new(block_start, block_end, transformed),
+        label: if outdent {
             "outdent-block"
         } else {
             "indent-block"
         },
-    ))
+    })
 }
 
-fn continue_markdown_block_transaction(snapshot: &EditorSnapshot) -> Option<Transaction> {
-    let text = &snapshot.text;
-    let selection = snapshot.selection.clamp(text.len());
+fn continue_markdown_block_edit(text: &str, selection: Selection) -> Option<RangeEdit> {
     if !selection.is_cursor() {
         return None;
     }
@@ -482,13 +1231,340 @@ fn continue_markdown_block_transaction(snapshot: &EditorSnapshot) -> Option<Tran
         return None;
     };
 
-    let next_cursor = selection.start + insert.len();
-    Some(Transaction::single(
-        TextChange::new(selection.start, selection.end, insert),
-        Some(Selection::cursor(next_cursor)),
-        ChangeOrigin::Command,
-        "continue-markdown-block",
-    ))
+    Some(insert_edit(selection, &insert, "continue-markdown-block"))
+}
+
+/// Line range a selection covers. A selection ending at the start of a line
+/// leaves that line out.
+fn selected_lines(text: &str, selection: Selection) -> (usize, usize) {
+    let start = line_start(text, selection.start);
+    let last = if !selection.is_cursor()
+        && selection.end > start
+        && line_start(text, selection.end) == selection.end
+    {
+        selection.end - 1
+    } else {
+        selection.end
+    };
+    (start, line_end(text, last))
+}
+
+fn shift_selection(selection: Selection, shift: isize) -> Selection {
+    Selection::new(
+        shift_position(selection.start, shift),
+        shift_position(selection.end, shift),
+    )
+}
+
+fn move_lines_edit(text: &str, selection: Selection, down: bool) -> Option<RangeEdit> {
+    let (start, end) = selected_lines(text, selection);
+    let block = &text[start..end];
+    let (change, shift) = if down {
+        if end >= text.len() {
+            return None;
+        }
+        let next_end = line_end(text, end + 1);
+        let next = &text[end + 1..next_end];
+        (
+            TextChange::new(start, next_end, format!("{next}\n{block}")),
+            next.len() as isize + 1,
+        )
+    } else {
+        if start == 0 {
+            return None;
+        }
+        let prev_start = line_start(text, start - 1);
+        let prev = &text[prev_start..start - 1];
+        (
+            TextChange::new(prev_start, end, format!("{block}\n{prev}")),
+            -(prev.len() as isize + 1),
+        )
+    };
+    Some(RangeEdit {
+        change,
+        selection_after: shift_selection(selection, shift),
+        label: "move-lines",
+    })
+}
+
+/// Copies the selected lines below themselves and moves the selection onto
+/// the copy.
+fn duplicate_lines_edit(text: &str, selection: Selection) -> RangeEdit {
+    let (start, end) = selected_lines(text, selection);
+    let block = &text[start..end];
+    RangeEdit {
+        change: TextChange::new(end, end, format!("\n{block}")),
+        selection_after: shift_selection(selection, block.len() as isize + 1),
+        label: "duplicate-lines",
+    }
+}
+
+/// Removes the selected lines with their line break. The caret keeps its
+/// column on the line that takes their place.
+fn delete_lines_edit(text: &str, selection: Selection) -> Option<RangeEdit> {
+    let (start, end) = selected_lines(text, selection);
+    if start == end && text.is_empty() {
+        return None;
+    }
+    let column = selection.start - start;
+    let (change, next_line) = if end < text.len() {
+        (TextChange::new(start, end + 1, ""), start)
+    } else if start > 0 {
+        (TextChange::new(start - 1, end, ""), line_start(text, start - 1))
+    } else {
+        (TextChange::new(start, end, ""), start)
+    };
+    let mut after = String::with_capacity(text.len());
+    after.push_str(&text[..change.start]);
+    after.push_str(&text[change.end..]);
+    let caret = offset_at_column(&after, next_line, column);
+    Some(RangeEdit {
+        change,
+        selection_after: Selection::cursor(caret),
+        label: "delete-lines",
+    })
+}
+
+/// Rewrites each line the selection covers with `edit_line`, which returns
+/// the byte range of the line to replace and its replacement. Selection ends
+/// inside a replaced range move to its end.
+fn edit_each_line(
+    text: &str,
+    selection: Selection,
+    label: &'static str,
+    edit_line: impl Fn(&str) -> Option<(usize, usize, String)>,
+) -> Option<RangeEdit> {
+    let (start, end) = selected_lines(text, selection);
+    let mut edits = Vec::new();
+    let mut line_start = start;
+    for line in text[start..end].split('\n') {
+        if let Some((from, to, insert)) = edit_line(line) {
+            edits.push(TextChange::new(line_start + from, line_start + to, insert));
+        }
+        line_start += line.len() + 1;
+    }
+    if edits.is_empty() {
+        return None;
+    }
+
+    let selection_after = Selection::new(
+        map_through_sorted(selection.start, &edits),
+        map_through_sorted(selection.end, &edits),
+    );
+    let mut block = String::new();
+    let mut copied = start;
+    for edit in &edits {
+        block.push_str(&text[copied..edit.start]);
+        block.push_str(&edit.insert);
+        copied = edit.end;
+    }
+    block.push_str(&text[copied..end]);
+    Some(RangeEdit {
+        change: TextChange::new(start, end, block),
+        selection_after,
+        label,
+    })
+}
+
+/// Maps `pos` through sorted, non-overlapping changes given in the
+/// coordinates of the text before all of them. Positions inside a replaced
+/// range move to its end.
+fn map_through_sorted(pos: usize, changes: &[TextChange]) -> usize {
+    let mut shift = 0isize;
+    for change in changes {
+        if pos >= change.end {
+            shift += change.insert.len() as isize - (change.end - change.start) as isize;
+        } else if pos > change.start {
+            return shift_position(change.start + change.insert.len(), shift);
+        }
+    }
+    shift_position(pos, shift)
+}
+
+fn list_marker_regex() -> &'static Regex {
+    static RE_LIST_MARKER: OnceLock<Regex> = OnceLock::new();
+    RE_LIST_MARKER.get_or_init(|| Regex::new(r"^(\s*)([-*+]|\d+[.)])(\s+|$)").unwrap())
+}
+
+/// `[ ]` and `[x]` swap; list items gain a checkbox and other lines become
+/// open tasks.
+fn toggle_task(line: &str) -> Option<(usize, usize, String)> {
+    static RE_TASK_BOX: OnceLock<Regex> = OnceLock::new();
+    let re_task_box = RE_TASK_BOX
+        .get_or_init(|| Regex::new(r"^\s*(?:[-*+]|\d+[.)])\s+\[( |x|X)\](?:\s|$)").unwrap());
+    if let Some(mark) = re_task_box.captures(line).and_then(|cap| cap.get(1)) {
+        let toggled = if mark.as_str() == " " { "x" } else { " " };
+        return Some((mark.start(), mark.end(), toggled.to_string()));
+    }
+    if let Some(cap) = list_marker_regex().captures(line) {
+        let end = cap.get(0).map(|m| m.end()).unwrap_or_default();
+        let space = if cap[3].is_empty() { " " } else { "" };
+        return Some((end, end, format!("{space}[ ] ")));
+    }
+    let indent = line.len() - line.trim_start().len();
+    Some((indent, indent, "- [ ] ".to_string()))
+}
+
+fn cycle_list_marker(line: &str) -> Option<(usize, usize, String)> {
+    let Some(cap) = list_marker_regex().captures(line) else {
+        if line.trim().is_empty() {
+            return None;
+        }
+        let indent = line.len() - line.trim_start().len();
+        return Some((indent, indent, "- ".to_string()));
+    };
+    let marker = cap.get(2)?;
+    let next = match marker.as_str() {
+        "-" => "*",
+        "*" => "+",
+        "+" => "1.",
+        _ => "-",
+    };
+    Some((marker.start(), marker.end(), next.to_string()))
+}
+
+/// Applies `transaction`, then renumbers the ordered lists around each
+/// resulting selection, as one transaction. Lists the edit did not leave
+/// misnumbered come back unchanged.
+fn with_renumbered_lists(
+    snapshot: &EditorSnapshot,
+    transaction: Transaction,
+) -> Option<Transaction> {
+    let mut scratch = snapshot.clone();
+    scratch.apply_transaction(transaction.clone()).ok()?;
+    let mut fixes: Vec<TextChange> = Vec::new();
+    for range in scratch.selections.ranges() {
+        for fix in renumber_list_changes(&scratch.text, range.start) {
+            if !fixes.contains(&fix) {
+                fixes.push(fix);
+            }
+        }
+    }
+    if fixes.is_empty() {
+        return Some(transaction);
+    }
+    fixes.sort_by_key(|fix| fix.start);
+    let selections = SelectionSet::new(
+        scratch
+            .selections
+            .ranges()
+            .iter()
+            .map(|range| {
+                Selection::new(
+                    map_through_sorted(range.start, &fixes),
+                    map_through_sorted(range.end, &fixes),
+                )
+            })
+            .collect(),
+        scratch.selections.primary_index(),
+    );
+    scratch
+        .apply_transaction(Transaction {
+            changes: fixes,
+            selection_after: Some(selections),
+            origin: ChangeOrigin::Command,
+            label: transaction.label,
+        })
+        .ok()?;
+    Some(Transaction {
+        changes: vec![minimal_text_change(&snapshot.text, &scratch.text)?],
+        selection_after: Some(scratch.selections.clone()),
+        origin: transaction.origin,
+        label: transaction.label,
+    })
+}
+
+/// Number changes that make each ordered list in the list around `pos`
+/// count up by one. Top-level lists keep their lowest number as the start;
+/// nested lists start at 1.
+fn renumber_list_changes(text: &str, pos: usize) -> Vec<TextChange> {
+    struct Run {
+        indent: usize,
+        ordered: bool,
+        nested: bool,
+        /// Digit range of each item and its current number.
+        items: Vec<(usize, usize, u64)>,
+    }
+
+    let in_list = |line: &str| {
+        list_marker_regex().is_match(line)
+            || (line.starts_with([' ', '\t']) && !line.trim().is_empty())
+    };
+    let mut start = line_start(text, pos);
+    if !in_list(&text[start..line_end(text, start)]) {
+        return Vec::new();
+    }
+    while start > 0 {
+        let prev = line_start(text, start - 1);
+        if !in_list(&text[prev..start - 1]) {
+            break;
+        }
+        start = prev;
+    }
+
+    let mut open: Vec<Run> = Vec::new();
+    let mut runs: Vec<Run> = Vec::new();
+    let mut ls = start;
+    while ls <= text.len() {
+        let le = line_end(text, ls);
+        let line = &text[ls..le];
+        if !in_list(line) {
+            break;
+        }
+        let indent = line.len() - line.trim_start().len();
+        let marker = list_marker_regex().captures(line).and_then(|cap| cap.get(2));
+        while open.last().is_some_and(|run| run.indent > indent) {
+            runs.extend(open.pop());
+        }
+        if let Some(marker) = marker {
+            let ordered = marker.as_str().starts_with(|c: char| c.is_ascii_digit());
+            if open
+                .last()
+                .is_some_and(|run| run.indent == indent && run.ordered != ordered)
+            {
+                runs.extend(open.pop());
+            }
+            if open.last().is_none_or(|run| run.indent != indent) {
+                let nested = !open.is_empty();
+                open.push(Run {
+                    indent,
+                    ordered,
+                    nested,
+                    items: Vec::new(),
+                });
+            }
+            if ordered {
+                let digits = &marker.as_str()[..marker.len() - 1];
+                let digits_start = ls + marker.start();
+                if let Some(run) = open.last_mut() {
+                    run.items.push((
+                        digits_start,
+                        digits_start + digits.len(),
+                        digits.parse().unwrap_or(1),
+                    ));
+                }
+            }
+        }
+        ls = le + 1;
+    }
+    runs.extend(open);
+
+    let mut changes = Vec::new();
+    for run in runs.iter().filter(|run| run.ordered) {
+        let first = if run.nested {
+            1
+        } else {
+            run.items.iter().map(|&(_, _, n)| n).min().unwrap_or(1)
+        };
+        for (index, &(start, end, number)) in run.items.iter().enumerate() {
+            let expected = first + index as u64;
+            if number != expected {
+                changes.push(TextChange::new(start, end, expected.to_string()));
+            }
+        }
+    }
+    changes.sort_by_key(|change| change.start);
+    changes
 }
 
 fn line_start(text: &str, pos: usize) -> usize {
@@ -514,7 +1590,7 @@ mod tests {
         snapshot.set_selection(Selection::cursor(0));
         let transaction = Transaction {
             changes: vec![TextChange::new(0, 0, ">>"), TextChange::new(11, 11, "<<")],
-            selection_after: Some(Selection::cursor(13)),
+            selection_after: Some(Selection::cursor(13).into()),
             origin: ChangeOrigin::Command,
             label: "wrap",
         };
@@ -522,7 +1598,7 @@ mod tests {
         let outcome = snapshot.apply_transaction(transaction).unwrap();
         assert!(outcome.text_changed);
         assert_eq!(snapshot.text, ">>hello world<<");
-        assert_eq!(snapshot.selection, Selection::cursor(13));
+        assert_eq!(snapshot.selection(), Selection::cursor(13));
         assert_eq!(snapshot.revision, 1);
     }
 
@@ -557,7 +1633,7 @@ mod tests {
 
         assert!(changed);
         assert_eq!(snapshot.text, "**bedrock**");
-        assert_eq!(snapshot.selection, Selection::cursor(11));
+        assert_eq!(snapshot.selection(), Selection::cursor(11));
     }
 
     #[test]
@@ -581,4 +1657,513 @@ mod tests {
         apply_markdown_command(&mut snapshot, MarkdownCommand::Outdent).unwrap();
         assert_eq!(snapshot.text, "a\nb");
     }
+
+    #[test]
+    fn minimal_text_change_trims_common_prefix_and_suffix() {
+        assert_eq!(minimal_text_change("same", "same"), None);
+        assert_eq!(
+            minimal_text_change("hello world", "hello brave world"),
+            Some(TextChange::new(6, 6, "brave "))
+        );
+        assert_eq!(
+            minimal_text_change("aaa", "aa"),
+            Some(TextChange::new(2, 3, ""))
+        );
+        // Never split a multi-byte character.
+        assert_eq!(
+            minimal_text_change("café", "cafè"),
+            Some(TextChange::new(3, 5, "è"))
+        );
+    }
+
+    #[test]
+    fn replace_document_maps_caret_through_the_change() {
+        let mut snapshot = EditorSnapshot::new("# Title\nbody text".to_string());
+        snapshot.set_selection(Selection::cursor(12));
+        let transaction =
+            replace_document_transaction(&snapshot, "# New Title\nbody text", "restore")
+                .unwrap();
+        snapshot.apply_transaction(transaction).unwrap();
+        assert_eq!(snapshot.text, "# New Title\nbody text");
+        assert_eq!(snapshot.selection(), Selection::cursor(16));
+    }
+
+    #[test]
+    fn mirror_keeps_each_view_selection() {
+        let mut left = EditorSnapshot::new("alpha\nbeta\ngamma".to_string());
+        left.set_selection(Selection::new(6, 10));
+        let mut right = left.clone();
+        right.set_selection(Selection::cursor(2));
+
+        let edit = Transaction::single(
+            TextChange::new(0, 0, "# "),
+            Some(Selection::cursor(2)),
+            ChangeOrigin::Input,
+            "input",
+        );
+        right.apply_transaction(edit).unwrap();
+
+        let mirror = mirror_transaction(&left, &right.text).unwrap();
+        left.apply_transaction(mirror).unwrap();
+        assert_eq!(left.text, right.text);
+        assert_eq!(left.selection(), Selection::new(8, 12));
+        assert!(mirror_transaction(&left, &right.text).is_none());
+    }
+
+    fn input(start: usize, end: usize, insert: &str) -> Transaction {
+        let caret = start + insert.len();
+        Transaction::single(
+            TextChange::new(start, end, insert),
+            Some(Selection::cursor(caret)),
+            ChangeOrigin::Input,
+            "input",
+        )
+    }
+
+    #[test]
+    fn history_undoes_and_redoes_with_selections() {
+        let mut snapshot = EditorSnapshot::new("hello world".to_string());
+        snapshot.set_selection(Selection::new(0, 5));
+        let mut history = History::default();
+        let wrap = Transaction {
+            changes: vec![TextChange::new(0, 0, "**"), TextChange::new(5, 5, "**")],
+            selection_after: Some(Selection::new(2, 7).into()),
+            origin: ChangeOrigin::Command,
+            label: "bold",
+        };
+        history.apply(&mut snapshot, wrap, 0.0).unwrap();
+        assert_eq!(snapshot.text, "**hello** world");
+
+        assert!(history.undo(&mut snapshot));
+        assert_eq!(snapshot.text, "hello world");
+        assert_eq!(snapshot.selection(), Selection::new(0, 5));
+        assert!(history.undo.is_empty());
+
+        assert!(history.redo(&mut snapshot));
+        assert_eq!(snapshot.text, "**hello** world");
+        assert_eq!(snapshot.selection(), Selection::new(2, 7));
+        assert!(!history.redo(&mut snapshot));
+    }
+
+    #[test]
+    fn history_groups_consecutive_typing() {
+        let mut snapshot = EditorSnapshot::new(String::new());
+        let mut history = History::default();
+        history.apply(&mut snapshot, input(0, 0, "a"), 0.0).unwrap();
+        history.apply(&mut snapshot, input(1, 1, "b"), 100.0).unwrap();
+        history.apply(&mut snapshot, input(2, 2, "c"), 200.0).unwrap();
+        // A pause starts a new group.
+        history
+            .apply(&mut snapshot, input(3, 3, " d"), 200.0 + HISTORY_GROUP_INTERVAL_MS + 1.0)
+            .unwrap();
+        assert_eq!(snapshot.text, "abc d");
+
+        assert!(history.undo(&mut snapshot));
+        assert_eq!(snapshot.text, "abc");
+        assert!(history.undo(&mut snapshot));
+        assert_eq!(snapshot.text, "");
+        assert!(history.undo.is_empty());
+    }
+
+    #[test]
+    fn history_does_not_group_commands_or_caret_jumps() {
+        let mut snapshot = EditorSnapshot::new("xy".to_string());
+        snapshot.set_selection(Selection::cursor(2));
+        let mut history = History::default();
+        history.apply(&mut snapshot, input(2, 2, "z"), 0.0).unwrap();
+        // Typing elsewhere in the note is a separate step even when quick.
+        history.apply(&mut snapshot, input(0, 0, "w"), 10.0).unwrap();
+        let command = Transaction::single(
+            TextChange::new(0, 0, "> "),
+            None,
+            ChangeOrigin::Command,
+            "quote",
+        );
+        history.apply(&mut snapshot, command, 20.0).unwrap();
+        assert_eq!(snapshot.text, "> wxyz");
+
+        assert!(history.undo(&mut snapshot));
+        assert_eq!(snapshot.text, "wxyz");
+        assert!(history.undo(&mut snapshot));
+        assert_eq!(snapshot.text, "xyz");
+        assert_eq!(snapshot.selection(), Selection::cursor(3));
+        assert!(history.undo(&mut snapshot));
+        assert_eq!(snapshot.text, "xy");
+        assert_eq!(snapshot.selection(), Selection::cursor(2));
+    }
+
+    #[test]
+    fn new_edit_clears_redo() {
+        let mut snapshot = EditorSnapshot::new(String::new());
+        let mut history = History::default();
+        history.apply(&mut snapshot, input(0, 0, "one"), 0.0).unwrap();
+        history.undo(&mut snapshot);
+        assert!(!history.redo.is_empty());
+        history.apply(&mut snapshot, input(0, 0, "two"), 5000.0).unwrap();
+        assert!(history.redo.is_empty());
+        assert_eq!(history.bytes, 3);
+    }
+
+    #[test]
+    fn history_caps_entries_and_bytes() {
+        let mut snapshot = EditorSnapshot::new(String::new());
+        let mut history = History::with_limits(3, usize::MAX);
+        for (i, ch) in ["a", "b", "c", "d", "e"].iter().enumerate() {
+            let pos = snapshot.text.len();
+            let command = Transaction::single(
+                TextChange::new(pos, pos, *ch),
+                None,
+                ChangeOrigin::Command,
+                "insert",
+            );
+            history.apply(&mut snapshot, command, i as f64).unwrap();
+        }
+        while history.undo(&mut snapshot) {}
+        assert_eq!(snapshot.text, "ab");
+
+        let mut snapshot = EditorSnapshot::new("0123456789".to_string());
+        let mut history = History::with_limits(100, 12);
+        for i in 0..4 {
+            let replace = Transaction::single(
+                TextChange::new(0, 10, format!("{i}{i}{i}{i}{i}{i}{i}{i}{i}{i}")),
+                None,
+                ChangeOrigin::Command,
+                "replace",
+            );
+            history.apply(&mut snapshot, replace, i as f64).unwrap();
+            assert!(history.bytes <= 20);
+        }
+        assert!(history.undo(&mut snapshot));
+        assert_eq!(snapshot.text, "2222222222");
+        assert!(history.undo.is_empty());
+    }
+
+    #[test]
+    fn history_resets_when_document_diverges() {
+        let mut snapshot = EditorSnapshot::new("abc".to_string());
+        let mut history = History::default();
+        let command = Transaction::single(
+            TextChange::new(3, 3, "def"),
+            None,
+            ChangeOrigin::Command,
+            "append",
+        );
+        history.apply(&mut snapshot, command, 0.0).unwrap();
+        let mut other = EditorSnapshot::new("a".to_string());
+        other.set_selection(Selection::cursor(1));
+        assert!(!history.undo(&mut other));
+        assert_eq!(other.text, "a");
+        assert_eq!(other.selection(), Selection::cursor(1));
+        assert!(history.undo.is_empty());
+        assert_eq!(history.bytes, 0);
+    }
+
+    fn multi(text: &str, ranges: &[(usize, usize)]) -> EditorSnapshot {
+        let mut snapshot = EditorSnapshot::new(text.to_string());
+        let ranges = ranges.iter().map(|&(s, e)| Selection::new(s, e)).collect();
+        snapshot.set_selections(SelectionSet::new(ranges, 0));
+        snapshot
+    }
+
+    fn ranges(snapshot: &EditorSnapshot) -> Vec<(usize, usize)> {
+        snapshot
+            .selections
+            .ranges()
+            .iter()
+            .map(|r| (r.start, r.end))
+            .collect()
+    }
+
+    #[test]
+    fn selection_set_sorts_merges_and_tracks_primary() {
+        let set = SelectionSet::new(
+            vec![
+                Selection::new(10, 12),
+                Selection::new(0, 4),
+                Selection::new(3, 6),
+                Selection::cursor(10),
+            ],
+            0,
+        );
+        assert_eq!(set.ranges(), &[Selection::new(0, 6), Selection::new(10, 12)]);
+        assert_eq!(set.primary(), Selection::new(10, 12));
+    }
+
+    #[test]
+    fn wrap_applies_per_range_in_one_transaction() {
+        let mut snapshot = multi("one two three", &[(0, 3), (8, 13)]);
+        let before = snapshot.revision;
+        apply_markdown_command(
+            &mut snapshot,
+            MarkdownCommand::Wrap {
+                open: "**",
+                close: "**",
+                label: "bold",
+            },
+        )
+        .unwrap();
+        assert_eq!(snapshot.text, "**one** two **three**");
+        assert_eq!(snapshot.revision, before + 1);
+        assert_eq!(ranges(&snapshot), vec![(7, 7), (21, 21)]);
+    }
+
+    #[test]
+    fn insert_block_replaces_blank_line_or_follows_current_one() {
+        let mut snapshot = EditorSnapshot::new("intro\n  \nend".to_string());
+        snapshot.set_selection(Selection::cursor(7));
+        apply_markdown_command(&mut snapshot, MarkdownCommand::InsertBlock(BlockKind::CodeFence("rust")))
+            .unwrap();
+        assert_eq!(snapshot.text, "intro\n```rust\n\n```\nend");
+        assert_eq!(snapshot.selection(), Selection::cursor(14));
+
+        let mut snapshot = EditorSnapshot::new("text".to_string());
+        snapshot.set_selection(Selection::cursor(2));
+        apply_markdown_command(&mut snapshot, MarkdownCommand::InsertBlock(BlockKind::Table)).unwrap();
+        assert_eq!(
+            snapshot.text,
+            "text\n| Column 1 | Column 2 |\n| --- | --- |\n|  |  |"
+        );
+        assert_eq!(snapshot.selection(), Selection::new(7, 15));
+
+        let mut snapshot = EditorSnapshot::new(String::new());
+        let transaction = insert_block_transaction(&snapshot, "# Meeting\n", "template").unwrap();
+        snapshot.apply_transaction(transaction).unwrap();
+        assert_eq!(snapshot.text, "# Meeting\n");
+        assert_eq!(snapshot.selection(), Selection::cursor(10));
+    }
+
+    #[test]
+    fn prefix_line_and_autopair_map_ranges_through_each_other() {
+        let mut snapshot = multi("a\nb\nc", &[(0, 0), (4, 4)]);
+        apply_markdown_command(
+            &mut snapshot,
+            MarkdownCommand::PrefixLine {
+                prefix: "> ",
+                label: "quote",
+            },
+        )
+        .unwrap();
+        assert_eq!(snapshot.text, "> a\nb\n> c");
+        assert_eq!(ranges(&snapshot), vec![(2, 2), (8, 8)]);
+
+        apply_markdown_command(&mut snapshot, MarkdownCommand::AutoPair { open: "(", close: ")" })
+            .unwrap();
+        assert_eq!(snapshot.text, "> ()a\nb\n> ()c");
+        assert_eq!(ranges(&snapshot), vec![(3, 3), (11, 11)]);
+    }
+
+    #[test]
+    fn prefix_line_once_for_cursors_on_the_same_line() {
+        let mut snapshot = multi("hello", &[(1, 1), (3, 3)]);
+        apply_markdown_command(
+            &mut snapshot,
+            MarkdownCommand::PrefixLine {
+                prefix: "- ",
+                label: "list",
+            },
+        )
+        .unwrap();
+        assert_eq!(snapshot.text, "- hello");
+        assert_eq!(ranges(&snapshot), vec![(3, 3), (5, 5)]);
+    }
+
+    #[test]
+    fn indent_and_continue_block_per_cursor() {
+        let mut snapshot = multi("- a\n- b", &[(3, 3), (7, 7)]);
+        apply_markdown_command(&mut snapshot, MarkdownCommand::ContinueBlock).unwrap();
+        assert_eq!(snapshot.text, "- a\n- \n- b\n- ");
+        assert_eq!(ranges(&snapshot), vec![(6, 6), (13, 13)]);
+
+        apply_markdown_command(&mut snapshot, MarkdownCommand::Indent).unwrap();
+        assert_eq!(snapshot.text, "- a\n-     \n- b\n-     ");
+        assert_eq!(ranges(&snapshot), vec![(10, 10), (21, 21)]);
+
+
+        let mut snapshot = multi("    a\n    b", &[(5, 5), (11, 11)]);
+        apply_markdown_command(&mut snapshot, MarkdownCommand::Outdent).unwrap();
+        assert_eq!(snapshot.text, "a\nb");
+        assert_eq!(ranges(&snapshot), vec![(1, 1), (3, 3)]);
+    }
+
+    #[test]
+    fn continue_block_breaks_plain_lines_when_multi() {
+        let mut snapshot = multi("- a\nplain", &[(3, 3), (9, 9)]);
+        apply_markdown_command(&mut snapshot, MarkdownCommand::ContinueBlock).unwrap();
+        assert_eq!(snapshot.text, "- a\n- \nplain\n");
+        assert_eq!(ranges(&snapshot), vec![(6, 6), (13, 13)]);
+    }
+
+    #[test]
+    fn typing_and_deleting_at_every_cursor() {
+        let mut snapshot = multi("ab\ncd", &[(1, 1), (4, 4)]);
+        let insert =
+            insert_text_transaction(&snapshot, "é", ChangeOrigin::Input, "input").unwrap();
+        snapshot.apply_transaction(insert).unwrap();
+        assert_eq!(snapshot.text, "aéb\ncéd");
+        assert_eq!(ranges(&snapshot), vec![(3, 3), (8, 8)]);
+
+        let backspace = delete_transaction(&snapshot, false).unwrap();
+        snapshot.apply_transaction(backspace).unwrap();
+        assert_eq!(snapshot.text, "ab\ncd");
+        assert_eq!(ranges(&snapshot), vec![(1, 1), (4, 4)]);
+
+        let delete = delete_transaction(&snapshot, true).unwrap();
+        snapshot.apply_transaction(delete).unwrap();
+        assert_eq!(snapshot.text, "a\nc");
+        assert_eq!(ranges(&snapshot), vec![(1, 1), (3, 3)]);
+    }
+
+    #[test]
+    fn select_next_occurrence_expands_word_then_adds_matches() {
+        let mut snapshot = EditorSnapshot::new("foo bar foo baz foo".to_string());
+        snapshot.set_selection(Selection::cursor(1));
+        assert!(select_next_occurrence(&mut snapshot));
+        assert_eq!(ranges(&snapshot), vec![(0, 3)]);
+        assert!(select_next_occurrence(&mut snapshot));
+        assert!(select_next_occurrence(&mut snapshot));
+        assert_eq!(ranges(&snapshot), vec![(0, 3), (8, 11), (16, 19)]);
+        assert_eq!(snapshot.selection(), Selection::new(16, 19));
+        assert!(!select_next_occurrence(&mut snapshot));
+    }
+
+    #[test]
+    fn column_selection_and_adjacent_cursors_clamp_to_short_lines() {
+        let text = "abcdef\nab\nabcdef";
+        let set = column_selection(text, 1, 14);
+        assert_eq!(
+            set.ranges(),
+            &[Selection::new(1, 4), Selection::new(8, 9), Selection::new(11, 14)]
+        );
+        assert_eq!(set.primary(), Selection::new(11, 14));
+
+        let mut snapshot = EditorSnapshot::new(text.to_string());
+        snapshot.set_selection(Selection::cursor(5));
+        assert!(add_cursor_on_adjacent_line(&mut snapshot, true));
+        assert!(add_cursor_on_adjacent_line(&mut snapshot, true));
+        assert_eq!(ranges(&snapshot), vec![(5, 5), (9, 9), (15, 15)]);
+        assert!(!add_cursor_on_adjacent_line(&mut snapshot, true));
+    }
+
+    #[test]
+    fn history_restores_every_cursor() {
+        let mut snapshot = multi("x\ny", &[(1, 1), (3, 3)]);
+        let mut history = History::default();
+        let insert = insert_text_transaction(&snapshot, "!", ChangeOrigin::Input, "input").unwrap();
+        history.apply(&mut snapshot, insert, 0.0).unwrap();
+        assert_eq!(snapshot.text, "x!\ny!");
+        assert!(history.undo(&mut snapshot));
+        assert_eq!(snapshot.text, "x\ny");
+        assert_eq!(ranges(&snapshot), vec![(1, 1), (3, 3)]);
+    }
+
+    fn run(text: &str, selection: Selection, command: MarkdownCommand) -> EditorSnapshot {
+        let mut snapshot = EditorSnapshot::new(text.to_string());
+        snapshot.set_selection(selection);
+        apply_markdown_command(&mut snapshot, command).unwrap();
+        snapshot
+    }
+
+    #[test]
+    fn move_lines_swaps_with_neighbours_and_renumbers() {
+        let up = MarkdownCommand::MoveLines { down: false };
+        let down = MarkdownCommand::MoveLines { down: true };
+        let snapshot = run("1. a\n2. b\n3. c", Selection::cursor(8), up);
+        assert_eq!(snapshot.text, "1. b\n2. a\n3. c");
+        assert_eq!(snapshot.selection(), Selection::cursor(3));
+
+        let snapshot = run("x\ny\nz", Selection::new(0, 3), down);
+        assert_eq!(snapshot.text, "z\nx\ny");
+        assert_eq!(snapshot.selection(), Selection::new(2, 5));
+
+        let mut snapshot = EditorSnapshot::new("x\ny".to_string());
+        assert!(!apply_markdown_command(&mut snapshot, down).unwrap());
+    }
+
+    #[test]
+    fn duplicate_and_delete_lines() {
+        let snapshot = run("1. a\n2. b", Selection::cursor(3), MarkdownCommand::DuplicateLines);
+        assert_eq!(snapshot.text, "1. a\n2. a\n3. b");
+        assert_eq!(snapshot.selection(), Selection::cursor(8));
+
+        let snapshot = run("one\ntwo\nthree", Selection::cursor(5), MarkdownCommand::DeleteLines);
+        assert_eq!(snapshot.text, "one\nthree");
+        assert_eq!(snapshot.selection(), Selection::cursor(5));
+
+        let snapshot = run("one\ntwo", Selection::cursor(6), MarkdownCommand::DeleteLines);
+        assert_eq!(snapshot.text, "one");
+        assert_eq!(snapshot.selection(), Selection::cursor(2));
+
+        let snapshot = run("1. a\n2. b\n3. c", Selection::cursor(8), MarkdownCommand::DeleteLines);
+        assert_eq!(snapshot.text, "1. a\n2. c");
+    }
+
+    #[test]
+    fn toggle_task_checks_unchecks_and_adds_checkboxes() {
+        let snapshot = run("- [ ] buy", Selection::cursor(9), MarkdownCommand::ToggleTask);
+        assert_eq!(snapshot.text, "- [x] buy");
+        assert_eq!(snapshot.selection(), Selection::cursor(9));
+        let snapshot = run("1. [X] buy", Selection::cursor(0), MarkdownCommand::ToggleTask);
+        assert_eq!(snapshot.text, "1. [ ] buy");
+
+        let snapshot = run("- milk", Selection::cursor(6), MarkdownCommand::ToggleTask);
+        assert_eq!(snapshot.text, "- [ ] milk");
+        assert_eq!(snapshot.selection(), Selection::cursor(10));
+        let snapshot = run("  note", Selection::cursor(6), MarkdownCommand::ToggleTask);
+        assert_eq!(snapshot.text, "  - [ ] note");
+        assert_eq!(snapshot.selection(), Selection::cursor(12));
+    }
+
+    #[test]
+    fn cycle_list_marker_steps_through_bullets_and_numbers() {
+        let mut snapshot = EditorSnapshot::new("- a\n- b\ntext".to_string());
+        let mut seen = Vec::new();
+        for _ in 0..4 {
+            snapshot.set_selection(Selection::new(0, 7));
+            apply_markdown_command(&mut snapshot, MarkdownCommand::CycleListMarker).unwrap();
+            seen.push(snapshot.text.clone());
+        }
+        assert_eq!(
+            seen,
+            vec!["* a\n* b\ntext", "+ a\n+ b\ntext", "1. a\n2. b\ntext", "- a\n- b\ntext"]
+        );
+
+        let snapshot = run("text", Selection::cursor(4), MarkdownCommand::CycleListMarker);
+        assert_eq!(snapshot.text, "- text");
+        assert_eq!(snapshot.selection(), Selection::cursor(6));
+    }
+
+    #[test]
+    fn list_edits_renumber_ordered_lists() {
+        let snapshot = run("1. a\n2. b\n3. c", Selection::cursor(5), MarkdownCommand::Indent);
+        assert_eq!(snapshot.text, "1. a\n    1. b\n2. c");
+        assert_eq!(snapshot.selection(), Selection::cursor(9));
+
+        let snapshot = run("1. a\n2. b", Selection::cursor(4), MarkdownCommand::ContinueBlock);
+        assert_eq!(snapshot.text, "1. a\n2. \n3. b");
+        assert_eq!(snapshot.selection(), Selection::cursor(8));
+
+        // A list that starts above 1 keeps its start.
+        let snapshot = run("5. a\n5. b", Selection::cursor(4), MarkdownCommand::DuplicateLines);
+        assert_eq!(snapshot.text, "5. a\n6. a\n7. b");
+
+        let snapshot = run("8. a\n9. b", Selection::cursor(9), MarkdownCommand::ContinueBlock);
+        assert_eq!(snapshot.text, "8. a\n9. b\n10. ");
+        assert_eq!(snapshot.selection(), Selection::cursor(14));
+    }
+
+    #[test]
+    fn pasting_a_url_over_a_selection_links_it() {
+        let mut snapshot = EditorSnapshot::new("see docs here".to_string());
+        snapshot.set_selection(Selection::new(4, 8));
+        let transaction =
+            link_selection_transaction(&snapshot, " https://example.com/a?b=1\n").unwrap();
+        snapshot.apply_transaction(transaction).unwrap();
+        assert_eq!(snapshot.text, "see [docs](https://example.com/a?b=1) here");
+        assert_eq!(snapshot.selection(), Selection::cursor(37));
+
+        assert!(link_selection_transaction(&snapshot, "https://example.com").is_none());
+        snapshot.set_selection(Selection::new(0, 3));
+        assert!(link_selection_transaction(&snapshot, "not a url").is_none());
+        assert!(link_selection_transaction(&snapshot, "https://a.com and more").is_none());
+    }
 }
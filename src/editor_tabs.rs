@@ -0,0 +1,260 @@
+use crate::app_state::{SavedTab, SavedTabs};
+use crate::editor_core::{EditorSnapshot, History};
+
+/// Editor state kept per tab so switching tabs does not lose undo history,
+/// the caret or the scroll position.
+#[derive(Clone, Debug)]
+pub struct EditorTab {
+    pub path: String,
+    pub pinned: bool,
+    /// `None` until the tab has been shown; restored tabs load lazily.
+    pub snapshot: Option<EditorSnapshot>,
+    pub history: History,
+    pub scroll_top: i32,
+}
+
+impl EditorTab {
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            pinned: false,
+            snapshot: None,
+            history: History::default(),
+            scroll_top: 0,
+        }
+    }
+
+    pub fn title(&self) -> String {
+        let name = self.path.rsplit('/').next().unwrap_or(&self.path);
+        name.strip_suffix(".md").unwrap_or(name).to_string()
+    }
+}
+
+/// Open tabs of one vault. Pinned tabs always stay in front of unpinned ones.
+#[derive(Clone, Debug, Default)]
+pub struct EditorTabs {
+    pub tabs: Vec<EditorTab>,
+    pub active: Option<String>,
+}
+
+impl EditorTabs {
+    pub fn from_persisted(state: &SavedTabs) -> Self {
+        let mut tabs = Self::default();
+        for saved in &state.tabs {
+            if saved.path.is_empty() || tabs.position(&saved.path).is_some() {
+                continue;
+            }
+            let mut tab = EditorTab::new(&saved.path);
+            tab.pinned = saved.pinned;
+            tabs.tabs.push(tab);
+        }
+        tabs.tabs.sort_by_key(|t| !t.pinned);
+        tabs.active = state
+            .active
+            .clone()
+            .filter(|active| tabs.position(active).is_some());
+        tabs
+    }
+
+    pub fn to_persisted(&self) -> SavedTabs {
+        SavedTabs {
+            tabs: self
+                .tabs
+                .iter()
+                .map(|t| SavedTab {
+                    path: t.path.clone(),
+                    pinned: t.pinned,
+                })
+                .collect(),
+            active: self.active.clone(),
+        }
+    }
+
+    pub fn position(&self, path: &str) -> Option<usize> {
+        self.tabs.iter().position(|t| t.path == path)
+    }
+
+    pub fn get(&self, path: &str) -> Option<&EditorTab> {
+        self.tabs.iter().find(|t| t.path == path)
+    }
+
+    pub fn get_mut(&mut self, path: &str) -> Option<&mut EditorTab> {
+        self.tabs.iter_mut().find(|t| t.path == path)
+    }
+
+    fn pinned_count(&self) -> usize {
+        self.tabs.iter().filter(|t| t.pinned).count()
+    }
+
+    /// Activates `path`, opening it right after the active tab when it is not
+    /// open yet. Returns whether a new tab was created.
+    pub fn open(&mut self, path: &str) -> bool {
+        if path.is_empty() {
+            return false;
+        }
+        let created = self.position(path).is_none();
+        if created {
+            let after_active = self
+                .active
+                .as_deref()
+                .and_then(|active| self.position(active))
+                .map(|i| i + 1)
+                .unwrap_or(self.tabs.len());
+            let index = after_active.max(self.pinned_count());
+            self.tabs.insert(index, EditorTab::new(path));
+        }
+        self.active = Some(path.to_string());
+        created
+    }
+
+    /// Closes `path` and returns the tab that should be shown next when the
+    /// active tab was closed: its right neighbour, else its left one.
+    pub fn close(&mut self, path: &str) -> Option<String> {
+        let index = self.position(path)?;
+        self.tabs.remove(index);
+        if self.active.as_deref() != Some(path) {
+            return None;
+        }
+        self.active = self
+            .tabs
+            .get(index)
+            .or_else(|| index.checked_sub(1).and_then(|i| self.tabs.get(i)))
+            .map(|t| t.path.clone());
+        self.active.clone()
+    }
+
+    /// Closes every unpinned tab except `keep`, which becomes active.
+    pub fn close_others(&mut self, keep: &str) {
+        if self.position(keep).is_none() {
+            return;
+        }
+        self.tabs.retain(|t| t.pinned || t.path == keep);
+        self.active = Some(keep.to_string());
+    }
+
+    /// Pinning moves a tab to the end of the pinned group; unpinning moves it
+    /// to the front of the unpinned ones.
+    pub fn toggle_pin(&mut self, path: &str) {
+        let Some(index) = self.position(path) else {
+            return;
+        };
+        let mut tab = self.tabs.remove(index);
+        tab.pinned = !tab.pinned;
+        let target = self.pinned_count();
+        self.tabs.insert(target, tab);
+    }
+
+    /// Moves `path` to `to_index`, clamped so it stays within its pinned or
+    /// unpinned group.
+    pub fn move_tab(&mut self, path: &str, to_index: usize) {
+        let Some(from) = self.position(path) else {
+            return;
+        };
+        let tab = self.tabs.remove(from);
+        let pinned = self.pinned_count();
+        let (low, high) = if tab.pinned {
+            (0, pinned)
+        } else {
+            (pinned, self.tabs.len())
+        };
+        self.tabs.insert(to_index.clamp(low, high), tab);
+    }
+
+    pub fn rename(&mut self, old_path: &str, new_path: &str) {
+        if let Some(tab) = self.get_mut(old_path) {
+            tab.path = new_path.to_string();
+        }
+        if self.active.as_deref() == Some(old_path) {
+            self.active = Some(new_path.to_string());
+        }
+    }
+
+    /// Drops tabs whose notes were deleted or moved outside the app.
+    pub fn retain_existing(&mut self, files: &[String]) {
+        self.tabs.retain(|t| files.contains(&t.path));
+        if let Some(active) = self.active.clone() {
+            if self.position(&active).is_none() {
+                self.active = self.tabs.first().map(|t| t.path.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(tabs: &EditorTabs) -> Vec<&str> {
+        tabs.tabs.iter().map(|t| t.path.as_str()).collect()
+    }
+
+    #[test]
+    fn opens_after_active_and_reuses_existing_tabs() {
+        let mut tabs = EditorTabs::default();
+        assert!(tabs.open("a.md"));
+        assert!(tabs.open("b.md"));
+        tabs.open("a.md");
+        assert!(tabs.open("c.md"));
+        assert!(!tabs.open("b.md"));
+        assert_eq!(paths(&tabs), vec!["a.md", "c.md", "b.md"]);
+        assert_eq!(tabs.active.as_deref(), Some("b.md"));
+    }
+
+    #[test]
+    fn closing_active_tab_activates_a_neighbour() {
+        let mut tabs = EditorTabs::default();
+        for p in ["a.md", "b.md", "c.md"] {
+            tabs.open(p);
+        }
+        tabs.open("b.md");
+        assert_eq!(tabs.close("b.md"), Some("c.md".to_string()));
+        assert_eq!(tabs.close("c.md"), Some("a.md".to_string()));
+        assert_eq!(tabs.close("missing.md"), None);
+        assert_eq!(tabs.close("a.md"), None);
+        assert!(tabs.tabs.is_empty());
+        assert_eq!(tabs.active, None);
+    }
+
+    #[test]
+    fn pinned_tabs_stay_in_front_and_survive_close_others() {
+        let mut tabs = EditorTabs::default();
+        for p in ["a.md", "b.md", "c.md"] {
+            tabs.open(p);
+        }
+        tabs.toggle_pin("c.md");
+        assert_eq!(paths(&tabs), vec!["c.md", "a.md", "b.md"]);
+
+        tabs.move_tab("b.md", 0);
+        assert_eq!(paths(&tabs), vec!["c.md", "b.md", "a.md"]);
+
+        tabs.open("d.md");
+        tabs.close_others("a.md");
+        assert_eq!(paths(&tabs), vec!["c.md", "a.md"]);
+        assert_eq!(tabs.active.as_deref(), Some("a.md"));
+    }
+
+    #[test]
+    fn round_trips_persisted_layout_and_drops_missing_notes() {
+        let saved = SavedTabs {
+            tabs: vec![
+                SavedTab {
+                    path: "a.md".to_string(),
+                    pinned: false,
+                },
+                SavedTab {
+                    path: "b.md".to_string(),
+                    pinned: true,
+                },
+            ],
+            active: Some("a.md".to_string()),
+        };
+        let mut tabs = EditorTabs::from_persisted(&saved);
+        assert_eq!(paths(&tabs), vec!["b.md", "a.md"]);
+
+        tabs.rename("a.md", "z.md");
+        tabs.retain_existing(&["z.md".to_string()]);
+        let persisted = tabs.to_persisted();
+        assert_eq!(persisted.tabs.len(), 1);
+        assert_eq!(persisted.active.as_deref(), Some("z.md"));
+    }
+}
@@ -0,0 +1,155 @@
+use crate::find_replace::FindOptions;
+use leptos::html;
+use leptos::prelude::*;
+
+const INPUT_STYLE: &str = "flex: 1; min-width: 6rem; padding: 0.2rem 0.45rem; font-size: 0.8rem; border: 1px solid var(--border-color); border-radius: 4px; background: var(--bg-primary); color: var(--text-primary);";
+const BUTTON_STYLE: &str = "padding: 0.15rem 0.5rem; font-size: 0.8rem;";
+
+fn toggle_style(active: bool) -> String {
+    format!(
+        "padding: 0.15rem 0.4rem; font-size: 0.75rem; font-family: var(--font-mono); border-radius: 4px; {}",
+        if active {
+            "background: var(--accent-color); color: white; border: 1px solid var(--accent-color);"
+        } else {
+            "background: transparent; color: var(--text-muted); border: 1px solid var(--border-color);"
+        }
+    )
+}
+
+/// Find/replace bar above the editor. Matching, stepping and replacing are
+/// done by `App`; this only edits the query and reports button presses.
+#[component]
+pub fn FindBar<FStep, FReplace, FReplaceAll, FClose>(
+    open: ReadSignal<bool>,
+    query: ReadSignal<String>,
+    set_query: WriteSignal<String>,
+    replacement: ReadSignal<String>,
+    set_replacement: WriteSignal<String>,
+    options: ReadSignal<FindOptions>,
+    set_options: WriteSignal<FindOptions>,
+    match_count: Signal<usize>,
+    active_match: ReadSignal<Option<usize>>,
+    error: ReadSignal<Option<String>>,
+    input_ref: NodeRef<html::Input>,
+    on_step: FStep,
+    on_replace: FReplace,
+    on_replace_all: FReplaceAll,
+    on_close: FClose,
+) -> impl IntoView
+where
+    FStep: Fn(bool) + 'static + Clone + Send,
+    FReplace: Fn() + 'static + Clone + Send,
+    FReplaceAll: Fn() + 'static + Clone + Send,
+    FClose: Fn() + 'static + Clone + Send,
+{
+    let step_key = on_step.clone();
+    let step_prev = on_step.clone();
+    let step_next = on_step;
+    let replace_key = on_replace.clone();
+    let close_find = on_close.clone();
+    let close_replace = on_close.clone();
+    let close_btn = on_close;
+
+    let status = move || {
+        if let Some(err) = error.get() {
+            return err;
+        }
+        let count = match_count.get();
+        if query.get().is_empty() {
+            String::new()
+        } else if count == 0 {
+            "No results".to_string()
+        } else {
+            match active_match.get() {
+                Some(index) => format!("{} of {count}", index + 1),
+                None => format!("{count} found"),
+            }
+        }
+    };
+
+    view! {
+        <div
+            class="find-bar"
+            style=move || format!(
+                "display: {}; flex-wrap: wrap; align-items: center; gap: 0.4rem; padding: 0.35rem 0.75rem; border-bottom: 1px solid var(--border-color); background: var(--bg-secondary);",
+                if open.get() { "flex" } else { "none" }
+            )
+        >
+            <input
+                node_ref=input_ref
+                type="text"
+                placeholder="Find"
+                style=INPUT_STYLE
+                prop:value=move || query.get()
+                on:input=move |ev| set_query.set(event_target_value(&ev))
+                on:keydown=move |ev: leptos::ev::KeyboardEvent| match ev.key().as_str() {
+                    "Enter" => {
+                        ev.prevent_default();
+                        step_key(!ev.shift_key());
+                    }
+                    "Escape" => {
+                        ev.prevent_default();
+                        close_find();
+                    }
+                    _ => {}
+                }
+            />
+            <button
+                style=move || toggle_style(options.get().case_sensitive)
+                title="Match case"
+                on:click=move |_| set_options.update(|o| o.case_sensitive = !o.case_sensitive)
+            >
+                "Aa"
+            </button>
+            <button
+                style=move || toggle_style(options.get().whole_word)
+                title="Whole word"
+                on:click=move |_| set_options.update(|o| o.whole_word = !o.whole_word)
+            >
+                "W"
+            </button>
+            <button
+                style=move || toggle_style(options.get().regex)
+                title="Regular expression"
+                on:click=move |_| set_options.update(|o| o.regex = !o.regex)
+            >
+                ".*"
+            </button>
+            <span
+                style=move || format!(
+                    "min-width: 5rem; font-size: 0.75rem; color: {};",
+                    if error.get().is_some() { "var(--error-color)" } else { "var(--text-muted)" }
+                )
+            >
+                {status}
+            </span>
+            <button style=BUTTON_STYLE title="Previous match (Shift+Enter)" on:click=move |_| step_prev(false)>
+                "↑"
+            </button>
+            <button style=BUTTON_STYLE title="Next match (Enter)" on:click=move |_| step_next(true)>
+                "↓"
+            </button>
+            <input
+                type="text"
+                placeholder="Replace"
+                style=INPUT_STYLE
+                prop:value=move || replacement.get()
+                on:input=move |ev| set_replacement.set(event_target_value(&ev))
+                on:keydown=move |ev: leptos::ev::KeyboardEvent| match ev.key().as_str() {
+                    "Enter" => {
+                        ev.prevent_default();
+                        replace_key();
+                    }
+                    "Escape" => {
+                        ev.prevent_default();
+                        close_replace();
+                    }
+                    _ => {}
+                }
+            />
+            <button style=BUTTON_STYLE on:click=move |_| on_replace()>"Replace"</button>
+            <button style=BUTTON_STYLE on:click=move |_| on_replace_all()>"Replace all"</button>
+            <button style=BUTTON_STYLE title="Close (Esc)" on:click=move |_| close_btn()>"×"</button>
+        </div>
+    }
+}
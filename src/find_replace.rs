@@ -0,0 +1,233 @@
+use crate::editor_core::{ChangeOrigin, EditorSnapshot, Selection, TextChange, Transaction};
+use regex::{Regex, RegexBuilder};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FindOptions {
+    pub case_sensitive: bool,
+    pub whole_word: bool,
+    pub regex: bool,
+}
+
+fn build_pattern(query: &str, options: FindOptions) -> Result<Regex, String> {
+    let body = if options.regex {
+        query.to_string()
+    } else {
+        regex::escape(query)
+    };
+    RegexBuilder::new(&body)
+        .case_insensitive(!options.case_sensitive)
+        .multi_line(true)
+        .build()
+        .map_err(|err| match err {
+            regex::Error::Syntax(msg) => msg.lines().last().unwrap_or_default().trim().to_string(),
+            other => other.to_string(),
+        })
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Whole-word test for the match `start..end`. Only an edge that is itself a
+/// word character must not run on into another one, so queries such as
+/// `#tag`, `(x)` or `foo.` still match next to spaces and punctuation.
+fn is_whole_word(text: &str, start: usize, end: usize) -> bool {
+    let found = &text[start..end];
+    let open_start = found.chars().next().is_some_and(is_word_char)
+        && text[..start].chars().next_back().is_some_and(is_word_char);
+    let open_end = found.chars().next_back().is_some_and(is_word_char)
+        && text[end..].chars().next().is_some_and(is_word_char);
+    !open_start && !open_end
+}
+
+/// All non-empty matches of `query` in `text`, in document order. An empty
+/// query matches nothing; an invalid regex returns its error message.
+pub fn find_matches(text: &str, query: &str, options: FindOptions) -> Result<Vec<Selection>, String> {
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+    let re = build_pattern(query, options)?;
+    Ok(re
+        .find_iter(text)
+        .filter(|m| !m.is_empty())
+        .filter(|m| !options.whole_word || is_whole_word(text, m.start(), m.end()))
+        .map(|m| Selection::new(m.start(), m.end()))
+        .collect())
+}
+
+/// Index of the match to step to from `from`: the first one starting after
+/// it going forward, the last one ending before it going back, wrapping at
+/// either end of the note.
+pub fn step_match(matches: &[Selection], from: Selection, forward: bool) -> Option<usize> {
+    if matches.is_empty() {
+        return None;
+    }
+    if forward {
+        let next = matches.iter().position(|m| m.start > from.start);
+        Some(next.unwrap_or(0))
+    } else {
+        let prev = matches.iter().rposition(|m| m.start < from.start);
+        Some(prev.unwrap_or(matches.len() - 1))
+    }
+}
+
+/// Text a match is replaced with. Regex mode expands `$1`/`${name}` groups.
+fn replacement_for(
+    text: &str,
+    range: Selection,
+    re: &Regex,
+    replacement: &str,
+    options: FindOptions,
+) -> String {
+    if !options.regex {
+        return replacement.to_string();
+    }
+    let mut out = String::new();
+    if let Some(caps) = re.captures_at(text, range.start) {
+        caps.expand(replacement, &mut out);
+    }
+    out
+}
+
+/// Replaces the match at `range` and leaves the caret after the inserted text.
+pub fn replace_match_transaction(
+    snapshot: &EditorSnapshot,
+    range: Selection,
+    query: &str,
+    replacement: &str,
+    options: FindOptions,
+) -> Result<Transaction, String> {
+    let re = build_pattern(query, options)?;
+    let insert = replacement_for(&snapshot.text, range, &re, replacement, options);
+    let caret = range.start + insert.len();
+    Ok(Transaction::single(
+        TextChange::new(range.start, range.end, insert),
+        Some(Selection::cursor(caret)),
+        ChangeOrigin::Command,
+        "replace",
+    ))
+}
+
+/// Replaces every match in one transaction, so a single undo restores them.
+pub fn replace_all_transaction(
+    snapshot: &EditorSnapshot,
+    query: &str,
+    replacement: &str,
+    options: FindOptions,
+) -> Result<Option<Transaction>, String> {
+    let matches = find_matches(&snapshot.text, query, options)?;
+    if matches.is_empty() {
+        return Ok(None);
+    }
+    let re = build_pattern(query, options)?;
+    let changes = matches
+        .into_iter()
+        .map(|range| {
+            let insert = replacement_for(&snapshot.text, range, &re, replacement, options);
+            TextChange::new(range.start, range.end, insert)
+        })
+        .collect();
+    Ok(Some(Transaction {
+        changes,
+        selection_after: None,
+        origin: ChangeOrigin::Command,
+        label: "replace-all",
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spans<'a>(text: &'a str, query: &str, options: FindOptions) -> Vec<&'a str> {
+        find_matches(text, query, options)
+            .unwrap()
+            .into_iter()
+            .map(|m| &text[m.start..m.end])
+            .collect()
+    }
+
+    #[test]
+    fn matching_respects_case_word_and_regex_options() {
+        let text = "Cat cat concat CAT";
+        assert_eq!(spans(text, "cat", FindOptions::default()).len(), 4);
+        let case = FindOptions {
+            case_sensitive: true,
+            ..FindOptions::default()
+        };
+        assert_eq!(spans(text, "cat", case), vec!["cat", "cat"]);
+        let word = FindOptions {
+            whole_word: true,
+            ..FindOptions::default()
+        };
+        assert_eq!(spans(text, "cat", word), vec!["Cat", "cat", "CAT"]);
+        let regex = FindOptions {
+            regex: true,
+            case_sensitive: true,
+            ..FindOptions::default()
+        };
+        assert_eq!(spans(text, "[A-Z]+", regex), vec!["C", "CAT"]);
+        assert_eq!(spans("a.b axb", "a.b", FindOptions::default()), vec!["a.b"]);
+        assert!(find_matches(text, "(", regex).is_err());
+        assert!(find_matches(text, "", regex).unwrap().is_empty());
+    }
+
+    #[test]
+    fn whole_word_accepts_queries_edged_with_punctuation() {
+        let word = FindOptions {
+            whole_word: true,
+            ..FindOptions::default()
+        };
+        assert_eq!(spans("a #tag, #tags and x#tag", "#tag", word), vec!["#tag", "#tag"]);
+        assert_eq!(spans("f(x) and (x) or (xy)", "(x)", word), vec!["(x)", "(x)"]);
+        assert_eq!(spans("foo. xfoo. foo.bar", "foo.", word), vec!["foo.", "foo."]);
+        assert_eq!(spans("café cafés", "café", word), vec!["café"]);
+    }
+
+    #[test]
+    fn stepping_wraps_around_the_note() {
+        let matches = vec![Selection::new(2, 4), Selection::new(8, 10)];
+        assert_eq!(step_match(&matches, Selection::cursor(0), true), Some(0));
+        assert_eq!(step_match(&matches, Selection::new(2, 4), true), Some(1));
+        assert_eq!(step_match(&matches, Selection::new(8, 10), true), Some(0));
+        assert_eq!(step_match(&matches, Selection::new(8, 10), false), Some(0));
+        assert_eq!(step_match(&matches, Selection::new(2, 4), false), Some(1));
+        assert_eq!(step_match(&[], Selection::cursor(0), true), None);
+    }
+
+    #[test]
+    fn replace_all_is_one_transaction_with_group_expansion() {
+        let mut snapshot = EditorSnapshot::new("- [ ] a\n- [ ] b".to_string());
+        snapshot.set_selection(Selection::cursor(15));
+        let options = FindOptions {
+            regex: true,
+            ..FindOptions::default()
+        };
+        let transaction = replace_all_transaction(&snapshot, r"\[ \] (\w)", "[x] $1!", options)
+            .unwrap()
+            .unwrap();
+        assert_eq!(transaction.changes.len(), 2);
+        snapshot.apply_transaction(transaction).unwrap();
+        assert_eq!(snapshot.text, "- [x] a!\n- [x] b!");
+        assert_eq!(snapshot.selection(), Selection::cursor(17));
+
+        let none = replace_all_transaction(&snapshot, "zzz", "y", FindOptions::default()).unwrap();
+        assert!(none.is_none());
+    }
+
+    #[test]
+    fn replace_single_match_moves_caret_past_it() {
+        let mut snapshot = EditorSnapshot::new("one two one".to_string());
+        let transaction = replace_match_transaction(
+            &snapshot,
+            Selection::new(8, 11),
+            "one",
+            "three",
+            FindOptions::default(),
+        )
+        .unwrap();
+        snapshot.apply_transaction(transaction).unwrap();
+        assert_eq!(snapshot.text, "one two three");
+        assert_eq!(snapshot.selection(), Selection::cursor(13));
+    }
+}
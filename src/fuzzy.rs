@@ -0,0 +1,115 @@
+/// Token match used by vault search: a plain substring hit, or a hit after
+/// dropping one character from the token. Returns `(matched, fuzzy)`.
+pub fn fuzzy_token_match(haystack: &str, token: &str) -> (bool, bool) {
+    if token.is_empty() {
+        return (true, false);
+    }
+    if haystack.contains(token) {
+        return (true, false);
+    }
+
+    let mut boundaries = Vec::new();
+    boundaries.push(0usize);
+    for (idx, _) in token.char_indices() {
+        if idx != 0 {
+            boundaries.push(idx);
+        }
+    }
+    if boundaries.len() < 4 {
+        return (false, false);
+    }
+    boundaries.push(token.len());
+
+    let last = boundaries.len() - 1;
+    for i in 0..last {
+        let start_a = boundaries[0];
+        let end_a = boundaries[i];
+        let start_b = boundaries[i + 1];
+        let end_b = boundaries[last];
+
+        let mut shortened = String::new();
+        shortened.push_str(&token[start_a..end_a]);
+        shortened.push_str(&token[start_b..end_b]);
+
+        if haystack.contains(&shortened) {
+            return (true, true);
+        }
+    }
+
+    (false, false)
+}
+
+/// Scores `query` as an in-order subsequence of `candidate`, ignoring case.
+/// `None` when some query character is missing. Runs of consecutive
+/// characters and hits at word starts score higher; a shorter candidate wins
+/// ties.
+pub fn fuzzy_score(candidate: &str, query: &str) -> Option<i32> {
+    let query: Vec<char> = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let mut score = 0i32;
+    let mut next = 0usize;
+    let mut previous_matched = false;
+    let mut previous_char: Option<char> = None;
+    for (index, ch) in candidate.chars().enumerate() {
+        if next == query.len() {
+            break;
+        }
+        let lower = ch.to_lowercase().next().unwrap_or(ch);
+        if lower == query[next] {
+            let word_start = previous_char.is_none_or(|prev| {
+                !prev.is_alphanumeric() || (prev.is_lowercase() && ch.is_uppercase())
+            });
+            score += 1;
+            if previous_matched {
+                score += 8;
+            }
+            if word_start {
+                score += 6;
+            }
+            if index == 0 {
+                score += 4;
+            }
+            next += 1;
+            previous_matched = true;
+        } else {
+            previous_matched = false;
+        }
+        previous_char = Some(ch);
+    }
+    if next < query.len() {
+        return None;
+    }
+    Some(score * 100 - candidate.chars().count() as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_match_allows_one_dropped_character() {
+        assert_eq!(fuzzy_token_match("fuzzy search", "search"), (true, false));
+        assert_eq!(fuzzy_token_match("fuzzy search", "searxch"), (true, true));
+        assert_eq!(fuzzy_token_match("fuzzy search", "abc"), (false, false));
+    }
+
+    #[test]
+    fn score_prefers_word_starts_and_runs() {
+        assert!(fuzzy_score("Toggle bold", "xyz").is_none());
+        assert_eq!(fuzzy_score("Anything", ""), Some(0));
+        let word_starts = fuzzy_score("New note", "nn").unwrap();
+        let scattered = fuzzy_score("Insert wikilink", "nn").unwrap();
+        assert!(word_starts > scattered);
+        let run = fuzzy_score("Split right", "split").unwrap();
+        let spread = fuzzy_score("Select previous line item", "split").unwrap();
+        assert!(run > spread);
+        assert!(fuzzy_score("editor:toggleBold", "tb").unwrap() > fuzzy_score("editor:tab", "tb").unwrap());
+    }
+}
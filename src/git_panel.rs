@@ -0,0 +1,165 @@
+use crate::app_state::{GitLogEntry, GitRepoInfo};
+use js_sys::Date;
+use leptos::prelude::*;
+use std::collections::HashMap;
+use wasm_bindgen::JsValue;
+
+fn diff_line_style(line: &str) -> &'static str {
+    if line.starts_with("+++") || line.starts_with("---") || line.starts_with("diff ") || line.starts_with("index ") {
+        "color: var(--text-muted);"
+    } else if line.starts_with("@@") {
+        "color: var(--accent-color);"
+    } else if line.starts_with('+') {
+        "background: rgba(34, 197, 94, 0.18); color: var(--text-primary);"
+    } else if line.starts_with('-') {
+        "background: rgba(239, 68, 68, 0.18); color: var(--text-primary);"
+    } else {
+        "color: var(--text-secondary);"
+    }
+}
+
+#[component]
+pub fn GitPanel<FCommit, FRefresh, FClose>(
+    repo_info: ReadSignal<GitRepoInfo>,
+    git_statuses: ReadSignal<HashMap<String, String>>,
+    note_diff: ReadSignal<String>,
+    note_log: ReadSignal<Vec<GitLogEntry>>,
+    commit_template: Signal<String>,
+    on_commit: FCommit,
+    on_refresh: FRefresh,
+    on_close: FClose,
+) -> impl IntoView
+where
+    FCommit: Fn(String) + 'static + Clone + Send,
+    FRefresh: Fn() + 'static + Clone + Send,
+    FClose: Fn() + 'static + Clone + Send,
+{
+    let (message, set_message) = signal(commit_template.get_untracked());
+
+    view! {
+        <aside style="width: 360px; border-left: 1px solid var(--border-color); background: var(--bg-secondary); display: flex; flex-direction: column; min-width: 0;">
+            <header style="height: var(--topbar-height); display: flex; align-items: center; padding: 0 1rem; border-bottom: 1px solid var(--border-color); color: var(--text-muted); font-size: 0.85rem; gap: 0.5rem;">
+                <span>
+                    {move || match repo_info.get().branch {
+                        Some(branch) => format!("Git • {branch}"),
+                        None => "Git".to_string(),
+                    }}
+                </span>
+                <button
+                    style="margin-left: auto; padding: 0.15rem 0.45rem; font-size: 0.72rem;"
+                    on:click=move |_| on_refresh()
+                >
+                    "Refresh"
+                </button>
+                <button
+                    style="padding: 0.15rem 0.45rem; font-size: 0.72rem;"
+                    on:click=move |_| on_close()
+                >
+                    "Close"
+                </button>
+            </header>
+            {move || {
+                if !repo_info.get().is_repo {
+                    return view! {
+                        <div style="padding: 1rem; color: var(--text-muted); font-size: 0.85rem;">
+                            "This vault is not a git repository."
+                        </div>
+                    }
+                    .into_any();
+                }
+
+                let commit = on_commit.clone();
+                view! {
+                    <div style="display: flex; flex-direction: column; gap: 0.4rem; padding: 0.75rem; border-bottom: 1px solid var(--border-color);">
+                        <div style="font-size: 0.78rem; color: var(--text-muted);">
+                            {move || match git_statuses.get().len() {
+                                0 => "Working tree clean.".to_string(),
+                                1 => "1 changed file".to_string(),
+                                n => format!("{n} changed files"),
+                            }}
+                        </div>
+                        <input
+                            style="padding: 0.35rem 0.5rem; border-radius: 4px; border: 1px solid var(--border-color); background: var(--bg-primary); color: var(--text-primary); font-size: 0.8rem;"
+                            type="text"
+                            title="Placeholders: {date}, {count}, {files}"
+                            prop:value=move || message.get()
+                            on:input=move |e| set_message.set(event_target_value(&e))
+                        />
+                        <button
+                            style="padding: 0.25rem 0.5rem; font-size: 0.75rem;"
+                            disabled=move || git_statuses.get().is_empty()
+                            on:click=move |_| commit(message.get_untracked())
+                        >
+                            "Commit all"
+                        </button>
+                    </div>
+                    <div style="padding: 0.5rem 0.75rem 0.25rem; font-size: 0.78rem; color: var(--text-muted);">
+                        "Changes against HEAD"
+                    </div>
+                    <div style="flex: 1; min-height: 0; overflow: auto; padding: 0 0.75rem 0.75rem; font-family: var(--font-mono, monospace); font-size: 0.76rem; white-space: pre-wrap; word-break: break-word;">
+                        {move || {
+                            let diff = note_diff.get();
+                            if diff.trim().is_empty() {
+                                return view! {
+                                    <div style="color: var(--text-muted);">"No changes in this note."</div>
+                                }
+                                .into_any();
+                            }
+                            view! {
+                                <>
+                                    {diff
+                                        .lines()
+                                        .map(|line| {
+                                            let style = diff_line_style(line);
+                                            let text = line.to_string();
+                                            view! { <div style=style>{text}</div> }
+                                        })
+                                        .collect::<Vec<_>>()}
+                                </>
+                            }
+                            .into_any()
+                        }}
+                    </div>
+                    <div style="max-height: 35%; overflow-y: auto; padding: 0.5rem 0.75rem; border-top: 1px solid var(--border-color);">
+                        <div style="font-size: 0.78rem; color: var(--text-muted); margin-bottom: 0.35rem;">
+                            "History of this note"
+                        </div>
+                        {move || {
+                            let log = note_log.get();
+                            if log.is_empty() {
+                                return view! {
+                                    <div style="font-size: 0.8rem; color: var(--text-muted);">"No commits yet."</div>
+                                }
+                                .into_any();
+                            }
+                            view! {
+                                <>
+                                    {log
+                                        .into_iter()
+                                        .map(|entry| {
+                                            let date = Date::new(&JsValue::from_f64(entry.timestamp as f64 * 1000.0));
+                                            let date_label: String =
+                                                date.to_locale_string("default", &JsValue::UNDEFINED).into();
+                                            view! {
+                                                <div style="padding: 0.25rem 0; font-size: 0.8rem; color: var(--text-secondary);" title=entry.hash.clone()>
+                                                    <div style="overflow: hidden; text-overflow: ellipsis; white-space: nowrap;">
+                                                        {entry.subject.clone()}
+                                                    </div>
+                                                    <div style="font-size: 0.72rem; color: var(--text-muted);">
+                                                        {format!("{} • {} • {}", entry.short_hash, entry.author, date_label)}
+                                                    </div>
+                                                </div>
+                                            }
+                                        })
+                                        .collect::<Vec<_>>()}
+                                </>
+                            }
+                            .into_any()
+                        }}
+                    </div>
+                }
+                .into_any()
+            }}
+        </aside>
+    }
+}
@@ -0,0 +1,168 @@
+use crate::app_state::HistoryVersion;
+use crate::text_diff::{diff_lines, diff_words, DiffKind, DiffSegment};
+use js_sys::Date;
+use leptos::prelude::*;
+use wasm_bindgen::JsValue;
+
+fn segment_style(kind: DiffKind) -> &'static str {
+    match kind {
+        DiffKind::Equal => "color: var(--text-secondary);",
+        DiffKind::Insert => "background: rgba(34, 197, 94, 0.18); color: var(--text-primary);",
+        DiffKind::Delete => {
+            "background: rgba(239, 68, 68, 0.18); color: var(--text-primary); text-decoration: line-through;"
+        }
+    }
+}
+
+fn render_line_diff(segments: Vec<DiffSegment>) -> AnyView {
+    view! {
+        <>
+            {segments
+                .into_iter()
+                .map(|segment| {
+                    let marker = match segment.kind {
+                        DiffKind::Equal => "  ",
+                        DiffKind::Insert => "+ ",
+                        DiffKind::Delete => "- ",
+                    };
+                    view! {
+                        <div style=segment_style(segment.kind)>
+                            {format!("{marker}{}", segment.text)}
+                        </div>
+                    }
+                })
+                .collect::<Vec<_>>()}
+        </>
+    }
+    .into_any()
+}
+
+fn render_word_diff(segments: Vec<DiffSegment>) -> AnyView {
+    view! {
+        <>
+            {segments
+                .into_iter()
+                .map(|segment| view! { <span style=segment_style(segment.kind)>{segment.text}</span> })
+                .collect::<Vec<_>>()}
+        </>
+    }
+    .into_any()
+}
+
+#[component]
+pub fn HistoryPanel<FSelect, FRestore, FClose>(
+    history_versions: ReadSignal<Vec<HistoryVersion>>,
+    selected_version: ReadSignal<Option<String>>,
+    version_text: ReadSignal<Option<String>>,
+    current_text: Signal<String>,
+    on_select: FSelect,
+    on_restore: FRestore,
+    on_close: FClose,
+) -> impl IntoView
+where
+    FSelect: Fn(String) + 'static + Clone + Send,
+    FRestore: Fn() + 'static + Clone + Send,
+    FClose: Fn() + 'static + Clone + Send,
+{
+    let (word_mode, set_word_mode) = signal(false);
+
+    view! {
+        <aside style="width: 360px; border-left: 1px solid var(--border-color); background: var(--bg-secondary); display: flex; flex-direction: column; min-width: 0;">
+            <header style="height: var(--topbar-height); display: flex; align-items: center; padding: 0 1rem; border-bottom: 1px solid var(--border-color); color: var(--text-muted); font-size: 0.85rem; gap: 0.5rem;">
+                <span>"Version History"</span>
+                <button
+                    style="margin-left: auto; padding: 0.15rem 0.45rem; font-size: 0.72rem;"
+                    on:click=move |_| on_close()
+                >
+                    "Close"
+                </button>
+            </header>
+            <div style="max-height: 35%; overflow-y: auto; padding: 0.5rem; border-bottom: 1px solid var(--border-color);">
+                {move || {
+                    let versions = history_versions.get();
+                    if versions.is_empty() {
+                        return view! {
+                            <div style="padding: 0.25rem 0.1rem; font-size: 0.8rem; color: var(--text-muted);">
+                                "No saved versions for this note yet."
+                            </div>
+                        }
+                        .into_any();
+                    }
+
+                    view! {
+                        <>
+                            {versions
+                                .into_iter()
+                                .map(|version| {
+                                    let id = version.id.clone();
+                                    let active_id = version.id.clone();
+                                    let saved = Date::new(&JsValue::from_f64(version.saved_at as f64));
+                                    let saved_label: String =
+                                        saved.to_locale_string("default", &JsValue::UNDEFINED).into();
+                                    let select = on_select.clone();
+                                    view! {
+                                        <div
+                                            style=move || {
+                                                let active = selected_version.get().as_deref() == Some(active_id.as_str());
+                                                format!(
+                                                    "padding: 0.3rem 0.6rem; border-radius: var(--radius-md); margin-bottom: 2px; font-size: 0.82rem; cursor: pointer; color: var(--text-secondary); {}",
+                                                    if active { "background: var(--bg-hover); color: var(--text-primary);" } else { "" }
+                                                )
+                                            }
+                                            on:click=move |_| select(id.clone())
+                                        >
+                                            <div>{saved_label}</div>
+                                            <div style="font-size: 0.74rem; color: var(--text-muted);">
+                                                {format!("{} bytes", version.size)}
+                                            </div>
+                                        </div>
+                                    }
+                                })
+                                .collect::<Vec<_>>()}
+                        </>
+                    }
+                    .into_any()
+                }}
+            </div>
+            <div style="display: flex; align-items: center; gap: 0.5rem; padding: 0.5rem 0.75rem; border-bottom: 1px solid var(--border-color);">
+                <button
+                    style=move || format!("padding: 0.15rem 0.45rem; font-size: 0.72rem; {}", if word_mode.get() { "" } else { "font-weight: 600;" })
+                    on:click=move |_| set_word_mode.set(false)
+                >
+                    "Lines"
+                </button>
+                <button
+                    style=move || format!("padding: 0.15rem 0.45rem; font-size: 0.72rem; {}", if word_mode.get() { "font-weight: 600;" } else { "" })
+                    on:click=move |_| set_word_mode.set(true)
+                >
+                    "Words"
+                </button>
+                <button
+                    style="margin-left: auto; padding: 0.15rem 0.45rem; font-size: 0.72rem;"
+                    disabled=move || version_text.get().is_none()
+                    on:click=move |_| on_restore()
+                >
+                    "Restore this version"
+                </button>
+            </div>
+            <div style="flex: 1; overflow: auto; padding: 0.75rem; font-family: var(--font-mono, monospace); font-size: 0.78rem; white-space: pre-wrap; word-break: break-word;">
+                {move || {
+                    let Some(old_text) = version_text.get() else {
+                        return view! {
+                            <div style="color: var(--text-muted); font-family: inherit;">
+                                "Select a version to compare it with the current note."
+                            </div>
+                        }
+                        .into_any();
+                    };
+                    let current = current_text.get();
+                    if word_mode.get() {
+                        render_word_diff(diff_words(&old_text, &current))
+                    } else {
+                        render_line_diff(diff_lines(&old_text, &current))
+                    }
+                }}
+            </div>
+        </aside>
+    }
+}
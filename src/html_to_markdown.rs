@@ -0,0 +1,866 @@
+use crate::table_editor::{ColumnAlign, Table};
+
+/// Elements dropped together with their content.
+const SKIPPED: &[&str] = &[
+    "head", "link", "meta", "noscript", "script", "style", "template", "title",
+];
+
+/// Elements that never have content or an end tag.
+const VOID: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// Elements that start a new markdown block.
+const BLOCKS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "body",
+    "center",
+    "dd",
+    "details",
+    "dialog",
+    "div",
+    "dl",
+    "dt",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "html",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "summary",
+    "table",
+    "ul",
+];
+
+#[derive(Debug)]
+enum Node {
+    Element(Element),
+    Text(String),
+}
+
+#[derive(Debug, Default)]
+struct Element {
+    name: String,
+    attrs: Vec<(String, String)>,
+    children: Vec<Node>,
+}
+
+impl Element {
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// A property of the inline `style` attribute, lowercased.
+    fn style(&self, property: &str) -> Option<String> {
+        self.attr("style")?.split(';').find_map(|declaration| {
+            let (key, value) = declaration.split_once(':')?;
+            key.trim()
+                .eq_ignore_ascii_case(property)
+                .then(|| value.trim().to_ascii_lowercase())
+        })
+    }
+
+    fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|node| match node {
+            Node::Element(element) => Some(element),
+            Node::Text(_) => None,
+        })
+    }
+
+    /// `<pre>`, or anything styled to keep its line breaks, like the
+    /// `white-space: pre` divs code editors put on the clipboard.
+    fn keeps_whitespace(&self) -> bool {
+        self.name == "pre"
+            || self
+                .style("white-space")
+                .is_some_and(|value| value.starts_with("pre") || value == "break-spaces")
+    }
+
+    fn is_block(&self) -> bool {
+        BLOCKS.contains(&self.name.as_str()) || self.keeps_whitespace()
+    }
+}
+
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest[1..]
+            .find(';')
+            .filter(|&end| end <= 10)
+            .and_then(|end| {
+                let ch = match &rest[1..1 + end] {
+                    "amp" => '&',
+                    "lt" => '<',
+                    "gt" => '>',
+                    "quot" => '"',
+                    "apos" => '\'',
+                    "nbsp" => ' ',
+                    entity => {
+                        let number = entity.strip_prefix('#')?;
+                        let code = match number.strip_prefix(['x', 'X']) {
+                            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                            None => number.parse().ok()?,
+                        };
+                        char::from_u32(code)?
+                    }
+                };
+                Some((ch, end + 2))
+            });
+        match decoded {
+            Some((ch, len)) => {
+                out.push(if ch == '\u{00A0}' { ' ' } else { ch });
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Reads a start tag after its `<`. Returns the element, whether it closed
+/// itself with `/>`, and the text after the tag.
+fn parse_tag(source: &str) -> (Element, bool, &str) {
+    let name_end = source
+        .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+        .unwrap_or(source.len());
+    let mut element = Element {
+        name: source[..name_end].to_ascii_lowercase(),
+        ..Element::default()
+    };
+    let mut rest = &source[name_end..];
+    let mut self_closing = false;
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            break;
+        }
+        if let Some(after) = rest.strip_prefix("/>") {
+            self_closing = true;
+            rest = after;
+            break;
+        }
+        if let Some(after) = rest.strip_prefix('>') {
+            rest = after;
+            break;
+        }
+        let key_end = rest
+            .find(|c: char| c.is_whitespace() || c == '=' || c == '>' || c == '/')
+            .unwrap_or(rest.len());
+        if key_end == 0 {
+            // A stray `/`, `=` or quote.
+            let skip = rest.chars().next().map(char::len_utf8).unwrap_or(1);
+            rest = &rest[skip..];
+            continue;
+        }
+        let key = rest[..key_end].to_ascii_lowercase();
+        rest = rest[key_end..].trim_start();
+        let mut value = String::new();
+        if let Some(after) = rest.strip_prefix('=') {
+            let after = after.trim_start();
+            let (raw, remaining) = match after.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let body = &after[1..];
+                    let end = body.find(quote).unwrap_or(body.len());
+                    (&body[..end], body.get(end + 1..).unwrap_or(""))
+                }
+                _ => {
+                    let end = after
+                        .find(|c: char| c.is_whitespace() || c == '>')
+                        .unwrap_or(after.len());
+                    (&after[..end], &after[end..])
+                }
+            };
+            value = decode_entities(raw);
+            rest = remaining;
+        }
+        element.attrs.push((key, value));
+    }
+    (element, self_closing, rest)
+}
+
+fn pop(stack: &mut Vec<Element>) {
+    if stack.len() > 1 {
+        if let Some(element) = stack.pop() {
+            if let Some(parent) = stack.last_mut() {
+                parent.children.push(Node::Element(element));
+            }
+        }
+    }
+}
+
+fn close(stack: &mut Vec<Element>, name: &str) {
+    if let Some(index) = (1..stack.len()).rev().find(|&i| stack[i].name == name) {
+        while stack.len() > index {
+            pop(stack);
+        }
+    }
+}
+
+/// Closes an open `name` that a new sibling ends implicitly, as `<li>` does
+/// for the previous item, without reaching past the nearest `scope` element.
+fn close_implied(stack: &mut Vec<Element>, name: &str, scope: &[&str]) {
+    for index in (1..stack.len()).rev() {
+        if stack[index].name == name {
+            while stack.len() > index {
+                pop(stack);
+            }
+            return;
+        }
+        if scope.contains(&stack[index].name.as_str()) {
+            return;
+        }
+    }
+}
+
+fn open(stack: &mut Vec<Element>, element: Element, self_closing: bool) {
+    match element.name.as_str() {
+        "li" => close_implied(stack, "li", &["ol", "ul"]),
+        "tr" => close_implied(stack, "tr", &["table"]),
+        "td" | "th" => {
+            close_implied(stack, "td", &["tr", "table"]);
+            close_implied(stack, "th", &["tr", "table"]);
+        }
+        _ => {}
+    }
+    if element.is_block() && stack.last().is_some_and(|top| top.name == "p") {
+        pop(stack);
+    }
+    if self_closing || VOID.contains(&element.name.as_str()) {
+        if let Some(parent) = stack.last_mut() {
+            parent.children.push(Node::Element(element));
+        }
+    } else {
+        stack.push(element);
+    }
+}
+
+fn push_text(stack: &mut [Element], text: String) {
+    let Some(parent) = stack.last_mut() else {
+        return;
+    };
+    match parent.children.last_mut() {
+        Some(Node::Text(previous)) => previous.push_str(&text),
+        _ => parent.children.push(Node::Text(text)),
+    }
+}
+
+/// A forgiving parse of clipboard HTML: unknown end tags are ignored and
+/// unclosed elements end with their parent.
+fn parse(html: &str) -> Element {
+    let mut stack = vec![Element::default()];
+    let mut rest = html;
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix("<!--") {
+            rest = after.find("-->").map_or("", |end| &after[end + 3..]);
+        } else if rest.starts_with("<!") || rest.starts_with("<?") {
+            rest = rest.find('>').map_or("", |end| &rest[end + 1..]);
+        } else if let Some(after) = rest.strip_prefix("</") {
+            let end = after.find('>').unwrap_or(after.len());
+            close(&mut stack, &after[..end].trim().to_ascii_lowercase());
+            rest = after.get(end + 1..).unwrap_or("");
+        } else if rest.starts_with('<') && rest[1..].starts_with(|c: char| c.is_ascii_alphabetic())
+        {
+            let (element, self_closing, after) = parse_tag(&rest[1..]);
+            rest = after;
+            let name = element.name.as_str();
+            if SKIPPED.contains(&name) {
+                if !VOID.contains(&name) && !self_closing {
+                    let end_tag = format!("</{name}");
+                    rest = match rest.to_ascii_lowercase().find(&end_tag) {
+                        Some(at) => rest[at..].find('>').map_or("", |end| &rest[at + end + 1..]),
+                        None => "",
+                    };
+                }
+                continue;
+            }
+            open(&mut stack, element, self_closing);
+        } else {
+            let first = rest.chars().next().map_or(1, char::len_utf8);
+            let end = rest[first..].find('<').map_or(rest.len(), |i| i + first);
+            push_text(&mut stack, decode_entities(&rest[..end]));
+            rest = &rest[end..];
+        }
+    }
+    while stack.len() > 1 {
+        pop(&mut stack);
+    }
+    stack.pop().unwrap_or_default()
+}
+
+/// Converts clipboard HTML (from a browser, word processor or code editor)
+/// to markdown: headings, paragraphs, lists and tasks, links, images,
+/// emphasis, code, quotes and tables.
+pub fn html_to_markdown(html: &str) -> String {
+    let root = parse(html);
+    let mut blocks = Vec::new();
+    render_blocks(&root.children, &mut blocks);
+    blocks.join("\n\n")
+}
+
+fn render_blocks(nodes: &[Node], blocks: &mut Vec<String>) {
+    let mut inline = String::new();
+    for node in nodes {
+        match node {
+            Node::Element(element) if element.is_block() => {
+                flush_paragraph(&mut inline, blocks);
+                render_block(element, blocks);
+            }
+            node => inline.push_str(&render_inline_node(node)),
+        }
+    }
+    flush_paragraph(&mut inline, blocks);
+}
+
+fn flush_paragraph(inline: &mut String, blocks: &mut Vec<String>) {
+    let text = tidy_lines(inline);
+    if !text.is_empty() {
+        blocks.push(text.split('\n').map(escape_line_start).collect::<Vec<_>>().join("\n"));
+    }
+    inline.clear();
+}
+
+/// Backslash-escapes every character of `special` in `text`, so pasted text
+/// reads back as the same text rather than as markup.
+fn escape(text: &str, special: &[char]) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if special.contains(&ch) {
+            out.push('\\');
+        }
+        out.push(ch);
+    }
+    out
+}
+
+/// Escapes what would make a paragraph line start a heading, quote, list item
+/// or rule.
+fn escape_line_start(line: &str) -> String {
+    let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let after = |at: usize| line[at..].chars().next();
+    let opens_block = |at: usize| after(at).is_none_or(char::is_whitespace);
+    let hashes = line.len() - line.trim_start_matches('#').len();
+    let marker = match line.chars().next() {
+        Some('#') if hashes <= 6 && opens_block(hashes) => Some(0),
+        Some('>') => Some(0),
+        Some('-' | '+') if opens_block(1) => Some(0),
+        Some('-') if line.chars().all(|c| c == '-' || c == ' ') => Some(0),
+        Some('0'..='9')
+            if digits <= 9 && matches!(after(digits), Some('.' | ')')) && opens_block(digits + 1) =>
+        {
+            Some(digits)
+        }
+        _ => None,
+    };
+    match marker {
+        Some(at) => format!("{}\\{}", &line[..at], &line[at..]),
+        None => line.to_string(),
+    }
+}
+
+/// Trims every line, squeezes repeated spaces and drops blank lines at
+/// either end.
+fn tidy_lines(text: &str) -> String {
+    let lines: Vec<String> = text
+        .split('\n')
+        .map(|line| {
+            line.split(' ')
+                .filter(|word| !word.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect();
+    let Some(first) = lines.iter().position(|line| !line.is_empty()) else {
+        return String::new();
+    };
+    let last = lines
+        .iter()
+        .rposition(|line| !line.is_empty())
+        .unwrap_or(first);
+    lines[first..=last].join("\n")
+}
+
+fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn render_block(element: &Element, blocks: &mut Vec<String>) {
+    let name = element.name.as_str();
+    if name == "pre" {
+        blocks.push(code_block(element));
+        return;
+    }
+    if element.keeps_whitespace() {
+        let text = raw_text(element);
+        let text = text.trim_matches('\n');
+        if !text.trim().is_empty() {
+            blocks.push(text.to_string());
+        }
+        return;
+    }
+    match name {
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+            let level = name[1..].parse().unwrap_or(1);
+            let text = one_line(&inline_children(element));
+            if !text.is_empty() {
+                blocks.push(format!("{} {text}", "#".repeat(level)));
+            }
+        }
+        "hr" => blocks.push("---".to_string()),
+        "ul" | "ol" => {
+            let list = render_list(element);
+            if !list.is_empty() {
+                blocks.push(list);
+            }
+        }
+        "table" => blocks.extend(render_table(element)),
+        "blockquote" => {
+            let mut inner = Vec::new();
+            render_blocks(&element.children, &mut inner);
+            if !inner.is_empty() {
+                let quoted: Vec<String> = inner
+                    .join("\n\n")
+                    .split('\n')
+                    .map(|line| {
+                        if line.is_empty() {
+                            ">".to_string()
+                        } else {
+                            format!("> {line}")
+                        }
+                    })
+                    .collect();
+                blocks.push(quoted.join("\n"));
+            }
+        }
+        _ => render_blocks(&element.children, blocks),
+    }
+}
+
+/// Text with line breaks kept: `<br>` and block boundaries become newlines.
+fn raw_text(element: &Element) -> String {
+    fn push(element: &Element, out: &mut String) {
+        for child in &element.children {
+            match child {
+                Node::Text(text) => out.push_str(text),
+                Node::Element(child) if child.name == "br" => out.push('\n'),
+                Node::Element(child) if BLOCKS.contains(&child.name.as_str()) => {
+                    if !out.is_empty() && !out.ends_with('\n') {
+                        out.push('\n');
+                    }
+                    push(child, out);
+                    if !out.ends_with('\n') {
+                        out.push('\n');
+                    }
+                }
+                Node::Element(child) => push(child, out),
+            }
+        }
+    }
+    let mut out = String::new();
+    push(element, &mut out);
+    out
+}
+
+fn code_block(pre: &Element) -> String {
+    let code = pre.elements().find(|child| child.name == "code");
+    let lang = [Some(pre), code]
+        .into_iter()
+        .flatten()
+        .find_map(|element| {
+            element.attr("class")?.split_whitespace().find_map(|class| {
+                class
+                    .strip_prefix("language-")
+                    .or_else(|| class.strip_prefix("lang-"))
+            })
+        })
+        .unwrap_or_default();
+    let text = raw_text(pre);
+    let text = text
+        .strip_prefix('\n')
+        .unwrap_or(&text)
+        .trim_end_matches('\n');
+    let longest = longest_run(text, '`');
+    let fence = "`".repeat(longest.max(2) + 1);
+    format!("{fence}{lang}\n{text}\n{fence}")
+}
+
+fn longest_run(text: &str, ch: char) -> usize {
+    text.split(|c| c != ch).map(str::len).max().unwrap_or(0)
+}
+
+fn render_list(list: &Element) -> String {
+    let ordered = list.name == "ol";
+    let mut number: u64 = list
+        .attr("start")
+        .and_then(|start| start.trim().parse().ok())
+        .unwrap_or(1);
+    let mut lines = Vec::new();
+    for item in list.elements().filter(|child| child.name == "li") {
+        let marker = if ordered {
+            number += 1;
+            format!("{}. ", number - 1)
+        } else {
+            "- ".to_string()
+        };
+        let checkbox = item
+            .elements()
+            .find(|child| {
+                child.name == "input"
+                    && child
+                        .attr("type")
+                        .is_some_and(|kind| kind.eq_ignore_ascii_case("checkbox"))
+            })
+            .map(|input| {
+                if input.attr("checked").is_some() {
+                    "[x] "
+                } else {
+                    "[ ] "
+                }
+            })
+            .unwrap_or_default();
+
+        let mut blocks = Vec::new();
+        render_blocks(&item.children, &mut blocks);
+        let body = blocks.join("\n");
+        let mut body_lines = body.split('\n');
+        lines.push(format!(
+            "{marker}{checkbox}{}",
+            body_lines.next().unwrap_or_default()
+        ));
+        // Nested lists and extra paragraphs sit under the item's text.
+        lines.extend(body_lines.map(|line| {
+            if line.is_empty() {
+                String::new()
+            } else {
+                format!("    {line}")
+            }
+        }));
+    }
+    lines.join("\n")
+}
+
+fn cell_align(cell: &Element) -> ColumnAlign {
+    let align = cell
+        .attr("align")
+        .map(str::to_ascii_lowercase)
+        .or_else(|| cell.style("text-align"));
+    match align.as_deref() {
+        Some("left") => ColumnAlign::Left,
+        Some("center") => ColumnAlign::Center,
+        Some("right") => ColumnAlign::Right,
+        _ => ColumnAlign::None,
+    }
+}
+
+fn table_rows<'a>(element: &'a Element, rows: &mut Vec<&'a Element>) {
+    for child in element.elements() {
+        match child.name.as_str() {
+            "tr" => rows.push(child),
+            "thead" | "tbody" | "tfoot" => table_rows(child, rows),
+            _ => {}
+        }
+    }
+}
+
+/// A pipe table laid out by the table editor. The first row becomes the
+/// header.
+fn render_table(table: &Element) -> Option<String> {
+    let mut row_elements = Vec::new();
+    table_rows(table, &mut row_elements);
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    let mut aligns = Vec::new();
+    for row in row_elements {
+        let cells: Vec<&Element> = row
+            .elements()
+            .filter(|cell| cell.name == "td" || cell.name == "th")
+            .collect();
+        if rows.is_empty() {
+            aligns = cells.iter().map(|cell| cell_align(cell)).collect();
+        }
+        rows.push(
+            cells
+                .iter()
+                .map(|cell| one_line(&inline_children(cell)).replace('|', "\\|"))
+                .collect(),
+        );
+    }
+    let columns = rows.iter().map(Vec::len).max().filter(|&n| n > 0)?;
+    for row in &mut rows {
+        row.resize(columns, String::new());
+    }
+    aligns.resize(columns, ColumnAlign::None);
+    let table = Table {
+        start: 0,
+        end: 0,
+        indent: String::new(),
+        rows,
+        aligns,
+    };
+    Some(table.render().0)
+}
+
+fn render_inline_node(node: &Node) -> String {
+    match node {
+        Node::Text(text) => {
+            escape(&collapse_whitespace(text), &['\\', '*', '_', '`', '[', ']'])
+        }
+        Node::Element(element) => render_inline(element),
+    }
+}
+
+/// Runs of whitespace become one space, as a browser would render them.
+fn collapse_whitespace(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if !ch.is_whitespace() {
+            out.push(ch);
+        } else if !out.ends_with(' ') {
+            out.push(' ');
+        }
+    }
+    out
+}
+
+fn inline_children(element: &Element) -> String {
+    element.children.iter().map(render_inline_node).collect()
+}
+
+/// Puts `mark` around the text, outside any leading or trailing whitespace.
+fn wrap(text: &str, mark: &str) -> String {
+    let core = text.trim();
+    if core.is_empty() {
+        return text.to_string();
+    }
+    let lead = &text[..text.len() - text.trim_start().len()];
+    let trail = &text[text.trim_end().len()..];
+    format!("{lead}{mark}{core}{mark}{trail}")
+}
+
+fn code_span(text: &str) -> String {
+    let text = text.replace('\n', " ");
+    if text.trim().is_empty() {
+        return text;
+    }
+    let fence = "`".repeat(longest_run(&text, '`') + 1);
+    if fence.len() > 1 {
+        format!("{fence} {text} {fence}")
+    } else {
+        format!("`{text}`")
+    }
+}
+
+/// Angle brackets keep URLs with spaces or parentheses in one piece.
+fn link_target(url: &str) -> String {
+    let url = url.trim();
+    if url.contains([' ', '(', ')', '<', '>']) {
+        format!("<{}>", escape(url, &['<', '>']))
+    } else {
+        url.to_string()
+    }
+}
+
+fn render_inline(element: &Element) -> String {
+    match element.name.as_str() {
+        "br" => "\n".to_string(),
+        "input" => String::new(),
+        "img" => match element.attr("src").map(str::trim) {
+            Some(src) if !src.is_empty() => format!(
+                "![{}]({})",
+                escape(&one_line(element.attr("alt").unwrap_or_default()), &['\\', '[', ']']),
+                link_target(src)
+            ),
+            _ => String::new(),
+        },
+        "a" => {
+            let text = inline_children(element);
+            let href = element.attr("href").map(str::trim).filter(|href| {
+                !href.is_empty()
+                    && !href.starts_with('#')
+                    && !href.to_ascii_lowercase().starts_with("javascript:")
+            });
+            let lead = &text[..text.len() - text.trim_start().len()];
+            let trail = &text[text.trim_end().len()..];
+            match href {
+                // A bare URL stays as typed, without escapes.
+                Some(href) if one_line(&raw_text(element)) == href => {
+                    format!("{lead}{href}{trail}")
+                }
+                Some(href) if !text.trim().is_empty() => {
+                    format!("{lead}[{}]({}){trail}", text.trim(), link_target(href))
+                }
+                _ => text,
+            }
+        }
+        // Google Docs wraps whole documents in `<b style="font-weight:normal">`.
+        "b" | "strong" => {
+            let text = inline_children(element);
+            match element.style("font-weight").as_deref() {
+                Some("normal" | "400") => text,
+                _ => wrap(&text, "**"),
+            }
+        }
+        "em" | "i" | "cite" => wrap(&inline_children(element), "*"),
+        "del" | "s" | "strike" => wrap(&inline_children(element), "~~"),
+        "mark" => wrap(&inline_children(element), "=="),
+        "code" | "kbd" | "samp" | "tt" => code_span(&raw_text(element)),
+        "span" => {
+            // Word processors mark emphasis with inline styles.
+            let bold = element.style("font-weight").is_some_and(|weight| {
+                weight == "bold"
+                    || weight == "bolder"
+                    || weight.parse::<u32>().is_ok_and(|w| w >= 600)
+            });
+            let italic = element
+                .style("font-style")
+                .is_some_and(|style| style == "italic");
+            let struck = ["text-decoration", "text-decoration-line"]
+                .iter()
+                .any(|property| {
+                    element
+                        .style(property)
+                        .is_some_and(|d| d.contains("line-through"))
+                });
+            let mut text = inline_children(element);
+            if struck {
+                text = wrap(&text, "~~");
+            }
+            if italic {
+                text = wrap(&text, "*");
+            }
+            if bold {
+                text = wrap(&text, "**");
+            }
+            text
+        }
+        _ => inline_children(element),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_headings_paragraphs_and_inline_markup() {
+        let html = "<meta charset='utf-8'><h2>Plan &amp; goals</h2>\
+            <p>Read <a href=\"https://example.com/a b\">the <b>spec</b></a>, then <em>ship</em>.<br>Use <code>cargo&nbsp;test</code></p>\
+            <p><img src=\"https://example.com/x.png\" alt=\"Chart\"> <del>old</del> <mark>new</mark></p>";
+        assert_eq!(
+            html_to_markdown(html),
+            "## Plan & goals\n\n\
+             Read [the **spec**](<https://example.com/a b>), then *ship*.\nUse `cargo test`\n\n\
+             ![Chart](https://example.com/x.png) ~~old~~ ==new=="
+        );
+    }
+
+    #[test]
+    fn converts_nested_lists_tasks_and_quotes() {
+        let html = "<ul><li>One<ul><li>Nested</li></ul><li><input type=checkbox checked> Done</ul>\
+            <ol start=3><li><p>Three</p></li><li>Four</li></ol>\
+            <blockquote><p>Quoted</p><p>Twice</p></blockquote>";
+        assert_eq!(
+            html_to_markdown(html),
+            "- One\n    - Nested\n- [x] Done\n\n3. Three\n4. Four\n\n> Quoted\n>\n> Twice"
+        );
+    }
+
+    #[test]
+    fn converts_tables_and_code_blocks() {
+        let html = "<table><thead><tr><th>Name</th><th align=right>Qty</th></tr></thead>\
+            <tbody><tr><td>pear</td><td>10</td></tr><tr><td>a|b</td></tr></tbody></table>\
+            <pre><code class=\"language-rust\">fn main() {\n    let x = 1 &lt; 2;\n}\n</code></pre>";
+        assert_eq!(
+            html_to_markdown(html),
+            "| Name |  Qty |\n| ---- | ---: |\n| pear |   10 |\n| a\\|b |      |\n\n\
+             ```rust\nfn main() {\n    let x = 1 < 2;\n}\n```"
+        );
+    }
+
+    #[test]
+    fn reads_word_processor_styles_and_code_editor_html() {
+        let docs = "<b style=\"font-weight:normal;\" id=\"docs-internal-guid\">\
+            <p><span style=\"font-weight:700\">Bold</span> and <span style=\"font-style:italic\">italic</span></p></b>";
+        assert_eq!(html_to_markdown(docs), "**Bold** and *italic*");
+
+        let editor = "<div style=\"color: #ccc; white-space: pre;\"><div><span>if x {</span></div>\
+            <div><span>    y();</span></div><div><br></div><div><span>}</span></div></div>";
+        assert_eq!(html_to_markdown(editor), "if x {\n    y();\n\n}");
+
+        assert_eq!(
+            html_to_markdown("<!--StartFragment--><script>x()</script>plain<!--EndFragment-->"),
+            "plain"
+        );
+    }
+
+    #[test]
+    fn escapes_text_that_would_read_as_markup() {
+        assert_eq!(
+            html_to_markdown("<p>*not bold* and _x_ with `tick` in C:\\dir</p>"),
+            "\\*not bold\\* and \\_x\\_ with \\`tick\\` in C:\\\\dir"
+        );
+        assert_eq!(html_to_markdown("<p># not heading</p>"), "\\# not heading");
+        assert_eq!(
+            html_to_markdown("<p>> quote<br>- item<br>+ item<br>12. item<br>---</p>"),
+            "\\> quote\n\\- item\n\\+ item\n12\\. item\n\\---"
+        );
+        assert_eq!(
+            html_to_markdown("<p>#tag, -5, 2024 was good</p>"),
+            "#tag, -5, 2024 was good"
+        );
+        assert_eq!(
+            html_to_markdown("<a href=\"https://x.io\">t]ext</a> <a href>[x]</a>"),
+            "[t\\]ext](https://x.io) \\[x\\]"
+        );
+        assert_eq!(
+            html_to_markdown("<img src=\"a.png\" alt=\"]\"> <img src=\"b.png\" alt=\"[x]\">"),
+            "![\\]](a.png) ![\\[x\\]](b.png)"
+        );
+        assert_eq!(
+            html_to_markdown("<a href=\"https://x.io/a_b\">https://x.io/a_b</a>"),
+            "https://x.io/a_b"
+        );
+    }
+
+    #[test]
+    fn keeps_non_ascii_text_in_paragraphs_lists_and_tables() {
+        assert_eq!(
+            html_to_markdown("<p>é début</p><p>日本語のテキスト</p>"),
+            "é début\n\n日本語のテキスト"
+        );
+        assert_eq!(html_to_markdown("<ul><li>ü</li><li>🙂 smile</li></ul>"), "- ü\n- 🙂 smile");
+        let table = html_to_markdown("<table><tr><th>名前</th></tr><tr><td>ß</td></tr></table>");
+        assert!(table.contains("名前"));
+        assert!(table.contains("| ß"));
+    }
+}
@@ -0,0 +1,324 @@
+use crate::editor_core::{ChangeOrigin, EditorSnapshot, Selection, TextChange, Transaction};
+use crate::fuzzy::fuzzy_score;
+use crate::markdown_syntax::{resolve_link_target, shortest_link_path, FileCache};
+use crate::quick_switcher::rank_notes;
+use crate::suggestion_popup::{Suggestion, SUGGESTION_LIMIT};
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LinkCompletionKind {
+    /// `[[que` — note names, paths and aliases.
+    Note,
+    /// `[[Note#que` — headings of the target note.
+    Heading,
+    /// `[[Note#^que` — block IDs of the target note.
+    Block,
+    /// `[[Note|que` — aliases of the target note.
+    Alias,
+}
+
+/// The wiki link being typed around the caret.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LinkQuery {
+    /// Byte offset just after `[[`.
+    pub start: usize,
+    /// End of the text an accepted suggestion replaces, including a closing
+    /// `]]` already on the line.
+    pub end: usize,
+    pub kind: LinkCompletionKind,
+    /// What was typed before the `#`, `#^` or `|` that selected `kind`;
+    /// empty for `Note`.
+    pub prefix: String,
+    /// The part being completed.
+    pub query: String,
+}
+
+impl LinkQuery {
+    /// The note path part of the link, without heading or block.
+    pub fn target(&self) -> &str {
+        self.prefix.split('#').next().unwrap_or_default().trim()
+    }
+}
+
+/// Finds an unclosed `[[` before `caret` on the same line.
+pub fn link_query_at(text: &str, caret: usize) -> Option<LinkQuery> {
+    if caret > text.len() || !text.is_char_boundary(caret) {
+        return None;
+    }
+    let line_start = text[..caret].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let before = &text[line_start..caret];
+    let open = before.rfind("[[")?;
+    let inner = &before[open + 2..];
+    if inner.contains(']') {
+        return None;
+    }
+    let start = line_start + open + 2;
+
+    let line_end = text[caret..].find('\n').map(|i| caret + i).unwrap_or(text.len());
+    let after = &text[caret..line_end];
+    let end = match after.find("]]") {
+        Some(close) if !after[..close].contains("[[") => caret + close + 2,
+        _ => caret,
+    };
+
+    let (kind, prefix, query) = if let Some((left, right)) = inner.split_once('|') {
+        (LinkCompletionKind::Alias, left, right)
+    } else if let Some((left, right)) = inner.split_once("#^") {
+        (LinkCompletionKind::Block, left, right)
+    } else if let Some((left, right)) = inner.split_once('#') {
+        (LinkCompletionKind::Heading, left, right)
+    } else {
+        (LinkCompletionKind::Note, "", inner)
+    };
+    Some(LinkQuery {
+        start,
+        end,
+        kind,
+        prefix: prefix.to_string(),
+        query: query.to_string(),
+    })
+}
+
+/// Values of `candidates` matching `query`, best first. An empty query keeps
+/// their order.
+fn rank_values<'a>(candidates: impl Iterator<Item = &'a str>, query: &str) -> Vec<&'a str> {
+    let mut scored: Vec<(i32, &str)> = candidates
+        .filter_map(|value| fuzzy_score(value, query).map(|score| (score, value)))
+        .collect();
+    if !query.trim().is_empty() {
+        scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+    }
+    scored
+        .into_iter()
+        .take(SUGGESTION_LIMIT)
+        .map(|(_, value)| value)
+        .collect()
+}
+
+/// Suggestions for `query` typed in `source_path`. `source_cache` is the
+/// current note's live cache, since `file_cache` lags behind typing.
+pub fn link_suggestions(
+    query: &LinkQuery,
+    source_path: &str,
+    source_cache: &FileCache,
+    files: &[String],
+    file_cache: &HashMap<String, FileCache>,
+) -> Vec<Suggestion> {
+    if query.kind == LinkCompletionKind::Note {
+        let aliases: HashMap<String, Vec<String>> = file_cache
+            .iter()
+            .filter(|(_, cache)| !cache.aliases.is_empty())
+            .map(|(path, cache)| (path.clone(), cache.aliases.clone()))
+            .collect();
+        return rank_notes(&query.query, files, &aliases, &[])
+            .into_iter()
+            .take(SUGGESTION_LIMIT)
+            .map(|note| {
+                let link = shortest_link_path(&note.path, source_path, files);
+                let path = note.path.strip_suffix(".md").unwrap_or(&note.path).to_string();
+                match note.alias {
+                    Some(alias) => Suggestion {
+                        insert: format!("{link}|{alias}"),
+                        label: alias,
+                        detail: path,
+                    },
+                    None => Suggestion {
+                        label: path.rsplit('/').next().unwrap_or(&path).to_string(),
+                        detail: path.clone(),
+                        insert: link,
+                    },
+                }
+            })
+            .collect();
+    }
+
+    let target = query.target();
+    let target_path = if target.is_empty() {
+        Some(source_path.to_string())
+    } else {
+        resolve_link_target(target, source_path, files)
+    };
+    let Some(target_path) = target_path else {
+        return Vec::new();
+    };
+    let cache = if target_path == source_path {
+        Some(source_cache)
+    } else {
+        file_cache.get(&target_path)
+    };
+    let Some(cache) = cache else {
+        return Vec::new();
+    };
+
+    let prefix = &query.prefix;
+    match query.kind {
+        LinkCompletionKind::Heading => {
+            let ranked = rank_values(cache.headings.iter().map(|h| h.text.as_str()), &query.query);
+            ranked
+                .into_iter()
+                .map(|text| {
+                    let level = cache
+                        .headings
+                        .iter()
+                        .find(|h| h.text == text)
+                        .map(|h| h.level)
+                        .unwrap_or(1);
+                    Suggestion {
+                        label: text.to_string(),
+                        detail: format!("H{level}"),
+                        insert: format!("{prefix}#{text}"),
+                    }
+                })
+                .collect()
+        }
+        LinkCompletionKind::Block => rank_values(cache.blocks.iter().map(String::as_str), &query.query)
+            .into_iter()
+            .map(|id| Suggestion {
+                label: format!("^{id}"),
+                detail: "block".to_string(),
+                insert: format!("{prefix}#^{id}"),
+            })
+            .collect(),
+        LinkCompletionKind::Alias => rank_values(cache.aliases.iter().map(String::as_str), &query.query)
+            .into_iter()
+            .map(|alias| Suggestion {
+                label: alias.to_string(),
+                detail: "alias".to_string(),
+                insert: format!("{prefix}|{alias}"),
+            })
+            .collect(),
+        LinkCompletionKind::Note => Vec::new(),
+    }
+}
+
+/// Replaces the typed link with `suggestion.insert` and closes it, as one undoable
+/// edit with the caret after `]]`.
+pub fn accept_link_transaction(
+    snapshot: &EditorSnapshot,
+    query: &LinkQuery,
+    suggestion: &Suggestion,
+) -> Option<Transaction> {
+    if query.end > snapshot.text.len() || query.start > query.end {
+        return None;
+    }
+    let insert = format!("{}]]", suggestion.insert);
+    let caret = query.start + insert.len();
+    Some(Transaction::single(
+        TextChange::new(query.start, query.end, insert),
+        Some(Selection::cursor(caret)),
+        ChangeOrigin::Command,
+        "link-completion",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown_syntax::extract_file_cache;
+
+    #[test]
+    fn link_query_reads_mode_and_closing_brackets() {
+        let text = "see [[Proj]] and";
+        let query = link_query_at(text, 10).unwrap();
+        assert_eq!(query.kind, LinkCompletionKind::Note);
+        assert_eq!((query.start, query.end), (6, 12));
+        assert_eq!(query.query, "Proj");
+
+        let query = link_query_at("[[Note#Intro", 12).unwrap();
+        assert_eq!(query.kind, LinkCompletionKind::Heading);
+        assert_eq!((query.target(), query.query.as_str()), ("Note", "Intro"));
+        assert_eq!(query.end, 12);
+
+        let query = link_query_at("[[Note#^ab]]", 10).unwrap();
+        assert_eq!(query.kind, LinkCompletionKind::Block);
+        assert_eq!(query.query, "ab");
+
+        let query = link_query_at("[[Note#Intro|sh", 15).unwrap();
+        assert_eq!(query.kind, LinkCompletionKind::Alias);
+        assert_eq!((query.target(), query.prefix.as_str()), ("Note", "Note#Intro"));
+
+        assert!(link_query_at("[[Done]] after", 14).is_none());
+        assert!(link_query_at("[[Other\nline", 12).is_none());
+    }
+
+    #[test]
+    fn suggestions_cover_notes_headings_blocks_and_aliases() {
+        let files = vec![
+            "Inbox.md".to_string(),
+            "a/Plan.md".to_string(),
+            "b/Plan.md".to_string(),
+        ];
+        let mut file_cache = HashMap::new();
+        file_cache.insert(
+            "a/Plan.md".to_string(),
+            extract_file_cache("---\naliases: [Roadmap]\n---\n# Goals\n## Risks\nKey line ^key\n"),
+        );
+        let source = extract_file_cache("# Today\n");
+
+        let notes = link_suggestions(
+            &link_query_at("[[plan", 6).unwrap(),
+            "Inbox.md",
+            &source,
+            &files,
+            &file_cache,
+        );
+        let inserts: Vec<&str> = notes.iter().map(|s| s.insert.as_str()).collect();
+        assert_eq!(inserts, vec!["a/Plan", "b/Plan"]);
+
+        let by_alias = link_suggestions(
+            &link_query_at("[[roadm", 7).unwrap(),
+            "Inbox.md",
+            &source,
+            &files,
+            &file_cache,
+        );
+        assert_eq!(by_alias[0].insert, "a/Plan|Roadmap");
+
+        let headings = link_suggestions(
+            &link_query_at("[[a/Plan#ri", 11).unwrap(),
+            "Inbox.md",
+            &source,
+            &files,
+            &file_cache,
+        );
+        assert_eq!(headings[0].insert, "a/Plan#Risks");
+        assert_eq!(headings[0].detail, "H2");
+
+        let own = link_suggestions(&link_query_at("[[#", 3).unwrap(), "Inbox.md", &source, &files, &file_cache);
+        assert_eq!(own[0].insert, "#Today");
+
+        let blocks = link_suggestions(
+            &link_query_at("[[a/Plan#^", 10).unwrap(),
+            "Inbox.md",
+            &source,
+            &files,
+            &file_cache,
+        );
+        assert_eq!(blocks[0].insert, "a/Plan#^key");
+
+        let alias = link_suggestions(
+            &link_query_at("[[a/Plan|", 9).unwrap(),
+            "Inbox.md",
+            &source,
+            &files,
+            &file_cache,
+        );
+        assert_eq!(alias[0].insert, "a/Plan|Roadmap");
+    }
+
+    #[test]
+    fn accepting_replaces_the_typed_link_in_one_transaction() {
+        let mut snapshot = EditorSnapshot::new("x [[pl]] y".to_string());
+        let query = link_query_at(&snapshot.text, 5).unwrap();
+        let suggestion = Suggestion {
+            label: "Plan".to_string(),
+            detail: String::new(),
+            insert: "a/Plan".to_string(),
+        };
+        let transaction = accept_link_transaction(&snapshot, &query, &suggestion).unwrap();
+        assert_eq!(transaction.changes.len(), 1);
+        snapshot.apply_transaction(transaction).unwrap();
+        assert_eq!(snapshot.text, "x [[a/Plan]] y");
+        assert_eq!(snapshot.selection(), Selection::cursor(12));
+    }
+}
@@ -1,16 +1,37 @@
 mod app;
 mod app_state;
+mod attachments;
+mod attachments_pane;
+mod command_palette;
+mod commands;
 mod editor_core;
 mod markdown_syntax;
+mod metadata_completion;
 mod metadata_sidebar;
 mod path_utils;
+mod quick_switcher;
 mod editor_pane;
+mod editor_tabs;
+mod find_bar;
+mod find_replace;
+mod fuzzy;
+mod html_to_markdown;
 mod sidebar_tree;
+mod slash_menu;
 mod sidebar_panel;
+mod split_pane;
+mod suggestion_popup;
+mod table_editor;
 mod tauri_bridge;
 mod top_bar;
 mod vault_tabs;
 mod recent_notes_pane;
+mod trash_pane;
+mod text_diff;
+mod history_panel;
+mod link_completion;
+mod git_panel;
+mod tab_bar;
 
 use app::*;
 use leptos::prelude::*;
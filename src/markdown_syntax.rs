@@ -16,6 +16,18 @@ pub struct FileCache {
     pub headings: Vec<HeadingCache>,
     pub tags: Vec<String>,
     pub links: Vec<String>,
+    pub aliases: Vec<String>,
+    /// Block IDs (`^id` at the end of a line), in document order.
+    pub blocks: Vec<String>,
+    pub properties: Vec<(String, Vec<String>)>,
+}
+
+/// How often a frontmatter property and each of its values occur in the vault.
+#[derive(Clone, Debug, Default)]
+pub struct PropertyIndex {
+    /// Notes that set the property.
+    pub notes: usize,
+    pub values: HashMap<String, usize>,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -25,6 +37,7 @@ pub struct MetadataCacheState {
     pub unresolved_links: HashMap<String, HashMap<String, usize>>,
     pub backlinks: HashMap<String, Vec<String>>,
     pub tags_index: HashMap<String, Vec<String>>,
+    pub properties_index: HashMap<String, PropertyIndex>,
 }
 
 struct InlineMatch {
@@ -253,6 +266,140 @@ fn code_fence_close(line: &str, marker: u8, min_len: usize) -> bool {
     trimmed[len..].trim().is_empty()
 }
 
+/// How the highlighter treats a whole line, given the lines before it.
+enum LineBlock {
+    /// Opening or closing fence of a code block.
+    Fence(Option<String>),
+    Code(Option<String>),
+    Math,
+    Frontmatter,
+    Comment,
+    Body,
+}
+
+/// Block state carried from line to line: frontmatter, fenced code, `$$`
+/// math and `%%` comments.
+#[derive(Default)]
+struct BlockScanner {
+    in_frontmatter: bool,
+    frontmatter_done: bool,
+    in_math_block: bool,
+    in_comment_block: bool,
+    code_fence: Option<(u8, usize, Option<String>)>,
+}
+
+impl BlockScanner {
+    /// Classifies `line` (without its newline) and advances the state.
+    fn classify(&mut self, line: &str) -> LineBlock {
+        let trimmed = line.trim();
+
+        if let Some((marker, min_len, lang)) = &self.code_fence {
+            let lang = lang.clone();
+            if code_fence_close(line, *marker, *min_len) {
+                self.code_fence = None;
+                return LineBlock::Fence(lang);
+            }
+            return LineBlock::Code(lang);
+        }
+
+        if self.in_math_block {
+            if trimmed == "$$" {
+                self.in_math_block = false;
+            }
+            return LineBlock::Math;
+        }
+
+        if self.in_frontmatter {
+            if trimmed == "---" || trimmed == "..." {
+                self.in_frontmatter = false;
+                self.frontmatter_done = true;
+            }
+            return LineBlock::Frontmatter;
+        }
+
+        if self.in_comment_block {
+            if line.matches("%%").count() % 2 == 1 {
+                self.in_comment_block = false;
+            }
+            return LineBlock::Comment;
+        }
+
+        if !self.frontmatter_done {
+            if trimmed == "---" {
+                self.in_frontmatter = true;
+                return LineBlock::Frontmatter;
+            }
+            if !trimmed.is_empty() {
+                self.frontmatter_done = true;
+            }
+        }
+
+        if let Some((marker, len, lang)) = code_fence_open(line) {
+            self.code_fence = Some((marker, len, lang.clone()));
+            return LineBlock::Fence(lang);
+        }
+
+        if trimmed == "$$" {
+            self.in_math_block = true;
+            return LineBlock::Math;
+        }
+
+        if line.matches("%%").count() % 2 == 1 {
+            self.in_comment_block = true;
+        }
+        LineBlock::Body
+    }
+}
+
+/// What the text at a caret is, as the highlighter sees it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CaretContext {
+    Body,
+    Frontmatter,
+    CodeBlock,
+    MathBlock,
+    Comment,
+    InlineCode,
+}
+
+fn inline_code_regex() -> &'static Regex {
+    static RE_CODE: OnceLock<Regex> = OnceLock::new();
+    RE_CODE.get_or_init(|| Regex::new(r"`([^`\n]+)`").unwrap())
+}
+
+/// Block or span the caret at byte `offset` sits in.
+pub fn caret_context(text: &str, offset: usize) -> CaretContext {
+    let mut scanner = BlockScanner::default();
+    let mut line_offset = 0usize;
+    let mut lines = text.split_inclusive('\n');
+    loop {
+        let line = lines.next();
+        let line_without_nl = line.map(|l| l.strip_suffix('\n').unwrap_or(l)).unwrap_or("");
+        let block = scanner.classify(line_without_nl);
+        let line_end = line_offset + line_without_nl.len();
+        if line.is_none() || offset <= line_end {
+            return match block {
+                LineBlock::Fence(_) | LineBlock::Code(_) => CaretContext::CodeBlock,
+                LineBlock::Math => CaretContext::MathBlock,
+                LineBlock::Frontmatter => CaretContext::Frontmatter,
+                LineBlock::Comment => CaretContext::Comment,
+                LineBlock::Body => {
+                    let column = offset.saturating_sub(line_offset);
+                    let in_code = inline_code_regex()
+                        .find_iter(line_without_nl)
+                        .any(|m| m.start() < column && column < m.end());
+                    if in_code {
+                        CaretContext::InlineCode
+                    } else {
+                        CaretContext::Body
+                    }
+                }
+            };
+        }
+        line_offset += line.map(str::len).unwrap_or(0);
+    }
+}
+
 fn escape_html(s: &str) -> String {
     s.replace('&', "&amp;")
         .replace('<', "&lt;")
@@ -665,6 +812,28 @@ pub fn collect_image_targets_for_note(text: &str) -> Vec<(String, bool)> {
     out
 }
 
+/// Targets of plain (non-embed) wiki and markdown links, which is how notes
+/// usually point at PDFs and other files. The flag marks wiki links.
+pub fn collect_link_targets_for_note(text: &str) -> Vec<(String, bool)> {
+    static RE_WIKI: OnceLock<Regex> = OnceLock::new();
+    static RE_MD_LINK: OnceLock<Regex> = OnceLock::new();
+
+    let re_wiki = RE_WIKI.get_or_init(|| Regex::new(r"(?:^|[^!])\[\[([^\]\n]+)\]\]").unwrap());
+    let re_md_link =
+        RE_MD_LINK.get_or_init(|| Regex::new(r"(?:^|[^!])\[[^\]\n]*\]\(([^)\n]+)\)").unwrap());
+
+    let mut out = Vec::new();
+    for cap in re_wiki.captures_iter(text) {
+        let raw = cap.get(1).map(|m| m.as_str()).unwrap_or_default();
+        out.push((strip_wiki_target(raw), true));
+    }
+    for cap in re_md_link.captures_iter(text) {
+        let raw = cap.get(1).map(|m| m.as_str()).unwrap_or_default();
+        out.push((strip_markdown_image_target(raw), false));
+    }
+    out
+}
+
 fn resolve_image_preview_html(
     ctx: Option<&ImageRenderContext>,
     target: &str,
@@ -718,7 +887,6 @@ pub fn highlight_inline(
     static RE_WIKI: OnceLock<Regex> = OnceLock::new();
     static RE_MD_LINK: OnceLock<Regex> = OnceLock::new();
     static RE_MD_IMAGE: OnceLock<Regex> = OnceLock::new();
-    static RE_CODE: OnceLock<Regex> = OnceLock::new();
     static RE_INLINE_MATH: OnceLock<Regex> = OnceLock::new();
     static RE_FOOTNOTE_REF: OnceLock<Regex> = OnceLock::new();
     static RE_INLINE_FOOTNOTE: OnceLock<Regex> = OnceLock::new();
@@ -730,7 +898,7 @@ pub fn highlight_inline(
     let re_md_link = RE_MD_LINK.get_or_init(|| Regex::new(r"\[([^\]\n]+)\]\(([^)\n]+)\)").unwrap());
     let re_md_image =
         RE_MD_IMAGE.get_or_init(|| Regex::new(r"!\[([^\]\n]*)\]\(([^)\n]+)\)").unwrap());
-    let re_code = RE_CODE.get_or_init(|| Regex::new(r"`([^`\n]+)`").unwrap());
+    let re_code = inline_code_regex();
     let re_inline_math = RE_INLINE_MATH.get_or_init(|| Regex::new(r"\$([^$\n]+)\$").unwrap());
     let re_footnote_ref = RE_FOOTNOTE_REF.get_or_init(|| Regex::new(r"\[\^[^\]\n]+\]").unwrap());
     let re_inline_footnote =
@@ -1062,6 +1230,27 @@ pub fn highlight_inline(
     out
 }
 
+/// A `| cell | cell |` line of a pipe table.
+pub fn is_table_row(line: &str) -> bool {
+    static RE_TABLE_ROW: OnceLock<Regex> = OnceLock::new();
+    RE_TABLE_ROW
+        .get_or_init(|| Regex::new(r"^\s*\|.*\|\s*$").unwrap())
+        .is_match(line)
+}
+
+/// The `| --- | :---: |` alignment row under a table header.
+pub fn is_table_separator(line: &str) -> bool {
+    static RE_TABLE_SEPARATOR: OnceLock<Regex> = OnceLock::new();
+    RE_TABLE_SEPARATOR
+        .get_or_init(|| {
+            Regex::new(
+                r"^\s*(?:\|?(?:\s*:?-{3,}:?\s*\|)+\s*:?-{3,}:?\s*\|?|\|\s*:?-{3,}:?\s*\|)\s*$",
+            )
+            .unwrap()
+        })
+        .is_match(line)
+}
+
 pub fn highlight_markdown(
     text: &str,
     caret: Option<usize>,
@@ -1074,8 +1263,6 @@ pub fn highlight_markdown(
     static RE_LIST: OnceLock<Regex> = OnceLock::new();
     static RE_ORDERED: OnceLock<Regex> = OnceLock::new();
     static RE_HR: OnceLock<Regex> = OnceLock::new();
-    static RE_TABLE_ROW: OnceLock<Regex> = OnceLock::new();
-    static RE_TABLE_SEPARATOR: OnceLock<Regex> = OnceLock::new();
     static RE_FOOTNOTE_DEF: OnceLock<Regex> = OnceLock::new();
 
     let re_heading = RE_HEADING.get_or_init(|| Regex::new(r"^(#{1,6})[^\S\n]+.*$").unwrap());
@@ -1088,29 +1275,19 @@ pub fn highlight_markdown(
     let re_hr = RE_HR.get_or_init(|| {
         Regex::new(r"^\s{0,3}(?:(?:\*[\t ]*){3,}|(?:-[\t ]*){3,}|(?:_[\t ]*){3,})\s*$").unwrap()
     });
-    let re_table_row = RE_TABLE_ROW.get_or_init(|| Regex::new(r"^\s*\|.*\|\s*$").unwrap());
-    let re_table_separator = RE_TABLE_SEPARATOR.get_or_init(|| {
-        Regex::new(r"^\s*\|?(?:\s*:?-{3,}:?\s*\|)+\s*:?-{3,}:?\s*\|?\s*$").unwrap()
-    });
     let re_footnote_def =
         RE_FOOTNOTE_DEF.get_or_init(|| Regex::new(r"^\s*\[\^[^\]]+\]:\s+.*$").unwrap());
 
     let mut out = String::new();
     let mut offset = 0usize;
-    let mut in_frontmatter = false;
-    let mut frontmatter_possible = true;
-    let mut in_math_block = false;
-    let mut in_comment_block = false;
-    let mut code_fence: Option<(u8, usize, Option<String>)> = None;
+    let mut scanner = BlockScanner::default();
 
     for line in text.split_inclusive('\n') {
         let line_len = line.len();
         let line_without_nl = line.strip_suffix('\n').unwrap_or(line);
-        let trimmed = line_without_nl.trim();
 
-        if let Some((marker, min_len, ref lang)) = code_fence {
-            let is_close = code_fence_close(line_without_nl, marker, min_len);
-            if is_close {
+        match scanner.classify(line_without_nl) {
+            LineBlock::Fence(lang) => {
                 let base = "hl-codeblock hl-code-fence";
                 let class = if let Some(ref lang_id) = lang {
                     format!("{base} hl-code-lang-{lang_id}")
@@ -1118,8 +1295,10 @@ pub fn highlight_markdown(
                     base.to_string()
                 };
                 out.push_str(&wrap_line(&class, escape_html(line)));
-                code_fence = None;
-            } else {
+                offset += line_len;
+                continue;
+            }
+            LineBlock::Code(lang) => {
                 let class = if let Some(ref lang_id) = lang {
                     format!("hl-codeblock hl-code-lang-{lang_id}")
                 } else {
@@ -1127,69 +1306,25 @@ pub fn highlight_markdown(
                 };
                 let inner = highlight_code_line(line, lang.as_deref());
                 out.push_str(&wrap_line(&class, inner));
+                offset += line_len;
+                continue;
             }
-            offset += line_len;
-            continue;
-        }
-
-        if in_math_block {
-            out.push_str(&wrap_line("hl-math-block", escape_html(line)));
-            if trimmed == "$$" {
-                in_math_block = false;
-            }
-            offset += line_len;
-            continue;
-        }
-
-        if in_frontmatter {
-            out.push_str(&wrap_line("hl-frontmatter", escape_html(line)));
-            if trimmed == "---" || trimmed == "..." {
-                in_frontmatter = false;
-                frontmatter_possible = false;
-            }
-            offset += line_len;
-            continue;
-        }
-
-        if in_comment_block {
-            out.push_str(&wrap_line("hl-comment", escape_html(line)));
-            if line_without_nl.matches("%%").count() % 2 == 1 {
-                in_comment_block = false;
+            LineBlock::Math => {
+                out.push_str(&wrap_line("hl-math-block", escape_html(line)));
+                offset += line_len;
+                continue;
             }
-            offset += line_len;
-            continue;
-        }
-
-        if frontmatter_possible {
-            if trimmed == "---" {
+            LineBlock::Frontmatter => {
                 out.push_str(&wrap_line("hl-frontmatter", escape_html(line)));
-                in_frontmatter = true;
                 offset += line_len;
                 continue;
             }
-            if !trimmed.is_empty() {
-                frontmatter_possible = false;
+            LineBlock::Comment => {
+                out.push_str(&wrap_line("hl-comment", escape_html(line)));
+                offset += line_len;
+                continue;
             }
-        }
-
-        if let Some((marker, len, lang)) = code_fence_open(line_without_nl) {
-            let base = "hl-codeblock hl-code-fence";
-            let class = if let Some(ref lang_id) = lang {
-                format!("{base} hl-code-lang-{lang_id}")
-            } else {
-                base.to_string()
-            };
-            out.push_str(&wrap_line(&class, escape_html(line)));
-            code_fence = Some((marker, len, lang));
-            offset += line_len;
-            continue;
-        }
-
-        if trimmed == "$$" {
-            out.push_str(&wrap_line("hl-math-block", escape_html(line)));
-            in_math_block = true;
-            offset += line_len;
-            continue;
+            LineBlock::Body => {}
         }
 
         let line_end = offset + line_len;
@@ -1220,19 +1355,12 @@ pub fn highlight_markdown(
             wrap_line("hl-task", line_html)
         } else if re_ordered.is_match(line_without_nl) || re_list.is_match(line_without_nl) {
             wrap_line("hl-list", line_html)
-        } else if re_table_separator.is_match(line_without_nl)
-            || re_table_row.is_match(line_without_nl)
-        {
+        } else if is_table_separator(line_without_nl) || is_table_row(line_without_nl) {
             wrap_line("hl-table", line_html)
         } else {
             line_html
         };
         out.push_str(&wrapped);
-
-        if line_without_nl.matches("%%").count() % 2 == 1 {
-            in_comment_block = true;
-        }
-
         offset += line_len;
     }
 
@@ -1257,20 +1385,83 @@ pub fn highlight_markdown_for_editor(
     highlight_markdown(text, caret, Some(&ctx))
 }
 
+/// Wraps the text covered by `ranges` (byte offsets into the note, sorted)
+/// in `<mark>` elements on top of already highlighted editor HTML. Marks are
+/// closed around tags and reopened before the next text, so the markup stays
+/// well nested.
+pub fn layer_find_matches(html: &str, ranges: &[(usize, usize)], active: Option<usize>) -> String {
+    if ranges.is_empty() {
+        return html.to_string();
+    }
+
+    let mut out = String::with_capacity(html.len() + ranges.len() * 40);
+    let mut text_pos = 0usize;
+    let mut current = 0usize;
+    let mut open = false;
+    let mut rest = html;
+
+    while let Some(ch) = rest.chars().next() {
+        if ch == '<' {
+            let tag_len = rest.find('>').map(|i| i + 1).unwrap_or(rest.len());
+            if open {
+                out.push_str("</mark>");
+                open = false;
+            }
+            out.push_str(&rest[..tag_len]);
+            rest = &rest[tag_len..];
+            continue;
+        }
+
+        // Entities produced by `escape_html` stand for one ASCII byte.
+        let (token_len, text_len) = if ch == '&' {
+            (rest.find(';').map(|i| i + 1).unwrap_or(1), 1)
+        } else {
+            (ch.len_utf8(), ch.len_utf8())
+        };
+
+        while current < ranges.len() && ranges[current].1 <= text_pos {
+            if open {
+                out.push_str("</mark>");
+                open = false;
+            }
+            current += 1;
+        }
+        if !open && current < ranges.len() && ranges[current].0 <= text_pos {
+            out.push_str(if Some(current) == active {
+                "<mark class=\"hl-find-match hl-find-active\">"
+            } else {
+                "<mark class=\"hl-find-match\">"
+            });
+            open = true;
+        }
+
+        out.push_str(&rest[..token_len]);
+        rest = &rest[token_len..];
+        text_pos += text_len;
+    }
+    if open {
+        out.push_str("</mark>");
+    }
+    out
+}
+
 pub fn extract_file_cache(text: &str) -> FileCache {
     static RE_HEADING: OnceLock<Regex> = OnceLock::new();
     static RE_WIKI: OnceLock<Regex> = OnceLock::new();
     static RE_MD_LINK: OnceLock<Regex> = OnceLock::new();
     static RE_TAG: OnceLock<Regex> = OnceLock::new();
+    static RE_BLOCK: OnceLock<Regex> = OnceLock::new();
 
     let re_heading = RE_HEADING.get_or_init(|| Regex::new(r"^(#{1,6})[ \t]+(.+?)\s*$").unwrap());
     let re_wiki = RE_WIKI.get_or_init(|| Regex::new(r"\[\[([^\]]+)\]\]").unwrap());
     let re_md_link = RE_MD_LINK.get_or_init(|| Regex::new(r"!?\[[^\]\n]*\]\(([^)\n]+)\)").unwrap());
     let re_tag = RE_TAG.get_or_init(|| Regex::new(r"#[A-Za-z][A-Za-z0-9_/-]*").unwrap());
+    let re_block = RE_BLOCK.get_or_init(|| Regex::new(r"(?:^|\s)\^([A-Za-z0-9-]+)\s*$").unwrap());
 
     let mut headings = Vec::new();
     let mut tags = Vec::new();
     let mut links = Vec::new();
+    let mut blocks = Vec::new();
 
     for (idx, line) in text.lines().enumerate() {
         if let Some(cap) = re_heading.captures(line) {
@@ -1288,6 +1479,9 @@ pub fn extract_file_cache(text: &str) -> FileCache {
         for tag in re_tag.find_iter(line) {
             tags.push(tag.as_str().trim_start_matches('#').to_ascii_lowercase());
         }
+        if let Some(cap) = re_block.captures(line) {
+            blocks.push(cap[1].to_string());
+        }
     }
 
     for cap in re_wiki.captures_iter(text) {
@@ -1331,9 +1525,77 @@ pub fn extract_file_cache(text: &str) -> FileCache {
         headings,
         tags,
         links,
+        aliases: frontmatter_aliases(text),
+        blocks,
+        properties: frontmatter_properties(text),
     }
 }
 
+/// Top-level YAML frontmatter properties in order, each with its values: an
+/// inline list (`key: [a, b]`), a block list (`- a` lines) or a single value.
+/// Quotes are stripped; nested maps are skipped.
+pub fn frontmatter_properties(text: &str) -> Vec<(String, Vec<String>)> {
+    let mut lines = text.lines();
+    if lines.next().map(str::trim) != Some("---") {
+        return Vec::new();
+    }
+    let clean = |raw: &str| raw.trim().trim_matches(|c| c == '"' || c == '\'').trim().to_string();
+
+    let mut properties: Vec<(String, Vec<String>)> = Vec::new();
+    let mut in_list = false;
+    for line in lines {
+        let trimmed = line.trim();
+        if trimmed == "---" || trimmed == "..." {
+            break;
+        }
+        if in_list {
+            if let Some(item) = trimmed.strip_prefix("- ") {
+                if let Some((_, values)) = properties.last_mut() {
+                    values.push(clean(item));
+                }
+                continue;
+            }
+            if line.starts_with([' ', '\t']) || trimmed.is_empty() {
+                continue;
+            }
+            in_list = false;
+        }
+        if line.starts_with([' ', '\t']) {
+            continue;
+        }
+        let Some((key, value)) = trimmed.split_once(':') else {
+            continue;
+        };
+        let key = key.trim();
+        if key.is_empty() || key.starts_with('#') {
+            continue;
+        }
+        let value = value.trim();
+        let values = if value.is_empty() {
+            in_list = true;
+            Vec::new()
+        } else if let Some(inner) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+            inner.split(',').map(clean).collect()
+        } else {
+            vec![clean(value)]
+        };
+        properties.push((key.to_string(), values));
+    }
+    for (_, values) in &mut properties {
+        values.retain(|value| !value.is_empty());
+    }
+    properties
+}
+
+/// `aliases`/`alias` from YAML frontmatter.
+pub fn frontmatter_aliases(text: &str) -> Vec<String> {
+    frontmatter_properties(text)
+        .into_iter()
+        .filter(|(key, _)| matches!(key.as_str(), "aliases" | "alias"))
+        .flat_map(|(_, values)| values)
+        .collect()
+}
+
 fn resolve_linkpath(
     linkpath: &str,
     source_path: &str,
@@ -1390,14 +1652,12 @@ fn resolve_linkpath(
     None
 }
 
-pub fn build_metadata_cache(
-    notes: &HashMap<String, String>,
-    files: &[String],
-) -> MetadataCacheState {
-    let mut state = MetadataCacheState::default();
+type LinkLookups = (HashMap<String, String>, HashMap<String, Vec<String>>);
+
+/// Case-insensitive path and file-stem indexes that `resolve_linkpath` reads.
+fn link_lookups(files: &[String]) -> LinkLookups {
     let mut file_lookup = HashMap::new();
     let mut stem_lookup: HashMap<String, Vec<String>> = HashMap::new();
-
     for path in files {
         file_lookup.insert(path.to_ascii_lowercase(), path.clone());
         let stem = Path::new(path)
@@ -1406,7 +1666,46 @@ pub fn build_metadata_cache(
             .unwrap_or(path)
             .to_ascii_lowercase();
         stem_lookup.entry(stem).or_default().push(path.clone());
+    }
+    (file_lookup, stem_lookup)
+}
 
+/// The note a link written in `source_path` points at, if any.
+pub fn resolve_link_target(linkpath: &str, source_path: &str, files: &[String]) -> Option<String> {
+    let (file_lookup, stem_lookup) = link_lookups(files);
+    resolve_linkpath(linkpath, source_path, &file_lookup, &stem_lookup)
+}
+
+/// Shortest link text that still resolves from `source_path` to `target`:
+/// the bare name when it is unique, otherwise the shortest trailing part of
+/// the path that does, falling back to the full vault path. `.md` is dropped.
+pub fn shortest_link_path(target: &str, source_path: &str, files: &[String]) -> String {
+    let (file_lookup, stem_lookup) = link_lookups(files);
+    let without_ext = if target.to_ascii_lowercase().ends_with(".md") {
+        &target[..target.len() - 3]
+    } else {
+        target
+    };
+    let parts: Vec<&str> = without_ext.split('/').collect();
+    for start in (0..parts.len()).rev() {
+        let candidate = parts[start..].join("/");
+        if resolve_linkpath(&candidate, source_path, &file_lookup, &stem_lookup).as_deref()
+            == Some(target)
+        {
+            return candidate;
+        }
+    }
+    without_ext.to_string()
+}
+
+pub fn build_metadata_cache(
+    notes: &HashMap<String, String>,
+    files: &[String],
+) -> MetadataCacheState {
+    let mut state = MetadataCacheState::default();
+    let (file_lookup, stem_lookup) = link_lookups(files);
+
+    for path in files {
         let text = notes.get(path).cloned().unwrap_or_default();
         let cache = extract_file_cache(&text);
         for tag in &cache.tags {
@@ -1416,6 +1715,13 @@ pub fn build_metadata_cache(
                 .or_default()
                 .push(path.clone());
         }
+        for (key, values) in &cache.properties {
+            let entry = state.properties_index.entry(key.clone()).or_default();
+            entry.notes += 1;
+            for value in values {
+                *entry.values.entry(value.clone()).or_insert(0) += 1;
+            }
+        }
         state.file_cache.insert(path.clone(), cache);
     }
 
@@ -1460,6 +1766,83 @@ mod tests {
         );
     }
 
+    #[test]
+    fn frontmatter_aliases_accept_inline_block_and_single_forms() {
+        assert_eq!(
+            frontmatter_aliases("---\naliases: [Alpha, \"Beta two\"]\n---\nbody"),
+            vec!["Alpha", "Beta two"]
+        );
+        assert_eq!(
+            frontmatter_aliases("---\ntitle: x\naliases:\n  - one\n  - 'two'\ntags: [a]\n---\n"),
+            vec!["one", "two"]
+        );
+        assert_eq!(frontmatter_aliases("---\nalias: Solo\n---\n"), vec!["Solo"]);
+        assert!(frontmatter_aliases("aliases: [not frontmatter]").is_empty());
+        assert!(frontmatter_aliases("---\ntitle: x\n---\naliases: [late]").is_empty());
+    }
+
+    #[test]
+    fn caret_context_follows_highlighter_blocks_and_code_spans() {
+        let text = "---\ntags: [a]\n---\nBody `code` end\n```rust\nlet x;\n```\n%%\nhidden\n%%\n";
+        assert_eq!(caret_context(text, 6), CaretContext::Frontmatter);
+        assert_eq!(caret_context(text, 20), CaretContext::Body);
+        assert_eq!(caret_context(text, 26), CaretContext::InlineCode);
+        assert_eq!(caret_context(text, 42), CaretContext::CodeBlock);
+        assert_eq!(caret_context(text, 56), CaretContext::Comment);
+        assert_eq!(caret_context(text, text.len()), CaretContext::Body);
+        assert_eq!(caret_context("---\nkey: ", 9), CaretContext::Frontmatter);
+    }
+
+    #[test]
+    fn frontmatter_properties_are_indexed_with_counts() {
+        let notes = HashMap::from([
+            ("a.md".to_string(), "---\nstatus: draft\ntags: [x]\n---\n".to_string()),
+            ("b.md".to_string(), "---\nstatus: done\nnested:\n  child: 1\n---\n".to_string()),
+            ("c.md".to_string(), "---\nstatus: draft\n---\n".to_string()),
+        ]);
+        let files = vec!["a.md".to_string(), "b.md".to_string(), "c.md".to_string()];
+        let cache = build_metadata_cache(&notes, &files);
+        let status = &cache.properties_index["status"];
+        assert_eq!(status.notes, 3);
+        assert_eq!(status.values["draft"], 2);
+        assert_eq!(status.values["done"], 1);
+        assert!(cache.properties_index["nested"].values.is_empty());
+        assert!(!cache.properties_index.contains_key("child"));
+    }
+
+    #[test]
+    fn shortest_link_path_is_the_shortest_text_that_resolves_back() {
+        let files = vec![
+            "Inbox.md".to_string(),
+            "a/Note.md".to_string(),
+            "b/Note.md".to_string(),
+            "b/deep/Note.md".to_string(),
+        ];
+        assert_eq!(shortest_link_path("Inbox.md", "a/Note.md", &files), "Inbox");
+        assert_eq!(shortest_link_path("a/Note.md", "Inbox.md", &files), "a/Note");
+        assert_eq!(shortest_link_path("b/deep/Note.md", "b/Note.md", &files), "deep/Note");
+        assert_eq!(shortest_link_path("b/deep/Note.md", "Inbox.md", &files), "b/deep/Note");
+        assert_eq!(resolve_link_target("Inbox", "a/Note.md", &files).as_deref(), Some("Inbox.md"));
+        assert_eq!(resolve_link_target("Note", "Inbox.md", &files), None);
+    }
+
+    #[test]
+    fn block_ids_are_collected_from_line_ends() {
+        let cache = extract_file_cache("Para one ^intro\n- item ^item-2\nnot^inline here\n");
+        assert_eq!(cache.blocks, vec!["intro", "item-2"]);
+    }
+
+    #[test]
+    fn find_matches_are_layered_across_highlight_spans() {
+        let html = highlight_markdown("a **b** & c", None, None);
+        let layered = layer_find_matches(&html, &[(2, 7), (8, 9)], Some(1));
+        assert_has(&layered, "<mark class=\"hl-find-match\">**</mark>");
+        assert_has(&layered, "<mark class=\"hl-find-match hl-find-active\">&amp;</mark>");
+        assert_eq!(layered.matches("<mark").count(), layered.matches("</mark>").count());
+        assert!(!layered.contains("<mark class=\"hl-find-match\"></mark>"));
+        assert_eq!(layer_find_matches(&html, &[], None), html);
+    }
+
     #[test]
     fn highlights_obsidian_inline_emphasis_variants() {
         let html = highlight_inline(
@@ -0,0 +1,346 @@
+use crate::editor_core::{ChangeOrigin, EditorSnapshot, Selection, TextChange, Transaction};
+use crate::fuzzy::fuzzy_score;
+use crate::markdown_syntax::{caret_context, CaretContext, MetadataCacheState};
+use crate::suggestion_popup::{Suggestion, SUGGESTION_LIMIT};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MetadataCompletionKind {
+    /// `#ta` in body text.
+    Tag,
+    /// A property name at the start of a frontmatter line. `append_colon` is
+    /// set when the line has no `:` yet.
+    PropertyKey { append_colon: bool },
+    /// A value after `key:`, inside `key: [..]` or on a `- ` line under `key:`.
+    PropertyValue { key: String },
+}
+
+/// The tag or frontmatter property being typed around the caret.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MetadataQuery {
+    /// Start of the text an accepted suggestion replaces (the `#` for tags).
+    pub start: usize,
+    pub end: usize,
+    pub kind: MetadataCompletionKind,
+    pub query: String,
+}
+
+fn is_tag_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '/' | '-')
+}
+
+fn line_bounds(text: &str, caret: usize) -> (usize, usize) {
+    let start = text[..caret].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let end = text[caret..].find('\n').map(|i| caret + i).unwrap_or(text.len());
+    (start, end)
+}
+
+/// Finds a tag or property to complete at `caret`. Code blocks, code spans,
+/// math and comments never complete, and a `#` starting a line is heading
+/// syntax rather than a tag.
+pub fn metadata_query_at(text: &str, caret: usize) -> Option<MetadataQuery> {
+    if caret > text.len() || !text.is_char_boundary(caret) {
+        return None;
+    }
+    match caret_context(text, caret) {
+        CaretContext::Body => tag_query_at(text, caret),
+        CaretContext::Frontmatter => property_query_at(text, caret),
+        _ => None,
+    }
+}
+
+fn tag_query_at(text: &str, caret: usize) -> Option<MetadataQuery> {
+    let (line_start, line_end) = line_bounds(text, caret);
+    let before = &text[line_start..caret];
+    let typed = before.len() - before.trim_end_matches(is_tag_char).len();
+    let hash = before.len().checked_sub(typed + 1)?;
+    if before.as_bytes()[hash] != b'#' || hash == 0 {
+        return None;
+    }
+    let preceding = before[..hash].chars().next_back()?;
+    if !preceding.is_whitespace() && preceding != '(' {
+        return None;
+    }
+    let query = &before[hash + 1..];
+    if query.chars().next().is_some_and(|c| !c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let after = &text[caret..line_end];
+    let tail = after.len() - after.trim_start_matches(is_tag_char).len();
+    Some(MetadataQuery {
+        start: line_start + hash,
+        end: caret + tail,
+        kind: MetadataCompletionKind::Tag,
+        query: query.to_string(),
+    })
+}
+
+fn property_query_at(text: &str, caret: usize) -> Option<MetadataQuery> {
+    let (line_start, line_end) = line_bounds(text, caret);
+    let line = &text[line_start..line_end];
+    if matches!(line.trim(), "---" | "...") {
+        return None;
+    }
+    let before = &text[line_start..caret];
+    let indent = before.len() - before.trim_start().len();
+    let rest = &before[indent..];
+
+    let (start, end, kind, query) = if let Some(item) = rest.strip_prefix("- ") {
+        // Block list item: the key is the nearest unindented `key:` above.
+        let key = text[..line_start].lines().rev().find_map(|prev| {
+            if prev.starts_with([' ', '\t']) || prev.trim_start().starts_with("- ") {
+                return None;
+            }
+            Some(prev.split_once(':').filter(|(_, v)| v.trim().is_empty()).map(|(k, _)| k))
+        })??;
+        (
+            caret - item.len(),
+            line_end,
+            MetadataCompletionKind::PropertyValue {
+                key: key.trim().to_string(),
+            },
+            item,
+        )
+    } else if indent > 0 {
+        return None;
+    } else if let Some((key, value)) = rest.split_once(':') {
+        let kind = MetadataCompletionKind::PropertyValue {
+            key: key.trim().to_string(),
+        };
+        if value.trim_start().starts_with('[') {
+            let item_start = value.rfind([',', '[']).map(|i| i + 1).unwrap_or(0);
+            let item = value[item_start..].trim_start();
+            let after = &text[caret..line_end];
+            let tail = after.find([',', ']']).unwrap_or(after.len());
+            (caret - item.len(), caret + tail, kind, item)
+        } else {
+            let item = value.trim_start();
+            (caret - item.len(), line_end, kind, item)
+        }
+    } else {
+        let after = &text[caret..line_end];
+        let (end, append_colon) = match after.find(':') {
+            Some(colon) => (caret + colon, false),
+            None => (line_end, true),
+        };
+        (
+            line_start,
+            end,
+            MetadataCompletionKind::PropertyKey { append_colon },
+            rest,
+        )
+    };
+
+    // Properties only complete once something is typed, so Enter still
+    // starts a new line after `key:` or `- `.
+    if query.trim().is_empty() {
+        return None;
+    }
+    Some(MetadataQuery {
+        start,
+        end,
+        kind,
+        query: query.to_string(),
+    })
+}
+
+/// Every tag and each of its parent segments (`a/b/c` also yields `a` and
+/// `a/b`) with the number of notes using it or anything nested under it.
+fn tag_counts(cache: &MetadataCacheState) -> HashMap<String, usize> {
+    let mut notes: HashMap<String, HashSet<&str>> = HashMap::new();
+    for (tag, files) in &cache.tags_index {
+        let mut prefix_end = 0;
+        for segment in tag.split('/') {
+            prefix_end += segment.len();
+            let entry = notes.entry(tag[..prefix_end].to_string()).or_default();
+            entry.extend(files.iter().map(String::as_str));
+            prefix_end += 1;
+        }
+    }
+    notes.into_iter().map(|(tag, files)| (tag, files.len())).collect()
+}
+
+/// Candidates matching `query`, best match first and more used ones first
+/// among equals.
+fn rank_counted(candidates: HashMap<String, usize>, query: &str) -> Vec<(String, usize)> {
+    let mut scored: Vec<(i32, String, usize)> = candidates
+        .into_iter()
+        .filter_map(|(value, count)| fuzzy_score(&value, query).map(|score| (score, value, count)))
+        .collect();
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then(b.2.cmp(&a.2)).then_with(|| a.1.cmp(&b.1)));
+    scored
+        .into_iter()
+        .take(SUGGESTION_LIMIT)
+        .map(|(_, value, count)| (value, count))
+        .collect()
+}
+
+fn notes_label(count: usize) -> String {
+    if count == 1 {
+        "1 note".to_string()
+    } else {
+        format!("{count} notes")
+    }
+}
+
+pub fn metadata_suggestions(query: &MetadataQuery, cache: &MetadataCacheState) -> Vec<Suggestion> {
+    match &query.kind {
+        MetadataCompletionKind::Tag => rank_counted(tag_counts(cache), &query.query)
+            .into_iter()
+            .map(|(tag, count)| Suggestion {
+                label: format!("#{tag}"),
+                detail: notes_label(count),
+                insert: format!("#{tag}"),
+            })
+            .collect(),
+        MetadataCompletionKind::PropertyKey { append_colon } => {
+            let keys = cache
+                .properties_index
+                .iter()
+                .map(|(key, index)| (key.clone(), index.notes))
+                .collect();
+            rank_counted(keys, &query.query)
+                .into_iter()
+                .map(|(key, count)| Suggestion {
+                    insert: if *append_colon { format!("{key}: ") } else { key.clone() },
+                    label: key,
+                    detail: notes_label(count),
+                })
+                .collect()
+        }
+        MetadataCompletionKind::PropertyValue { key } => {
+            let mut values = cache
+                .properties_index
+                .get(key)
+                .map(|index| index.values.clone())
+                .unwrap_or_default();
+            if matches!(key.as_str(), "tags" | "tag") {
+                for (tag, count) in tag_counts(cache) {
+                    let entry = values.entry(tag).or_insert(0);
+                    *entry = (*entry).max(count);
+                }
+            }
+            rank_counted(values, &query.query)
+                .into_iter()
+                .map(|(value, count)| Suggestion {
+                    label: value.clone(),
+                    detail: notes_label(count),
+                    insert: value,
+                })
+                .collect()
+        }
+    }
+}
+
+/// Replaces the typed tag or property with `suggestion.insert` as one
+/// undoable edit, leaving the caret after it.
+pub fn accept_metadata_transaction(
+    snapshot: &EditorSnapshot,
+    query: &MetadataQuery,
+    suggestion: &Suggestion,
+) -> Option<Transaction> {
+    if query.end > snapshot.text.len() || query.start > query.end {
+        return None;
+    }
+    let caret = query.start + suggestion.insert.len();
+    Some(Transaction::single(
+        TextChange::new(query.start, query.end, suggestion.insert.clone()),
+        Some(Selection::cursor(caret)),
+        ChangeOrigin::Command,
+        "metadata-completion",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown_syntax::build_metadata_cache;
+
+    fn query(text: &str) -> Option<MetadataQuery> {
+        let caret = text.find('|').unwrap();
+        let text = text.replace('|', "");
+        metadata_query_at(&text, caret)
+    }
+
+    #[test]
+    fn tag_queries_skip_code_headings_and_words() {
+        let q = query("Body #pro| here").unwrap();
+        assert_eq!((q.start, q.end, q.query.as_str()), (5, 9, "pro"));
+        assert_eq!(q.kind, MetadataCompletionKind::Tag);
+        assert_eq!(query("a #|").unwrap().query, "");
+        assert_eq!(query("a #pr|oj b").unwrap().end, 7);
+
+        assert!(query("#|").is_none());
+        assert!(query("## Head|").is_none());
+        assert!(query("C#|").is_none());
+        assert!(query("a #1|").is_none());
+        assert!(query("a `#ta|g`").is_none());
+        assert!(query("```\n#ta|\n```").is_none());
+    }
+
+    #[test]
+    fn property_queries_cover_keys_values_and_lists() {
+        let q = query("---\nsta|\n---\n").unwrap();
+        assert_eq!(q.kind, MetadataCompletionKind::PropertyKey { append_colon: true });
+        assert_eq!((q.start, q.end), (4, 7));
+
+        let q = query("---\nst|atus: x\n---\n").unwrap();
+        assert_eq!(q.kind, MetadataCompletionKind::PropertyKey { append_colon: false });
+        assert_eq!(q.end, 10);
+
+        let q = query("---\nstatus: dr|\n---\n").unwrap();
+        assert_eq!(q.kind, MetadataCompletionKind::PropertyValue { key: "status".to_string() });
+        assert_eq!((q.start, q.query.as_str()), (12, "dr"));
+
+        let q = query("---\ntags: [one, tw|]\n---\n").unwrap();
+        assert_eq!((q.query.as_str(), q.end - q.start), ("tw", 2));
+
+        let q = query("---\ntags:\n  - one\n  - tw|\n---\n").unwrap();
+        assert_eq!(q.kind, MetadataCompletionKind::PropertyValue { key: "tags".to_string() });
+        assert_eq!(q.query, "tw");
+
+        assert!(query("---\nstatus: |\n---\n").is_none());
+        assert!(query("---\ntags:\n  - |\n---\n").is_none());
+        assert!(query("---\ntitle: x\n---\nstat|").is_none());
+    }
+
+    #[test]
+    fn suggestions_use_tag_and_property_indexes() {
+        let notes = HashMap::from([
+            ("a.md".to_string(), "---\nstatus: draft\n---\n#project/alpha".to_string()),
+            ("b.md".to_string(), "---\nstatus: done\n---\n#project/beta #misc".to_string()),
+        ]);
+        let files = vec!["a.md".to_string(), "b.md".to_string()];
+        let cache = build_metadata_cache(&notes, &files);
+
+        let tags = metadata_suggestions(&query("x #proj|").unwrap(), &cache);
+        assert_eq!(tags[0].insert, "#project");
+        assert_eq!(tags[0].detail, "2 notes");
+        assert!(tags.iter().any(|s| s.insert == "#project/beta"));
+
+        let keys = metadata_suggestions(&query("---\nst|\n---\n").unwrap(), &cache);
+        assert_eq!(keys[0].insert, "status: ");
+
+        let values = metadata_suggestions(&query("---\nstatus: d|\n---\n").unwrap(), &cache);
+        let inserts: Vec<&str> = values.iter().map(|s| s.insert.as_str()).collect();
+        assert_eq!(inserts, vec!["done", "draft"]);
+
+        let tag_values = metadata_suggestions(&query("---\ntags: [mi|]\n---\n").unwrap(), &cache);
+        assert_eq!(tag_values[0].insert, "misc");
+    }
+
+    #[test]
+    fn accepting_replaces_the_typed_tag() {
+        let mut snapshot = EditorSnapshot::new("x #pro y".to_string());
+        let q = metadata_query_at(&snapshot.text, 6).unwrap();
+        let suggestion = Suggestion {
+            label: String::new(),
+            detail: String::new(),
+            insert: "#project".to_string(),
+        };
+        let transaction = accept_metadata_transaction(&snapshot, &q, &suggestion).unwrap();
+        snapshot.apply_transaction(transaction).unwrap();
+        assert_eq!(snapshot.text, "x #project y");
+        assert_eq!(snapshot.selection(), Selection::cursor(10));
+    }
+}
@@ -44,6 +44,26 @@ pub fn normalize_rel_path(path: &str) -> String {
     path.trim().replace('\\', "/").trim_matches('/').to_string()
 }
 
+/// Where `path` ends up when the folder `old_dir` is moved to `new_dir`;
+/// `None` when it is not inside `old_dir`.
+pub fn reparent_path(path: &str, old_dir: &str, new_dir: &str) -> Option<String> {
+    path.strip_prefix(old_dir)
+        .and_then(|rest| rest.strip_prefix('/'))
+        .map(|rest| format!("{new_dir}/{rest}"))
+}
+
+/// Where `path` ends up after the `(from, to)` moves, when one of them moved
+/// it or a folder containing it.
+pub fn moved_path(path: &str, moves: &[(String, String)]) -> Option<String> {
+    moves.iter().find_map(|(from, to)| {
+        if path == from {
+            Some(to.clone())
+        } else {
+            reparent_path(path, from, to)
+        }
+    })
+}
+
 pub fn vault_display_name(path: &str) -> String {
     let normalized = normalize_slashes(path.trim().trim_end_matches('/'));
     normalized
@@ -0,0 +1,321 @@
+use crate::fuzzy::fuzzy_score;
+use leptos::html;
+use leptos::prelude::*;
+use std::collections::HashMap;
+
+/// Rows shown at once; the vault can hold far more notes than fit on screen.
+pub const QUICK_SWITCHER_LIMIT: usize = 50;
+
+/// Recently opened notes within this many entries get a ranking boost.
+const RECENT_BOOST_WINDOW: usize = 10;
+const RECENT_BOOST_STEP: i32 = 60;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NoteMatch {
+    pub path: String,
+    /// Set when the note matched through one of its aliases rather than its
+    /// name or path.
+    pub alias: Option<String>,
+}
+
+fn strip_md(path: &str) -> &str {
+    if path.to_ascii_lowercase().ends_with(".md") {
+        &path[..path.len() - 3]
+    } else {
+        path
+    }
+}
+
+fn recency_boost(path: &str, recent: &[String]) -> i32 {
+    recent
+        .iter()
+        .take(RECENT_BOOST_WINDOW)
+        .position(|p| p == path)
+        .map(|rank| (RECENT_BOOST_WINDOW - rank) as i32 * RECENT_BOOST_STEP)
+        .unwrap_or(0)
+}
+
+/// Ranks notes for the quick switcher. The query is matched as a subsequence
+/// of the file name, the full path (weighted lower) and each alias; notes
+/// from `recent` (most recent first) are boosted. An empty query lists recent
+/// notes first, then the rest in vault order.
+pub fn rank_notes(
+    query: &str,
+    files: &[String],
+    aliases: &HashMap<String, Vec<String>>,
+    recent: &[String],
+) -> Vec<NoteMatch> {
+    let query = query.trim();
+    if query.is_empty() {
+        let mut out: Vec<NoteMatch> = recent
+            .iter()
+            .filter(|path| files.contains(path))
+            .map(|path| NoteMatch {
+                path: path.clone(),
+                alias: None,
+            })
+            .collect();
+        out.extend(
+            files
+                .iter()
+                .filter(|path| !recent.contains(path))
+                .map(|path| NoteMatch {
+                    path: path.clone(),
+                    alias: None,
+                }),
+        );
+        out.truncate(QUICK_SWITCHER_LIMIT);
+        return out;
+    }
+
+    let mut scored: Vec<(i32, NoteMatch)> = files
+        .iter()
+        .filter_map(|path| {
+            let stem_path = strip_md(path);
+            let name = stem_path.rsplit('/').next().unwrap_or(stem_path);
+            let mut best = fuzzy_score(name, query);
+            if let Some(score) = fuzzy_score(stem_path, query).map(|s| s / 2) {
+                best = Some(best.map_or(score, |b| b.max(score)));
+            }
+            let mut alias = None;
+            for candidate in aliases.get(path).into_iter().flatten() {
+                if let Some(score) = fuzzy_score(candidate, query) {
+                    if best.is_none_or(|b| score > b) {
+                        best = Some(score);
+                        alias = Some(candidate.clone());
+                    }
+                }
+            }
+            best.map(|score| {
+                (
+                    score + recency_boost(path, recent),
+                    NoteMatch {
+                        path: path.clone(),
+                        alias,
+                    },
+                )
+            })
+        })
+        .collect();
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.path.cmp(&b.1.path)));
+    scored
+        .into_iter()
+        .take(QUICK_SWITCHER_LIMIT)
+        .map(|(_, m)| m)
+        .collect()
+}
+
+/// Ctrl+O overlay for jumping to a note by name. Enter opens the selected
+/// note, Ctrl/Cmd+Enter opens it in the split pane and Shift+Enter creates a
+/// note named after the query.
+#[component]
+pub fn QuickSwitcher<FOpen, FSplit, FCreate, FClose>(
+    open: ReadSignal<bool>,
+    files: ReadSignal<Vec<String>>,
+    aliases: Signal<HashMap<String, Vec<String>>>,
+    recent: Signal<Vec<String>>,
+    on_open: FOpen,
+    on_open_split: FSplit,
+    on_create: FCreate,
+    on_close: FClose,
+) -> impl IntoView
+where
+    FOpen: Fn(String) + 'static + Clone + Send,
+    FSplit: Fn(String) + 'static + Clone + Send,
+    FCreate: Fn(String) + 'static + Clone + Send,
+    FClose: Fn() + 'static + Clone + Send,
+{
+    let (query, set_query) = signal(String::new());
+    let (selected, set_selected) = signal(0usize);
+    let input_ref = NodeRef::<html::Input>::new();
+
+    let entries = Memo::new(move |_| {
+        rank_notes(&query.get(), &files.get(), &aliases.get(), &recent.get())
+    });
+
+    Effect::new(move |_| {
+        if open.get() {
+            set_query.set(String::new());
+            set_selected.set(0);
+            request_animation_frame(move || {
+                if let Some(input) = input_ref.get() {
+                    let _ = input.focus();
+                }
+            });
+        }
+    });
+
+    let open_key = on_open.clone();
+    let close_key = on_close.clone();
+    let close_backdrop = on_close;
+
+    let on_keydown = move |ev: leptos::ev::KeyboardEvent| {
+        let count = entries.with_untracked(Vec::len);
+        match ev.key().as_str() {
+            "ArrowDown" if count > 0 => {
+                ev.prevent_default();
+                set_selected.update(|i| *i = (*i + 1) % count);
+            }
+            "ArrowUp" if count > 0 => {
+                ev.prevent_default();
+                set_selected.update(|i| *i = (*i + count - 1) % count);
+            }
+            "Enter" => {
+                ev.prevent_default();
+                let typed = query.get_untracked().trim().to_string();
+                let chosen = entries
+                    .with_untracked(|list| list.get(selected.get_untracked()).map(|m| m.path.clone()));
+                if ev.shift_key() || (chosen.is_none() && !typed.is_empty()) {
+                    if !typed.is_empty() {
+                        on_create(typed);
+                    }
+                } else if let Some(path) = chosen {
+                    if ev.ctrl_key() || ev.meta_key() {
+                        on_open_split(path);
+                    } else {
+                        open_key(path);
+                    }
+                }
+            }
+            "Escape" => {
+                ev.prevent_default();
+                close_key();
+            }
+            _ => {}
+        }
+    };
+
+    view! {
+        <div
+            class="quick-switcher-backdrop"
+            style=move || format!(
+                "display: {}; position: fixed; inset: 0; z-index: 1100; background: rgba(0,0,0,0.2); justify-content: center; align-items: flex-start; padding-top: 12vh;",
+                if open.get() { "flex" } else { "none" }
+            )
+            on:click=move |_| close_backdrop()
+        >
+            <div
+                style="width: min(36rem, 90vw); max-height: 60vh; display: flex; flex-direction: column; background: var(--bg-secondary); border: 1px solid var(--border-color); border-radius: var(--radius-lg); box-shadow: 0 12px 32px rgba(0,0,0,0.25); overflow: hidden;"
+                on:click=|ev| ev.stop_propagation()
+            >
+                <input
+                    node_ref=input_ref
+                    type="text"
+                    placeholder="Find or create a note"
+                    style="padding: 0.7rem 0.9rem; font-size: 0.95rem; border: none; border-bottom: 1px solid var(--border-color); background: var(--bg-primary); color: var(--text-primary); outline: none;"
+                    prop:value=move || query.get()
+                    on:input=move |ev| {
+                        set_query.set(event_target_value(&ev));
+                        set_selected.set(0);
+                    }
+                    on:keydown=on_keydown
+                />
+                <div style="overflow-y: auto; padding: 0.25rem 0;">
+                    {move || {
+                        let current = selected.get();
+                        let list = entries.get();
+                        if list.is_empty() {
+                            let typed = query.get();
+                            let message = if typed.trim().is_empty() {
+                                "No notes in this vault".to_string()
+                            } else {
+                                format!("No matching notes. Enter creates \u{201c}{}\u{201d}", typed.trim())
+                            };
+                            return view! {
+                                <div style="padding: 0.6rem 0.9rem; font-size: 0.85rem; color: var(--text-muted);">
+                                    {message}
+                                </div>
+                            }
+                            .into_any();
+                        }
+                        list.into_iter()
+                            .enumerate()
+                            .map(|(index, entry)| {
+                                let stem = strip_md(&entry.path).to_string();
+                                let (folder, name) = match stem.rsplit_once('/') {
+                                    Some((folder, name)) => (folder.to_string(), name.to_string()),
+                                    None => (String::new(), stem.clone()),
+                                };
+                                let title = entry.alias.clone().unwrap_or(name);
+                                let detail = if entry.alias.is_some() { stem } else { folder };
+                                let path = entry.path.clone();
+                                let open_row = on_open.clone();
+                                let style = format!(
+                                    "display: flex; align-items: baseline; gap: 0.75rem; padding: 0.45rem 0.9rem; font-size: 0.85rem; cursor: pointer; color: var(--text-primary); {}",
+                                    if index == current { "background: color-mix(in srgb, var(--accent-color) 15%, transparent);" } else { "" }
+                                );
+                                view! {
+                                    <div
+                                        style=style
+                                        title=entry.path.clone()
+                                        on:mouseenter=move |_| set_selected.set(index)
+                                        on:click=move |_| open_row(path.clone())
+                                    >
+                                        <span style="flex: 1; overflow: hidden; text-overflow: ellipsis; white-space: nowrap;">{title}</span>
+                                        <span style="font-size: 0.75rem; color: var(--text-muted); overflow: hidden; text-overflow: ellipsis; white-space: nowrap; max-width: 50%;">{detail}</span>
+                                    </div>
+                                }
+                            })
+                            .collect::<Vec<_>>()
+                            .into_any()
+                    }}
+                </div>
+                <div style="display: flex; gap: 1rem; padding: 0.4rem 0.9rem; border-top: 1px solid var(--border-color); font-size: 0.7rem; color: var(--text-muted);">
+                    <span>"Enter to open"</span>
+                    <span>"Ctrl+Enter to open in split"</span>
+                    <span>"Shift+Enter to create"</span>
+                </div>
+            </div>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(matches: &[NoteMatch]) -> Vec<&str> {
+        matches.iter().map(|m| m.path.as_str()).collect()
+    }
+
+    #[test]
+    fn ranks_name_matches_above_path_matches_and_reports_aliases() {
+        let files = vec![
+            "projects/Roadmap.md".to_string(),
+            "Reading list.md".to_string(),
+            "daily/2024-01-01.md".to_string(),
+        ];
+        let mut aliases = HashMap::new();
+        aliases.insert("daily/2024-01-01.md".to_string(), vec!["New Year".to_string()]);
+
+        let ranked = rank_notes("road", &files, &aliases, &[]);
+        assert_eq!(paths(&ranked), vec!["projects/Roadmap.md"]);
+
+        let ranked = rank_notes("proj", &files, &aliases, &[]);
+        assert_eq!(paths(&ranked), vec!["projects/Roadmap.md"]);
+
+        let ranked = rank_notes("new year", &files, &aliases, &[]);
+        assert_eq!(ranked[0].path, "daily/2024-01-01.md");
+        assert_eq!(ranked[0].alias.as_deref(), Some("New Year"));
+
+        assert!(rank_notes("zzz", &files, &aliases, &[]).is_empty());
+    }
+
+    #[test]
+    fn recent_notes_lead_empty_queries_and_break_close_scores() {
+        let files = vec!["Alpha.md".to_string(), "Alps.md".to_string(), "Beta.md".to_string()];
+        let recent = vec!["Beta.md".to_string(), "Gone.md".to_string()];
+        let empty = rank_notes("", &files, &HashMap::new(), &recent);
+        assert_eq!(paths(&empty), vec!["Beta.md", "Alpha.md", "Alps.md"]);
+
+        assert_eq!(
+            paths(&rank_notes("al", &files, &HashMap::new(), &[])),
+            vec!["Alps.md", "Alpha.md"]
+        );
+        let recent = vec!["Alpha.md".to_string()];
+        assert_eq!(
+            paths(&rank_notes("al", &files, &HashMap::new(), &recent)),
+            vec!["Alpha.md", "Alps.md"]
+        );
+    }
+}
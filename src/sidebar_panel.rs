@@ -1,52 +1,29 @@
-use crate::app_state::RecentNoteEntry;
+use crate::app_state::{RecentNoteEntry, TrashEntry};
+use crate::attachments_pane::AttachmentsPane;
+use crate::fuzzy::fuzzy_token_match;
 use crate::sidebar_tree::{
-    add_empty_dirs_to_tree, build_file_tree, build_sidebar_entries, SidebarContextMenu,
-    SidebarEntry,
+    add_empty_dirs_to_tree, build_file_tree, build_sidebar_entries, can_drop_into, drag_roots,
+    parent_folder, visible_range, SidebarContextMenu, SidebarEntry,
 };
+use crate::trash_pane::TrashPane;
 use crate::vault_tabs::VaultTabs;
 use js_sys::Date;
 use leptos::prelude::*;
 use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 use wasm_bindgen::JsValue;
 
-fn fuzzy_token_match(haystack: &str, token: &str) -> (bool, bool) {
-    if token.is_empty() {
-        return (true, false);
+/// Short badge and colour for a git status reported by the backend.
+fn git_status_badge(status: &str) -> (&'static str, &'static str) {
+    match status {
+        "added" => ("A", "#16a34a"),
+        "untracked" => ("U", "#16a34a"),
+        "deleted" => ("D", "#dc2626"),
+        "conflicted" => ("!", "#dc2626"),
+        "renamed" => ("R", "#2563eb"),
+        _ => ("M", "#d97706"),
     }
-    if haystack.contains(token) {
-        return (true, false);
-    }
-
-    let mut boundaries = Vec::new();
-    boundaries.push(0usize);
-    for (idx, _) in token.char_indices() {
-        if idx != 0 {
-            boundaries.push(idx);
-        }
-    }
-    if boundaries.len() < 4 {
-        return (false, false);
-    }
-    boundaries.push(token.len());
-
-    let last = boundaries.len() - 1;
-    for i in 0..last {
-        let start_a = boundaries[0];
-        let end_a = boundaries[i];
-        let start_b = boundaries[i + 1];
-        let end_b = boundaries[last];
-
-        let mut shortened = String::new();
-        shortened.push_str(&token[start_a..end_a]);
-        shortened.push_str(&token[start_b..end_b]);
-
-        if haystack.contains(&shortened) {
-            return (true, true);
-        }
-    }
-
-    (false, false)
 }
 
 #[component]
@@ -60,8 +37,14 @@ pub fn SidebarPanel<
     FSelect,
     FCreateNoteInFolder,
     FCreateFolderInFolder,
+    FRenameFolder,
     FDeleteFolder,
     FDeleteNote,
+    FRestoreTrash,
+    FEmptyTrash,
+    FRenameAttachment,
+    FTrashAttachments,
+    FMoveItems,
 >(
     vault_path: ReadSignal<String>,
     open_vaults: ReadSignal<Vec<String>>,
@@ -80,6 +63,11 @@ pub fn SidebarPanel<
     note_texts_lower: ReadSignal<HashMap<String, String>>,
     search_query: ReadSignal<String>,
     set_search_query: WriteSignal<String>,
+    trash_entries: ReadSignal<Vec<TrashEntry>>,
+    trash_retention_days: Signal<u32>,
+    attachments: ReadSignal<Vec<String>>,
+    orphaned_attachments: Memo<Vec<String>>,
+    git_statuses: ReadSignal<HashMap<String, String>>,
     on_open_vault: FOpen,
     on_close_vault: FClose,
     on_new_note: FNew,
@@ -89,8 +77,14 @@ pub fn SidebarPanel<
     on_select_file: FSelect,
     create_note_in_folder: FCreateNoteInFolder,
     create_folder_in_folder: FCreateFolderInFolder,
+    rename_folder: FRenameFolder,
     delete_folder: FDeleteFolder,
     delete_note: FDeleteNote,
+    on_restore_trash: FRestoreTrash,
+    on_empty_trash: FEmptyTrash,
+    on_rename_attachment: FRenameAttachment,
+    on_trash_attachments: FTrashAttachments,
+    on_move_items: FMoveItems,
 ) -> impl IntoView
 where
     FOpen: Fn() + 'static + Clone,
@@ -102,8 +96,14 @@ where
     FSelect: Fn(String) + 'static + Clone + Send,
     FCreateNoteInFolder: Fn(String) + 'static + Clone + Send,
     FCreateFolderInFolder: Fn(String) + 'static + Clone + Send,
+    FRenameFolder: Fn(String) + 'static + Clone + Send,
     FDeleteFolder: Fn(String) + 'static + Clone + Send,
     FDeleteNote: Fn(String) + 'static + Clone + Send,
+    FRestoreTrash: Fn(String) + 'static + Clone + Send,
+    FEmptyTrash: Fn() + 'static + Clone + Send,
+    FRenameAttachment: Fn(String) + 'static + Clone + Send,
+    FTrashAttachments: Fn(Vec<String>) + 'static + Clone + Send,
+    FMoveItems: Fn(Vec<String>, String) + 'static + Clone + Send,
 {
     let open_vaults_signal = open_vaults;
     let vault_path_signal = vault_path;
@@ -123,6 +123,86 @@ where
     let search_query_signal = search_query;
     let set_search_query_signal = set_search_query;
 
+    // Shift/Ctrl-click selection in the file tree and the drag in progress;
+    // `drop_target` is the highlighted folder, "" for the vault root.
+    let (selected_paths, set_selected_paths) = signal(Vec::<String>::new());
+    let (selection_anchor, set_selection_anchor) = signal(None::<String>);
+    let (dragging, set_dragging) = signal(Vec::<String>::new());
+    let (drop_target, set_drop_target) = signal(None::<String>);
+
+    // Applies a click to the selection. Returns true when a modifier made it
+    // a selection click, so the row should not open or toggle.
+    let select_on_click = move |ev: &leptos::ev::MouseEvent, path: &str, rows: &[String]| {
+        if ev.shift_key() {
+            let anchor = selection_anchor.get_untracked().unwrap_or_else(|| path.to_string());
+            set_selected_paths.set(visible_range(rows, &anchor, path));
+            set_selection_anchor.set(Some(anchor));
+            return true;
+        }
+        set_selection_anchor.set(Some(path.to_string()));
+        if ev.ctrl_key() || ev.meta_key() {
+            set_selected_paths.update(|selected| {
+                match selected.iter().position(|p| p == path) {
+                    Some(index) => {
+                        selected.remove(index);
+                    }
+                    None => selected.push(path.to_string()),
+                }
+            });
+            return true;
+        }
+        set_selected_paths.set(Vec::new());
+        false
+    };
+    let is_selected =
+        move |path: &str| selected_paths.with(|selected| selected.iter().any(|p| p == path));
+
+    // Dragging a selected row drags the whole selection.
+    let start_drag = move |ev: &leptos::ev::DragEvent, path: &str| {
+        let selected = selected_paths.get_untracked();
+        let paths = if selected.iter().any(|p| p == path) {
+            drag_roots(&selected)
+        } else {
+            vec![path.to_string()]
+        };
+        if let Some(data) = ev.data_transfer() {
+            data.set_effect_allowed("move");
+            let _ = data.set_data("text/plain", &paths.join("\n"));
+        }
+        set_dragging.set(paths);
+    };
+    let end_drag = move || {
+        set_dragging.set(Vec::new());
+        set_drop_target.set(None);
+    };
+    let drag_over = move |ev: &leptos::ev::DragEvent, folder: &str| {
+        ev.stop_propagation();
+        if !can_drop_into(&dragging.get_untracked(), folder) {
+            set_drop_target.set(None);
+            return;
+        }
+        ev.prevent_default();
+        if let Some(data) = ev.data_transfer() {
+            data.set_drop_effect("move");
+        }
+        if drop_target.get_untracked().as_deref() != Some(folder) {
+            set_drop_target.set(Some(folder.to_string()));
+        }
+    };
+    // The paths to move when the drag is released over `folder`.
+    let take_drop = move |ev: &leptos::ev::DragEvent, folder: &str| {
+        ev.prevent_default();
+        ev.stop_propagation();
+        let paths = dragging.get_untracked();
+        end_drag();
+        if !can_drop_into(&paths, folder) {
+            return None;
+        }
+        set_selected_paths.set(Vec::new());
+        Some(paths)
+    };
+    let root_move = on_move_items.clone();
+
     view! {
         <nav class="sidebar" style="width: var(--sidebar-width); border-right: 1px solid var(--border-color); display: flex; flex-direction: column; background: var(--bg-secondary); transition: all 0.3s ease;">
             <VaultTabs
@@ -166,8 +246,44 @@ where
                 >
                     "Recent notes"
                 </button>
+                <button
+                    style=move || format!(
+                        "flex: 1; padding: 0.4rem 0.5rem; font-size: 0.8rem; border: none; border-radius: 0; background: transparent; color: {}; border-bottom: 2px solid {};",
+                        if sidebar_tab_signal.get() == "attachments" { "var(--accent-color)" } else { "var(--text-muted)" },
+                        if sidebar_tab_signal.get() == "attachments" { "var(--accent-color)" } else { "transparent" }
+                    )
+                    on:click=move |_| set_sidebar_tab_signal.set("attachments".to_string())
+                >
+                    "Attachments"
+                </button>
+                <button
+                    style=move || format!(
+                        "flex: 1; padding: 0.4rem 0.5rem; font-size: 0.8rem; border: none; border-radius: 0; background: transparent; color: {}; border-bottom: 2px solid {};",
+                        if sidebar_tab_signal.get() == "trash" { "var(--accent-color)" } else { "var(--text-muted)" },
+                        if sidebar_tab_signal.get() == "trash" { "var(--accent-color)" } else { "transparent" }
+                    )
+                    on:click=move |_| set_sidebar_tab_signal.set("trash".to_string())
+                >
+                    "Trash"
+                </button>
             </div>
-            <div class="file-list" style="flex: 1; overflow-y: auto; padding: 0.75rem 0.5rem;">
+            <div
+                class="file-list"
+                style=move || format!(
+                    "flex: 1; overflow-y: auto; padding: 0.75rem 0.5rem; {}",
+                    if drop_target.get().as_deref() == Some("") { "box-shadow: inset 0 0 0 2px var(--accent-color);" } else { "" }
+                )
+                on:dragover=move |ev: leptos::ev::DragEvent| drag_over(&ev, "")
+                on:dragleave=move |_| set_drop_target.set(None)
+                on:drop={
+                    let root_move = root_move.clone();
+                    move |ev: leptos::ev::DragEvent| {
+                        if let Some(paths) = take_drop(&ev, "") {
+                            root_move(paths, String::new());
+                        }
+                    }
+                }
+            >
                 {move || {
                     let tab = sidebar_tab_signal.get();
                     if tab == "recent" {
@@ -444,6 +560,26 @@ where
                             </div>
                         }
                         .into_any();
+                    } else if tab == "trash" {
+                        return view! {
+                            <TrashPane
+                                trash_entries=trash_entries
+                                retention_days=trash_retention_days
+                                on_restore=on_restore_trash.clone()
+                                on_empty=on_empty_trash.clone()
+                            />
+                        }
+                        .into_any();
+                    } else if tab == "attachments" {
+                        return view! {
+                            <AttachmentsPane
+                                attachments=attachments
+                                orphans=orphaned_attachments
+                                on_rename=on_rename_attachment.clone()
+                                on_trash_orphans=on_trash_attachments.clone()
+                            />
+                        }
+                        .into_any();
                     }
 
                     let files_in_vault = files_signal.get();
@@ -464,8 +600,12 @@ where
                     let mut tree = build_file_tree(&files_in_vault);
                     add_empty_dirs_to_tree(&mut tree, &empty_dirs_signal.get());
                     let rows = build_sidebar_entries(&tree, &expanded_folders_read.get());
+                    let statuses = git_statuses.get();
 
                     let select_handler = on_select_file.clone();
+                    let move_handler = on_move_items.clone();
+                    let row_paths: Arc<Vec<String>> =
+                        Arc::new(rows.iter().map(|row| row.path().to_string()).collect());
                     let set_expanded = set_expanded_folders_signal;
                     let set_context_menu = set_sidebar_context_menu_signal;
                     let current_file_for_rows = current_file_signal;
@@ -478,6 +618,12 @@ where
                                         SidebarEntry::Folder { path, name, depth, note_count, expanded } => {
                                             let toggle_path = path.clone();
                                             let context_path = path.clone();
+                                            let drag_path = path.clone();
+                                            let target_path = path.clone();
+                                            let drop_path = path.clone();
+                                            let highlight_path = path.clone();
+                                            let folder_rows = row_paths.clone();
+                                            let folder_move = move_handler.clone();
                                             let indent = 0.45 + (depth as f32 * 0.95);
                                             let chevron = if expanded { "▾" } else { "▸" };
                                             let row_bg = if expanded {
@@ -489,10 +635,21 @@ where
                                             view! {
                                                 <div
                                                     class="folder-item"
-                                                    style=format!(
-                                                        "display: flex; align-items: center; gap: 0.4rem; padding: 0.34rem 0.5rem 0.34rem {indent}rem; cursor: pointer; border-radius: var(--radius-md); margin-bottom: 2px; font-size: 0.82rem; color: var(--text-secondary); transition: background 0.15s ease; {row_bg}"
+                                                    draggable="true"
+                                                    style=move || format!(
+                                                        "display: flex; align-items: center; gap: 0.4rem; padding: 0.34rem 0.5rem 0.34rem {indent}rem; cursor: pointer; border-radius: var(--radius-md); margin-bottom: 2px; font-size: 0.82rem; color: var(--text-secondary); transition: background 0.15s ease; {row_bg} {}",
+                                                        if drop_target.get().as_deref() == Some(highlight_path.as_str()) {
+                                                            "background: color-mix(in srgb, var(--accent-color) 24%, transparent); box-shadow: inset 0 0 0 1px var(--accent-color);"
+                                                        } else if is_selected(&highlight_path) {
+                                                            "background: color-mix(in srgb, var(--accent-color) 18%, transparent);"
+                                                        } else {
+                                                            ""
+                                                        }
                                                     )
-                                                    on:click=move |_| {
+                                                    on:click=move |ev: leptos::ev::MouseEvent| {
+                                                        if select_on_click(&ev, &toggle_path, &folder_rows) {
+                                                            return;
+                                                        }
                                                         set_expanded.update(|expanded_set| {
                                                             if expanded_set.contains(&toggle_path) {
                                                                 expanded_set.remove(&toggle_path);
@@ -505,6 +662,14 @@ where
                                                         ev.prevent_default();
                                                         set_context_menu.set(Some(SidebarContextMenu::Folder { path: context_path.clone(), x: ev.client_x() as f64, y: ev.client_y() as f64 }));
                                                     }
+                                                    on:dragstart=move |ev: leptos::ev::DragEvent| start_drag(&ev, &drag_path)
+                                                    on:dragend=move |_| end_drag()
+                                                    on:dragover=move |ev: leptos::ev::DragEvent| drag_over(&ev, &target_path)
+                                                    on:drop=move |ev: leptos::ev::DragEvent| {
+                                                        if let Some(paths) = take_drop(&ev, &drop_path) {
+                                                            folder_move(paths, drop_path.clone());
+                                                        }
+                                                    }
                                                     title=path
                                                 >
                                                     <span style="width: 0.8rem; text-align: center; color: var(--text-muted);">
@@ -513,6 +678,12 @@ where
                                                     <span style="overflow: hidden; text-overflow: ellipsis; white-space: nowrap;">
                                                         {name}
                                                     </span>
+                                                    {
+                                                        let prefix = format!("{path}/");
+                                                        statuses.keys().any(|p| p.starts_with(&prefix)).then(|| view! {
+                                                            <span style="width: 0.4rem; height: 0.4rem; border-radius: 50%; background: #d97706; flex-shrink: 0;" title="Contains uncommitted changes"></span>
+                                                        })
+                                                    }
                                                     <span style="margin-left: auto; font-size: 0.72rem; color: var(--text-muted);">
                                                         {note_count.to_string()}
                                                     </span>
@@ -524,26 +695,64 @@ where
                                             let filename = path.clone();
                                             let active_path = path.clone();
                                             let context_file_path = path.clone();
+                                            let click_path = path.clone();
+                                            let drag_path = path.clone();
+                                            let selected_path = path.clone();
+                                            let folder = parent_folder(&path).to_string();
+                                            let drop_folder = folder.clone();
+                                            let file_rows = row_paths.clone();
+                                            let file_move = move_handler.clone();
                                             let is_active = move || current_file_for_rows.get() == active_path;
                                             let indent = 1.5 + (depth as f32 * 0.95);
                                             let row_select = select_handler.clone();
                                             let set_context_menu_row = set_context_menu.clone();
+                                            let git_badge = statuses.get(&path).map(|status| {
+                                                let (letter, color) = git_status_badge(status);
+                                                view! {
+                                                    <span style=format!("margin-left: auto; font-size: 0.7rem; font-weight: 600; color: {color};") title=status.clone()>
+                                                        {letter}
+                                                    </span>
+                                                }
+                                            });
 
                                             view! {
                                                 <div
                                                     class="file-item"
+                                                    draggable="true"
                                                     style=move || format!(
-                                                        "padding: 0.38rem 0.65rem 0.38rem {indent}rem; cursor: pointer; border-radius: var(--radius-md); margin-bottom: 2px; font-size: 0.84rem; transition: background 0.2s, color 0.2s; {}",
-                                                        if is_active() { "background: var(--accent-color); color: white;" } else { "color: var(--text-secondary);" }
+                                                        "display: flex; align-items: center; gap: 0.4rem; padding: 0.38rem 0.65rem 0.38rem {indent}rem; cursor: pointer; border-radius: var(--radius-md); margin-bottom: 2px; font-size: 0.84rem; transition: background 0.2s, color 0.2s; {}",
+                                                        if is_active() {
+                                                            "background: var(--accent-color); color: white;"
+                                                        } else if is_selected(&selected_path) {
+                                                            "background: color-mix(in srgb, var(--accent-color) 18%, transparent); color: var(--text-primary);"
+                                                        } else {
+                                                            "color: var(--text-secondary);"
+                                                        }
                                                     )
-                                                    on:click=move |_| row_select(filename.clone())
+                                                    on:click=move |ev: leptos::ev::MouseEvent| {
+                                                        if !select_on_click(&ev, &click_path, &file_rows) {
+                                                            row_select(filename.clone());
+                                                        }
+                                                    }
                                                     on:contextmenu=move |ev: leptos::ev::MouseEvent| {
                                                         ev.prevent_default();
                                                         set_context_menu_row.set(Some(SidebarContextMenu::File { path: context_file_path.clone(), x: ev.client_x() as f64, y: ev.client_y() as f64 }));
                                                     }
-                                                    title=path
+                                                    // Dropping onto a note drops into the note's folder.
+                                                    on:dragstart=move |ev: leptos::ev::DragEvent| start_drag(&ev, &drag_path)
+                                                    on:dragend=move |_| end_drag()
+                                                    on:dragover=move |ev: leptos::ev::DragEvent| drag_over(&ev, &folder)
+                                                    on:drop=move |ev: leptos::ev::DragEvent| {
+                                                        if let Some(paths) = take_drop(&ev, &drop_folder) {
+                                                            file_move(paths, drop_folder.clone());
+                                                        }
+                                                    }
+                                                    title=path.clone()
                                                 >
-                                                    {name}
+                                                    <span style="overflow: hidden; text-overflow: ellipsis; white-space: nowrap;">
+                                                        {name}
+                                                    </span>
+                                                    {git_badge}
                                                 </div>
                                             }
                                             .into_any()
@@ -561,10 +770,12 @@ where
             Some(SidebarContextMenu::Folder { path, x, y }) => {
                 let path_for_note = path.clone();
                 let path_for_folder = path.clone();
+                let path_for_rename = path.clone();
                 let path_for_delete = path.clone();
                 let close_menu = set_sidebar_context_menu_signal;
                 let create_note = create_note_in_folder.clone();
                 let create_folder = create_folder_in_folder.clone();
+                let rename_folder_cb = rename_folder.clone();
                 let delete_folder_cb = delete_folder.clone();
                 view! {
                     <div
@@ -587,6 +798,12 @@ where
                             >
                                 "New folder"
                             </button>
+                            <button
+                                style="display: block; width: 100%; padding: 0.4rem 0.75rem; text-align: left; font-size: 0.85rem; background: transparent; border: none; cursor: pointer; color: var(--text-primary);"
+                                on:click=move |_| rename_folder_cb(path_for_rename.clone())
+                            >
+                                "Rename or move folder"
+                            </button>
                             <button
                                 style="display: block; width: 100%; padding: 0.4rem 0.75rem; text-align: left; font-size: 0.85rem; background: transparent; border: none; cursor: pointer; color: var(--text-primary);"
                                 on:click=move |_| delete_folder_cb(path_for_delete.clone())
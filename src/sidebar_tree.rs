@@ -230,3 +230,100 @@ pub fn expand_parent_folders(expanded_folders: &mut HashSet<String>, file_path:
     }
 }
 
+impl SidebarEntry {
+    pub fn path(&self) -> &str {
+        match self {
+            SidebarEntry::Folder { path, .. } | SidebarEntry::File { path, .. } => path,
+        }
+    }
+}
+
+/// The folder an entry lives in; empty for the vault root.
+pub fn parent_folder(path: &str) -> &str {
+    path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("")
+}
+
+/// The selected paths that have to move themselves: anything inside another
+/// selected folder travels with it.
+pub fn drag_roots(paths: &[String]) -> Vec<String> {
+    paths
+        .iter()
+        .filter(|path| {
+            !paths
+                .iter()
+                .any(|other| path.starts_with(&format!("{other}/")))
+        })
+        .cloned()
+        .collect()
+}
+
+/// Whether `path` is `folder` itself or somewhere below it.
+pub fn is_within(path: &str, folder: &str) -> bool {
+    path == folder || path.starts_with(&format!("{folder}/"))
+}
+
+/// Whether dropping `dragged` onto `folder` would move anything: a folder
+/// cannot go into itself, and items already in `folder` stay put.
+pub fn can_drop_into(dragged: &[String], folder: &str) -> bool {
+    !dragged.is_empty()
+        && dragged.iter().all(|path| !is_within(folder, path))
+        && dragged.iter().any(|path| parent_folder(path) != folder)
+}
+
+/// Paths of the visible rows from `anchor` to `target`, either way round,
+/// for Shift-click selection. Just `target` when `anchor` is not visible.
+pub fn visible_range(rows: &[String], anchor: &str, target: &str) -> Vec<String> {
+    let find = |path: &str| rows.iter().position(|row| row == path);
+    match (find(anchor), find(target)) {
+        (Some(a), Some(b)) => rows[a.min(b)..=a.max(b)].to_vec(),
+        _ => vec![target.to_string()],
+    }
+}
+
+/// `name` with ` 2`, ` 3`, ... before a `.md` extension, for suggesting a
+/// free name when a moved item collides with an existing one.
+pub fn numbered_name(name: &str, number: usize) -> String {
+    match name.strip_suffix(".md") {
+        Some(stem) => format!("{stem} {number}.md"),
+        None => format!("{name} {number}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(items: &[&str]) -> Vec<String> {
+        items.iter().map(|item| item.to_string()).collect()
+    }
+
+    #[test]
+    fn dragging_skips_nested_selections_and_refuses_useless_drops() {
+        let dragged = drag_roots(&paths(&["projects", "projects/Plan.md", "Inbox.md"]));
+        assert_eq!(dragged, paths(&["projects", "Inbox.md"]));
+
+        assert!(can_drop_into(&dragged, "archive"));
+        assert!(!can_drop_into(&dragged, "projects"));
+        assert!(!can_drop_into(&dragged, "projects/q3"));
+        assert!(can_drop_into(&paths(&["projects/Plan.md"]), ""));
+        assert!(!can_drop_into(&paths(&["projects/Plan.md"]), "projects"));
+        assert!(!can_drop_into(&[], "archive"));
+
+        assert!(is_within("projects", "projects"));
+        assert!(is_within("projects/q3", "projects"));
+        assert!(!is_within("projects-old", "projects"));
+    }
+
+    #[test]
+    fn shift_click_selects_visible_ranges_and_names_get_numbers() {
+        let rows = paths(&["a", "a/One.md", "b", "Two.md"]);
+        assert_eq!(
+            visible_range(&rows, "Two.md", "a/One.md"),
+            paths(&["a/One.md", "b", "Two.md"])
+        );
+        assert_eq!(visible_range(&rows, "gone", "b"), paths(&["b"]));
+
+        assert_eq!(numbered_name("Plan.md", 2), "Plan 2.md");
+        assert_eq!(numbered_name("Ideas", 3), "Ideas 3");
+    }
+}
@@ -0,0 +1,313 @@
+use crate::editor_core::{
+    apply_markdown_command, insert_block_transaction, insert_text_transaction, minimal_text_change,
+    BlockKind, ChangeOrigin, EditorSnapshot, MarkdownCommand, Selection, TextChange, Transaction,
+};
+use crate::fuzzy::fuzzy_score;
+use crate::markdown_syntax::{caret_context, CaretContext};
+use crate::suggestion_popup::Suggestion;
+
+/// Suggestion ids for vault templates are this prefix plus the note path.
+pub const TEMPLATE_ID_PREFIX: &str = "template:";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SlashAction {
+    Block(BlockKind),
+    Date,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SlashItem {
+    pub id: &'static str,
+    pub title: &'static str,
+    /// Short preview of the markdown it inserts.
+    pub hint: &'static str,
+    pub action: SlashAction,
+}
+
+const fn block(
+    id: &'static str,
+    title: &'static str,
+    hint: &'static str,
+    kind: BlockKind,
+) -> SlashItem {
+    SlashItem {
+        id,
+        title,
+        hint,
+        action: SlashAction::Block(kind),
+    }
+}
+
+/// Built-in entries of the `/` menu, in the order shown for an empty query.
+pub const SLASH_ITEMS: &[SlashItem] = &[
+    block("heading-1", "Heading 1", "#", BlockKind::Heading(1)),
+    block("heading-2", "Heading 2", "##", BlockKind::Heading(2)),
+    block("heading-3", "Heading 3", "###", BlockKind::Heading(3)),
+    block("heading-4", "Heading 4", "####", BlockKind::Heading(4)),
+    block("heading-5", "Heading 5", "#####", BlockKind::Heading(5)),
+    block("heading-6", "Heading 6", "######", BlockKind::Heading(6)),
+    block("task-list", "Task list", "- [ ]", BlockKind::TaskList),
+    block("table", "Table", "| |", BlockKind::Table),
+    block("code", "Code block", "```", BlockKind::CodeFence("")),
+    block("code-rust", "Code block: Rust", "```rust", BlockKind::CodeFence("rust")),
+    block("code-python", "Code block: Python", "```python", BlockKind::CodeFence("python")),
+    block(
+        "code-javascript",
+        "Code block: JavaScript",
+        "```javascript",
+        BlockKind::CodeFence("javascript"),
+    ),
+    block(
+        "code-typescript",
+        "Code block: TypeScript",
+        "```typescript",
+        BlockKind::CodeFence("typescript"),
+    ),
+    block("code-bash", "Code block: Bash", "```bash", BlockKind::CodeFence("bash")),
+    block("code-json", "Code block: JSON", "```json", BlockKind::CodeFence("json")),
+    block("math", "Math block", "$$", BlockKind::MathBlock),
+    block("callout-note", "Callout: Note", "> [!note]", BlockKind::Callout("note")),
+    block("callout-tip", "Callout: Tip", "> [!tip]", BlockKind::Callout("tip")),
+    block("callout-info", "Callout: Info", "> [!info]", BlockKind::Callout("info")),
+    block("callout-todo", "Callout: Todo", "> [!todo]", BlockKind::Callout("todo")),
+    block("callout-example", "Callout: Example", "> [!example]", BlockKind::Callout("example")),
+    block("callout-quote", "Callout: Quote", "> [!quote]", BlockKind::Callout("quote")),
+    block("callout-warning", "Callout: Warning", "> [!warning]", BlockKind::Callout("warning")),
+    block("callout-danger", "Callout: Danger", "> [!danger]", BlockKind::Callout("danger")),
+    block("horizontal-rule", "Horizontal rule", "---", BlockKind::HorizontalRule),
+    SlashItem {
+        id: "date",
+        title: "Today's date",
+        hint: "YYYY-MM-DD",
+        action: SlashAction::Date,
+    },
+];
+
+/// `/query` typed on an otherwise empty line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SlashQuery {
+    /// Offset of the `/`.
+    pub start: usize,
+    pub end: usize,
+    pub query: String,
+}
+
+/// Values substituted into inserted dates and templates.
+pub struct SlashContext<'a> {
+    /// `YYYY-MM-DD`.
+    pub today: &'a str,
+    /// Name of the note being edited.
+    pub title: &'a str,
+}
+
+/// Notes under a top-level `Templates/` folder, in any case.
+pub fn template_paths(files: &[String]) -> Vec<String> {
+    files
+        .iter()
+        .filter(|path| {
+            path.split_once('/')
+                .is_some_and(|(folder, _)| folder.eq_ignore_ascii_case("templates"))
+        })
+        .cloned()
+        .collect()
+}
+
+/// `{{date}}` and `{{title}}` in a template body.
+pub fn expand_template(body: &str, context: &SlashContext) -> String {
+    body.replace("{{date}}", context.today)
+        .replace("{{title}}", context.title)
+}
+
+pub fn slash_query_at(text: &str, caret: usize) -> Option<SlashQuery> {
+    if caret > text.len() || !text.is_char_boundary(caret) {
+        return None;
+    }
+    if caret_context(text, caret) != CaretContext::Body {
+        return None;
+    }
+    let line_start = text[..caret].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let line_end = text[caret..].find('\n').map(|i| caret + i).unwrap_or(text.len());
+    if !text[caret..line_end].trim().is_empty() {
+        return None;
+    }
+    let before = &text[line_start..caret];
+    let indent = before.len() - before.trim_start().len();
+    let query = before[indent..].strip_prefix('/')?;
+    if query.starts_with(char::is_whitespace)
+        || !query
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == ' ' || c == '-' || c == '\'')
+    {
+        return None;
+    }
+    Some(SlashQuery {
+        start: line_start + indent,
+        end: line_end,
+        query: query.to_string(),
+    })
+}
+
+/// Menu entries matching the query: built-in blocks, then one entry per
+/// template note. Each suggestion's `insert` is the entry id.
+pub fn slash_suggestions(query: &SlashQuery, templates: &[String]) -> Vec<Suggestion> {
+    let mut entries: Vec<(String, String, String)> = SLASH_ITEMS
+        .iter()
+        .map(|item| (item.title.to_string(), item.hint.to_string(), item.id.to_string()))
+        .collect();
+    for path in templates {
+        let name = path
+            .rsplit('/')
+            .next()
+            .unwrap_or(path)
+            .trim_end_matches(".md");
+        entries.push((
+            format!("Template: {name}"),
+            path.clone(),
+            format!("{TEMPLATE_ID_PREFIX}{path}"),
+        ));
+    }
+
+    let mut scored: Vec<(i32, usize, Suggestion)> = entries
+        .into_iter()
+        .enumerate()
+        .filter_map(|(order, (label, detail, id))| {
+            let score = fuzzy_score(&label, &query.query).max(fuzzy_score(&id, &query.query))?;
+            Some((
+                score,
+                order,
+                Suggestion {
+                    label,
+                    detail,
+                    insert: id,
+                },
+            ))
+        })
+        .collect();
+    if !query.query.trim().is_empty() {
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    }
+    scored.into_iter().map(|(_, _, suggestion)| suggestion).collect()
+}
+
+/// Removes `/query` and inserts the chosen entry in its place, as one
+/// transaction. `template` is the body of the template note for template ids.
+pub fn accept_slash_transaction(
+    snapshot: &EditorSnapshot,
+    query: &SlashQuery,
+    id: &str,
+    template: Option<&str>,
+    context: &SlashContext,
+) -> Option<Transaction> {
+    if query.end > snapshot.text.len() || query.start > query.end {
+        return None;
+    }
+    let mut scratch = snapshot.clone();
+    scratch
+        .apply_transaction(Transaction::single(
+            TextChange::new(query.start, query.end, ""),
+            Some(Selection::cursor(query.start)),
+            ChangeOrigin::Command,
+            "slash-command",
+        ))
+        .ok()?;
+
+    if id.starts_with(TEMPLATE_ID_PREFIX) {
+        let body = expand_template(template?, context);
+        let insert = insert_block_transaction(&scratch, &body, "insert-template")?;
+        scratch.apply_transaction(insert).ok()?;
+    } else {
+        match SLASH_ITEMS.iter().find(|item| item.id == id)?.action {
+            SlashAction::Block(kind) => {
+                apply_markdown_command(&mut scratch, MarkdownCommand::InsertBlock(kind)).ok()?;
+            }
+            SlashAction::Date => {
+                let insert = insert_text_transaction(
+                    &scratch,
+                    context.today,
+                    ChangeOrigin::Command,
+                    "insert-date",
+                )?;
+                scratch.apply_transaction(insert).ok()?;
+            }
+        }
+    }
+
+    let change = minimal_text_change(&snapshot.text, &scratch.text)?;
+    Some(Transaction {
+        changes: vec![change],
+        selection_after: Some(scratch.selections.clone()),
+        origin: ChangeOrigin::Command,
+        label: "slash-command",
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTEXT: SlashContext = SlashContext {
+        today: "2024-05-01",
+        title: "Standup",
+    };
+
+    #[test]
+    fn slash_only_opens_on_an_otherwise_empty_line() {
+        let q = slash_query_at("text\n  /tab", 11).unwrap();
+        assert_eq!((q.start, q.end, q.query.as_str()), (7, 11, "tab"));
+        assert_eq!(slash_query_at("/", 1).unwrap().query, "");
+
+        assert!(slash_query_at("a /tab", 6).is_none());
+        assert!(slash_query_at("/tab x", 4).is_none());
+        assert!(slash_query_at("/usr/bin", 8).is_none());
+        assert!(slash_query_at("```\n/tab\n```", 8).is_none());
+    }
+
+    #[test]
+    fn suggestions_rank_blocks_and_templates() {
+        let templates = template_paths(&[
+            "Templates/Meeting.md".to_string(),
+            "notes/Templates.md".to_string(),
+        ]);
+        assert_eq!(templates, vec!["Templates/Meeting.md"]);
+
+        let all = slash_suggestions(&slash_query_at("/", 1).unwrap(), &templates);
+        assert_eq!(all[0].insert, "heading-1");
+
+        let warn = slash_suggestions(&slash_query_at("/warn", 5).unwrap(), &templates);
+        assert_eq!(warn[0].insert, "callout-warning");
+
+        let meet = slash_suggestions(&slash_query_at("/meet", 5).unwrap(), &templates);
+        assert_eq!(meet[0].insert, "template:Templates/Meeting.md");
+    }
+
+    #[test]
+    fn accepting_replaces_the_slash_in_one_transaction() {
+        let mut snapshot = EditorSnapshot::new("a\n/code\nb".to_string());
+        let q = slash_query_at(&snapshot.text, 7).unwrap();
+        let transaction = accept_slash_transaction(&snapshot, &q, "code-rust", None, &CONTEXT).unwrap();
+        assert_eq!(transaction.changes.len(), 1);
+        snapshot.apply_transaction(transaction).unwrap();
+        assert_eq!(snapshot.text, "a\n```rust\n\n```\nb");
+        assert_eq!(snapshot.selection(), Selection::cursor(10));
+
+        let mut snapshot = EditorSnapshot::new("/da".to_string());
+        let q = slash_query_at(&snapshot.text, 3).unwrap();
+        let transaction = accept_slash_transaction(&snapshot, &q, "date", None, &CONTEXT).unwrap();
+        snapshot.apply_transaction(transaction).unwrap();
+        assert_eq!(snapshot.text, "2024-05-01");
+
+        let mut snapshot = EditorSnapshot::new("/m".to_string());
+        let q = slash_query_at(&snapshot.text, 2).unwrap();
+        let transaction = accept_slash_transaction(
+            &snapshot,
+            &q,
+            "template:Templates/Meeting.md",
+            Some("# {{title}}\n{{date}}\n"),
+            &CONTEXT,
+        )
+        .unwrap();
+        snapshot.apply_transaction(transaction).unwrap();
+        assert_eq!(snapshot.text, "# Standup\n2024-05-01\n");
+        assert!(accept_slash_transaction(&snapshot, &q, "template:x", None, &CONTEXT).is_none());
+    }
+}
@@ -0,0 +1,94 @@
+use leptos::html;
+use leptos::prelude::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SplitDirection {
+    /// Panes side by side.
+    Vertical,
+    /// Panes stacked on top of each other.
+    Horizontal,
+}
+
+/// Second editor next to the main one. It holds its own note and selection;
+/// text, rendering and syncing are owned by `App`.
+#[component]
+pub fn SplitPane<FInput, FKey, FSel, FPick, FClose>(
+    direction: ReadSignal<Option<SplitDirection>>,
+    file: ReadSignal<String>,
+    files: ReadSignal<Vec<String>>,
+    html: ReadSignal<String>,
+    show_syntax: ReadSignal<bool>,
+    editor_ref: NodeRef<html::Div>,
+    on_input: FInput,
+    on_keydown: FKey,
+    on_selection: FSel,
+    on_pick: FPick,
+    on_close: FClose,
+) -> impl IntoView
+where
+    FInput: Fn() + 'static + Clone + Send,
+    FKey: Fn(leptos::ev::KeyboardEvent) + 'static + Clone + Send,
+    FSel: Fn() + 'static + Clone + Send,
+    FPick: Fn(String) + 'static + Clone + Send,
+    FClose: Fn() + 'static + Clone + Send,
+{
+    let on_sel_click = on_selection.clone();
+    let on_sel_keyup = on_selection.clone();
+    let on_sel_mouseup = on_selection.clone();
+    let on_sel_focus = on_selection;
+
+    view! {
+        <div
+            class="split-pane"
+            style=move || {
+                let border = match direction.get() {
+                    Some(SplitDirection::Vertical) => "border-left",
+                    Some(SplitDirection::Horizontal) => "border-top",
+                    None => return "display: none;".to_string(),
+                };
+                format!("flex: 1; display: flex; flex-direction: column; min-width: 0; min-height: 0; {border}: 1px solid var(--border-color);")
+            }
+        >
+            <div style="display: flex; align-items: center; gap: 0.5rem; padding: 0.35rem 0.75rem; border-bottom: 1px solid var(--border-color); background: var(--bg-secondary); font-size: 0.8rem;">
+                <select
+                    style="flex: 1; min-width: 0; font-size: 0.8rem;"
+                    prop:value=move || file.get()
+                    on:change=move |ev| on_pick(event_target_value(&ev))
+                >
+                    {move || {
+                        files
+                            .get()
+                            .into_iter()
+                            .map(|path| {
+                                let label = path.clone();
+                                view! { <option value=path>{label}</option> }
+                            })
+                            .collect::<Vec<_>>()
+                    }}
+                </select>
+                <button
+                    style="padding: 0.1rem 0.45rem; font-size: 0.8rem;"
+                    title="Close split"
+                    on:click=move |_| on_close()
+                >
+                    "×"
+                </button>
+            </div>
+            <div
+                node_ref=editor_ref
+                class="editor-surface"
+                class:show-syntax=move || show_syntax.get()
+                style="flex: 1; width: 100%; padding: 2rem 3rem; font-family: var(--font-editor); font-size: var(--editor-font-size); line-height: 1.6; color: var(--text-primary); white-space: pre-wrap; word-wrap: break-word; box-sizing: border-box; overflow-x: auto; overflow-y: auto; outline: none; caret-color: var(--text-primary);"
+                contenteditable="true"
+                spellcheck="false"
+                inner_html=move || html.get()
+                on:input=move |_| on_input()
+                on:keydown=on_keydown
+                on:click=move |_| on_sel_click()
+                on:keyup=move |_| on_sel_keyup()
+                on:mouseup=move |_| on_sel_mouseup()
+                on:focus=move |_| on_sel_focus()
+            ></div>
+        </div>
+    }
+}
@@ -0,0 +1,75 @@
+use leptos::prelude::*;
+
+/// Rows offered at once by any inline completion.
+pub const SUGGESTION_LIMIT: usize = 20;
+
+/// One completion row. What `insert` replaces is up to the completion source.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Suggestion {
+    pub label: String,
+    pub detail: String,
+    pub insert: String,
+}
+
+/// Inline completion list anchored below the editor caret. `position` is the
+/// caret's bottom-left corner in viewport coordinates; the list hides when it
+/// is `None` or there is nothing to suggest. Keyboard handling stays with the
+/// editor so focus never leaves it.
+#[component]
+pub fn SuggestionPopup<FPick>(
+    items: ReadSignal<Vec<Suggestion>>,
+    active: ReadSignal<usize>,
+    position: ReadSignal<Option<(f64, f64)>>,
+    on_pick: FPick,
+) -> impl IntoView
+where
+    FPick: Fn(usize) + 'static + Clone + Send,
+{
+    view! {
+        {move || {
+            let list = items.get();
+            let Some((left, top)) = position.get() else {
+                return ().into_any();
+            };
+            if list.is_empty() {
+                return ().into_any();
+            }
+            let current = active.get();
+            let rows = list
+                .into_iter()
+                .enumerate()
+                .map(|(index, Suggestion { label, detail, .. })| {
+                    let pick = on_pick.clone();
+                    let style = format!(
+                        "display: flex; align-items: baseline; gap: 0.75rem; padding: 0.3rem 0.7rem; font-size: 0.85rem; cursor: pointer; color: var(--text-primary); {}",
+                        if index == current { "background: color-mix(in srgb, var(--accent-color) 15%, transparent);" } else { "" }
+                    );
+                    view! {
+                        <div
+                            style=style
+                            on:mousedown=move |ev: leptos::ev::MouseEvent| {
+                                // Keep the caret in the editor.
+                                ev.prevent_default();
+                                pick(index);
+                            }
+                        >
+                            <span style="flex: 1; overflow: hidden; text-overflow: ellipsis; white-space: nowrap;">{label}</span>
+                            <span style="font-size: 0.75rem; color: var(--text-muted); overflow: hidden; text-overflow: ellipsis; white-space: nowrap; max-width: 50%;">{detail}</span>
+                        </div>
+                    }
+                })
+                .collect::<Vec<_>>();
+            view! {
+                <div
+                    class="suggestion-popup"
+                    style=format!(
+                        "position: fixed; left: {left}px; top: {top}px; z-index: 1050; min-width: 14rem; max-width: 28rem; max-height: 16rem; overflow-y: auto; padding: 0.25rem 0; background: var(--bg-secondary); border: 1px solid var(--border-color); border-radius: var(--radius-md); box-shadow: 0 8px 24px rgba(0,0,0,0.2);"
+                    )
+                >
+                    {rows}
+                </div>
+            }
+            .into_any()
+        }}
+    }
+}
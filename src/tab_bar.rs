@@ -0,0 +1,160 @@
+use crate::editor_tabs::EditorTabs;
+use leptos::prelude::*;
+
+const MENU_BUTTON_STYLE: &str = "display: block; width: 100%; padding: 0.4rem 0.75rem; text-align: left; font-size: 0.85rem; background: transparent; border: none; cursor: pointer; color: var(--text-primary);";
+
+#[component]
+pub fn TabBar<FSelect, FClose, FCloseOthers, FPin, FMove>(
+    tabs: ReadSignal<EditorTabs>,
+    on_select: FSelect,
+    on_close: FClose,
+    on_close_others: FCloseOthers,
+    on_toggle_pin: FPin,
+    on_move: FMove,
+) -> impl IntoView
+where
+    FSelect: Fn(String) + 'static + Clone + Send,
+    FClose: Fn(String) + 'static + Clone + Send,
+    FCloseOthers: Fn(String) + 'static + Clone + Send,
+    FPin: Fn(String) + 'static + Clone + Send,
+    FMove: Fn(String, usize) + 'static + Clone + Send,
+{
+    let (dragging, set_dragging) = signal(None::<String>);
+    let (menu, set_menu) = signal(None::<(String, f64, f64)>);
+    let on_close_menu = on_close.clone();
+
+    view! {
+        <div
+            class="editor-tabs"
+            style=move || format!(
+                "display: {}; align-items: stretch; height: 2.1rem; overflow-x: auto; overflow-y: hidden; border-bottom: 1px solid var(--border-color); background: var(--bg-secondary);",
+                if tabs.get().tabs.is_empty() { "none" } else { "flex" }
+            )
+        >
+            {move || {
+                let state = tabs.get();
+                let active = state.active.clone().unwrap_or_default();
+                state
+                    .tabs
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, tab)| {
+                        let is_active = tab.path == active;
+                        let title = tab.title();
+                        let pinned = tab.pinned;
+                        let path = tab.path.clone();
+                        let path_select = path.clone();
+                        let path_aux = path.clone();
+                        let path_drag = path.clone();
+                        let path_menu = path.clone();
+                        let path_close = path.clone();
+                        let select = on_select.clone();
+                        let close_aux = on_close.clone();
+                        let close_btn = on_close.clone();
+                        let move_tab = on_move.clone();
+                        let style = format!(
+                            "display: flex; align-items: center; gap: 0.35rem; padding: 0 0.6rem; max-width: 12rem; font-size: 0.8rem; cursor: pointer; user-select: none; border-right: 1px solid var(--border-color); {}",
+                            if is_active {
+                                "background: var(--bg-primary); color: var(--text-primary); box-shadow: inset 0 -2px 0 var(--accent-color);"
+                            } else {
+                                "color: var(--text-muted);"
+                            }
+                        );
+                        view! {
+                            <div
+                                style=style
+                                title=path
+                                draggable="true"
+                                on:click=move |_| select(path_select.clone())
+                                on:mousedown=move |ev: leptos::ev::MouseEvent| {
+                                    // Suppress middle-button autoscroll so auxclick can close the tab.
+                                    if ev.button() == 1 {
+                                        ev.prevent_default();
+                                    }
+                                }
+                                on:auxclick=move |ev: leptos::ev::MouseEvent| {
+                                    if ev.button() == 1 {
+                                        ev.prevent_default();
+                                        close_aux(path_aux.clone());
+                                    }
+                                }
+                                on:contextmenu=move |ev: leptos::ev::MouseEvent| {
+                                    ev.prevent_default();
+                                    set_menu.set(Some((path_menu.clone(), ev.client_x() as f64, ev.client_y() as f64)));
+                                }
+                                on:dragstart=move |_| set_dragging.set(Some(path_drag.clone()))
+                                on:dragend=move |_| set_dragging.set(None)
+                                on:dragover=move |ev: leptos::ev::DragEvent| ev.prevent_default()
+                                on:drop=move |ev: leptos::ev::DragEvent| {
+                                    ev.prevent_default();
+                                    if let Some(dragged) = dragging.get_untracked() {
+                                        move_tab(dragged, index);
+                                    }
+                                    set_dragging.set(None);
+                                }
+                            >
+                                {pinned.then(|| view! { <span style="font-size: 0.7rem;">"📌"</span> })}
+                                <span style="overflow: hidden; text-overflow: ellipsis; white-space: nowrap;">{title}</span>
+                                <button
+                                    style=format!(
+                                        "display: {}; padding: 0 0.2rem; font-size: 0.8rem; line-height: 1; background: transparent; border: none; cursor: pointer; color: var(--text-muted);",
+                                        if pinned { "none" } else { "inline" }
+                                    )
+                                    title="Close"
+                                    on:click=move |ev| {
+                                        ev.stop_propagation();
+                                        close_btn(path_close.clone());
+                                    }
+                                >
+                                    "×"
+                                </button>
+                            </div>
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            }}
+        </div>
+        {move || match menu.get() {
+            Some((path, x, y)) => {
+                let pinned = tabs.get_untracked().get(&path).is_some_and(|t| t.pinned);
+                let path_pin = path.clone();
+                let path_close = path.clone();
+                let path_others = path.clone();
+                let toggle_pin = on_toggle_pin.clone();
+                let close = on_close_menu.clone();
+                let close_others = on_close_others.clone();
+                view! {
+                    <div
+                        style="position: fixed; inset: 0; z-index: 1000;"
+                        on:click=move |_| set_menu.set(None)
+                    >
+                        <div
+                            style=format!("position: absolute; left: {}px; top: {}px; background: var(--bg-secondary); border: 1px solid var(--border-color); border-radius: var(--radius-md); padding: 0.25rem 0; box-shadow: 0 4px 12px rgba(0,0,0,0.15); min-width: 8rem;", x, y)
+                        >
+                            <button
+                                style=MENU_BUTTON_STYLE
+                                on:click=move |_| toggle_pin(path_pin.clone())
+                            >
+                                {if pinned { "Unpin" } else { "Pin" }}
+                            </button>
+                            <button
+                                style=MENU_BUTTON_STYLE
+                                on:click=move |_| close(path_close.clone())
+                            >
+                                "Close"
+                            </button>
+                            <button
+                                style=MENU_BUTTON_STYLE
+                                on:click=move |_| close_others(path_others.clone())
+                            >
+                                "Close others"
+                            </button>
+                        </div>
+                    </div>
+                }
+                .into_any()
+            }
+            None => ().into_any(),
+        }}
+    }
+}
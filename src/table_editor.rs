@@ -0,0 +1,681 @@
+use crate::editor_core::{ChangeOrigin, EditorSnapshot, Selection, TextChange, Transaction};
+use crate::markdown_syntax::{caret_context, is_table_row, is_table_separator, CaretContext};
+use std::cmp::Ordering;
+use unicode_width::UnicodeWidthStr;
+
+/// An edit to the pipe table around the caret. Every command also realigns
+/// the table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TableCommand {
+    Format,
+    /// Tab: select the next cell, adding a row after the last one.
+    NextCell,
+    /// Shift+Tab: select the previous cell.
+    PreviousCell,
+    /// Enter: start a row below, or leave the table from an empty last row.
+    NewRow,
+    InsertRowAbove,
+    InsertRowBelow,
+    DeleteRow,
+    MoveRowUp,
+    MoveRowDown,
+    InsertColumnLeft,
+    InsertColumnRight,
+    DeleteColumn,
+    MoveColumnLeft,
+    MoveColumnRight,
+    SortAscending,
+    SortDescending,
+    /// Steps the column through none, left, center and right alignment.
+    CycleAlignment,
+}
+
+impl TableCommand {
+    fn label(self) -> &'static str {
+        match self {
+            TableCommand::Format => "table-format",
+            TableCommand::NextCell | TableCommand::PreviousCell => "table-move-cell",
+            TableCommand::NewRow | TableCommand::InsertRowAbove | TableCommand::InsertRowBelow => {
+                "table-insert-row"
+            }
+            TableCommand::DeleteRow => "table-delete-row",
+            TableCommand::MoveRowUp | TableCommand::MoveRowDown => "table-move-row",
+            TableCommand::InsertColumnLeft | TableCommand::InsertColumnRight => {
+                "table-insert-column"
+            }
+            TableCommand::DeleteColumn => "table-delete-column",
+            TableCommand::MoveColumnLeft | TableCommand::MoveColumnRight => "table-move-column",
+            TableCommand::SortAscending | TableCommand::SortDescending => "table-sort",
+            TableCommand::CycleAlignment => "table-align",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColumnAlign {
+    #[default]
+    None,
+    Left,
+    Center,
+    Right,
+}
+
+impl ColumnAlign {
+    fn parse(cell: &str) -> Self {
+        let cell = cell.trim();
+        let left = cell.starts_with(':');
+        let right = cell.len() > 1 && cell.ends_with(':');
+        match (left, right) {
+            (true, true) => ColumnAlign::Center,
+            (true, false) => ColumnAlign::Left,
+            (false, true) => ColumnAlign::Right,
+            (false, false) => ColumnAlign::None,
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            ColumnAlign::None => ColumnAlign::Left,
+            ColumnAlign::Left => ColumnAlign::Center,
+            ColumnAlign::Center => ColumnAlign::Right,
+            ColumnAlign::Right => ColumnAlign::None,
+        }
+    }
+
+    /// Narrowest column whose alignment cell still has the three dashes a
+    /// separator needs.
+    fn min_width(self) -> usize {
+        match self {
+            ColumnAlign::None => 3,
+            ColumnAlign::Left | ColumnAlign::Right => 4,
+            ColumnAlign::Center => 5,
+        }
+    }
+
+    fn separator(self, width: usize) -> String {
+        match self {
+            ColumnAlign::None => "-".repeat(width),
+            ColumnAlign::Left => format!(":{}", "-".repeat(width - 1)),
+            ColumnAlign::Center => format!(":{}:", "-".repeat(width - 2)),
+            ColumnAlign::Right => format!("{}:", "-".repeat(width - 1)),
+        }
+    }
+}
+
+/// A pipe table parsed from the note. Every row has one cell per column.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Table {
+    /// Byte range of the table's lines, without the trailing newline.
+    pub start: usize,
+    pub end: usize,
+    /// Leading whitespace of the header line, repeated on every line.
+    pub indent: String,
+    /// The header row, then the body rows. The alignment row lives in
+    /// `aligns`.
+    pub rows: Vec<Vec<String>>,
+    pub aligns: Vec<ColumnAlign>,
+}
+
+/// The caret's cell. `row` indexes `Table::rows`, so a caret on the
+/// alignment row counts as the header; `offset` is the caret's byte offset
+/// into the trimmed cell text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CellPosition {
+    pub row: usize,
+    pub col: usize,
+    pub offset: usize,
+}
+
+/// Byte ranges between the unescaped pipes of a row, outside code spans.
+/// Text after the last pipe only counts when it is not blank.
+fn cell_spans(line: &str) -> Vec<(usize, usize)> {
+    let mut begin = line.len() - line.trim_start().len();
+    if line[begin..].starts_with('|') {
+        begin += 1;
+    }
+    let mut spans = Vec::new();
+    let mut start = begin;
+    let mut in_code = false;
+    let mut escaped = false;
+    for (i, ch) in line[begin..].char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match ch {
+            '\\' => escaped = true,
+            '`' => in_code = !in_code,
+            '|' if !in_code => {
+                spans.push((start, begin + i));
+                start = begin + i + 1;
+            }
+            _ => {}
+        }
+    }
+    if !line[start..].trim().is_empty() {
+        spans.push((start, line.len()));
+    }
+    spans
+}
+
+/// Range of the trimmed text inside a cell span.
+fn trimmed_span(line: &str, (start, end): (usize, usize)) -> (usize, usize) {
+    let raw = &line[start..end];
+    let lead = raw.len() - raw.trim_start().len();
+    (start + lead, start + lead + raw.trim().len())
+}
+
+fn line_bounds(text: &str, pos: usize) -> (usize, usize) {
+    let start = text[..pos].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let end = text[pos..]
+        .find('\n')
+        .map(|i| pos + i)
+        .unwrap_or(text.len());
+    (start, end)
+}
+
+/// The table whose lines contain `caret`, and the caret's cell in it. A
+/// table is a run of pipe rows whose second line is an alignment row.
+pub fn table_at(text: &str, caret: usize) -> Option<(Table, CellPosition)> {
+    if caret > text.len() || !text.is_char_boundary(caret) {
+        return None;
+    }
+    if !matches!(
+        caret_context(text, caret),
+        CaretContext::Body | CaretContext::InlineCode
+    ) {
+        return None;
+    }
+    let caret_line = line_bounds(text, caret);
+    if !is_table_row(&text[caret_line.0..caret_line.1]) {
+        return None;
+    }
+
+    let mut lines = vec![caret_line];
+    while lines[0].0 > 0 {
+        let (start, end) = line_bounds(text, lines[0].0 - 1);
+        if !is_table_row(&text[start..end]) {
+            break;
+        }
+        lines.insert(0, (start, end));
+    }
+    while let Some(&(_, last_end)) = lines.last().filter(|(_, end)| *end < text.len()) {
+        let (start, end) = line_bounds(text, last_end + 1);
+        if !is_table_row(&text[start..end]) {
+            break;
+        }
+        lines.push((start, end));
+    }
+    if lines.len() < 2 || !is_table_separator(&text[lines[1].0..lines[1].1]) {
+        return None;
+    }
+
+    let cells = |(start, end): (usize, usize)| -> Vec<String> {
+        let line = &text[start..end];
+        cell_spans(line)
+            .into_iter()
+            .map(|span| line[span.0..span.1].trim().to_string())
+            .collect()
+    };
+    let mut rows: Vec<Vec<String>> = lines
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| *index != 1)
+        .map(|(_, &bounds)| cells(bounds))
+        .collect();
+    let mut aligns: Vec<ColumnAlign> = cells(lines[1])
+        .iter()
+        .map(|cell| ColumnAlign::parse(cell))
+        .collect();
+    let columns = rows
+        .iter()
+        .map(Vec::len)
+        .chain([aligns.len(), 1])
+        .max()
+        .unwrap_or(1);
+    for row in &mut rows {
+        row.resize(columns, String::new());
+    }
+    aligns.resize(columns, ColumnAlign::None);
+
+    let line_index = lines.iter().position(|&bounds| bounds == caret_line)?;
+    let line = &text[caret_line.0..caret_line.1];
+    let column = caret - caret_line.0;
+    let spans = cell_spans(line);
+    let col = spans
+        .iter()
+        .position(|&(_, end)| column <= end)
+        .unwrap_or(spans.len().saturating_sub(1))
+        .min(columns - 1);
+    let offset = match spans.get(col) {
+        Some(&span) if line_index != 1 => {
+            let (start, end) = trimmed_span(line, span);
+            column.clamp(start, end) - start
+        }
+        _ => 0,
+    };
+    let position = CellPosition {
+        row: line_index.saturating_sub(1),
+        col,
+        offset,
+    };
+
+    let indent = {
+        let header = &text[lines[0].0..lines[0].1];
+        header[..header.len() - header.trim_start().len()].to_string()
+    };
+    Some((
+        Table {
+            start: lines[0].0,
+            end: lines[lines.len() - 1].1,
+            indent,
+            rows,
+            aligns,
+        },
+        position,
+    ))
+}
+
+impl Table {
+    fn columns(&self) -> usize {
+        self.aligns.len()
+    }
+
+    /// The table with every column padded to its widest cell, measured in
+    /// terminal columns so CJK text and emoji line up. Also returns the byte
+    /// offset of each cell's text within the result, by row and column.
+    pub fn render(&self) -> (String, Vec<Vec<usize>>) {
+        let widths: Vec<usize> = (0..self.columns())
+            .map(|col| {
+                self.rows
+                    .iter()
+                    .map(|row| row[col].width())
+                    .max()
+                    .unwrap_or(0)
+                    .max(self.aligns[col].min_width())
+            })
+            .collect();
+        let mut out = String::new();
+        let mut offsets = Vec::with_capacity(self.rows.len());
+        for (index, row) in self.rows.iter().enumerate() {
+            if index > 0 {
+                out.push('\n');
+            }
+            out.push_str(&self.indent);
+            let mut starts = Vec::with_capacity(row.len());
+            for (col, cell) in row.iter().enumerate() {
+                let pad = widths[col] - cell.width();
+                let (left, right) = match self.aligns[col] {
+                    ColumnAlign::Right => (pad, 0),
+                    ColumnAlign::Center => (pad / 2, pad - pad / 2),
+                    ColumnAlign::None | ColumnAlign::Left => (0, pad),
+                };
+                out.push_str("| ");
+                out.push_str(&" ".repeat(left));
+                starts.push(out.len());
+                out.push_str(cell);
+                out.push_str(&" ".repeat(right + 1));
+            }
+            out.push('|');
+            offsets.push(starts);
+
+            if index == 0 {
+                out.push('\n');
+                out.push_str(&self.indent);
+                for (align, width) in self.aligns.iter().zip(&widths) {
+                    out.push_str("| ");
+                    out.push_str(&align.separator(*width));
+                    out.push(' ');
+                }
+                out.push('|');
+            }
+        }
+        (out, offsets)
+    }
+
+    fn empty_row(&self) -> Vec<String> {
+        vec![String::new(); self.columns()]
+    }
+}
+
+/// Orders numbers numerically and everything else case-insensitively.
+fn compare_cells(a: &str, b: &str) -> Ordering {
+    match (a.parse::<f64>(), b.parse::<f64>()) {
+        (Ok(x), Ok(y)) => x.partial_cmp(&y).unwrap_or(Ordering::Equal),
+        _ => a.to_lowercase().cmp(&b.to_lowercase()),
+    }
+}
+
+/// Where the caret goes after a command: a cell, and either an offset in its
+/// text or the whole text selected.
+struct Target {
+    row: usize,
+    col: usize,
+    offset: Option<usize>,
+}
+
+/// Applies `command` to the table around the primary caret and realigns it,
+/// as one transaction. Returns `None` outside a table, with several cursors,
+/// or when the command has nothing to do (e.g. deleting the header row).
+pub fn table_transaction(snapshot: &EditorSnapshot, command: TableCommand) -> Option<Transaction> {
+    if snapshot.selections.is_multi() {
+        return None;
+    }
+    let (mut table, at) = table_at(&snapshot.text, snapshot.selection().start)?;
+    let columns = table.columns();
+    let last_row = table.rows.len() - 1;
+    let stay = Target {
+        row: at.row,
+        col: at.col,
+        offset: Some(at.offset),
+    };
+
+    let target = match command {
+        TableCommand::Format => stay,
+        TableCommand::NextCell => {
+            let (row, col) = if at.col + 1 < columns {
+                (at.row, at.col + 1)
+            } else {
+                if at.row == last_row {
+                    table.rows.push(table.empty_row());
+                }
+                (at.row + 1, 0)
+            };
+            Target {
+                row,
+                col,
+                offset: None,
+            }
+        }
+        TableCommand::PreviousCell => {
+            let (row, col) = match (at.row, at.col) {
+                (0, 0) => (0, 0),
+                (row, 0) => (row - 1, columns - 1),
+                (row, col) => (row, col - 1),
+            };
+            Target {
+                row,
+                col,
+                offset: None,
+            }
+        }
+        TableCommand::NewRow
+            if at.row > 0
+                && at.row == last_row
+                && table.rows[at.row].iter().all(String::is_empty) =>
+        {
+            table.rows.pop();
+            let (rendered, _) = table.render();
+            let caret = table.start + rendered.len() + 1;
+            return Some(Transaction::single(
+                TextChange::new(table.start, table.end, format!("{rendered}\n")),
+                Some(Selection::cursor(caret)),
+                ChangeOrigin::Command,
+                "table-exit",
+            ));
+        }
+        TableCommand::NewRow | TableCommand::InsertRowBelow => {
+            table.rows.insert(at.row + 1, table.empty_row());
+            Target {
+                row: at.row + 1,
+                col: at.col,
+                offset: Some(0),
+            }
+        }
+        TableCommand::InsertRowAbove => {
+            // Nothing goes above the header; a new row there becomes the
+            // first body row instead.
+            let row = at.row.max(1);
+            table.rows.insert(row, table.empty_row());
+            Target {
+                row,
+                col: at.col,
+                offset: Some(0),
+            }
+        }
+        TableCommand::DeleteRow => {
+            if at.row == 0 {
+                return None;
+            }
+            table.rows.remove(at.row);
+            Target {
+                row: at.row.min(table.rows.len() - 1),
+                col: at.col,
+                offset: Some(0),
+            }
+        }
+        TableCommand::MoveRowUp => {
+            if at.row < 2 {
+                return None;
+            }
+            table.rows.swap(at.row, at.row - 1);
+            Target {
+                row: at.row - 1,
+                ..stay
+            }
+        }
+        TableCommand::MoveRowDown => {
+            if at.row == 0 || at.row == last_row {
+                return None;
+            }
+            table.rows.swap(at.row, at.row + 1);
+            Target {
+                row: at.row + 1,
+                ..stay
+            }
+        }
+        TableCommand::InsertColumnLeft | TableCommand::InsertColumnRight => {
+            let col = if command == TableCommand::InsertColumnLeft {
+                at.col
+            } else {
+                at.col + 1
+            };
+            for row in &mut table.rows {
+                row.insert(col, String::new());
+            }
+            table.aligns.insert(col, ColumnAlign::None);
+            Target {
+                row: at.row,
+                col,
+                offset: Some(0),
+            }
+        }
+        TableCommand::DeleteColumn => {
+            if columns == 1 {
+                return None;
+            }
+            for row in &mut table.rows {
+                row.remove(at.col);
+            }
+            table.aligns.remove(at.col);
+            Target {
+                row: at.row,
+                col: at.col.min(columns - 2),
+                offset: Some(0),
+            }
+        }
+        TableCommand::MoveColumnLeft | TableCommand::MoveColumnRight => {
+            let other = if command == TableCommand::MoveColumnLeft {
+                at.col.checked_sub(1)?
+            } else {
+                Some(at.col + 1).filter(|&col| col < columns)?
+            };
+            for row in &mut table.rows {
+                row.swap(at.col, other);
+            }
+            table.aligns.swap(at.col, other);
+            Target { col: other, ..stay }
+        }
+        TableCommand::SortAscending | TableCommand::SortDescending => {
+            let descending = command == TableCommand::SortDescending;
+            // Empty cells stay at the bottom either way.
+            table.rows[1..].sort_by(|a, b| match (a[at.col].is_empty(), b[at.col].is_empty()) {
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                _ if descending => compare_cells(&b[at.col], &a[at.col]),
+                _ => compare_cells(&a[at.col], &b[at.col]),
+            });
+            stay
+        }
+        TableCommand::CycleAlignment => {
+            table.aligns[at.col] = table.aligns[at.col].next();
+            stay
+        }
+    };
+
+    let (rendered, offsets) = table.render();
+    let cell = &table.rows[target.row][target.col];
+    let cell_start = table.start + offsets[target.row][target.col];
+    let selection = match target.offset {
+        Some(offset) => {
+            let mut offset = offset.min(cell.len());
+            while !cell.is_char_boundary(offset) {
+                offset -= 1;
+            }
+            Selection::cursor(cell_start + offset)
+        }
+        None => Selection::new(cell_start, cell_start + cell.len()),
+    };
+    Some(Transaction::single(
+        TextChange::new(table.start, table.end, rendered),
+        Some(selection),
+        ChangeOrigin::Command,
+        command.label(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TABLE: &str = "intro\n|Name|Qty|\n|---|---:|\n|pear|10|\n|fig|2|\noutro";
+
+    fn run(text: &str, caret: usize, command: TableCommand) -> (String, Selection) {
+        let mut snapshot = EditorSnapshot::new(text.to_string());
+        snapshot.set_selection(Selection::cursor(caret));
+        let transaction = table_transaction(&snapshot, command).expect("table command applies");
+        snapshot.apply_transaction(transaction).unwrap();
+        let selection = snapshot.selection();
+        (snapshot.text, selection)
+    }
+
+    fn selected(text: &str, selection: Selection) -> &str {
+        &text[selection.start..selection.end]
+    }
+
+    #[test]
+    fn parses_cells_alignment_and_caret_position() {
+        let caret = TABLE.find("ear").unwrap();
+        let (table, at) = table_at(TABLE, caret).unwrap();
+        assert_eq!(
+            table.rows,
+            vec![vec!["Name", "Qty"], vec!["pear", "10"], vec!["fig", "2"]]
+        );
+        assert_eq!(table.aligns, vec![ColumnAlign::None, ColumnAlign::Right]);
+        assert_eq!(
+            at,
+            CellPosition {
+                row: 1,
+                col: 0,
+                offset: 1
+            }
+        );
+        assert_eq!(
+            &TABLE[table.start..table.end],
+            "|Name|Qty|\n|---|---:|\n|pear|10|\n|fig|2|"
+        );
+
+        let (table, _) = table_at("| a \\| b | `c|d` |\n| --- | --- |", 2).unwrap();
+        assert_eq!(table.rows[0], vec!["a \\| b", "`c|d`"]);
+
+        assert!(table_at("| a | b |\n| c | d |", 2).is_none());
+        assert!(table_at("```\n| a |\n| --- |\n```", 6).is_none());
+        assert!(table_at(TABLE, 2).is_none());
+    }
+
+    #[test]
+    fn format_pads_columns_by_display_width() {
+        let text = "|名前|x|\n|:---:|---|\n|a|yy|";
+        let (out, selection) = run(text, 1, TableCommand::Format);
+        assert_eq!(out, "| 名前  | x   |\n| :---: | --- |\n|   a   | yy  |");
+        assert_eq!(selection, Selection::cursor(out.find("名前").unwrap()));
+    }
+
+    #[test]
+    fn tab_and_shift_tab_select_cells_and_grow_the_table() {
+        let caret = TABLE.find("10").unwrap();
+        let (out, selection) = run(TABLE, caret, TableCommand::NextCell);
+        assert_eq!(selected(&out, selection), "fig");
+
+        let caret = TABLE.find("2|").unwrap();
+        let (out, selection) = run(TABLE, caret, TableCommand::NextCell);
+        assert!(out.ends_with("| fig  |    2 |\n|      |      |\noutro"));
+        assert_eq!(
+            selection,
+            Selection::cursor(out.rfind("\n|      |").unwrap() + 3)
+        );
+
+        let caret = TABLE.find("fig").unwrap();
+        let (out, selection) = run(TABLE, caret, TableCommand::PreviousCell);
+        assert_eq!(selected(&out, selection), "10");
+    }
+
+    #[test]
+    fn enter_adds_a_row_and_leaves_from_an_empty_last_row() {
+        let caret = TABLE.find("Qty").unwrap();
+        let (out, selection) = run(TABLE, caret, TableCommand::NewRow);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[3], "|      |      |");
+        assert_eq!(
+            selection,
+            Selection::cursor(out.find("|      |").unwrap() + 13)
+        );
+
+        let text = "| a |\n| --- |\n| b |\n|  |\nafter";
+        let (out, selection) = run(text, 22, TableCommand::NewRow);
+        assert_eq!(out, "| a   |\n| --- |\n| b   |\n\nafter");
+        assert_eq!(selection, Selection::cursor(24));
+    }
+
+    #[test]
+    fn row_and_column_commands_rearrange_cells() {
+        let pear = TABLE.find("pear").unwrap();
+        let (out, _) = run(TABLE, pear, TableCommand::MoveRowDown);
+        assert!(out.contains("| fig  |    2 |\n| pear |   10 |"));
+        let (out, _) = run(TABLE, pear, TableCommand::DeleteRow);
+        assert!(!out.contains("pear"));
+        let (out, _) = run(TABLE, pear, TableCommand::InsertRowAbove);
+        assert!(out.contains("| ---- | ---: |\n|      |      |\n| pear"));
+
+        let (out, _) = run(TABLE, pear, TableCommand::MoveColumnRight);
+        assert!(out.contains("|  Qty | Name |\n| ---: | ---- |\n|   10 | pear |"));
+        let (out, selection) = run(TABLE, pear, TableCommand::InsertColumnRight);
+        assert!(out.contains("| Name |     |  Qty |"));
+        assert_eq!(
+            selection,
+            Selection::cursor(out.find("pear |").unwrap() + 7)
+        );
+        let (out, _) = run(TABLE, pear, TableCommand::DeleteColumn);
+        assert!(out.contains("|  Qty |\n| ---: |\n|   10 |"));
+
+        let mut snapshot = EditorSnapshot::new(TABLE.to_string());
+        snapshot.set_selection(Selection::cursor(7));
+        assert!(table_transaction(&snapshot, TableCommand::DeleteRow).is_none());
+        assert!(table_transaction(&snapshot, TableCommand::MoveColumnLeft).is_none());
+    }
+
+    #[test]
+    fn sorting_and_alignment_edit_the_current_column() {
+        let text = "| n |\n| --- |\n| 10 |\n|  |\n| 9 |\n| b |";
+        let (out, _) = run(text, 2, TableCommand::SortAscending);
+        assert_eq!(out, "| n   |\n| --- |\n| 9   |\n| 10  |\n| b   |\n|     |");
+        let (out, _) = run(text, 2, TableCommand::SortDescending);
+        assert_eq!(out, "| n   |\n| --- |\n| b   |\n| 10  |\n| 9   |\n|     |");
+
+        let name = TABLE.find("Name").unwrap();
+        let (out, _) = run(TABLE, name, TableCommand::CycleAlignment);
+        assert!(out.contains("| :--- | ---: |"));
+        let (out, _) = run(&out, 9, TableCommand::CycleAlignment);
+        assert!(out.contains("| :---: | ---: |"));
+    }
+}
@@ -1,5 +1,5 @@
 use crate::app_state::{
-    AppSettings, RecentNoteEntry, VaultImportReport, VaultNote, VaultSessionState,
+    AppSettings, CommandError, GitFileStatus, GitLogEntry, GitRepoInfo, HistoryVersion, RecentNoteEntry, SavedTabs, TrashEntry, VaultImportReport, VaultLoadEvent, VaultSessionState,
 };
 use js_sys::{Object, Reflect};
 use serde::{Deserialize, Serialize};
@@ -10,11 +10,34 @@ use wasm_bindgen::JsValue;
 extern "C" {
     #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"])]
     async fn invoke(cmd: &str, args: JsValue) -> JsValue;
+
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"], js_name = invoke, catch)]
+    async fn try_invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(js_namespace = ["navigator", "clipboard"], js_name = readText, catch)]
+    async fn clipboard_read_text() -> Result<JsValue, JsValue>;
 }
 
-#[derive(Serialize)]
-struct ReadDirArgs<'a> {
-    path: &'a str,
+/// Like `invoke`, but surfaces a rejected command as a `CommandError`
+/// instead of dropping it.
+async fn invoke_checked(cmd: &str, args: JsValue) -> Result<JsValue, CommandError> {
+    try_invoke(cmd, args).await.map_err(|err| {
+        serde_wasm_bindgen::from_value::<CommandError>(err.clone()).unwrap_or_else(|_| {
+            CommandError::Io {
+                message: err.as_string().unwrap_or_else(|| format!("{cmd} failed")),
+            }
+        })
+    })
+}
+
+/// The clipboard's plain text, or `None` when it is empty or the webview
+/// refuses to read it.
+pub async fn read_clipboard_text() -> Option<String> {
+    clipboard_read_text()
+        .await
+        .ok()?
+        .as_string()
+        .filter(|text| !text.is_empty())
 }
 
 #[derive(Deserialize)]
@@ -24,42 +47,111 @@ pub struct ReadDirResult {
 }
 
 #[derive(Serialize)]
-struct ReadFileArgs<'a> {
+struct WriteFileArgs<'a> {
+    vault_id: &'a str,
     path: &'a str,
+    content: &'a str,
 }
 
 #[derive(Serialize)]
-struct WriteFileArgs<'a> {
-    path: &'a str,
-    content: &'a str,
+struct SaveAttachmentArgs<'a> {
+    vault_id: &'a str,
+    folder: &'a str,
+    file_name: &'a str,
+    data_base64: &'a str,
 }
 
 #[derive(Serialize)]
 struct SaveSettingsArgs<'a> {
-    vault_path: &'a str,
+    vault_id: &'a str,
     settings: &'a str,
 }
 
 #[derive(Serialize)]
-struct VaultPathArgs<'a> {
-    vault_path: &'a str,
+struct VaultIdArgs<'a> {
+    vault_id: &'a str,
 }
 
 #[derive(Serialize)]
 struct RenameNoteArgs<'a> {
-    vault_path: &'a str,
+    vault_id: &'a str,
     old_path: &'a str,
     new_path: &'a str,
 }
 
+#[derive(Serialize)]
+struct ItemMove<'a> {
+    from: &'a str,
+    to: &'a str,
+}
+
+#[derive(Serialize)]
+struct MoveItemsArgs<'a> {
+    vault_id: &'a str,
+    moves: Vec<ItemMove<'a>>,
+}
+
+#[derive(Serialize)]
+struct VaultItemArgs<'a> {
+    vault_id: &'a str,
+    path: &'a str,
+}
+
+#[derive(Serialize)]
+struct ListTrashArgs<'a> {
+    vault_id: &'a str,
+    retention_days: u32,
+}
+
+#[derive(Serialize)]
+struct TrashItemArgs<'a> {
+    vault_id: &'a str,
+    id: &'a str,
+}
+
+#[derive(Serialize)]
+pub struct HistoryPolicyArgs {
+    pub min_interval_secs: u64,
+    pub max_versions: u32,
+    pub max_age_days: u32,
+}
+
+#[derive(Serialize)]
+struct SnapshotNoteArgs<'a> {
+    vault_id: &'a str,
+    path: &'a str,
+    content: &'a str,
+    policy: HistoryPolicyArgs,
+}
+
+#[derive(Serialize)]
+struct NoteVersionArgs<'a> {
+    vault_id: &'a str,
+    path: &'a str,
+    version_id: &'a str,
+}
+
+#[derive(Serialize)]
+struct GitCommitArgs<'a> {
+    vault_id: &'a str,
+    template: &'a str,
+}
+
+#[derive(Serialize)]
+struct GitFileLogArgs<'a> {
+    vault_id: &'a str,
+    path: &'a str,
+    limit: Option<usize>,
+}
+
 #[derive(Serialize)]
 struct SaveRecentNotesArgs<'a> {
-    vault_path: &'a str,
+    vault_id: &'a str,
     entries: &'a [RecentNoteEntry],
 }
 
-pub async fn read_dir(path: &str) -> ReadDirResult {
-    let args = serde_wasm_bindgen::to_value(&ReadDirArgs { path }).unwrap();
+pub async fn read_dir(vault_id: &str) -> ReadDirResult {
+    let args = serde_wasm_bindgen::to_value(&VaultIdArgs { vault_id }).unwrap();
     let dir_val = invoke("read_dir", args).await;
     serde_wasm_bindgen::from_value::<ReadDirResult>(dir_val).unwrap_or_else(|_| ReadDirResult {
         notes: Vec::new(),
@@ -67,91 +159,318 @@ pub async fn read_dir(path: &str) -> ReadDirResult {
     })
 }
 
-pub async fn read_vault_notes(vault_path: &str) -> Vec<VaultNote> {
-    let vault_args =
-        serde_wasm_bindgen::to_value(&VaultPathArgs { vault_path }).unwrap();
-    let notes_val = invoke("read_vault_notes", vault_args).await;
-    serde_wasm_bindgen::from_value::<Vec<VaultNote>>(notes_val).unwrap_or_default()
+/// Streams the vault's notes through a Tauri channel, calling `on_event` for
+/// every batch, and resolves once the backend reports `Finished`.
+pub async fn load_vault_notes<F>(vault_id: &str, mut on_event: F) -> Result<(), CommandError>
+where
+    F: FnMut(VaultLoadEvent) + 'static,
+{
+    let channel_error = || CommandError::Io {
+        message: "Tauri channels are unavailable".to_string(),
+    };
+    let ctor = ["__TAURI__", "core", "Channel"]
+        .iter()
+        .try_fold(JsValue::from(js_sys::global()), |obj, key| {
+            Reflect::get(&obj, &JsValue::from_str(key)).ok()
+        })
+        .and_then(|c| c.dyn_into::<js_sys::Function>().ok())
+        .ok_or_else(channel_error)?;
+    let channel = Reflect::construct(&ctor, &js_sys::Array::new()).map_err(|_| channel_error())?;
+
+    let mut finish = None::<js_sys::Function>;
+    let finished = js_sys::Promise::new(&mut |resolve, _| finish = Some(resolve));
+    // Resolves with `null` on `Finished`, or with a message when an event
+    // cannot be decoded; nothing after either is delivered.
+    let on_message = Closure::<dyn FnMut(JsValue)>::new(move |raw: JsValue| {
+        if finish.is_none() {
+            return;
+        }
+        let event = match serde_wasm_bindgen::from_value::<VaultLoadEvent>(raw) {
+            Ok(event) => event,
+            Err(err) => {
+                if let Some(resolve) = finish.take() {
+                    let message = format!("Unreadable vault load event: {err}");
+                    let _ = resolve.call1(&JsValue::NULL, &JsValue::from_str(&message));
+                }
+                return;
+            }
+        };
+        let done = matches!(event, VaultLoadEvent::Finished { .. });
+        on_event(event);
+        if done {
+            if let Some(resolve) = finish.take() {
+                let _ = resolve.call0(&JsValue::NULL);
+            }
+        }
+    });
+    let onmessage = JsValue::from_str("onmessage");
+    let _ = Reflect::set(&channel, &onmessage, on_message.as_ref());
+
+    let args = Object::new();
+    let _ = Reflect::set(&args, &JsValue::from_str("vault_id"), &JsValue::from_str(vault_id));
+    let _ = Reflect::set(&args, &JsValue::from_str("on_event"), &channel);
+    let mut result = invoke_checked("load_vault_notes", args.into()).await.map(|_| ());
+    if result.is_ok() {
+        // Channel messages may still be in flight when the command returns.
+        let outcome = wasm_bindgen_futures::JsFuture::from(finished).await;
+        if let Some(message) = outcome.ok().and_then(|value| value.as_string()) {
+            result = Err(CommandError::Io { message });
+        }
+    }
+    let _ = Reflect::set(&channel, &onmessage, &JsValue::UNDEFINED);
+    drop(on_message);
+    result
 }
 
-pub async fn read_recent_notes(vault_path: &str) -> Vec<RecentNoteEntry> {
+pub async fn read_recent_notes(vault_id: &str) -> Vec<RecentNoteEntry> {
     let args =
-        serde_wasm_bindgen::to_value(&VaultPathArgs { vault_path }).unwrap_or(JsValue::NULL);
+        serde_wasm_bindgen::to_value(&VaultIdArgs { vault_id }).unwrap_or(JsValue::NULL);
     let val = invoke("read_recent_notes", args).await;
     serde_wasm_bindgen::from_value::<Vec<RecentNoteEntry>>(val).unwrap_or_default()
 }
 
-pub async fn cache_recent_notes(vault_path: &str, entries: &[RecentNoteEntry]) {
-    let args = serde_wasm_bindgen::to_value(&SaveRecentNotesArgs { vault_path, entries }).unwrap();
+pub async fn cache_recent_notes(vault_id: &str, entries: &[RecentNoteEntry]) {
+    let args = serde_wasm_bindgen::to_value(&SaveRecentNotesArgs { vault_id, entries }).unwrap();
     let _ = invoke("cache_recent_notes", args).await;
 }
 
-pub async fn load_plugins_css(vault_path: &str) -> Option<String> {
+pub async fn load_plugins_css(vault_id: &str) -> Option<String> {
     let vault_args =
-        serde_wasm_bindgen::to_value(&VaultPathArgs { vault_path }).unwrap();
+        serde_wasm_bindgen::to_value(&VaultIdArgs { vault_id }).unwrap();
     let css_val = invoke("load_plugins_css", vault_args).await;
     css_val.as_string()
 }
 
-pub async fn load_settings(vault_path: &str) -> Option<String> {
+pub async fn load_settings(vault_id: &str) -> Option<String> {
     let vault_args =
-        serde_wasm_bindgen::to_value(&VaultPathArgs { vault_path }).unwrap();
+        serde_wasm_bindgen::to_value(&VaultIdArgs { vault_id }).unwrap();
     let s_val = invoke("load_settings", vault_args).await;
     s_val.as_string()
 }
 
-pub async fn save_settings(vault_path: &str, settings: &AppSettings) {
-    if let Ok(s_json) = serde_json::to_string(settings) {
-        let args = serde_wasm_bindgen::to_value(&SaveSettingsArgs {
-            vault_path,
-            settings: &s_json,
-        })
-        .unwrap();
-        invoke("save_settings", args).await;
-    }
+pub async fn load_keymap(vault_id: &str) -> Option<String> {
+    let vault_args =
+        serde_wasm_bindgen::to_value(&VaultIdArgs { vault_id }).unwrap();
+    let k_val = invoke("load_keymap", vault_args).await;
+    k_val.as_string()
 }
 
-pub async fn read_file(path: &str) -> Option<String> {
-    let args = serde_wasm_bindgen::to_value(&ReadFileArgs { path }).unwrap();
+pub async fn save_settings(vault_id: &str, settings: &AppSettings) -> Result<(), CommandError> {
+    let s_json = serde_json::to_string(settings).map_err(|e| CommandError::Io {
+        message: e.to_string(),
+    })?;
+    let args = serde_wasm_bindgen::to_value(&SaveSettingsArgs {
+        vault_id,
+        settings: &s_json,
+    })
+    .unwrap();
+    invoke_checked("save_settings", args).await.map(|_| ())
+}
+
+pub async fn read_file(vault_id: &str, path: &str) -> Option<String> {
+    let args = serde_wasm_bindgen::to_value(&VaultItemArgs { vault_id, path }).unwrap();
     let text_val = invoke("read_file", args).await;
     text_val.as_string()
 }
 
-pub async fn read_file_base64(path: &str) -> Option<String> {
-    let args = serde_wasm_bindgen::to_value(&ReadFileArgs { path }).unwrap();
+pub async fn read_file_base64(vault_id: &str, path: &str) -> Option<String> {
+    let args = serde_wasm_bindgen::to_value(&VaultItemArgs { vault_id, path }).unwrap();
     let value = invoke("read_file_base64", args).await;
     value.as_string()
 }
 
-pub async fn write_file(path: &str, content: &str) {
-    let args = serde_wasm_bindgen::to_value(&WriteFileArgs { path, content }).unwrap();
-    invoke("write_file", args).await;
+pub async fn write_file(vault_id: &str, path: &str, content: &str) -> Result<(), CommandError> {
+    let args = serde_wasm_bindgen::to_value(&WriteFileArgs {
+        vault_id,
+        path,
+        content,
+    })
+    .unwrap();
+    invoke_checked("write_file", args).await.map(|_| ())
+}
+
+/// Writes base64 file data under `folder`, returning the vault-relative path
+/// the backend picked to avoid overwriting an existing file.
+pub async fn save_attachment(
+    vault_id: &str,
+    folder: &str,
+    file_name: &str,
+    data_base64: &str,
+) -> Result<String, CommandError> {
+    let args = serde_wasm_bindgen::to_value(&SaveAttachmentArgs {
+        vault_id,
+        folder,
+        file_name,
+        data_base64,
+    })
+    .unwrap();
+    let result = invoke_checked("save_attachment", args).await?;
+    Ok(result.as_string().unwrap_or_default())
+}
+
+pub async fn rename_folder(
+    vault_id: &str,
+    old_path: &str,
+    new_path: &str,
+) -> Result<String, CommandError> {
+    let args = serde_wasm_bindgen::to_value(&RenameNoteArgs {
+        vault_id,
+        old_path,
+        new_path,
+    })
+    .unwrap();
+    let result = invoke_checked("rename_folder", args).await?;
+    Ok(result.as_string().unwrap_or_default())
+}
+
+/// Moves notes and folders as `(from, to)` pairs in one batch, rewriting links
+/// once; resolves to the new paths.
+pub async fn move_items(
+    vault_id: &str,
+    moves: &[(String, String)],
+) -> Result<Vec<String>, CommandError> {
+    let args = serde_wasm_bindgen::to_value(&MoveItemsArgs {
+        vault_id,
+        moves: moves
+            .iter()
+            .map(|(from, to)| ItemMove { from, to })
+            .collect(),
+    })
+    .unwrap();
+    let result = invoke_checked("move_items", args).await?;
+    Ok(serde_wasm_bindgen::from_value::<Vec<String>>(result).unwrap_or_default())
+}
+
+pub async fn list_attachments(vault_id: &str) -> Vec<String> {
+    let args = serde_wasm_bindgen::to_value(&VaultIdArgs { vault_id }).unwrap();
+    let value = invoke("list_attachments", args).await;
+    serde_wasm_bindgen::from_value::<Vec<String>>(value).unwrap_or_default()
+}
+
+pub async fn rename_attachment(
+    vault_id: &str,
+    old_path: &str,
+    new_path: &str,
+) -> Result<String, CommandError> {
+    let args = serde_wasm_bindgen::to_value(&RenameNoteArgs {
+        vault_id,
+        old_path,
+        new_path,
+    })
+    .unwrap();
+    let result = invoke_checked("rename_attachment", args).await?;
+    Ok(result.as_string().unwrap_or_default())
+}
+
+pub async fn create_dir(vault_id: &str, path: &str) -> Result<(), CommandError> {
+    let args = serde_wasm_bindgen::to_value(&VaultItemArgs { vault_id, path }).unwrap();
+    invoke_checked("create_dir", args).await.map(|_| ())
+}
+
+pub async fn delete_file(vault_id: &str, path: &str) -> Result<(), CommandError> {
+    let args = serde_wasm_bindgen::to_value(&VaultItemArgs { vault_id, path }).unwrap();
+    invoke_checked("delete_file", args).await.map(|_| ())
 }
 
-pub async fn create_dir(path: &str) {
-    let args = serde_wasm_bindgen::to_value(&ReadFileArgs { path }).unwrap();
-    let _ = invoke("create_dir", args).await;
+pub async fn delete_dir(vault_id: &str, path: &str) -> Result<(), CommandError> {
+    let args = serde_wasm_bindgen::to_value(&VaultItemArgs { vault_id, path }).unwrap();
+    invoke_checked("delete_dir", args).await.map(|_| ())
 }
 
-pub async fn delete_file(path: &str) {
-    let args = serde_wasm_bindgen::to_value(&ReadFileArgs { path }).unwrap();
-    let _ = invoke("delete_file", args).await;
+pub async fn list_trash(vault_id: &str, retention_days: u32) -> Vec<TrashEntry> {
+    let args = serde_wasm_bindgen::to_value(&ListTrashArgs {
+        vault_id,
+        retention_days,
+    })
+    .unwrap();
+    let value = invoke("list_trash", args).await;
+    serde_wasm_bindgen::from_value::<Vec<TrashEntry>>(value).unwrap_or_default()
+}
+
+pub async fn restore_trash_item(vault_id: &str, id: &str) -> Result<String, CommandError> {
+    let args = serde_wasm_bindgen::to_value(&TrashItemArgs { vault_id, id }).unwrap();
+    let value = invoke_checked("restore_trash_item", args).await?;
+    Ok(value.as_string().unwrap_or_default())
 }
 
-pub async fn delete_dir(path: &str) {
-    let args = serde_wasm_bindgen::to_value(&ReadFileArgs { path }).unwrap();
-    let _ = invoke("delete_dir", args).await;
+pub async fn empty_vault_trash(vault_id: &str) {
+    let args = serde_wasm_bindgen::to_value(&VaultIdArgs { vault_id }).unwrap();
+    let _ = invoke("empty_vault_trash", args).await;
+}
+
+pub async fn snapshot_note(vault_id: &str, path: &str, content: &str, policy: HistoryPolicyArgs) {
+    let args = serde_wasm_bindgen::to_value(&SnapshotNoteArgs {
+        vault_id,
+        path,
+        content,
+        policy,
+    })
+    .unwrap();
+    let _ = invoke("snapshot_note", args).await;
 }
 
-pub async fn rename_note(vault_path: &str, old_path: &str, new_path: &str) -> String {
+pub async fn list_note_history(vault_id: &str, path: &str) -> Vec<HistoryVersion> {
+    let args = serde_wasm_bindgen::to_value(&VaultItemArgs { vault_id, path }).unwrap();
+    let value = invoke("list_note_history", args).await;
+    serde_wasm_bindgen::from_value::<Vec<HistoryVersion>>(value).unwrap_or_default()
+}
+
+pub async fn read_note_version(vault_id: &str, path: &str, version_id: &str) -> Option<String> {
+    let args = serde_wasm_bindgen::to_value(&NoteVersionArgs {
+        vault_id,
+        path,
+        version_id,
+    })
+    .unwrap();
+    invoke("read_note_version", args).await.as_string()
+}
+
+pub async fn git_repo_info(vault_id: &str) -> GitRepoInfo {
+    let args = serde_wasm_bindgen::to_value(&VaultIdArgs { vault_id }).unwrap();
+    let value = invoke("git_repo_info", args).await;
+    serde_wasm_bindgen::from_value::<GitRepoInfo>(value).unwrap_or_default()
+}
+
+pub async fn git_status(vault_id: &str) -> Vec<GitFileStatus> {
+    let args = serde_wasm_bindgen::to_value(&VaultIdArgs { vault_id }).unwrap();
+    let value = invoke("git_status", args).await;
+    serde_wasm_bindgen::from_value::<Vec<GitFileStatus>>(value).unwrap_or_default()
+}
+
+pub async fn git_diff_note(vault_id: &str, path: &str) -> String {
+    let args = serde_wasm_bindgen::to_value(&VaultItemArgs { vault_id, path }).unwrap();
+    invoke("git_diff_note", args).await.as_string().unwrap_or_default()
+}
+
+pub async fn git_commit_all(vault_id: &str, template: &str) -> Result<Option<String>, CommandError> {
+    let args = serde_wasm_bindgen::to_value(&GitCommitArgs {
+        vault_id,
+        template,
+    })
+    .unwrap();
+    Ok(invoke_checked("git_commit_all", args).await?.as_string())
+}
+
+pub async fn git_file_log(vault_id: &str, path: &str, limit: Option<usize>) -> Vec<GitLogEntry> {
+    let args = serde_wasm_bindgen::to_value(&GitFileLogArgs {
+        vault_id,
+        path,
+        limit,
+    })
+    .unwrap();
+    let value = invoke("git_file_log", args).await;
+    serde_wasm_bindgen::from_value::<Vec<GitLogEntry>>(value).unwrap_or_default()
+}
+
+pub async fn rename_note(vault_id: &str, old_path: &str, new_path: &str) -> Result<String, CommandError> {
     let args = serde_wasm_bindgen::to_value(&RenameNoteArgs {
-        vault_path,
+        vault_id,
         old_path,
         new_path,
     })
     .unwrap();
-    let result = invoke("rename_note", args).await;
-    result.as_string().unwrap_or_else(|| new_path.to_string())
+    let result = invoke_checked("rename_note", args).await?;
+    Ok(result.as_string().unwrap_or_else(|| new_path.to_string()))
 }
 
 pub async fn import_obsidian_vault_with_picker() -> Option<VaultImportReport> {
@@ -179,6 +498,7 @@ pub async fn save_vault_session(
     open_vaults: &[String],
     active_vault: Option<&str>,
     active_vault_recent: Option<&[RecentNoteEntry]>,
+    active_vault_tabs: Option<&SavedTabs>,
 ) -> VaultSessionState {
     let payload = Object::new();
     let open_value = serde_wasm_bindgen::to_value(open_vaults).unwrap_or(JsValue::NULL);
@@ -204,6 +524,11 @@ pub async fn save_vault_session(
             &entries_value,
         );
     }
+    if let Some(tabs) = active_vault_tabs {
+        let tabs_value = serde_wasm_bindgen::to_value(tabs).unwrap_or(JsValue::NULL);
+        let _ = Reflect::set(&payload, &JsValue::from_str("active_vault_tabs"), &tabs_value);
+        let _ = Reflect::set(&payload, &JsValue::from_str("activeVaultTabs"), &tabs_value);
+    }
 
     let result = invoke("save_vault_session", payload.into()).await;
     serde_wasm_bindgen::from_value::<VaultSessionState>(result).unwrap_or_default()
@@ -0,0 +1,197 @@
+/// Above this many LCS cells we stop computing an exact diff and report the
+/// differing middle section as one delete plus one insert.
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiffKind {
+    Equal,
+    Insert,
+    Delete,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiffSegment {
+    pub kind: DiffKind,
+    pub text: String,
+}
+
+fn diff_tokens<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<(DiffKind, &'a str)> {
+    let prefix = old
+        .iter()
+        .zip(new.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let max_suffix = old.len().min(new.len()) - prefix;
+    let suffix = old
+        .iter()
+        .rev()
+        .zip(new.iter().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let mut out = Vec::with_capacity(old.len().max(new.len()));
+    out.extend(old[..prefix].iter().map(|t| (DiffKind::Equal, *t)));
+
+    let n = old_mid.len();
+    let m = new_mid.len();
+    if n == 0 || m == 0 || (n + 1).saturating_mul(m + 1) > MAX_LCS_CELLS {
+        out.extend(old_mid.iter().map(|t| (DiffKind::Delete, *t)));
+        out.extend(new_mid.iter().map(|t| (DiffKind::Insert, *t)));
+    } else {
+        // lcs[i][j] = length of the LCS of old_mid[i..] and new_mid[j..].
+        let width = m + 1;
+        let mut lcs = vec![0u32; (n + 1) * width];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[i * width + j] = if old_mid[i] == new_mid[j] {
+                    lcs[(i + 1) * width + j + 1] + 1
+                } else {
+                    lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0usize, 0usize);
+        while i < n && j < m {
+            if old_mid[i] == new_mid[j] {
+                out.push((DiffKind::Equal, old_mid[i]));
+                i += 1;
+                j += 1;
+            } else if lcs[(i + 1) * width + j] >= lcs[i * width + j + 1] {
+                out.push((DiffKind::Delete, old_mid[i]));
+                i += 1;
+            } else {
+                out.push((DiffKind::Insert, new_mid[j]));
+                j += 1;
+            }
+        }
+        out.extend(old_mid[i..].iter().map(|t| (DiffKind::Delete, *t)));
+        out.extend(new_mid[j..].iter().map(|t| (DiffKind::Insert, *t)));
+    }
+
+    out.extend(old[old.len() - suffix..].iter().map(|t| (DiffKind::Equal, *t)));
+    out
+}
+
+/// Splits text into runs of word characters, runs of whitespace, and single
+/// punctuation characters so word diffs keep spacing intact.
+fn split_words(text: &str) -> Vec<&str> {
+    #[derive(PartialEq)]
+    enum Class {
+        Word,
+        Space,
+        Other,
+    }
+    let class_of = |c: char| {
+        if c.is_alphanumeric() || c == '_' {
+            Class::Word
+        } else if c.is_whitespace() {
+            Class::Space
+        } else {
+            Class::Other
+        }
+    };
+
+    let mut tokens = Vec::new();
+    let mut start = 0usize;
+    let mut current: Option<Class> = None;
+    for (idx, ch) in text.char_indices() {
+        let class = class_of(ch);
+        let continues = match &current {
+            Some(prev) => *prev == class && class != Class::Other,
+            None => false,
+        };
+        if !continues {
+            if idx > start {
+                tokens.push(&text[start..idx]);
+            }
+            start = idx;
+        }
+        current = Some(class);
+    }
+    if start < text.len() {
+        tokens.push(&text[start..]);
+    }
+    tokens
+}
+
+/// One segment per line, each tagged as unchanged, added, or removed.
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffSegment> {
+    let old_lines: Vec<&str> = old.split('\n').collect();
+    let new_lines: Vec<&str> = new.split('\n').collect();
+    diff_tokens(&old_lines, &new_lines)
+        .into_iter()
+        .map(|(kind, text)| DiffSegment {
+            kind,
+            text: text.to_string(),
+        })
+        .collect()
+}
+
+/// Inline word-level diff; adjacent tokens of the same kind are merged.
+pub fn diff_words(old: &str, new: &str) -> Vec<DiffSegment> {
+    let old_words = split_words(old);
+    let new_words = split_words(new);
+    let mut segments: Vec<DiffSegment> = Vec::new();
+    for (kind, text) in diff_tokens(&old_words, &new_words) {
+        match segments.last_mut() {
+            Some(last) if last.kind == kind => last.text.push_str(text),
+            _ => segments.push(DiffSegment {
+                kind,
+                text: text.to_string(),
+            }),
+        }
+    }
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(segments: &[DiffSegment]) -> String {
+        segments
+            .iter()
+            .map(|s| match s.kind {
+                DiffKind::Equal => format!(" {}", s.text),
+                DiffKind::Insert => format!("+{}", s.text),
+                DiffKind::Delete => format!("-{}", s.text),
+            })
+            .collect::<Vec<_>>()
+            .join("|")
+    }
+
+    #[test]
+    fn line_diff_marks_added_and_removed_lines() {
+        let diff = diff_lines("a\nb\nc", "a\nc\nd");
+        assert_eq!(render(&diff), " a|-b| c|+d");
+    }
+
+    #[test]
+    fn identical_texts_are_all_equal() {
+        let diff = diff_lines("x\ny", "x\ny");
+        assert!(diff.iter().all(|s| s.kind == DiffKind::Equal));
+    }
+
+    #[test]
+    fn word_diff_preserves_whitespace_and_merges_runs() {
+        let diff = diff_words("the quick fox", "the slow brown fox");
+        assert_eq!(render(&diff), " the |-quick|+slow brown|  fox");
+
+        let rebuilt_new: String = diff
+            .iter()
+            .filter(|s| s.kind != DiffKind::Delete)
+            .map(|s| s.text.as_str())
+            .collect();
+        assert_eq!(rebuilt_new, "the slow brown fox");
+    }
+
+    #[test]
+    fn splits_punctuation_into_single_tokens() {
+        assert_eq!(split_words("**bold**, ok"), vec!["*", "*", "bold", "*", "*", ",", " ", "ok"]);
+    }
+}
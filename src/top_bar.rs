@@ -2,18 +2,23 @@ use crate::path_utils::vault_display_name;
 use leptos::prelude::*;
 
 #[component]
-pub fn TopBar<FOpen, FImport, FRename>(
+pub fn TopBar<FOpen, FImport, FRename, FHistory, FGit>(
     current_file: ReadSignal<String>,
     vault_path: ReadSignal<String>,
     save_status: ReadSignal<String>,
+    show_git: Signal<bool>,
     on_open_vault: FOpen,
     on_import_obsidian: FImport,
     on_rename: FRename,
+    on_history: FHistory,
+    on_git: FGit,
 ) -> impl IntoView
 where
     FOpen: Fn() + 'static + Clone,
     FImport: Fn() + 'static + Clone,
     FRename: Fn() + 'static + Clone,
+    FHistory: Fn() + 'static + Clone,
+    FGit: Fn() + 'static + Clone,
 {
     view! {
         <header class="topbar" style="height: var(--topbar-height); border-bottom: 1px solid var(--border-color); display: flex; align-items: center; justify-content: space-between; padding: 0 1.5rem; color: var(--text-muted); font-size: 0.9rem; gap: 1rem;">
@@ -52,6 +57,21 @@ where
                 >
                     "Rename"
                 </button>
+                <button
+                    style="padding: 0.25rem 0.6rem; font-size: 0.75rem;"
+                    on:click=move |_| on_history()
+                >
+                    "History"
+                </button>
+                <button
+                    style=move || format!(
+                        "padding: 0.25rem 0.6rem; font-size: 0.75rem; {}",
+                        if show_git.get() { "" } else { "display: none;" }
+                    )
+                    on:click=move |_| on_git()
+                >
+                    "Git"
+                </button>
             </div>
         </header>
     }
@@ -0,0 +1,91 @@
+use crate::app_state::TrashEntry;
+use js_sys::Date;
+use leptos::prelude::*;
+use wasm_bindgen::JsValue;
+
+#[component]
+pub fn TrashPane<FRestore, FEmpty>(
+    trash_entries: ReadSignal<Vec<TrashEntry>>,
+    retention_days: Signal<u32>,
+    on_restore: FRestore,
+    on_empty: FEmpty,
+) -> impl IntoView
+where
+    FRestore: Fn(String) + 'static + Clone + Send,
+    FEmpty: Fn() + 'static + Clone + Send,
+{
+    view! {
+        <div style="display: flex; flex-direction: column; gap: 0.5rem;">
+            <div style="display: flex; align-items: center; gap: 0.5rem; padding: 0 0.1rem;">
+                <span style="font-size: 0.75rem; color: var(--text-muted);">
+                    {move || match retention_days.get() {
+                        0 => "Deleted items are kept until the trash is emptied.".to_string(),
+                        days => format!("Deleted items are removed after {days} days."),
+                    }}
+                </span>
+                <button
+                    style="margin-left: auto; padding: 0.2rem 0.5rem; font-size: 0.75rem;"
+                    disabled=move || trash_entries.get().is_empty()
+                    on:click=move |_| on_empty()
+                >
+                    "Empty trash"
+                </button>
+            </div>
+            {move || {
+                let entries = trash_entries.get();
+                if entries.is_empty() {
+                    return view! {
+                        <div style="padding: 0.25rem 0.1rem; font-size: 0.8rem; color: var(--text-muted);">
+                            "Trash is empty."
+                        </div>
+                    }
+                    .into_any();
+                }
+
+                view! {
+                    <>
+                        {entries
+                            .into_iter()
+                            .map(|entry| {
+                                let id = entry.id.clone();
+                                let name = entry
+                                    .original_path
+                                    .rsplit('/')
+                                    .next()
+                                    .unwrap_or(&entry.original_path)
+                                    .to_string();
+                                let deleted = Date::new(&JsValue::from_f64(entry.deleted_at as f64));
+                                let deleted_label: String =
+                                    deleted.to_locale_string("default", &JsValue::UNDEFINED).into();
+                                let icon = if entry.is_dir { "▸ " } else { "" };
+                                let restore = on_restore.clone();
+                                view! {
+                                    <div
+                                        style="padding: 0.3rem 0.6rem 0.3rem 0.8rem; border-radius: var(--radius-md); margin-bottom: 2px; font-size: 0.82rem; color: var(--text-secondary); display: flex; align-items: center; gap: 0.4rem;"
+                                        title=entry.original_path.clone()
+                                    >
+                                        <div style="min-width: 0; flex: 1;">
+                                            <div style="overflow: hidden; text-overflow: ellipsis; white-space: nowrap;">
+                                                {format!("{icon}{name}")}
+                                            </div>
+                                            <div style="font-size: 0.74rem; color: var(--text-muted); overflow: hidden; text-overflow: ellipsis; white-space: nowrap;">
+                                                {format!("{} • Deleted {}", entry.original_path, deleted_label)}
+                                            </div>
+                                        </div>
+                                        <button
+                                            style="padding: 0.15rem 0.45rem; font-size: 0.72rem;"
+                                            on:click=move |_| restore(id.clone())
+                                        >
+                                            "Restore"
+                                        </button>
+                                    </div>
+                                }
+                            })
+                            .collect::<Vec<_>>()}
+                    </>
+                }
+                .into_any()
+            }}
+        </div>
+    }
+}